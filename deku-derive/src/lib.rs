@@ -1,10 +1,12 @@
 /*!
-Procedural macros that implement `DekuRead` and `DekuWrite` traits
+Procedural macros that implement `DekuRead`, `DekuWrite` and `DekuSchema` traits
  */
 
 #![warn(missing_docs)]
 
-use crate::macros::{deku_read::emit_deku_read, deku_write::emit_deku_write};
+use crate::macros::{
+    deku_read::emit_deku_read, deku_schema::emit_deku_schema, deku_write::emit_deku_write,
+};
 use darling::{ast, FromDeriveInput, FromField, FromMeta, FromVariant, ToTokens};
 use proc_macro2::TokenStream;
 use quote::quote;
@@ -97,10 +99,140 @@ impl FromMeta for Num {
     }
 }
 
+/// Integer type of a `#[repr(...)]` attribute, such as `u8` in `#[repr(C, u8)]`
+fn repr_int_type(attrs: &[syn::Attribute]) -> Option<syn::Ident> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("repr"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(syn::Meta::List(list)) => Some(list.nested),
+            _ => None,
+        })
+        .flatten()
+        .find_map(|nested| match nested {
+            syn::NestedMeta::Meta(syn::Meta::Path(path)) => path
+                .get_ident()
+                .filter(|ident| repr_int_bits(ident).is_some())
+                .cloned(),
+            _ => None,
+        })
+}
+
+/// Bit width of a fixed size integer `#[repr(...)]`, `isize`/`usize` are platform dependent
+fn repr_int_bits(repr: &syn::Ident) -> Option<usize> {
+    match repr.to_string().as_str() {
+        "u8" | "i8" => Some(8),
+        "u16" | "i16" => Some(16),
+        "u32" | "i32" => Some(32),
+        "u64" | "i64" => Some(64),
+        "u128" | "i128" => Some(128),
+        _ => None,
+    }
+}
+
+/// Value of an integer literal expression such as `1`, `0x10` or `-1`
+fn literal_int(expr: &syn::Expr) -> Option<i128> {
+    match expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(v),
+            ..
+        }) => v.base10_parse().ok(),
+        syn::Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Neg(_),
+            expr,
+            ..
+        }) => literal_int(expr).map(|v| -v),
+        syn::Expr::Paren(syn::ExprParen { expr, .. }) => literal_int(expr),
+        _ => None,
+    }
+}
+
+/// Validate variants don't share the same literal `id` or discriminant
+fn validate_unique_ids(variants: &[VariantData]) -> Result<(), TokenStream> {
+    let has_discriminant = variants.iter().any(|v| v.discriminant.is_some());
+
+    let mut seen: Vec<(i128, &syn::Ident)> = Vec::with_capacity(variants.len());
+    // implicit discriminants follow the previous one
+    let mut next_discriminant = Some(0);
+    for variant in variants.iter().filter(|v| !v.unknown) {
+        let discriminant = match &variant.discriminant {
+            Some(expr) => literal_int(expr),
+            None => next_discriminant,
+        };
+        next_discriminant = discriminant.map(|v| v + 1);
+
+        let id = match (&variant.id, &variant.id_pat) {
+            (Some(Id::TokenStream(id)), _) => syn::parse2(id.clone())
+                .ok()
+                .and_then(|expr| literal_int(&expr)),
+            (Some(Id::LitByteStr(_)), _) | (None, Some(_)) => None,
+            (None, None) if has_discriminant => discriminant,
+            (None, None) => None,
+        };
+
+        if let Some(id) = id {
+            if let Some((_, other)) = seen.iter().find(|(v, _)| *v == id) {
+                return Err(cerror(
+                    variant.ident.span(),
+                    &format!(
+                        "conflicting: variants `{}` and `{}` both use id {}",
+                        other, variant.ident, id
+                    ),
+                ));
+            }
+            seen.push((id, &variant.ident));
+        }
+    }
+
+    Ok(())
+}
+
 fn cerror(span: proc_macro2::Span, msg: &str) -> TokenStream {
     syn::Error::new(span, msg).to_compile_error()
 }
 
+/// Validate a `pad_fill` byte string isn't empty
+fn validate_pad_fill(pad_fill: Option<&Id>) -> Result<(), TokenStream> {
+    match pad_fill {
+        Some(Id::LitByteStr(v)) if v.value().is_empty() => {
+            Err(cerror(v.span(), "`pad_fill` cannot be empty"))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Validate `assert_msg`/`assert_err` are used alone and with an assertion
+fn validate_assert_error(
+    has_assert: bool,
+    asserts: &str,
+    assert_msg: Option<&syn::LitStr>,
+    assert_err: Option<&TokenStream>,
+) -> Result<(), TokenStream> {
+    if let (Some(assert_msg), Some(_)) = (assert_msg, assert_err) {
+        return Err(cerror(
+            assert_msg.span(),
+            "conflicting: both `assert_msg` and `assert_err` specified",
+        ));
+    }
+
+    if !has_assert {
+        if let Some(assert_msg) = assert_msg {
+            return Err(cerror(
+                assert_msg.span(),
+                &format!("`assert_msg` must be used with {}", asserts),
+            ));
+        }
+        if let Some(assert_err) = assert_err {
+            return Err(cerror(
+                assert_err.span(),
+                &format!("`assert_err` must be used with {}", asserts),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
 /// A post-processed version of `DekuReceiver`
 #[derive(Debug)]
 struct DekuData {
@@ -111,6 +243,9 @@ struct DekuData {
     /// Endianness for all fields
     endian: Option<syn::LitStr>,
 
+    /// field selecting the endianness of the fields after it
+    endian_from: Option<syn::LitStr>,
+
     /// top-level context, argument list
     ctx: Option<syn::punctuated::Punctuated<syn::FnArg, syn::token::Comma>>,
 
@@ -120,12 +255,54 @@ struct DekuData {
     /// A magic value that must appear at the start of this struct/enum's data
     magic: Option<syn::LitByteStr>,
 
+    /// assertion on the fields, checked after reading and before writing
+    assert: Option<TokenStream>,
+
+    /// function validating the value, checked after reading and before writing
+    validate: Option<TokenStream>,
+
+    /// message of a failed `assert` or `validate`, formatted with the fields
+    assert_msg: Option<syn::LitStr>,
+
+    /// user error of a failed `assert` or `validate`
+    assert_err: Option<TokenStream>,
+
+    /// version of the data, compared against the `since`/`until_version` of the fields
+    version: Option<TokenStream>,
+
+    /// pad the end of the struct/enum to a multiple of this many bytes
+    align: Option<TokenStream>,
+
+    /// alignment is relative to the input instead of the start of the struct/enum
+    align_input: bool,
+
+    /// default value written to padding bits
+    pad_fill: Option<Id>,
+
+    /// default to erroring when reading padding bits which don't match the fill value
+    pad_strict: bool,
+
+    /// default to erroring when reading reserved bits which don't match their value
+    reserved_strict: bool,
+
+    /// default to erroring when writing a field whose `cond` is false and value isn't the default
+    cond_strict: bool,
+
     /// enum only: `id` value
     id: Option<Id>,
 
-    /// enum only: type of the enum `id`
+    /// enum only: type of the enum `id`, from `type` or an integer `#[repr(...)]`
     id_type: Option<TokenStream>,
 
+    /// enum only: integer `#[repr(...)]` of the enum
+    repr: Option<syn::Ident>,
+
+    /// enum only: custom reader code for the enum `id`
+    id_reader: Option<TokenStream>,
+
+    /// enum only: custom writer code for the enum `id`
+    id_writer: Option<TokenStream>,
+
     /// enum only: bit size of the enum `id`
     bits: Option<Num>,
 
@@ -167,16 +344,63 @@ impl DekuData {
             ),
         };
 
+        // `since`/`until_version` fields compare against the container `version`
+        let version = receiver.version?;
+        let with_version = |f: FieldData| FieldData {
+            version: version
+                .clone()
+                .filter(|_| f.since.is_some() || f.until_version.is_some()),
+            ..f
+        };
+        let data = match data {
+            ast::Data::Struct(fields) => ast::Data::Struct(fields.map(with_version)),
+            ast::Data::Enum(variants) => ast::Data::Enum(
+                variants
+                    .into_iter()
+                    .map(|v| VariantData {
+                        fields: v.fields.map(with_version),
+                        ..v
+                    })
+                    .collect(),
+            ),
+        };
+
+        let repr = match data {
+            ast::Data::Enum(_) => repr_int_type(&receiver.attrs),
+            ast::Data::Struct(_) => None,
+        };
+
+        // infer the `type` from the `#[repr(...)]` if no `type` or `id` is given
+        let id_type = match (receiver.id_type?, &receiver.id, &repr) {
+            (None, None, Some(repr)) => Some(quote! { #repr }),
+            (id_type, _, _) => id_type,
+        };
+
         let data = Self {
             ident: receiver.ident,
             generics: receiver.generics,
             data,
             endian: receiver.endian,
+            endian_from: receiver.endian_from,
             ctx: receiver.ctx,
             ctx_default: receiver.ctx_default,
             magic: receiver.magic,
+            assert: receiver.assert?,
+            validate: receiver.validate?,
+            assert_msg: receiver.assert_msg,
+            assert_err: receiver.assert_err?,
+            version,
+            align: receiver.align?,
+            align_input: receiver.align_input,
+            pad_fill: receiver.pad_fill,
+            pad_strict: receiver.pad_strict,
+            reserved_strict: receiver.reserved_strict,
+            cond_strict: receiver.cond_strict,
             id: receiver.id,
-            id_type: receiver.id_type?,
+            id_type,
+            repr,
+            id_reader: receiver.id_reader?,
+            id_writer: receiver.id_writer?,
             bits: receiver.bits,
             bytes: receiver.bytes,
         };
@@ -196,13 +420,77 @@ impl DekuData {
             ));
         }
 
+        validate_pad_fill(data.pad_fill.as_ref())?;
+
+        validate_assert_error(
+            data.assert.is_some() || data.validate.is_some(),
+            "`assert` or `validate`",
+            data.assert_msg.as_ref(),
+            data.assert_err.as_ref(),
+        )?;
+
+        // Validate `since`/`until_version` are used with a top-level `version`
+        if data.version.is_none() {
+            let fields: Vec<&FieldData> = match &data.data {
+                ast::Data::Struct(fields) => fields.iter().collect(),
+                ast::Data::Enum(variants) => {
+                    variants.iter().flat_map(|v| v.fields.iter()).collect()
+                }
+            };
+            if let Some(f) = fields
+                .into_iter()
+                .find(|f| f.since.is_some() || f.until_version.is_some())
+            {
+                return Err(cerror(
+                    f.since.as_ref().or(f.until_version.as_ref()).span(),
+                    "`since` and `until_version` must be used with a top-level `version`",
+                ));
+            }
+        }
+
+        match &data.data {
+            ast::Data::Struct(fields) => {
+                validate_at_offset_fields(&fields.iter().collect::<Vec<_>>())?
+            }
+            ast::Data::Enum(variants) => {
+                for variant in variants {
+                    validate_at_offset_fields(&variant.fields.iter().collect::<Vec<_>>())?;
+                }
+            }
+        }
+
         match data.data {
-            ast::Data::Struct(_) => {
+            ast::Data::Struct(ref fields) => {
+                // Validate `endian_from` names a field
+                if let Some(endian_from) = &data.endian_from {
+                    let name = endian_from.value();
+                    let found = fields
+                        .iter()
+                        .enumerate()
+                        .any(|(i, f)| f.get_ident(i, false).to_string() == name);
+                    if !found {
+                        return Err(cerror(
+                            endian_from.span(),
+                            &format!("`endian_from` field `{}` not found", name),
+                        ));
+                    }
+                }
+
                 // Validate id_* attributes are being used on an enum
                 if data.id_type.is_some() {
                     Err(cerror(data.id_type.span(), "`type` only supported on enum"))
                 } else if data.id.is_some() {
                     Err(cerror(data.id.span(), "`id` only supported on enum"))
+                } else if data.id_reader.is_some() {
+                    Err(cerror(
+                        data.id_reader.span(),
+                        "`id_reader` only supported on enum",
+                    ))
+                } else if data.id_writer.is_some() {
+                    Err(cerror(
+                        data.id_writer.span(),
+                        "`id_writer` only supported on enum",
+                    ))
                 } else if data.bytes.is_some() {
                     Err(cerror(data.bytes.span(), "`bytes` only supported on enum"))
                 } else if data.bits.is_some() {
@@ -211,7 +499,20 @@ impl DekuData {
                     Ok(())
                 }
             }
-            ast::Data::Enum(_) => {
+            ast::Data::Enum(ref variants) => {
+                if data.endian_from.is_some() {
+                    return Err(cerror(
+                        data.endian_from.span(),
+                        "`endian_from` only supported on struct",
+                    ));
+                }
+                if data.assert.is_some() {
+                    return Err(cerror(
+                        data.assert.span(),
+                        "`assert` only supported on struct, use `validate` on enum",
+                    ));
+                }
+
                 // Validate `type` or `id` is specified
                 if data.id_type.is_none() && data.id.is_none() {
                     return Err(cerror(
@@ -228,6 +529,14 @@ impl DekuData {
                     ));
                 }
 
+                // Validate `id_reader`/`id_writer` aren't used with `id`
+                if data.id.is_some() && (data.id_reader.is_some() || data.id_writer.is_some()) {
+                    return Err(cerror(
+                        data.ident.span(),
+                        "error: cannot use `id_reader` or `id_writer` with `id`",
+                    ));
+                }
+
                 // Validate `id_*` used correctly
                 if data.id.is_some() && data.bits.is_some() {
                     return Err(cerror(
@@ -250,7 +559,50 @@ impl DekuData {
                     ));
                 }
 
-                Ok(())
+                // Validate `bits` or `bytes` fit in the `#[repr(...)]`
+                if let Some(repr) = &data.repr {
+                    let width = repr_int_bits(repr).unwrap_or(usize::MAX);
+                    let size = match (&data.bits, &data.bytes) {
+                        (Some(bits), _) => Some((bits, bits.0.base10_parse::<usize>().ok())),
+                        (None, Some(bytes)) => {
+                            Some((bytes, bytes.0.base10_parse::<usize>().ok().map(|n| n * 8)))
+                        }
+                        (None, None) => None,
+                    };
+                    if let Some((num, Some(bit_size))) = size {
+                        if bit_size > width {
+                            return Err(cerror(
+                                num.span(),
+                                &format!(
+                                    "id size of {} bits doesn't fit in `repr({})`",
+                                    bit_size, repr
+                                ),
+                            ));
+                        }
+                    }
+                }
+
+                // Validate a single catch-all variant
+                let mut unknown = variants.iter().filter(|v| v.unknown);
+                if let (Some(_), Some(other)) = (unknown.next(), unknown.next()) {
+                    return Err(cerror(
+                        other.ident.span(),
+                        "`unknown` must be specified only once",
+                    ));
+                }
+                if variants.iter().any(|v| v.unknown) {
+                    if let Some(other) = variants.iter().find(|v| {
+                        v.default.unwrap_or(false)
+                            || v.id_pat.as_ref().is_some_and(|pat| pat.to_string() == "_")
+                    }) {
+                        return Err(cerror(
+                            other.ident.span(),
+                            "conflicting: `unknown` variant and a `default` or `id_pat = \"_\"` variant",
+                        ));
+                    }
+                }
+
+                validate_unique_ids(variants)
             }
         }
     }
@@ -267,6 +619,12 @@ impl DekuData {
             .map_or_else(|e| e.to_compile_error(), |tks| tks)
     }
 
+    /// Emit a schema. On error, a compiler error is emitted
+    fn emit_schema(&self) -> TokenStream {
+        self.emit_schema_checked()
+            .map_or_else(|e| e.to_compile_error(), |tks| tks)
+    }
+
     /// Same as `emit_reader`, but won't auto convert error to compile error
     fn emit_reader_checked(&self) -> Result<TokenStream, syn::Error> {
         emit_deku_read(self)
@@ -276,6 +634,11 @@ impl DekuData {
     fn emit_writer_checked(&self) -> Result<TokenStream, syn::Error> {
         emit_deku_write(self)
     }
+
+    /// Same as `emit_schema`, but won't auto convert error to compile error
+    fn emit_schema_checked(&self) -> Result<TokenStream, syn::Error> {
+        emit_deku_schema(self)
+    }
 }
 
 /// Common variables from `DekuData` for `emit_enum` read/write functions
@@ -382,9 +745,33 @@ struct FieldData {
     /// a predicate to decide when to stop reading elements into the container
     until: Option<TokenStream>,
 
+    /// like `until`, but the element matching the predicate isn't stored
+    until_exclusive: Option<TokenStream>,
+
+    /// element ending the container, consumed on read and written after the elements
+    terminator: Option<TokenStream>,
+
+    /// number of bytes the field is read from and written to
+    window_bytes: Option<TokenStream>,
+
+    /// handling of the bytes of the window not used by the field: `skip`, `error` or `keep`
+    rest: Option<syn::LitStr>,
+
+    /// byte offset of the field from the start of the input, read without moving the cursor
+    at: Option<TokenStream>,
+
     /// apply a function to the field after it's read
     map: Option<TokenStream>,
 
+    /// apply a function to the field before it's written
+    write_map: Option<TokenStream>,
+
+    /// on-wire type the field is converted from/into
+    as_type: Option<TokenStream>,
+
+    /// convert with `TryFrom`/`TryInto` instead of `From`/`Into`
+    try_from: bool,
+
     /// context passed to the field
     ctx: Option<Punctuated<syn::Expr, syn::token::Comma>>,
 
@@ -412,6 +799,36 @@ struct FieldData {
     /// pad a number of bytes after
     pad_bytes_after: Option<TokenStream>,
 
+    /// pad to a multiple of a number of bits before
+    align_bits_before: Option<TokenStream>,
+
+    /// pad to a multiple of a number of bytes before
+    align_before: Option<TokenStream>,
+
+    /// pad to a multiple of a number of bits after
+    align_bits_after: Option<TokenStream>,
+
+    /// pad to a multiple of a number of bytes after
+    align_after: Option<TokenStream>,
+
+    /// value written to padding bits
+    pad_fill: Option<Id>,
+
+    /// error when reading padding bits which don't match the fill value
+    pad_strict: bool,
+
+    /// number of reserved bits before the field
+    reserved_bits: Option<TokenStream>,
+
+    /// number of reserved bytes before the field
+    reserved_bytes: Option<TokenStream>,
+
+    /// value of the reserved bits
+    reserved_value: Option<TokenStream>,
+
+    /// error when reading reserved bits which don't match their value
+    reserved_strict: bool,
+
     /// read field as temporary value, isn't stored
     temp: bool,
 
@@ -424,11 +841,29 @@ struct FieldData {
     /// condition to parse field
     cond: Option<TokenStream>,
 
+    /// error when writing the field while `cond` is false and the value isn't the default
+    cond_strict: bool,
+
+    /// first version the field is present in
+    since: Option<TokenStream>,
+
+    /// last version the field is present in
+    until_version: Option<TokenStream>,
+
+    /// container `version`, set for fields using `since` or `until_version`
+    version: Option<TokenStream>,
+
     // assertion on field
     assert: Option<TokenStream>,
 
     // assert value of field
     assert_eq: Option<TokenStream>,
+
+    /// message of a failed assertion, formatted with the fields
+    assert_msg: Option<syn::LitStr>,
+
+    /// user error of a failed assertion
+    assert_err: Option<TokenStream>,
 }
 
 impl FieldData {
@@ -449,7 +884,15 @@ impl FieldData {
             bits_read: receiver.bits_read?,
             bytes_read: receiver.bytes_read?,
             until: receiver.until?,
+            until_exclusive: receiver.until_exclusive?,
+            terminator: receiver.terminator?,
+            window_bytes: receiver.window_bytes?,
+            rest: receiver.rest,
+            at: receiver.at?,
             map: receiver.map?,
+            write_map: receiver.write_map?,
+            as_type: receiver.as_type?,
+            try_from: receiver.try_from,
             ctx,
             update: receiver.update?,
             reader: receiver.reader?,
@@ -459,12 +902,28 @@ impl FieldData {
             pad_bytes_before: receiver.pad_bytes_before?,
             pad_bits_after: receiver.pad_bits_after?,
             pad_bytes_after: receiver.pad_bytes_after?,
+            align_bits_before: receiver.align_bits_before?,
+            align_before: receiver.align_before?,
+            align_bits_after: receiver.align_bits_after?,
+            align_after: receiver.align_after?,
+            pad_fill: receiver.pad_fill,
+            pad_strict: receiver.pad_strict,
+            reserved_bits: receiver.reserved_bits?,
+            reserved_bytes: receiver.reserved_bytes?,
+            reserved_value: receiver.reserved_value?,
+            reserved_strict: receiver.reserved_strict,
             temp: receiver.temp,
             temp_value: receiver.temp_value?,
             default: receiver.default?,
             cond: receiver.cond?,
+            cond_strict: receiver.cond_strict,
+            since: receiver.since?,
+            until_version: receiver.until_version?,
+            version: None,
             assert: receiver.assert?,
             assert_eq: receiver.assert_eq?,
+            assert_msg: receiver.assert_msg,
+            assert_err: receiver.assert_err?,
         };
 
         FieldData::validate(&data)?;
@@ -475,6 +934,13 @@ impl FieldData {
     }
 
     fn validate(data: &FieldData) -> Result<(), TokenStream> {
+        validate_assert_error(
+            data.assert.is_some() || data.assert_eq.is_some(),
+            "`assert` or `assert_eq`",
+            data.assert_msg.as_ref(),
+            data.assert_err.as_ref(),
+        )?;
+
         // Validate either `read_bytes` or `read_bits` is specified
         if data.bits_read.is_some() && data.bytes_read.is_some() {
             return Err(cerror(
@@ -483,6 +949,64 @@ impl FieldData {
             ));
         }
 
+        // Validate `terminator`/`until_exclusive` replace the other limits
+        if let Some(terminator) = data.terminator.as_ref().or(data.until_exclusive.as_ref()) {
+            if data.count.is_some()
+                || data.bits_read.is_some()
+                || data.bytes_read.is_some()
+                || data.until.is_some()
+                || data.reader.is_some()
+                || data.writer.is_some()
+            {
+                return Err(cerror(
+                    terminator.span(),
+                    "conflicting: `terminator` and `until_exclusive` cannot be used with `count`, `bits_read`, `bytes_read`, `until`, `reader` or `writer`",
+                ));
+            }
+        }
+        if data.until_exclusive.is_some() && data.terminator.is_none() {
+            return Err(cerror(
+                data.until_exclusive.span(),
+                "`until_exclusive` must be used with `terminator`, the value written after the elements",
+            ));
+        }
+
+        // Validate `rest` is used with `window_bytes` and names a handling
+        if let Some(rest) = &data.rest {
+            if data.window_bytes.is_none() {
+                return Err(cerror(
+                    rest.span(),
+                    "`rest` must be used with `window_bytes`",
+                ));
+            }
+            if !matches!(rest.value().as_str(), "skip" | "error" | "keep") {
+                return Err(cerror(
+                    rest.span(),
+                    "`rest` must be one of \"skip\", \"error\" or \"keep\"",
+                ));
+            }
+        }
+
+        // Validate `at` isn't used with attributes moving the cursor
+        if data.at.is_some()
+            && (data.window_bytes.is_some()
+                || data.pad_bits_before.is_some()
+                || data.pad_bytes_before.is_some()
+                || data.pad_bits_after.is_some()
+                || data.pad_bytes_after.is_some()
+                || data.align_bits_before.is_some()
+                || data.align_before.is_some()
+                || data.align_bits_after.is_some()
+                || data.align_after.is_some()
+                || data.reserved_bits.is_some()
+                || data.reserved_bytes.is_some())
+        {
+            return Err(cerror(
+                data.at.span(),
+                "conflicting: `at` cannot be used with `window_bytes`, padding, alignment or reserved bits",
+            ));
+        }
+
         // Validate either `count` or `bits_read`/`bytes_read` is specified
         if data.count.is_some() && (data.bits_read.is_some() || data.bytes_read.is_some()) {
             if data.bits_read.is_some() {
@@ -507,8 +1031,99 @@ impl FieldData {
             ));
         }
 
+        // Validate either `align_bits_*` or `align_*` is specified
+        if data.align_bits_before.is_some() && data.align_before.is_some() {
+            return Err(cerror(
+                data.align_bits_before.span(),
+                "conflicting: both `align_bits_before` and `align_before` specified on field",
+            ));
+        }
+        if data.align_bits_after.is_some() && data.align_after.is_some() {
+            return Err(cerror(
+                data.align_bits_after.span(),
+                "conflicting: both `align_bits_after` and `align_after` specified on field",
+            ));
+        }
+
+        validate_pad_fill(data.pad_fill.as_ref())?;
+
+        // Validate either `reserved_bits` or `reserved_bytes` is specified
+        if data.reserved_bits.is_some() && data.reserved_bytes.is_some() {
+            return Err(cerror(
+                data.reserved_bits.span(),
+                "conflicting: both `reserved_bits` and `reserved_bytes` specified on field",
+            ));
+        }
+
+        // Validate `reserved_*` used with a reserved range
+        if data.reserved_bits.is_none() && data.reserved_bytes.is_none() {
+            if data.reserved_value.is_some() {
+                return Err(cerror(
+                    data.reserved_value.span(),
+                    "`reserved_value` must be used with `reserved_bits` or `reserved_bytes`",
+                ));
+            }
+            if data.reserved_strict {
+                return Err(cerror(
+                    data.ident.span(),
+                    "`reserved_strict` must be used with `reserved_bits` or `reserved_bytes`",
+                ));
+            }
+        }
+
+        // Validate either `write_map` or `writer` is specified
+        if data.write_map.is_some() && data.writer.is_some() {
+            return Err(cerror(
+                data.write_map.span(),
+                "conflicting: both `write_map` and `writer` specified on field",
+            ));
+        }
+
+        // Validate `as_type` isn't used with other conversions
+        if data.as_type.is_some() {
+            for (attr, name) in [
+                (&data.map, "map"),
+                (&data.write_map, "write_map"),
+                (&data.reader, "reader"),
+                (&data.writer, "writer"),
+            ] {
+                if attr.is_some() {
+                    return Err(cerror(
+                        data.as_type.span(),
+                        &format!("conflicting: both `as_type` and `{name}` specified on field"),
+                    ));
+                }
+            }
+        } else if data.try_from {
+            return Err(cerror(
+                data.ident.span(),
+                "`try_from` must be used with `as_type`",
+            ));
+        }
+
+        // Validate usage of `cond_strict`
+        if data.cond_strict && (data.cond.is_none() || data.skip) {
+            return Err(cerror(
+                data.ident.span(),
+                "`cond_strict` must be used with `cond` and without `skip`",
+            ));
+        }
+
+        // Validate usage of `since`/`until_version`
+        if data.skip && (data.since.is_some() || data.until_version.is_some()) {
+            return Err(cerror(
+                data.ident.span(),
+                "conflicting: `skip` cannot be used with `since` or `until_version`",
+            ));
+        }
+
         // Validate usage of `default` attribute
-        if data.default.is_some() && (!data.skip && data.cond.is_none()) {
+        if data.default.is_some()
+            && (!data.skip
+                && data.cond.is_none()
+                && data.since.is_none()
+                && data.until_version.is_none())
+        {
             // FIXME: Use `Span::join` once out of nightly
             return Err(cerror(
                 data.default.span(),
@@ -519,6 +1134,38 @@ impl FieldData {
         Ok(())
     }
 
+    /// Handling of the unused bytes of `window_bytes`, defaults to `error`
+    fn window_rest(&self) -> String {
+        self.rest
+            .as_ref()
+            .map_or_else(|| "error".to_string(), |rest| rest.value())
+    }
+
+    /// Condition on the container `version` from `since`/`until_version`
+    fn version_cond(&self) -> Option<TokenStream> {
+        let version = self.version.as_ref()?;
+        let since = self
+            .since
+            .as_ref()
+            .map(|since| quote! { (#version) >= (#since) });
+        let until = self
+            .until_version
+            .as_ref()
+            .map(|until| quote! { (#version) <= (#until) });
+        match (since, until) {
+            (Some(since), Some(until)) => Some(quote! { #since && #until }),
+            (since, until) => since.or(until),
+        }
+    }
+
+    /// Condition to read the field: `cond` combined with the version condition
+    fn read_cond(&self) -> Option<TokenStream> {
+        match (self.version_cond(), &self.cond) {
+            (Some(version_cond), Some(cond)) => Some(quote! { (#version_cond) && (#cond) }),
+            (version_cond, cond) => version_cond.or_else(|| cond.clone()),
+        }
+    }
+
     /// Get ident of the field
     /// `index` is provided in the case of un-named structs
     /// `prefix` is true in the case of variable declarations, false if original field is desired
@@ -528,6 +1175,52 @@ impl FieldData {
     }
 }
 
+/// Index of the field holding the offset of the field `f` with `at`, filled in when writing
+///
+/// `at` has to name a field of the same struct/variant which is always written as-is
+fn at_offset_field(fields: &[&FieldData], f: &FieldData) -> Option<usize> {
+    let at = f.at.as_ref()?.to_string();
+    fields.iter().enumerate().position(|(i, offset_field)| {
+        offset_field.get_ident(i, true).to_string() == at
+            && offset_field.at.is_none()
+            && offset_field.writer.is_none()
+            && offset_field.write_map.is_none()
+            && offset_field.as_type.is_none()
+            && offset_field.terminator.is_none()
+            && offset_field.window_bytes.is_none()
+            && offset_field.ctx.is_none()
+            && offset_field.cond.is_none()
+            && offset_field.since.is_none()
+            && offset_field.until_version.is_none()
+            && !offset_field.temp
+            && !offset_field.skip
+    })
+}
+
+/// Validate fields with `at` don't share the field holding their offset
+fn validate_at_offset_fields(fields: &[&FieldData]) -> Result<(), TokenStream> {
+    let mut seen: Vec<(usize, TokenStream)> = Vec::new();
+    for (i, f) in fields.iter().enumerate() {
+        if let Some(offset_field) = at_offset_field(fields, f) {
+            let ident = f.get_ident(i, false);
+            if let Some((_, other)) = seen.iter().find(|(v, _)| *v == offset_field) {
+                return Err(cerror(
+                    f.at.span(),
+                    &format!(
+                        "conflicting: `at` fields `{}` and `{}` both use the offset field `{}`",
+                        other,
+                        ident,
+                        fields[offset_field].get_ident(offset_field, false)
+                    ),
+                ));
+            }
+            seen.push((offset_field, ident));
+        }
+    }
+
+    Ok(())
+}
+
 /// A post-processed version of `VariantReceiver`
 #[derive(Debug)]
 struct VariantData {
@@ -549,18 +1242,44 @@ struct VariantData {
 
     /// variant `default` option
     default: Option<bool>,
+
+    /// variant capturing unknown ids, and optionally the remaining bytes
+    unknown: bool,
+
+    /// variant magic value, following the `id`
+    magic: Option<syn::LitByteStr>,
+
+    /// default endianness of the variant fields
+    endian: Option<syn::LitStr>,
+
+    /// context bindings available to the variant fields, as `name = expr`
+    ctx: Option<Punctuated<syn::Expr, syn::token::Comma>>,
 }
 
 impl VariantData {
     fn from_receiver(receiver: DekuVariantReceiver) -> Result<Self, TokenStream> {
+        let ctx = receiver
+            .ctx?
+            .map(|s| s.parse_with(Punctuated::parse_terminated))
+            .transpose()
+            .map_err(|e| e.to_compile_error())?;
+        let endian = receiver.endian;
+
+        // variant `endian` applies to fields which don't specify their own
         let fields = ast::Fields::new(
             receiver.fields.style,
             receiver
                 .fields
                 .fields
                 .into_iter()
-                .map(FieldData::from_receiver)
-                .collect::<Result<Vec<_>, _>>()?,
+                .map(|f| {
+                    let mut f = FieldData::from_receiver(f)?;
+                    if f.endian.is_none() {
+                        f.endian = endian.clone();
+                    }
+                    Ok(f)
+                })
+                .collect::<Result<Vec<_>, TokenStream>>()?,
         );
 
         let ret = Self {
@@ -572,6 +1291,10 @@ impl VariantData {
             id: receiver.id,
             id_pat: receiver.id_pat?,
             default: receiver.default,
+            unknown: receiver.unknown,
+            magic: receiver.magic,
+            endian,
+            ctx,
         };
 
         VariantData::validate(&ret)?;
@@ -579,6 +1302,21 @@ impl VariantData {
         Ok(ret)
     }
 
+    /// Variant `ctx` bindings as `(name, expr)`
+    fn ctx_bindings(&self) -> Vec<(&syn::Ident, &syn::Expr)> {
+        self.ctx
+            .iter()
+            .flatten()
+            .filter_map(|binding| match binding {
+                syn::Expr::Assign(syn::ExprAssign { left, right, .. }) => match left.as_ref() {
+                    syn::Expr::Path(path) => path.path.get_ident().map(|ident| (ident, &**right)),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect()
+    }
+
     fn validate(data: &VariantData) -> Result<(), TokenStream> {
         if data.id.is_some() && data.id_pat.is_some() {
             // FIXME: Use `Span::join` once out of nightly
@@ -597,22 +1335,93 @@ impl VariantData {
             }
         }
 
+        if data.unknown {
+            if data.id.is_some() || data.id_pat.is_some() || data.default.is_some() {
+                return Err(cerror(
+                    data.ident.span(),
+                    "conflicting: `unknown` cannot be used with `id`, `id_pat` or `default`",
+                ));
+            }
+            if data.reader.is_some() || data.writer.is_some() {
+                return Err(cerror(
+                    data.ident.span(),
+                    "conflicting: `unknown` cannot be used with `reader` or `writer`",
+                ));
+            }
+            if !matches!(data.fields.len(), 1 | 2) {
+                return Err(cerror(
+                    data.ident.span(),
+                    "`unknown` variant must have a field for the id and optionally one for the remaining bytes",
+                ));
+            }
+        }
+
+        // Validate `magic` can be placed after the `id`
+        if data.magic.is_some() {
+            if data.unknown || data.reader.is_some() || data.writer.is_some() {
+                return Err(cerror(
+                    data.magic.span(),
+                    "conflicting: `magic` cannot be used with `unknown`, `reader` or `writer`",
+                ));
+            }
+            if data.id_pat.is_some() {
+                return Err(cerror(
+                    data.magic.span(),
+                    "conflicting: `magic` cannot be used with `id_pat`, the id is read by the variant fields",
+                ));
+            }
+        }
+
+        // Validate `ctx` is a list of bindings, not shadowing the variant fields
+        if let Some(ctx) = &data.ctx {
+            if data.unknown || data.reader.is_some() || data.writer.is_some() {
+                return Err(cerror(
+                    ctx.span(),
+                    "conflicting: `ctx` cannot be used with `unknown`, `reader` or `writer`",
+                ));
+            }
+            if data.ctx_bindings().len() != ctx.len() {
+                return Err(cerror(
+                    ctx.span(),
+                    "variant `ctx` must be a list of bindings, e.g. `ctx = \"a = 1, b = 2\"`",
+                ));
+            }
+            for (i, f) in data.fields.iter().enumerate() {
+                let field_ident = f.get_ident(i, true).to_string();
+                if let Some((ident, _)) = data
+                    .ctx_bindings()
+                    .into_iter()
+                    .find(|(ident, _)| ident.to_string() == field_ident)
+                {
+                    return Err(cerror(
+                        ident.span(),
+                        "variant `ctx` binding cannot have the name of a field of the variant",
+                    ));
+                }
+            }
+        }
+
         Ok(())
     }
 }
 
 /// Receiver for the top-level struct or enum
 #[derive(Debug, FromDeriveInput)]
-#[darling(attributes(deku), supports(struct_any, enum_any))]
+#[darling(attributes(deku), forward_attrs(repr), supports(struct_any, enum_any))]
 struct DekuReceiver {
     ident: syn::Ident,
     generics: syn::Generics,
     data: ast::Data<DekuVariantReceiver, DekuFieldReceiver>,
+    attrs: Vec<syn::Attribute>,
 
     /// Endianness for all fields
     #[darling(default)]
     endian: Option<syn::LitStr>,
 
+    /// field selecting the endianness of the fields after it
+    #[darling(default)]
+    endian_from: Option<syn::LitStr>,
+
     /// top-level context, argument list
     #[darling(default)]
     ctx: Option<syn::punctuated::Punctuated<syn::FnArg, syn::token::Comma>>,
@@ -625,6 +1434,50 @@ struct DekuReceiver {
     #[darling(default)]
     magic: Option<syn::LitByteStr>,
 
+    /// assertion on the fields, checked after reading and before writing
+    #[darling(default = "default_res_opt", map = "map_litstr_as_tokenstream")]
+    assert: Result<Option<TokenStream>, ReplacementError>,
+
+    /// function validating the value, checked after reading and before writing
+    #[darling(default = "default_res_opt", map = "map_litstr_as_tokenstream")]
+    validate: Result<Option<TokenStream>, ReplacementError>,
+
+    /// message of a failed `assert` or `validate`, formatted with the fields
+    #[darling(default)]
+    assert_msg: Option<syn::LitStr>,
+
+    /// user error of a failed `assert` or `validate`
+    #[darling(default = "default_res_opt", map = "map_litstr_as_tokenstream")]
+    assert_err: Result<Option<TokenStream>, ReplacementError>,
+
+    /// version of the data, compared against the `since`/`until_version` of the fields
+    #[darling(default = "default_res_opt", map = "map_litstr_as_tokenstream")]
+    version: Result<Option<TokenStream>, ReplacementError>,
+
+    /// pad the end of the struct/enum to a multiple of this many bytes
+    #[darling(default = "default_res_opt", map = "map_litstr_as_tokenstream")]
+    align: Result<Option<TokenStream>, ReplacementError>,
+
+    /// alignment is relative to the input instead of the start of the struct/enum
+    #[darling(default)]
+    align_input: bool,
+
+    /// default value written to padding bits
+    #[darling(default)]
+    pad_fill: Option<Id>,
+
+    /// default to erroring when reading padding bits which don't match the fill value
+    #[darling(default)]
+    pad_strict: bool,
+
+    /// default to erroring when reading reserved bits which don't match their value
+    #[darling(default)]
+    reserved_strict: bool,
+
+    /// default to erroring when writing a field whose `cond` is false and value isn't the default
+    #[darling(default)]
+    cond_strict: bool,
+
     /// enum only: `id` value
     #[darling(default)]
     id: Option<Id>,
@@ -637,6 +1490,14 @@ struct DekuReceiver {
     )]
     id_type: Result<Option<TokenStream>, ReplacementError>,
 
+    /// enum only: custom reader code for the enum `id`
+    #[darling(default = "default_res_opt", map = "map_litstr_as_tokenstream")]
+    id_reader: Result<Option<TokenStream>, ReplacementError>,
+
+    /// enum only: custom writer code for the enum `id`
+    #[darling(default = "default_res_opt", map = "map_litstr_as_tokenstream")]
+    id_writer: Result<Option<TokenStream>, ReplacementError>,
+
     /// enum only: bit size of the enum `id`
     #[darling(default)]
     bits: Option<Num>,
@@ -661,15 +1522,79 @@ fn apply_replacements(input: &syn::LitStr) -> Result<Cow<'_, syn::LitStr>, Repla
         )).map_err(|e| e.with_span(&input).write_errors());
     }
 
-    let input_str = input_value
-        .replace("deku::input", "__deku_input") // part of the public API `from_bytes`
-        .replace("deku::input_bits", "__deku_input_bits") // part of the public API `read`
-        .replace("deku::output", "__deku_output") // part of the public API `write`
-        .replace("deku::rest", "__deku_rest")
-        .replace("deku::bit_offset", "__deku_bit_offset")
-        .replace("deku::byte_offset", "__deku_byte_offset");
+    let tokens: TokenStream = input.parse().map_err(|e| e.to_compile_error())?;
+    let replaced = replace_internal_paths(tokens.clone()).to_string();
+    if replaced == tokens.to_string() {
+        return Ok(Cow::Borrowed(input));
+    }
+
+    Ok(Cow::Owned(syn::LitStr::new(&replaced, input.span())))
+}
+
+/// Replaces the `deku::` paths to internal variables, e.g. `deku::rest`, in `input`
+fn replace_internal_paths(input: TokenStream) -> TokenStream {
+    use proc_macro2::{Group, TokenTree};
+
+    let tokens: Vec<TokenTree> = input.into_iter().collect();
+    let mut output = Vec::with_capacity(tokens.len());
+    let mut i = 0;
+    while i < tokens.len() {
+        // `deku::name`, not part of a longer path
+        let in_path = i > 0 && matches!(&tokens[i - 1], TokenTree::Punct(p) if p.as_char() == ':');
+        let internal = match &tokens[i..] {
+            [TokenTree::Ident(deku), sep1, sep2, TokenTree::Ident(name), ..]
+                if !in_path && deku == "deku" && is_path_sep(sep1, sep2) =>
+            {
+                internal_ident(name)
+            }
+            _ => None,
+        };
+
+        if let Some(internal) = internal {
+            output.push(TokenTree::Ident(internal));
+            i += 4;
+            continue;
+        }
+
+        match &tokens[i] {
+            TokenTree::Group(group) => {
+                let mut new_group =
+                    Group::new(group.delimiter(), replace_internal_paths(group.stream()));
+                new_group.set_span(group.span());
+                output.push(TokenTree::Group(new_group));
+            }
+            token => output.push(token.clone()),
+        }
+        i += 1;
+    }
+
+    output.into_iter().collect()
+}
+
+/// Returns true if the tokens are a `::`
+fn is_path_sep(first: &proc_macro2::TokenTree, second: &proc_macro2::TokenTree) -> bool {
+    use proc_macro2::{Spacing, TokenTree};
+
+    matches!(
+        (first, second),
+        (TokenTree::Punct(a), TokenTree::Punct(b))
+            if a.as_char() == ':' && a.spacing() == Spacing::Joint && b.as_char() == ':'
+    )
+}
 
-    Ok(Cow::Owned(syn::LitStr::new(&input_str, input.span())))
+/// Internal variable of `deku::name`
+fn internal_ident(name: &proc_macro2::Ident) -> Option<proc_macro2::Ident> {
+    let internal = match name.to_string().as_str() {
+        "input" => "__deku_input",           // part of the public API `from_bytes`
+        "input_bits" => "__deku_input_bits", // part of the public API `read`
+        "output" => "__deku_output",         // part of the public API `write`
+        "rest" => "__deku_rest",
+        "id" => "__deku_variant_id",
+        "bit_offset" => "__deku_bit_offset",
+        "byte_offset" => "__deku_byte_offset",
+        _ => return None,
+    };
+    Some(proc_macro2::Ident::new(internal, name.span()))
 }
 
 /// Calls apply replacements on Option<LitStr>
@@ -756,10 +1681,42 @@ struct DekuFieldReceiver {
     #[darling(default = "default_res_opt", map = "map_litstr_as_tokenstream")]
     until: Result<Option<TokenStream>, ReplacementError>,
 
+    /// like `until`, but the element matching the predicate isn't stored
+    #[darling(default = "default_res_opt", map = "map_litstr_as_tokenstream")]
+    until_exclusive: Result<Option<TokenStream>, ReplacementError>,
+
+    /// element ending the container, consumed on read and written after the elements
+    #[darling(default = "default_res_opt", map = "map_litstr_as_tokenstream")]
+    terminator: Result<Option<TokenStream>, ReplacementError>,
+
+    /// number of bytes the field is read from and written to
+    #[darling(default = "default_res_opt", map = "map_litstr_as_tokenstream")]
+    window_bytes: Result<Option<TokenStream>, ReplacementError>,
+
+    /// handling of the bytes of the window not used by the field: `skip`, `error` or `keep`
+    #[darling(default)]
+    rest: Option<syn::LitStr>,
+
+    /// byte offset of the field from the start of the input, read without moving the cursor
+    #[darling(default = "default_res_opt", map = "map_litstr_as_tokenstream")]
+    at: Result<Option<TokenStream>, ReplacementError>,
+
     /// apply a function to the field after it's read
     #[darling(default = "default_res_opt", map = "map_litstr_as_tokenstream")]
     map: Result<Option<TokenStream>, ReplacementError>,
 
+    /// apply a function to the field before it's written
+    #[darling(default = "default_res_opt", map = "map_litstr_as_tokenstream")]
+    write_map: Result<Option<TokenStream>, ReplacementError>,
+
+    /// on-wire type the field is converted from/into
+    #[darling(default = "default_res_opt", map = "map_litstr_as_tokenstream")]
+    as_type: Result<Option<TokenStream>, ReplacementError>,
+
+    /// convert with `TryFrom`/`TryInto` instead of `From`/`Into`
+    #[darling(default)]
+    try_from: bool,
+
     /// context passed to the field.
     /// A comma separated argument list.
     // TODO: The type of it should be `Punctuated<Expr, Comma>`
@@ -799,6 +1756,46 @@ struct DekuFieldReceiver {
     #[darling(default = "default_res_opt", map = "map_litstr_as_tokenstream")]
     pad_bytes_after: Result<Option<TokenStream>, ReplacementError>,
 
+    /// pad to a multiple of a number of bits before
+    #[darling(default = "default_res_opt", map = "map_litstr_as_tokenstream")]
+    align_bits_before: Result<Option<TokenStream>, ReplacementError>,
+
+    /// pad to a multiple of a number of bytes before
+    #[darling(default = "default_res_opt", map = "map_litstr_as_tokenstream")]
+    align_before: Result<Option<TokenStream>, ReplacementError>,
+
+    /// pad to a multiple of a number of bits after
+    #[darling(default = "default_res_opt", map = "map_litstr_as_tokenstream")]
+    align_bits_after: Result<Option<TokenStream>, ReplacementError>,
+
+    /// pad to a multiple of a number of bytes after
+    #[darling(default = "default_res_opt", map = "map_litstr_as_tokenstream")]
+    align_after: Result<Option<TokenStream>, ReplacementError>,
+
+    /// value written to padding bits
+    #[darling(default)]
+    pad_fill: Option<Id>,
+
+    /// error when reading padding bits which don't match the fill value
+    #[darling(default)]
+    pad_strict: bool,
+
+    /// number of reserved bits before the field
+    #[darling(default = "default_res_opt", map = "map_litstr_as_tokenstream")]
+    reserved_bits: Result<Option<TokenStream>, ReplacementError>,
+
+    /// number of reserved bytes before the field
+    #[darling(default = "default_res_opt", map = "map_litstr_as_tokenstream")]
+    reserved_bytes: Result<Option<TokenStream>, ReplacementError>,
+
+    /// value of the reserved bits
+    #[darling(default = "default_res_opt", map = "map_litstr_as_tokenstream")]
+    reserved_value: Result<Option<TokenStream>, ReplacementError>,
+
+    /// error when reading reserved bits which don't match their value
+    #[darling(default)]
+    reserved_strict: bool,
+
     /// read field as temporary value, isn't stored
     #[darling(default)]
     temp: bool,
@@ -815,6 +1812,18 @@ struct DekuFieldReceiver {
     #[darling(default = "default_res_opt", map = "map_litstr_as_tokenstream")]
     cond: Result<Option<TokenStream>, ReplacementError>,
 
+    /// error when writing the field while `cond` is false and the value isn't the default
+    #[darling(default)]
+    cond_strict: bool,
+
+    /// first version the field is present in
+    #[darling(default = "default_res_opt", map = "map_litstr_as_tokenstream")]
+    since: Result<Option<TokenStream>, ReplacementError>,
+
+    /// last version the field is present in
+    #[darling(default = "default_res_opt", map = "map_litstr_as_tokenstream")]
+    until_version: Result<Option<TokenStream>, ReplacementError>,
+
     // assertion on field
     #[darling(default = "default_res_opt", map = "map_litstr_as_tokenstream")]
     assert: Result<Option<TokenStream>, ReplacementError>,
@@ -822,6 +1831,14 @@ struct DekuFieldReceiver {
     // assert value of field
     #[darling(default = "default_res_opt", map = "map_litstr_as_tokenstream")]
     assert_eq: Result<Option<TokenStream>, ReplacementError>,
+
+    /// message of a failed assertion, formatted with the fields
+    #[darling(default)]
+    assert_msg: Option<syn::LitStr>,
+
+    /// user error of a failed assertion
+    #[darling(default = "default_res_opt", map = "map_litstr_as_tokenstream")]
+    assert_err: Result<Option<TokenStream>, ReplacementError>,
 }
 
 /// Receiver for the variant-level attributes inside a enum
@@ -851,6 +1868,22 @@ struct DekuVariantReceiver {
     /// variant `id` value
     #[darling(default)]
     default: Option<bool>,
+
+    /// variant capturing unknown ids
+    #[darling(default)]
+    unknown: bool,
+
+    /// variant magic value
+    #[darling(default)]
+    magic: Option<syn::LitByteStr>,
+
+    /// default endianness of the variant fields
+    #[darling(default)]
+    endian: Option<syn::LitStr>,
+
+    /// default context passed to the variant fields
+    #[darling(default = "default_res_opt", map = "map_option_litstr")]
+    ctx: Result<Option<syn::LitStr>, ReplacementError>,
 }
 
 /// Entry function for `DekuRead` proc-macro
@@ -871,6 +1904,15 @@ pub fn proc_deku_write(input: proc_macro::TokenStream) -> proc_macro::TokenStrea
     }
 }
 
+/// Entry function for `DekuSchema` proc-macro
+#[proc_macro_derive(DekuSchema, attributes(deku))]
+pub fn proc_deku_schema(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    match DekuData::from_input(input.into()) {
+        Ok(data) => data.emit_schema().into(),
+        Err(err) => err.into(),
+    }
+}
+
 fn is_not_deku(attr: &syn::Attribute) -> bool {
     attr.path
         .get_ident()
@@ -925,6 +1967,8 @@ struct DekuDerive {
     read: bool,
     #[darling(default, rename = "DekuWrite")]
     write: bool,
+    #[darling(default, rename = "DekuSchema")]
+    schema: bool,
 }
 
 /// Entry function for `deku_derive` proc-macro
@@ -957,6 +2001,13 @@ pub fn deku_derive(
         TokenStream::new()
     };
 
+    // Generate `DekuSchema` impl
+    let schema_impl = if args.schema {
+        data.emit_schema()
+    } else {
+        TokenStream::new()
+    };
+
     // Remove the temp fields
     let mut input = syn::parse_macro_input!(item as syn::DeriveInput);
 
@@ -999,6 +2050,8 @@ pub fn deku_derive(
 
         #write_impl
 
+        #schema_impl
+
         #input
     )
     .into()
@@ -1010,6 +2063,21 @@ mod tests {
     use rstest::rstest;
     use syn::parse_str;
 
+    #[rstest(input, expected,
+        case("deku::rest.len()", "__deku_rest . len ()"),
+        case("deku::input_bits", "__deku_input_bits"),
+        case("foo(deku::id, deku::byte_offset)", "foo (__deku_variant_id , __deku_byte_offset)"),
+        // not an internal variable
+        case("deku::identity(deku::ctx::Endian::Big)", "deku::identity(deku::ctx::Endian::Big)"),
+        case("deku::identity(deku::id)", "deku :: identity (__deku_variant_id)"),
+        case("::deku::rest", "::deku::rest"),
+        case("\"deku::rest\"", "\"deku::rest\""),
+    )]
+    fn test_apply_replacements(input: &str, expected: &str) {
+        let input = syn::LitStr::new(input, proc_macro2::Span::call_site());
+        assert_eq!(expected, apply_replacements(&input).unwrap().value());
+    }
+
     #[rstest(input,
         // Valid struct
         case::struct_empty(r#"struct Test {}"#),
@@ -1060,7 +2128,10 @@ mod tests {
         let res_reader = data.emit_reader_checked();
         let res_writer = data.emit_writer_checked();
 
+        let res_schema = data.emit_schema_checked();
+
         res_reader.unwrap();
         res_writer.unwrap();
+        res_schema.unwrap();
     }
 }
@@ -1,8 +1,10 @@
 use crate::macros::{
-    gen_ctx_types_and_arg, gen_field_args, gen_internal_field_ident, gen_internal_field_idents,
-    gen_type_from_ctx_id, pad_bits, token_contains_string, wrap_default_ctx,
+    align_bits, container_endian, emit_container_assert, emit_container_validate, emit_endian_from,
+    gen_assert_error, gen_ctx_types_and_arg, gen_field_args, gen_internal_field_ident,
+    gen_internal_field_idents, gen_type_from_ctx_id, needs_align_start, pad_bits, reserved_bits,
+    token_contains_string, wrap_default_ctx, Padding,
 };
-use crate::{DekuData, DekuDataEnum, DekuDataStruct, FieldData, Id};
+use crate::{DekuData, DekuDataEnum, DekuDataStruct, FieldData, Id, VariantData};
 use darling::{
     ast::{Data, Fields},
     ToTokens,
@@ -36,7 +38,10 @@ fn emit_struct(input: &DekuData) -> Result<TokenStream, syn::Error> {
         fields,
     } = DekuDataStruct::try_from(input)?;
 
-    let magic_read = emit_magic_read(input);
+    let magic_read = emit_magic_read(input.magic.as_ref());
+    let (align_start, align_end) = emit_align_start_end(input);
+    let container_assert = emit_container_assert(input);
+    let container_validate = emit_container_validate(input, quote! { &__deku_value });
 
     // check if the first field has an ident, if not, it's a unnamed struct
     let is_named_struct = fields
@@ -65,14 +70,21 @@ fn emit_struct(input: &DekuData) -> Result<TokenStream, syn::Error> {
                 use ::#crate_::bitvec::BitView;
                 let __deku_input_bits = __deku_input.0.view_bits::<::#crate_::bitvec::Msb0>();
 
+                let __deku_layout = &::#crate_::layout::ReadLayout::new(__deku_input_bits);
                 let mut __deku_rest = __deku_input_bits;
                 __deku_rest = &__deku_rest[__deku_input.1..];
+                #align_start
 
                 #magic_read
 
                 #(#field_reads)*
+                #align_end
+                #container_assert
                 let __deku_value = #initialize_struct;
+                #container_validate
 
+                // the data read at an offset is read too
+                let __deku_rest = __deku_layout.rest(__deku_rest);
                 let __deku_pad = 8 * ((__deku_rest.len() + 7) / 8) - __deku_rest.len();
                 let __deku_read_idx = __deku_input_bits.len() - (__deku_rest.len() + __deku_pad);
 
@@ -98,33 +110,39 @@ fn emit_struct(input: &DekuData) -> Result<TokenStream, syn::Error> {
     let read_body = quote! {
         use core::convert::TryFrom;
         let mut __deku_rest = __deku_input_bits;
+        #align_start
 
         #magic_read
 
         #(#field_reads)*
+        #align_end
+        #container_assert
         let __deku_value = #initialize_struct;
+        #container_validate
 
         Ok((__deku_rest, __deku_value))
     };
 
-    tokens.extend(quote! {
-        impl #imp ::#crate_::DekuRead<#lifetime, #ctx_types> for #ident #wher {
-            fn read(__deku_input_bits: &#lifetime ::#crate_::bitvec::BitSlice<u8, ::#crate_::bitvec::Msb0>, #ctx_arg) -> core::result::Result<(&#lifetime ::#crate_::bitvec::BitSlice<u8, ::#crate_::bitvec::Msb0>, Self), ::#crate_::DekuError> {
-                #read_body
-            }
-        }
-    });
+    tokens.extend(emit_read_impl(
+        input,
+        &imp,
+        &ident,
+        wher,
+        (&ctx_types, &ctx_arg),
+        &read_body,
+    ));
 
     if input.ctx.is_some() && input.ctx_default.is_some() {
         let read_body = wrap_default_ctx(read_body, &input.ctx, &input.ctx_default);
 
-        tokens.extend(quote! {
-            impl #imp ::#crate_::DekuRead<#lifetime> for #ident #wher {
-                fn read(__deku_input_bits: &#lifetime ::#crate_::bitvec::BitSlice<u8, ::#crate_::bitvec::Msb0>, _: ()) -> core::result::Result<(&#lifetime ::#crate_::bitvec::BitSlice<u8, ::#crate_::bitvec::Msb0>, Self), ::#crate_::DekuError> {
-                    #read_body
-                }
-            }
-        });
+        tokens.extend(emit_read_impl(
+            input,
+            &imp,
+            &ident,
+            wher,
+            (&quote! { () }, &quote! { _: () }),
+            &read_body,
+        ));
     }
 
     // println!("{}", tokens.to_string());
@@ -153,15 +171,22 @@ fn emit_enum(input: &DekuData) -> Result<TokenStream, syn::Error> {
 
     let ident_as_string = ident.to_string();
 
-    let magic_read = emit_magic_read(input);
+    let magic_read = emit_magic_read(input.magic.as_ref());
+    let (align_start, align_end) = emit_align_start_end(input);
 
     let mut has_default_match = false;
     let mut default_reader = None;
     let mut pre_match_tokens = Vec::with_capacity(variants.len());
     let mut variant_matches = Vec::with_capacity(variants.len());
+    let mut unknown_match = None;
     let mut deku_ids = Vec::with_capacity(variants.len());
 
     let has_discriminant = variants.iter().any(|v| v.discriminant.is_some());
+    // discriminants have the type of the `#[repr(...)]`, `isize` if none is given
+    let discriminant_type = input
+        .repr
+        .as_ref()
+        .map_or(quote!(isize), |repr| quote!(#repr));
 
     for variant in variants {
         // check if the first field has an ident, if not, it's a unnamed struct
@@ -172,7 +197,9 @@ fn emit_enum(input: &DekuData) -> Result<TokenStream, syn::Error> {
             .and_then(|v| v.ident.as_ref())
             .is_some();
 
-        let (consume_id, variant_id) = if let Some(variant_id) = &variant.id {
+        let (consume_id, variant_id) = if variant.unknown {
+            (true, quote! { _ })
+        } else if let Some(variant_id) = &variant.id {
             match variant_id {
                 Id::TokenStream(v) => (true, quote! {&#v}.into_token_stream()),
                 Id::LitByteStr(v) => (true, v.into_token_stream()),
@@ -183,7 +210,7 @@ fn emit_enum(input: &DekuData) -> Result<TokenStream, syn::Error> {
             let ident = &variant.ident;
             let internal_ident = gen_internal_field_ident(&quote!(#ident));
             pre_match_tokens.push(quote! {
-                let #internal_ident = <#id_type>::try_from(Self::#ident as isize)?;
+                let #internal_ident = <#id_type as core::convert::TryFrom<#discriminant_type>>::try_from(Self::#ident as #discriminant_type)?;
             });
             (true, quote! { _ if __deku_variant_id == #internal_ident })
         } else {
@@ -203,6 +230,10 @@ fn emit_enum(input: &DekuData) -> Result<TokenStream, syn::Error> {
 
         let variant_read_func = if variant_reader.is_some() {
             quote! { #variant_reader; }
+        } else if variant.unknown {
+            let (read_unknown, deku_id) = emit_unknown_read(variant, variant_is_named);
+            deku_ids.push(deku_id);
+            read_unknown
         } else {
             let (field_idents, field_reads) =
                 emit_field_reads(input, &variant.fields.as_ref(), &ident)?;
@@ -224,6 +255,11 @@ fn emit_enum(input: &DekuData) -> Result<TokenStream, syn::Error> {
 
                 let deku_id = quote! { Self :: #initialize_enum => Ok(#deref #variant_id)};
                 deku_ids.push(deku_id);
+            } else if variant.id_pat.is_none() && has_discriminant {
+                let deku_id = quote! {
+                    Self :: #initialize_enum => Ok(<#id_type as core::convert::TryFrom<#discriminant_type>>::try_from(Self::#variant_ident as #discriminant_type)?)
+                };
+                deku_ids.push(deku_id);
             }
 
             // if we're consuming an id, set the rest to new_rest before reading the variant
@@ -235,9 +271,14 @@ fn emit_enum(input: &DekuData) -> Result<TokenStream, syn::Error> {
                 quote! {}
             };
 
+            let variant_magic_read = emit_magic_read(variant.magic.as_ref());
+            let variant_ctx = super::emit_variant_ctx(variant);
+
             quote! {
                 {
                     #new_rest
+                    #variant_magic_read
+                    #variant_ctx
                     #(#field_reads)*
                     Self :: #initialize_enum
                 }
@@ -254,12 +295,20 @@ fn emit_enum(input: &DekuData) -> Result<TokenStream, syn::Error> {
             default_reader = Some(variant_read_func.clone())
         }
 
-        variant_matches.push(quote! {
+        let variant_match = quote! {
             #variant_id => {
                 #variant_read_func
             }
-        });
+        };
+
+        // the `unknown` catch-all must not shadow the variants declared after it
+        if variant.unknown {
+            unknown_match = Some(variant_match);
+        } else {
+            variant_matches.push(variant_match);
+        }
     }
+    variant_matches.extend(unknown_match);
 
     // if no default match, return error
     if !has_default_match && default_reader.is_none() {
@@ -291,6 +340,10 @@ fn emit_enum(input: &DekuData) -> Result<TokenStream, syn::Error> {
         quote! {
             let (__deku_new_rest, __deku_variant_id) = (__deku_rest, (#id));
         }
+    } else if let Some(id_reader) = &input.id_reader {
+        quote! {
+            let (__deku_new_rest, __deku_variant_id): (_, #id_type) = #id_reader?;
+        }
     } else if id_type.is_some() {
         quote! {
             let (__deku_new_rest, __deku_variant_id) = <#id_type>::read(__deku_rest, (#id_args))?;
@@ -300,6 +353,8 @@ fn emit_enum(input: &DekuData) -> Result<TokenStream, syn::Error> {
         unreachable!();
     };
 
+    let container_validate = emit_container_validate(input, quote! { &__deku_value });
+
     let variant_read = quote! {
         #variant_id_read
 
@@ -308,6 +363,7 @@ fn emit_enum(input: &DekuData) -> Result<TokenStream, syn::Error> {
         let __deku_value = match &__deku_variant_id {
             #(#variant_matches),*
         };
+        #container_validate
     };
 
     // Implement `DekuContainerRead` for types that don't need a context
@@ -318,13 +374,18 @@ fn emit_enum(input: &DekuData) -> Result<TokenStream, syn::Error> {
                 use ::#crate_::bitvec::BitView;
                 let __deku_input_bits = __deku_input.0.view_bits::<::#crate_::bitvec::Msb0>();
 
+                let __deku_layout = &::#crate_::layout::ReadLayout::new(__deku_input_bits);
                 let mut __deku_rest = __deku_input_bits;
                 __deku_rest = &__deku_rest[__deku_input.1..];
+                #align_start
 
                 #magic_read
 
                 #variant_read
+                #align_end
 
+                // the data read at an offset is read too
+                let __deku_rest = __deku_layout.rest(__deku_rest);
                 let __deku_pad = 8 * ((__deku_rest.len() + 7) / 8) - __deku_rest.len();
                 let __deku_read_idx = __deku_input_bits.len() - (__deku_rest.len() + __deku_pad);
 
@@ -349,33 +410,43 @@ fn emit_enum(input: &DekuData) -> Result<TokenStream, syn::Error> {
     let read_body = quote! {
         use core::convert::TryFrom;
         let mut __deku_rest = __deku_input_bits;
+        #align_start
 
         #magic_read
 
         #variant_read
+        #align_end
 
         Ok((__deku_rest, __deku_value))
     };
 
+    let read_impl = emit_read_impl(
+        input,
+        &imp,
+        &ident,
+        wher,
+        (&ctx_types, &ctx_arg),
+        &read_body,
+    );
     tokens.extend(quote! {
         #[allow(non_snake_case)]
-        impl #imp ::#crate_::DekuRead<#lifetime, #ctx_types> for #ident #wher {
-            fn read(__deku_input_bits: &#lifetime ::#crate_::bitvec::BitSlice<u8, ::#crate_::bitvec::Msb0>, #ctx_arg) -> core::result::Result<(&#lifetime ::#crate_::bitvec::BitSlice<u8, ::#crate_::bitvec::Msb0>, Self), ::#crate_::DekuError> {
-                #read_body
-            }
-        }
+        #read_impl
     });
 
     if input.ctx.is_some() && input.ctx_default.is_some() {
         let read_body = wrap_default_ctx(read_body, &input.ctx, &input.ctx_default);
 
+        let read_impl = emit_read_impl(
+            input,
+            &imp,
+            &ident,
+            wher,
+            (&quote! { () }, &quote! { _: () }),
+            &read_body,
+        );
         tokens.extend(quote! {
             #[allow(non_snake_case)]
-            impl #imp ::#crate_::DekuRead<#lifetime> for #ident #wher {
-                fn read(__deku_input_bits: &#lifetime ::#crate_::bitvec::BitSlice<u8, ::#crate_::bitvec::Msb0>, _: ()) -> core::result::Result<(&#lifetime ::#crate_::bitvec::BitSlice<u8, ::#crate_::bitvec::Msb0>, Self), ::#crate_::DekuError> {
-                    #read_body
-                }
-            }
+            #read_impl
         });
     }
 
@@ -407,9 +478,9 @@ fn emit_enum(input: &DekuData) -> Result<TokenStream, syn::Error> {
     Ok(tokens)
 }
 
-fn emit_magic_read(input: &DekuData) -> TokenStream {
+fn emit_magic_read(magic: Option<&syn::LitByteStr>) -> TokenStream {
     let crate_ = super::get_crate_name();
-    if let Some(magic) = &input.magic {
+    if let Some(magic) = magic {
         quote! {
             let __deku_magic = #magic;
 
@@ -427,6 +498,62 @@ fn emit_magic_read(input: &DekuData) -> TokenStream {
     }
 }
 
+/// Record the start of the struct/enum and pad its end for `align`
+/// `DekuRead` with `read` reading as the outermost input, and `read_in` as a part of it
+fn emit_read_impl(
+    input: &DekuData,
+    imp: &syn::ImplGenerics,
+    ident: &TokenStream,
+    wher: Option<&syn::WhereClause>,
+    (ctx_types, ctx_arg): (&TokenStream, &TokenStream),
+    read_body: &TokenStream,
+) -> TokenStream {
+    let crate_ = super::get_crate_name();
+
+    // `read_in` needs a named lifetime for the input and the layout
+    let (lifetime, read_in_lifetime) = match input.generics.lifetimes().next() {
+        Some(lifetime) => (quote!(#lifetime), quote!()),
+        None => (quote!('__deku), quote!(<'__deku>)),
+    };
+    let read_lifetime = match input.generics.lifetimes().next() {
+        Some(_) => lifetime.clone(),
+        None => quote!('_),
+    };
+
+    quote! {
+        impl #imp ::#crate_::DekuRead<#read_lifetime, #ctx_types> for #ident #wher {
+            fn read(__deku_input_bits: &#read_lifetime ::#crate_::bitvec::BitSlice<u8, ::#crate_::bitvec::Msb0>, __deku_ctx: #ctx_types) -> core::result::Result<(&#read_lifetime ::#crate_::bitvec::BitSlice<u8, ::#crate_::bitvec::Msb0>, Self), ::#crate_::DekuError> {
+                ::#crate_::layout::read(__deku_input_bits, __deku_ctx)
+            }
+
+            fn read_in #read_in_lifetime(__deku_layout: &::#crate_::layout::ReadLayout<#lifetime>, __deku_input_bits: &#lifetime ::#crate_::bitvec::BitSlice<u8, ::#crate_::bitvec::Msb0>, #ctx_arg) -> core::result::Result<(&#lifetime ::#crate_::bitvec::BitSlice<u8, ::#crate_::bitvec::Msb0>, Self), ::#crate_::DekuError> {
+                #read_body
+            }
+        }
+    }
+}
+
+fn emit_align_start_end(input: &DekuData) -> (TokenStream, TokenStream) {
+    // with `align_input`, the offset is from the start of the outermost input instead
+    let align_start = if needs_align_start(input) && !input.align_input {
+        quote! {
+            let __deku_align_start = __deku_rest;
+        }
+    } else {
+        quote! {}
+    };
+
+    let padding = Padding::new(input, None);
+    let align_end = align_bits(
+        None,
+        input.align.as_ref(),
+        input.align_input,
+        |bit_size, align_input| emit_alignment(bit_size, align_input, &padding),
+    );
+
+    (align_start, align_end)
+}
+
 struct FieldIdent {
     field_ident: TokenStream,
     is_temp: bool,
@@ -482,8 +609,9 @@ fn emit_bit_byte_offsets(
     (bit_offset, byte_offset)
 }
 
-fn emit_padding(bit_size: &TokenStream) -> TokenStream {
+fn emit_padding(bit_size: &TokenStream, padding: &Padding) -> TokenStream {
     let crate_ = super::get_crate_name();
+    let skip_padding = emit_skip_padding(padding);
     quote! {
         {
             use core::convert::TryFrom;
@@ -494,11 +622,219 @@ fn emit_padding(bit_size: &TokenStream) -> TokenStream {
                 ))
             )?;
 
-            if __deku_rest.len() >= __deku_pad {
-                let (__deku_padded_bits, __deku_new_rest) = __deku_rest.split_at(__deku_pad);
+            #skip_padding
+        }
+    }
+}
+
+fn emit_alignment(bit_size: &TokenStream, align_input: bool, padding: &Padding) -> TokenStream {
+    let crate_ = super::get_crate_name();
+    // `align_input` aligns against the outermost input, see `ReadLayout::offset`
+    let offset = if align_input {
+        quote! { __deku_layout.offset(__deku_rest)? }
+    } else {
+        quote! { usize::try_from(unsafe { __deku_rest.as_bitptr().offset_from(__deku_align_start.as_bitptr()) } )? }
+    };
+    let skip_padding = emit_skip_padding(padding);
+    quote! {
+        {
+            use core::convert::TryFrom;
+            let __deku_align = usize::try_from(#bit_size).map_err(|e|
+                ::#crate_::DekuError::InvalidParam(format!(
+                    "Invalid alignment param \"({})\": cannot convert to usize",
+                    stringify!(#bit_size)
+                ))
+            )?;
+            if __deku_align == 0 {
+                return Err(::#crate_::DekuError::InvalidParam(format!(
+                    "Invalid alignment param \"({})\": must be greater than 0",
+                    stringify!(#bit_size)
+                )));
+            }
+
+            let __deku_offset = #offset;
+            let __deku_pad = (__deku_align - __deku_offset % __deku_align) % __deku_align;
+
+            #skip_padding
+        }
+    }
+}
+
+/// Skip `__deku_pad` bits, checking them against the fill value in strict mode
+fn emit_skip_padding(padding: &Padding) -> TokenStream {
+    let crate_ = super::get_crate_name();
+
+    let check_padding = if padding.strict {
+        let name = &padding.name;
+        let fill_str = padding.fill_str();
+        let mismatch = match padding.fill_bytes() {
+            Some(fill) => quote! {
+                {
+                    let __deku_fill: &[u8] = #fill;
+                    let __deku_fill_bits = <[u8] as ::#crate_::bitvec::BitView>::view_bits::<::#crate_::bitvec::Msb0>(__deku_fill);
+                    __deku_padded_bits
+                        .iter()
+                        .by_vals()
+                        .enumerate()
+                        .any(|(__deku_i, __deku_bit)| __deku_bit != __deku_fill_bits[__deku_i % __deku_fill_bits.len()])
+                }
+            },
+            None => quote! { __deku_padded_bits.any() },
+        };
+        quote! {
+            if #mismatch {
+                return Err(::#crate_::DekuError::Parse(format!(
+                    "Invalid padding in {}: expected fill {}",
+                    #name,
+                    #fill_str
+                )));
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    quote! {
+        if __deku_rest.len() >= __deku_pad {
+            let (__deku_padded_bits, __deku_new_rest) = __deku_rest.split_at(__deku_pad);
+            #check_padding
+            __deku_rest = __deku_new_rest;
+        } else {
+            return Err(::#crate_::DekuError::Incomplete(::#crate_::error::NeedSize::new(__deku_pad)));
+        }
+    }
+}
+
+/// Read an `unknown` variant: the id already read and optionally the remaining bytes.
+/// Returns the read tokens and the `deku_id` match arm
+fn emit_unknown_read(variant: &VariantData, variant_is_named: bool) -> (TokenStream, TokenStream) {
+    let crate_ = super::get_crate_name();
+    let variant_ident = &variant.ident;
+
+    // dedicated locals, field names could clash with internal variables
+    let locals = [quote! { __deku_unknown_id }, quote! { __deku_unknown_rest }];
+    let fields = variant
+        .fields
+        .iter()
+        .zip(&locals)
+        .enumerate()
+        .map(|(i, (f, local))| {
+            if variant_is_named {
+                let field_ident = f.get_ident(i, false);
+                quote! { #field_ident: #local }
+            } else {
+                local.clone()
+            }
+        });
+    let initialize_enum = super::gen_enum_init(variant_is_named, variant_ident, fields);
+
+    let read_rest = (variant.fields.len() == 2).then(|| {
+        quote! {
+            let (__deku_new_rest, __deku_unknown_rest) = ::#crate_::DekuRead::read(
+                __deku_rest,
+                (::#crate_::ctx::Limit::new_byte_size(::#crate_::ctx::ByteSize(__deku_rest.len() / 8)), ()),
+            )?;
+            __deku_rest = __deku_new_rest;
+        }
+    });
+
+    let read = quote! {
+        {
+            __deku_rest = __deku_new_rest;
+            let __deku_unknown_id = core::clone::Clone::clone(&__deku_variant_id);
+            #read_rest
+            Self :: #initialize_enum
+        }
+    };
+    let deku_id =
+        quote! { Self :: #initialize_enum => Ok(core::clone::Clone::clone(__deku_unknown_id)) };
+
+    (read, deku_id)
+}
+
+/// Conversion from the `as_type` on-wire value to the field type, like a `map` closure
+fn emit_as_type_map(
+    ident: &str,
+    field_ident_str: &str,
+    as_type: &TokenStream,
+    field_type: &syn::Type,
+    try_from: bool,
+) -> TokenStream {
+    let crate_ = super::get_crate_name();
+    if try_from {
+        quote! {
+            (|__deku_value: #as_type| -> core::result::Result<#field_type, ::#crate_::DekuError> {
+                <#field_type as core::convert::TryFrom<#as_type>>::try_from(__deku_value).map_err(|_| {
+                    ::#crate_::DekuError::Parse(format!(
+                        "Invalid {}.{}: cannot convert from {}",
+                        #ident,
+                        #field_ident_str,
+                        stringify!(#as_type)
+                    ))
+                })
+            })
+        }
+    } else {
+        quote! {
+            (|__deku_value: #as_type| -> core::result::Result<#field_type, ::#crate_::DekuError> {
+                Ok(<#field_type as core::convert::From<#as_type>>::from(__deku_value))
+            })
+        }
+    }
+}
+
+/// Skip the reserved range before a field, checking its value in strict mode
+fn emit_reserved(input: &DekuData, f: &FieldData, field_ident_str: &str) -> TokenStream {
+    let crate_ = super::get_crate_name();
+    let bit_size = match reserved_bits(f) {
+        Some(bit_size) => bit_size,
+        None => return quote! {},
+    };
+    let value = f.reserved_value.clone().unwrap_or_else(|| quote! { 0 });
+    let name = format!("{}.{}", input.ident, field_ident_str);
+
+    let check_reserved = if f.reserved_strict || input.reserved_strict {
+        quote! {
+            let __deku_reserved_expected = __deku_reserved.iter().by_vals().enumerate().all(|(__deku_i, __deku_bit)| {
+                let __deku_shift = __deku_reserved_size - 1 - __deku_i;
+                __deku_bit == (__deku_shift < 64 && (__deku_reserved_value >> __deku_shift) & 1 == 1)
+            });
+            if !__deku_reserved_expected {
+                return Err(::#crate_::DekuError::Parse(format!(
+                    "Invalid reserved bits before {}: expected {:#x}",
+                    #name,
+                    __deku_reserved_value
+                )));
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    quote! {
+        {
+            use core::convert::TryFrom;
+            let __deku_reserved_size = usize::try_from(#bit_size).map_err(|e|
+                ::#crate_::DekuError::InvalidParam(format!(
+                    "Invalid reserved param \"({})\": cannot convert to usize",
+                    stringify!(#bit_size)
+                ))
+            )?;
+            let __deku_reserved_value: u64 = #value;
+            if __deku_reserved_size < 64 && (__deku_reserved_value >> __deku_reserved_size) != 0 {
+                return Err(::#crate_::DekuError::InvalidParam(format!(
+                    "Invalid reserved value {:#x}: does not fit in {} bits",
+                    __deku_reserved_value,
+                    __deku_reserved_size
+                )));
+            }
+
+            if __deku_rest.len() >= __deku_reserved_size {
+                let (__deku_reserved, __deku_new_rest) = __deku_rest.split_at(__deku_reserved_size);
+                #check_reserved
                 __deku_rest = __deku_new_rest;
             } else {
-                return Err(::#crate_::DekuError::Incomplete(::#crate_::error::NeedSize::new(__deku_pad)));
+                return Err(::#crate_::DekuError::Incomplete(::#crate_::error::NeedSize::new(__deku_reserved_size)));
             }
         }
     }
@@ -513,9 +849,11 @@ fn emit_field_read(
     let crate_ = super::get_crate_name();
     let field_type = &f.ty;
 
-    let field_endian = f.endian.as_ref().or(input.endian.as_ref());
+    let container_endian = container_endian(input, i);
+    let field_endian = f.endian.as_ref().or(container_endian.as_ref());
 
     let field_reader = &f.reader;
+    let field_cond = f.read_cond();
 
     // fields to check usage of bit/byte offset
     let field_check_vars = [
@@ -523,7 +861,11 @@ fn emit_field_read(
         &f.bits_read,
         &f.bytes_read,
         &f.until,
-        &f.cond,
+        &f.until_exclusive,
+        &f.terminator,
+        &f.window_bytes,
+        &f.at,
+        &field_cond,
         &f.default,
         &f.map,
         &f.reader,
@@ -534,29 +876,39 @@ fn emit_field_read(
 
     let (bit_offset, byte_offset) = emit_bit_byte_offsets(&field_check_vars);
 
-    let field_map = f
-        .map
-        .as_ref()
-        .map(|v| {
-            quote! { (#v) }
-        })
-        .or_else(|| Some(quote! { core::result::Result::<_, ::#crate_::DekuError>::Ok }));
-
     let ident = ident.to_string();
     let field_ident = f.get_ident(i, true);
     let field_ident_str = field_ident.to_string();
+
+    let field_map = if let Some(as_type) = &f.as_type {
+        emit_as_type_map(&ident, &field_ident_str, as_type, &f.ty, f.try_from)
+    } else {
+        f.map
+            .as_ref()
+            .map(|v| {
+                quote! { (#v) }
+            })
+            .unwrap_or_else(|| quote! { core::result::Result::<_, ::#crate_::DekuError>::Ok })
+    };
     let internal_field_ident = gen_internal_field_ident(&field_ident);
 
     let field_assert = f.assert.as_ref().map(|v| {
+        let error = gen_assert_error(
+            f.assert_msg.as_ref(),
+            f.assert_err.as_ref(),
+            quote! {
+                format!(
+                    "{}.{} field failed assertion: {}",
+                    #ident,
+                    #field_ident_str,
+                    stringify!(#v)
+                )
+            },
+        );
         quote! {
             if (!(#v)) {
                 // assertion is false, raise error
-                return Err(::#crate_::DekuError::Assertion(format!(
-                            "{}.{} field failed assertion: {}",
-                            #ident,
-                            #field_ident_str,
-                            stringify!(#v)
-                        )));
+                return Err(#error);
             } else {
                 // do nothing
             }
@@ -564,15 +916,22 @@ fn emit_field_read(
     });
 
     let field_assert_eq = f.assert_eq.as_ref().map(|v| {
+        let error = gen_assert_error(
+            f.assert_msg.as_ref(),
+            f.assert_err.as_ref(),
+            quote! {
+                format!(
+                    "{}.{} field failed assertion: {}",
+                    #ident,
+                    #field_ident_str,
+                    stringify!(#field_ident == #v)
+                )
+            },
+        );
         quote! {
             if (!(#internal_field_ident == (#v))) {
                 // assertion is false, raise error
-                return Err(::#crate_::DekuError::Assertion(format!(
-                            "{}.{} field failed assertion: {}",
-                            #ident,
-                            #field_ident_str,
-                            stringify!(#field_ident == #v)
-                        )));
+                return Err(#error);
             } else {
                 // do nothing
             }
@@ -605,7 +964,10 @@ fn emit_field_read(
         //   b: Vec<_>
         // }
 
-        let type_as_deku_read = if f.map.is_some() {
+        let type_as_deku_read = if let Some(as_type) = &f.as_type {
+            // read the on-wire type, converted with `field_map`
+            quote!(<#as_type as ::#crate_::DekuRead<'_, _>>)
+        } else if f.map.is_some() {
             // with map, field_type cannot be used as the
             // resulting type is within the function.
             quote!(::#crate_::DekuRead)
@@ -617,55 +979,168 @@ fn emit_field_read(
             quote! {
                 {
                     use core::borrow::Borrow;
-                    #type_as_deku_read::read(__deku_rest, (::#crate_::ctx::Limit::new_count(usize::try_from(*((#field_count).borrow()))?), (#read_args)))
+                    #type_as_deku_read::read_in(__deku_layout, __deku_rest, (::#crate_::ctx::Limit::new_count(usize::try_from(*((#field_count).borrow()))?), (#read_args)))
                 }
             }
         } else if let Some(field_bits) = &f.bits_read {
             quote! {
                 {
                     use core::borrow::Borrow;
-                    #type_as_deku_read::read(__deku_rest, (::#crate_::ctx::Limit::new_bit_size(::#crate_::ctx::BitSize(usize::try_from(*((#field_bits).borrow()))?)), (#read_args)))
+                    #type_as_deku_read::read_in(__deku_layout, __deku_rest, (::#crate_::ctx::Limit::new_bit_size(::#crate_::ctx::BitSize(usize::try_from(*((#field_bits).borrow()))?)), (#read_args)))
                 }
             }
         } else if let Some(field_bytes) = &f.bytes_read {
             quote! {
                 {
                     use core::borrow::Borrow;
-                    #type_as_deku_read::read(__deku_rest, (::#crate_::ctx::Limit::new_byte_size(::#crate_::ctx::ByteSize(usize::try_from(*((#field_bytes).borrow()))?)), (#read_args)))
+                    #type_as_deku_read::read_in(__deku_layout, __deku_rest, (::#crate_::ctx::Limit::new_byte_size(::#crate_::ctx::ByteSize(usize::try_from(*((#field_bytes).borrow()))?)), (#read_args)))
                 }
             }
         } else if let Some(field_until) = &f.until {
             // We wrap the input into another closure here to enforce that it is actually a callable
             // Otherwise, an incorrectly passed-in integer could unexpectedly convert into a `Count` limit
-            quote! {#type_as_deku_read::read(__deku_rest, (::#crate_::ctx::Limit::new_until(#field_until), (#read_args)))}
+            quote! {#type_as_deku_read::read_in(__deku_layout, __deku_rest, (::#crate_::ctx::Limit::new_until(#field_until), (#read_args)))}
+        } else if f.until_exclusive.is_some() || f.terminator.is_some() {
+            let limit = match (&f.until_exclusive, &f.terminator) {
+                (Some(field_until), _) => {
+                    quote! { ::#crate_::ctx::Limit::new_until_exclusive(#field_until) }
+                }
+                (None, Some(field_terminator)) => {
+                    quote! { ::#crate_::ctx::Limit::new_terminator(#field_terminator) }
+                }
+                (None, None) => unreachable!(),
+            };
+            // the terminator is left in the rest, it is consumed as a container of one element
+            quote! {
+                match #type_as_deku_read::read_in(__deku_layout, __deku_rest, (#limit, (#read_args))) {
+                    Ok((__deku_new_rest, __deku_value)) => {
+                        #type_as_deku_read::read_in(__deku_layout, __deku_new_rest, (::#crate_::ctx::Limit::new_count(1), (#read_args)))
+                            .map(|(__deku_new_rest, __deku_terminator)| {
+                                let _ = [&__deku_value, &__deku_terminator];
+                                (__deku_new_rest, __deku_value)
+                            })
+                    }
+                    Err(e) => Err(e),
+                }
+            }
         } else {
-            quote! {#type_as_deku_read::read(__deku_rest, (#read_args))}
+            quote! {#type_as_deku_read::read_in(__deku_layout, __deku_rest, (#read_args))}
         }
     };
 
+    let reserved = emit_reserved(input, f, &field_ident_str);
+    let padding = Padding::new(input, Some((f, field_ident_str.clone())));
     let pad_bits_before = pad_bits(
         f.pad_bits_before.as_ref(),
         f.pad_bytes_before.as_ref(),
-        emit_padding,
+        |bit_size| emit_padding(bit_size, &padding),
     );
     let pad_bits_after = pad_bits(
         f.pad_bits_after.as_ref(),
         f.pad_bytes_after.as_ref(),
-        emit_padding,
+        |bit_size| emit_padding(bit_size, &padding),
+    );
+    let align_bits_before = align_bits(
+        f.align_bits_before.as_ref(),
+        f.align_before.as_ref(),
+        input.align_input,
+        |bit_size, align_input| emit_alignment(bit_size, align_input, &padding),
+    );
+    let align_bits_after = align_bits(
+        f.align_bits_after.as_ref(),
+        f.align_after.as_ref(),
+        input.align_input,
+        |bit_size, align_input| emit_alignment(bit_size, align_input, &padding),
     );
 
-    let field_read_normal = quote! {
-        let (__deku_new_rest, __deku_value) = #field_read_func?;
-        let __deku_value: #field_type = #field_map(__deku_value)?;
+    let field_read_normal = match &f.window_bytes {
+        Some(window_bytes) => {
+            // the field is read from the window, the rest of the input is resumed after it
+            let window_rest = match f.window_rest().as_str() {
+                "skip" => quote! { __deku_window_after },
+                "keep" => quote! {
+                    __deku_rest.split_at(__deku_window_bits - __deku_window_rest.len()).1
+                },
+                _ => quote! {
+                    if __deku_window_rest.is_empty() {
+                        __deku_window_after
+                    } else {
+                        return Err(::#crate_::DekuError::Parse(format!(
+                            "{}.{} field has {} unread bits in its window",
+                            #ident,
+                            #field_ident_str,
+                            __deku_window_rest.len()
+                        )));
+                    }
+                },
+            };
+            quote! {
+                let __deku_window_bits = {
+                    use core::borrow::Borrow;
+                    use core::convert::TryFrom;
+                    usize::try_from(*((#window_bytes).borrow()))? * 8
+                };
+                if __deku_rest.len() < __deku_window_bits {
+                    return Err(::#crate_::DekuError::Incomplete(::#crate_::error::NeedSize::new(__deku_window_bits)));
+                }
+                let (__deku_window, __deku_window_after) = __deku_rest.split_at(__deku_window_bits);
+                let (__deku_window_rest, __deku_value) = {
+                    let __deku_rest = __deku_window;
+                    #field_read_func
+                }?;
+                let __deku_new_rest = #window_rest;
+                let __deku_value: #field_type = #field_map(__deku_value)?;
+
+                __deku_rest = __deku_new_rest;
+
+                __deku_value
+            }
+        }
+        None => quote! {
+            let (__deku_new_rest, __deku_value) = #field_read_func?;
+            let __deku_value: #field_type = #field_map(__deku_value)?;
 
-        __deku_rest = __deku_new_rest;
+            __deku_rest = __deku_new_rest;
 
-        __deku_value
+            __deku_value
+        },
+    };
+
+    let field_read_normal = match &f.at {
+        Some(at) => {
+            // the field is read at its offset from the start of the outermost input, the cursor
+            // isn't moved
+            quote! {
+                let __deku_at_bits = {
+                    use core::borrow::Borrow;
+                    use core::convert::TryFrom;
+                    usize::try_from(*((#at).borrow()))? * 8
+                };
+                if __deku_layout.input().len() < __deku_at_bits {
+                    return Err(::#crate_::DekuError::Parse(format!(
+                        "{}.{} field offset is past the end of the input: {} of {} bits",
+                        #ident,
+                        #field_ident_str,
+                        __deku_at_bits,
+                        __deku_layout.input().len()
+                    )));
+                }
+                let __deku_at_base = __deku_layout.enter(__deku_at_bits);
+                let mut __deku_rest = &__deku_layout.input()[__deku_at_bits..];
+                let __deku_value = {
+                    #field_read_normal
+                };
+                __deku_layout.leave(__deku_at_base, __deku_rest);
+
+                __deku_value
+            }
+        }
+        None => field_read_normal,
     };
 
     let field_default = &f.default;
 
-    let field_read_tokens = match (f.skip, &f.cond) {
+    let field_read_tokens = match (f.skip, &field_cond) {
         (true, Some(field_cond)) => {
             // #[deku(skip, cond = "...")] ==> `skip` if `cond`
             quote! {
@@ -699,8 +1174,12 @@ fn emit_field_read(
         }
     };
 
+    let endian_from = emit_endian_from(input, i, &field_ident);
+
     let field_read = quote! {
+        #reserved
         #pad_bits_before
+        #align_bits_before
 
         #bit_offset
         #byte_offset
@@ -715,6 +1194,8 @@ fn emit_field_read(
         #field_assert_eq
 
         #pad_bits_after
+        #align_bits_after
+        #endian_from
     };
 
     Ok((field_ident, field_read))
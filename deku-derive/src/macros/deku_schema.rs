@@ -0,0 +1,300 @@
+use crate::{DekuData, FieldData, Id, Num, VariantData};
+use darling::ast::{Data, Fields};
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+
+pub(crate) fn emit_deku_schema(input: &DekuData) -> Result<TokenStream, syn::Error> {
+    let crate_ = super::get_crate_name();
+
+    let (imp, ty, wher) = input.generics.split_for_impl();
+    let ident = &input.ident;
+    let ident_as_string = ident.to_string();
+
+    let endian = emit_opt_str(input.endian.as_ref().map(|v| v.value()));
+    let endian_from = emit_opt_str(input.endian_from.as_ref().map(|v| v.value()));
+    let magic = match &input.magic {
+        Some(magic) => quote! { core::option::Option::Some(#magic) },
+        None => quote! { core::option::Option::None },
+    };
+    let ctx = emit_opt_tokens(input.ctx.as_ref());
+    let ctx_default = emit_opt_tokens(input.ctx_default.as_ref());
+    let align = emit_opt_tokens(input.align.as_ref());
+    let align_input = input.align_input;
+    let pad_fill = emit_opt_tokens(input.pad_fill.as_ref());
+    let pad_strict = input.pad_strict;
+    let reserved_strict = input.reserved_strict;
+
+    let data = match &input.data {
+        Data::Struct(fields) => {
+            let fields = emit_fields(fields)?;
+            quote! {
+                ::#crate_::schema::SchemaData::Struct({
+                    const __DEKU_FIELDS: &[::#crate_::schema::FieldSchema] = &[#(#fields),*];
+                    __DEKU_FIELDS
+                })
+            }
+        }
+        Data::Enum(variants) => {
+            let id = emit_opt_tokens(input.id.as_ref());
+            let id_type = emit_opt_tokens(input.id_type.as_ref());
+            let bits = emit_opt_num(input.bits.as_ref())?;
+            let bytes = emit_opt_num(input.bytes.as_ref())?;
+            let custom_id = input.id_reader.is_some() || input.id_writer.is_some();
+            let variants = variants
+                .iter()
+                .map(emit_variant)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            quote! {
+                ::#crate_::schema::SchemaData::Enum({
+                    const __DEKU_VARIANTS: &[::#crate_::schema::VariantSchema] = &[#(#variants),*];
+                    let mut __deku_enum = ::#crate_::schema::EnumSchema::new(__DEKU_VARIANTS);
+                    __deku_enum.id = #id;
+                    __deku_enum.id_type = #id_type;
+                    __deku_enum.bits = #bits;
+                    __deku_enum.bytes = #bytes;
+                    __deku_enum.custom_id = #custom_id;
+                    __deku_enum
+                })
+            }
+        }
+    };
+
+    let tokens = quote! {
+        impl #imp ::#crate_::DekuSchema for #ident #ty #wher {
+            const SCHEMA: ::#crate_::schema::Schema = {
+                let mut __deku_schema = ::#crate_::schema::Schema::new(#ident_as_string, #data);
+                __deku_schema.endian = #endian;
+                __deku_schema.endian_from = #endian_from;
+                __deku_schema.magic = #magic;
+                __deku_schema.ctx = #ctx;
+                __deku_schema.ctx_default = #ctx_default;
+                __deku_schema.align = #align;
+                __deku_schema.align_input = #align_input;
+                __deku_schema.pad_fill = #pad_fill;
+                __deku_schema.pad_strict = #pad_strict;
+                __deku_schema.reserved_strict = #reserved_strict;
+                __deku_schema
+            };
+        }
+    };
+
+    // println!("{}", tokens.to_string());
+    Ok(tokens)
+}
+
+fn emit_variant(variant: &VariantData) -> Result<TokenStream, syn::Error> {
+    let crate_ = super::get_crate_name();
+
+    let ident = variant.ident.to_string();
+    let id = emit_opt_str(variant.id.as_ref().map(|v| match v {
+        Id::TokenStream(v) => v.to_string(),
+        Id::LitByteStr(v) => v.to_token_stream().to_string(),
+    }));
+    let id_pat = emit_opt_tokens(variant.id_pat.as_ref());
+    let discriminant = emit_opt_tokens(variant.discriminant.as_ref());
+    let default = variant.default.unwrap_or(false);
+    let unknown = variant.unknown;
+    let custom = variant.reader.is_some() || variant.writer.is_some();
+    let magic = match &variant.magic {
+        Some(magic) => quote! { core::option::Option::Some(#magic) },
+        None => quote! { core::option::Option::None },
+    };
+    let endian = emit_opt_str(variant.endian.as_ref().map(|v| v.value()));
+    let ctx = emit_opt_tokens(variant.ctx.as_ref());
+    let fields = emit_fields(&variant.fields)?;
+
+    Ok(quote! {
+        {
+            const __DEKU_FIELDS: &[::#crate_::schema::FieldSchema] = &[#(#fields),*];
+            let mut __deku_variant = ::#crate_::schema::VariantSchema::new(#ident, __DEKU_FIELDS);
+            __deku_variant.id = #id;
+            __deku_variant.id_pat = #id_pat;
+            __deku_variant.discriminant = #discriminant;
+            __deku_variant.default = #default;
+            __deku_variant.unknown = #unknown;
+            __deku_variant.custom = #custom;
+            __deku_variant.magic = #magic;
+            __deku_variant.endian = #endian;
+            __deku_variant.ctx = #ctx;
+            __deku_variant
+        }
+    })
+}
+
+fn emit_fields(fields: &Fields<FieldData>) -> Result<Vec<TokenStream>, syn::Error> {
+    fields.iter().map(emit_field).collect()
+}
+
+fn emit_field(f: &FieldData) -> Result<TokenStream, syn::Error> {
+    let crate_ = super::get_crate_name();
+
+    let ident = emit_opt_str(f.ident.as_ref().map(|v| v.to_string()));
+    let ty = compact_tokens(&f.ty.to_token_stream());
+    let endian = emit_opt_str(f.endian.as_ref().map(|v| v.value()));
+    let bits = emit_opt_num(f.bits.as_ref())?;
+    let bytes = emit_opt_num(f.bytes.as_ref())?;
+    let count = emit_opt_tokens(f.count.as_ref());
+    let bits_read = emit_opt_tokens(f.bits_read.as_ref());
+    let bytes_read = emit_opt_tokens(f.bytes_read.as_ref());
+    let until = emit_opt_tokens(f.until.as_ref());
+    let until_exclusive = emit_opt_tokens(f.until_exclusive.as_ref());
+    let terminator = emit_opt_tokens(f.terminator.as_ref());
+    let window_bytes = emit_opt_tokens(f.window_bytes.as_ref());
+    let window_rest = emit_opt_str(f.window_bytes.as_ref().map(|_| f.window_rest()));
+    let at = emit_opt_tokens(f.at.as_ref());
+    let ctx = emit_opt_tokens(f.ctx.as_ref());
+    let map = emit_opt_tokens(f.map.as_ref());
+    let write_map = emit_opt_tokens(f.write_map.as_ref());
+    let as_type = emit_opt_str(f.as_type.as_ref().map(compact_tokens));
+    let try_from = f.try_from;
+    // `since`/`until_version` are described as a `cond` on the version
+    let field_cond = f.read_cond();
+    let cond = emit_opt_tokens(field_cond.as_ref());
+    // `default` is always populated, only report it when it can be used
+    let default = emit_opt_tokens(
+        f.default
+            .as_ref()
+            .filter(|_| f.skip || field_cond.is_some()),
+    );
+    let skip = f.skip;
+    let temp = f.temp;
+    let pad_bits_before = emit_opt_tokens(f.pad_bits_before.as_ref());
+    let pad_bytes_before = emit_opt_tokens(f.pad_bytes_before.as_ref());
+    let pad_bits_after = emit_opt_tokens(f.pad_bits_after.as_ref());
+    let pad_bytes_after = emit_opt_tokens(f.pad_bytes_after.as_ref());
+    let align_bits_before = emit_opt_tokens(f.align_bits_before.as_ref());
+    let align_before = emit_opt_tokens(f.align_before.as_ref());
+    let align_bits_after = emit_opt_tokens(f.align_bits_after.as_ref());
+    let align_after = emit_opt_tokens(f.align_after.as_ref());
+    let pad_fill = emit_opt_tokens(f.pad_fill.as_ref());
+    let pad_strict = f.pad_strict;
+    let reserved_bits = emit_opt_tokens(f.reserved_bits.as_ref());
+    let reserved_bytes = emit_opt_tokens(f.reserved_bytes.as_ref());
+    let reserved_value = emit_opt_tokens(f.reserved_value.as_ref());
+    let reserved_strict = f.reserved_strict;
+    let assert = emit_opt_tokens(f.assert.as_ref());
+    let assert_eq = emit_opt_tokens(f.assert_eq.as_ref());
+    let custom = f.reader.is_some() || f.writer.is_some();
+
+    Ok(quote! {
+        {
+            let mut __deku_field = ::#crate_::schema::FieldSchema::new(#ident, #ty);
+            __deku_field.endian = #endian;
+            __deku_field.bits = #bits;
+            __deku_field.bytes = #bytes;
+            __deku_field.count = #count;
+            __deku_field.bits_read = #bits_read;
+            __deku_field.bytes_read = #bytes_read;
+            __deku_field.until = #until;
+            __deku_field.until_exclusive = #until_exclusive;
+            __deku_field.terminator = #terminator;
+            __deku_field.window_bytes = #window_bytes;
+            __deku_field.window_rest = #window_rest;
+            __deku_field.at = #at;
+            __deku_field.ctx = #ctx;
+            __deku_field.map = #map;
+            __deku_field.write_map = #write_map;
+            __deku_field.as_type = #as_type;
+            __deku_field.try_from = #try_from;
+            __deku_field.cond = #cond;
+            __deku_field.default = #default;
+            __deku_field.skip = #skip;
+            __deku_field.temp = #temp;
+            __deku_field.pad_bits_before = #pad_bits_before;
+            __deku_field.pad_bytes_before = #pad_bytes_before;
+            __deku_field.pad_bits_after = #pad_bits_after;
+            __deku_field.pad_bytes_after = #pad_bytes_after;
+            __deku_field.align_bits_before = #align_bits_before;
+            __deku_field.align_before = #align_before;
+            __deku_field.align_bits_after = #align_bits_after;
+            __deku_field.align_after = #align_after;
+            __deku_field.pad_fill = #pad_fill;
+            __deku_field.pad_strict = #pad_strict;
+            __deku_field.reserved_bits = #reserved_bits;
+            __deku_field.reserved_bytes = #reserved_bytes;
+            __deku_field.reserved_value = #reserved_value;
+            __deku_field.reserved_strict = #reserved_strict;
+            __deku_field.assert = #assert;
+            __deku_field.assert_eq = #assert_eq;
+            __deku_field.custom = #custom;
+            __deku_field
+        }
+    })
+}
+
+/// `Some("...")` or `None`
+fn emit_opt_str(s: Option<String>) -> TokenStream {
+    match s {
+        Some(s) => quote! { core::option::Option::Some(#s) },
+        None => quote! { core::option::Option::None },
+    }
+}
+
+/// `Some("tokens")` or `None`
+fn emit_opt_tokens<T: ToTokens>(t: Option<&T>) -> TokenStream {
+    emit_opt_str(t.map(|t| t.to_token_stream().to_string()))
+}
+
+/// `Some(n)` or `None`
+fn emit_opt_num(n: Option<&Num>) -> Result<TokenStream, syn::Error> {
+    Ok(match n {
+        Some(n) => {
+            let n = n.0.base10_parse::<usize>()?;
+            quote! { core::option::Option::Some(#n) }
+        }
+        None => quote! { core::option::Option::None },
+    })
+}
+
+/// Stringify tokens while removing the spacing added between punctuation:
+/// `Vec < u8 >` -> `Vec<u8>`, `[u8 ; 4]` -> `[u8; 4]`, `& 'a [u8]` -> `&'a [u8]`
+fn compact_tokens(tokens: &TokenStream) -> String {
+    let s = tokens.to_string();
+    let chars = s.chars().collect::<Vec<_>>();
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+
+    let mut out = String::with_capacity(s.len());
+    for (i, c) in chars.iter().enumerate() {
+        if *c == ' ' {
+            let prev = out.chars().last();
+            let next = chars.get(i + 1).copied();
+            let keep = match (prev, next) {
+                (Some(p), Some(n)) => {
+                    (is_word(p) && (is_word(n) || n == '[' || n == '(' || n == '&'))
+                        || p == ','
+                        || p == ';'
+                }
+                _ => false,
+            };
+            if !keep {
+                continue;
+            }
+        }
+        out.push(*c);
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest(
+        input,
+        expected,
+        case("u8", "u8"),
+        case("Vec<u8>", "Vec<u8>"),
+        case("[u8; 4]", "[u8; 4]"),
+        case("(u8, Vec<u16>)", "(u8, Vec<u16>)"),
+        case("&'a [u8]", "&'a [u8]"),
+        case("HashMap<u8, Option<u32>>", "HashMap<u8, Option<u32>>"),
+        case("std::ffi::CString", "std::ffi::CString")
+    )]
+    fn test_compact_tokens(input: &str, expected: &str) {
+        let tokens: TokenStream = input.parse().unwrap();
+        assert_eq!(expected, compact_tokens(&tokens));
+    }
+}
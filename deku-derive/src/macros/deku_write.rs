@@ -1,12 +1,14 @@
 use crate::macros::{
-    gen_ctx_types_and_arg, gen_field_args, gen_struct_destruction, pad_bits, token_contains_string,
-    wrap_default_ctx,
+    align_bits, container_endian, emit_container_assert, emit_container_validate, emit_endian_from,
+    gen_assert_error, gen_ctx_types_and_arg, gen_field_args, gen_struct_destruction,
+    needs_align_start, pad_bits, reserved_bits, token_contains_string, wrap_default_ctx, Padding,
 };
-use crate::{DekuData, DekuDataEnum, DekuDataStruct, FieldData, Id};
+use crate::{at_offset_field, DekuData, DekuDataEnum, DekuDataStruct, FieldData, Id};
 use darling::ast::{Data, Fields};
-use proc_macro2::TokenStream;
-use quote::quote;
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
 use std::convert::TryFrom;
+use syn::spanned::Spanned;
 
 pub(crate) fn emit_deku_write(input: &DekuData) -> Result<TokenStream, syn::Error> {
     match &input.data {
@@ -26,7 +28,8 @@ fn emit_struct(input: &DekuData) -> Result<TokenStream, syn::Error> {
         fields,
     } = DekuDataStruct::try_from(input)?;
 
-    let magic_write = emit_magic_write(input);
+    let magic_write = emit_magic_write(input.magic.as_ref());
+    let (align_start, align_end) = emit_align_start_end(input);
 
     let field_writes = emit_field_writes(input, &fields, None, &ident)?;
     let field_updates = emit_field_updates(&fields, Some(quote! { self. }));
@@ -43,17 +46,29 @@ fn emit_struct(input: &DekuData) -> Result<TokenStream, syn::Error> {
 
     let destructured = gen_struct_destruction(named, &input.ident, field_idents);
 
+    // checked before writing any field
+    let container_validate = emit_container_validate(input, quote! { self });
+    let container_assert = emit_container_assert(input);
+    let ctx_save = emit_ctx_save(input);
+
     // Implement `DekuContainerWrite` for types that don't need a context
     if input.ctx.is_none() || (input.ctx.is_some() && input.ctx_default.is_some()) {
         let to_bits_body = wrap_default_ctx(
             quote! {
+                #container_validate
+                #ctx_save
                 match *self {
                     #destructured => {
+                        #container_assert
                         let mut __deku_acc: ::#crate_::bitvec::BitVec<u8, ::#crate_::bitvec::Msb0> = ::#crate_::bitvec::BitVec::new();
                         let __deku_output = &mut __deku_acc;
+                        let __deku_layout = &mut ::#crate_::layout::WriteLayout::new(__deku_output);
+                        #align_start
 
                         #magic_write
                         #(#field_writes)*
+                        #align_end
+                        __deku_layout.finish(__deku_output)?;
 
                     Ok(__deku_acc)
                     }
@@ -97,10 +112,15 @@ fn emit_struct(input: &DekuData) -> Result<TokenStream, syn::Error> {
     let (ctx_types, ctx_arg) = gen_ctx_types_and_arg(input.ctx.as_ref())?;
 
     let write_body = quote! {
+        #container_validate
+        #ctx_save
         match *self {
             #destructured => {
+                #container_assert
+                #align_start
                 #magic_write
                 #(#field_writes)*
+                #align_end
 
                 Ok(())
             }
@@ -120,25 +140,26 @@ fn emit_struct(input: &DekuData) -> Result<TokenStream, syn::Error> {
             }
         }
 
-        impl #imp DekuWrite<#ctx_types> for #ident #wher {
-            #[allow(unused_variables)]
-            fn write(&self, __deku_output: &mut ::#crate_::bitvec::BitVec<u8, ::#crate_::bitvec::Msb0>, #ctx_arg) -> core::result::Result<(), ::#crate_::DekuError> {
-                #write_body
-            }
-        }
     });
 
+    tokens.extend(emit_write_impl(
+        &imp,
+        &ident,
+        wher,
+        (&ctx_types, &ctx_arg),
+        &write_body,
+    ));
+
     if input.ctx.is_some() && input.ctx_default.is_some() {
         let write_body = wrap_default_ctx(write_body, &input.ctx, &input.ctx_default);
 
-        tokens.extend(quote! {
-            impl #imp DekuWrite for #ident #wher {
-                #[allow(unused_variables)]
-                fn write(&self, __deku_output: &mut ::#crate_::bitvec::BitVec<u8, ::#crate_::bitvec::Msb0>, _: ()) -> core::result::Result<(), ::#crate_::DekuError> {
-                    #write_body
-                }
-            }
-        });
+        tokens.extend(emit_write_impl(
+            &imp,
+            &ident,
+            wher,
+            (&quote! { () }, &quote! { _: () }),
+            &write_body,
+        ));
     }
 
     // println!("{}", tokens.to_string());
@@ -159,13 +180,22 @@ fn emit_enum(input: &DekuData) -> Result<TokenStream, syn::Error> {
         id_args,
     } = DekuDataEnum::try_from(input)?;
 
-    let magic_write = emit_magic_write(input);
+    let magic_write = emit_magic_write(input.magic.as_ref());
+    let (align_start, align_end) = emit_align_start_end(input);
+    let container_validate = emit_container_validate(input, quote! { self });
+    let ctx_save = emit_ctx_save(input);
 
     let mut variant_writes = Vec::with_capacity(variants.len());
     let mut variant_updates = Vec::with_capacity(variants.len());
 
     let has_discriminant = variants.iter().any(|v| v.discriminant.is_some());
 
+    // `deku::id` is the id to write in `id_writer`
+    let id_write = match &input.id_writer {
+        Some(id_writer) => quote! { #id_writer?; },
+        None => quote! { __deku_variant_id.write(__deku_output, (#id_args))?; },
+    };
+
     for variant in variants {
         // check if the first field has an ident, if not, it's a unnamed struct
         let variant_is_named = variant
@@ -193,18 +223,25 @@ fn emit_enum(input: &DekuData) -> Result<TokenStream, syn::Error> {
                 let _ = (#id);
             }
         } else if id_type.is_some() {
-            if let Some(variant_id) = &variant.id {
+            if variant.unknown {
+                // the captured id is written back unchanged
+                let id_ident = variant.fields.fields[0].get_ident(0, true);
+                quote! {
+                    let __deku_variant_id: #id_type = core::clone::Clone::clone(#id_ident);
+                    #id_write
+                }
+            } else if let Some(variant_id) = &variant.id {
                 match variant_id {
                     Id::TokenStream(v) => {
                         quote! {
                             let mut __deku_variant_id: #id_type = #v;
-                            __deku_variant_id.write(__deku_output, (#id_args))?;
+                            #id_write
                         }
                     }
                     Id::LitByteStr(v) => {
                         quote! {
                             let mut __deku_variant_id: #id_type = *#v;
-                            __deku_variant_id.write(__deku_output, (#id_args))?;
+                            #id_write
                         }
                     }
                 }
@@ -213,7 +250,7 @@ fn emit_enum(input: &DekuData) -> Result<TokenStream, syn::Error> {
             } else if has_discriminant {
                 quote! {
                     let mut __deku_variant_id: #id_type = Self::#variant_ident as #id_type;
-                    __deku_variant_id.write(__deku_output, (#id_args))?;
+                    #id_write
                 }
             } else {
                 return Err(syn::Error::new(
@@ -230,12 +267,36 @@ fn emit_enum(input: &DekuData) -> Result<TokenStream, syn::Error> {
 
         let variant_write = if variant_writer.is_some() {
             quote! { #variant_writer ?; }
+        } else if variant.unknown {
+            // with `id` from the context, the captured id isn't written
+            let ignore_id = id.map(|_| {
+                let id_ident = variant.fields.fields[0].get_ident(0, true);
+                quote! { let _ = #id_ident; }
+            });
+            let write_rest = variant.fields.fields.get(1).map(|f| {
+                let rest_ident = f.get_ident(1, true);
+                quote! {
+                    ::#crate_::DekuWrite::write(#rest_ident, __deku_output, ())?;
+                }
+            });
+
+            quote! {
+                {
+                    #variant_id_write
+                    #ignore_id
+                    #write_rest
+                }
+            }
         } else {
             let field_writes = emit_field_writes(input, &variant.fields.as_ref(), None, &ident)?;
+            let variant_magic_write = emit_magic_write(variant.magic.as_ref());
+            let variant_ctx = super::emit_variant_ctx(variant);
 
             quote! {
                 {
                     #variant_id_write
+                    #variant_magic_write
+                    #variant_ctx
                     #(#field_writes)*
                 }
             }
@@ -260,14 +321,20 @@ fn emit_enum(input: &DekuData) -> Result<TokenStream, syn::Error> {
     if input.ctx.is_none() || (input.ctx.is_some() && input.ctx_default.is_some()) {
         let to_bits_body = wrap_default_ctx(
             quote! {
+                #container_validate
                 let mut __deku_acc: ::#crate_::bitvec::BitVec<u8, ::#crate_::bitvec::Msb0> = ::#crate_::bitvec::BitVec::new();
                 let __deku_output = &mut __deku_acc;
+                let __deku_layout = &mut ::#crate_::layout::WriteLayout::new(__deku_output);
+                #align_start
 
                 #magic_write
+                #ctx_save
 
                 match self {
                     #(#variant_writes),*
                 }
+                #align_end
+                __deku_layout.finish(__deku_output)?;
 
                 Ok(__deku_acc)
             },
@@ -309,11 +376,15 @@ fn emit_enum(input: &DekuData) -> Result<TokenStream, syn::Error> {
     let (ctx_types, ctx_arg) = gen_ctx_types_and_arg(input.ctx.as_ref())?;
 
     let write_body = quote! {
+        #container_validate
+        #align_start
         #magic_write
+        #ctx_save
 
         match self {
             #(#variant_writes),*
         }
+        #align_end
 
         Ok(())
     };
@@ -334,33 +405,34 @@ fn emit_enum(input: &DekuData) -> Result<TokenStream, syn::Error> {
             }
         }
 
-        impl #imp DekuWrite<#ctx_types> for #ident #wher {
-            #[allow(unused_variables)]
-            fn write(&self, __deku_output: &mut ::#crate_::bitvec::BitVec<u8, ::#crate_::bitvec::Msb0>, #ctx_arg) -> core::result::Result<(), ::#crate_::DekuError> {
-                #write_body
-            }
-        }
     });
 
+    tokens.extend(emit_write_impl(
+        &imp,
+        &ident,
+        wher,
+        (&ctx_types, &ctx_arg),
+        &write_body,
+    ));
+
     if input.ctx.is_some() && input.ctx_default.is_some() {
         let write_body = wrap_default_ctx(write_body, &input.ctx, &input.ctx_default);
 
-        tokens.extend(quote! {
-            impl #imp DekuWrite for #ident #wher {
-                #[allow(unused_variables)]
-                fn write(&self, __deku_output: &mut ::#crate_::bitvec::BitVec<u8, ::#crate_::bitvec::Msb0>, _: ()) -> core::result::Result<(), ::#crate_::DekuError> {
-                    #write_body
-                }
-            }
-        });
+        tokens.extend(emit_write_impl(
+            &imp,
+            &ident,
+            wher,
+            (&quote! { () }, &quote! { _: () }),
+            &write_body,
+        ));
     }
 
     // println!("{}", tokens.to_string());
     Ok(tokens)
 }
 
-fn emit_magic_write(input: &DekuData) -> TokenStream {
-    if let Some(magic) = &input.magic {
+fn emit_magic_write(magic: Option<&syn::LitByteStr>) -> TokenStream {
+    if let Some(magic) = magic {
         quote! {
             #magic.write(__deku_output, ())?;
         }
@@ -369,16 +441,155 @@ fn emit_magic_write(input: &DekuData) -> TokenStream {
     }
 }
 
+/// `DekuWrite` with `write` writing as the outermost output, and `write_in` as a part of it
+fn emit_write_impl(
+    imp: &syn::ImplGenerics,
+    ident: &TokenStream,
+    wher: Option<&syn::WhereClause>,
+    (ctx_types, ctx_arg): (&TokenStream, &TokenStream),
+    write_body: &TokenStream,
+) -> TokenStream {
+    let crate_ = super::get_crate_name();
+
+    quote! {
+        impl #imp DekuWrite<#ctx_types> for #ident #wher {
+            fn write(&self, __deku_output: &mut ::#crate_::bitvec::BitVec<u8, ::#crate_::bitvec::Msb0>, __deku_ctx: #ctx_types) -> core::result::Result<(), ::#crate_::DekuError> {
+                ::#crate_::layout::write(self, __deku_output, __deku_ctx)
+            }
+
+            #[allow(unused_variables)]
+            fn write_in(&self, __deku_layout: &mut ::#crate_::layout::WriteLayout, __deku_output: &mut ::#crate_::bitvec::BitVec<u8, ::#crate_::bitvec::Msb0>, #ctx_arg) -> core::result::Result<(), ::#crate_::DekuError> {
+                #write_body
+            }
+        }
+    }
+}
+
+/// Record the start of the struct/enum for `align`, and pad its end for `align`
+fn emit_align_start_end(input: &DekuData) -> (TokenStream, TokenStream) {
+    // with `align_input`, the offset is from the start of the outermost output instead
+    let align_start = if needs_align_start(input) && !input.align_input {
+        quote! {
+            let __deku_align_start = __deku_output.len();
+        }
+    } else {
+        quote! {}
+    };
+
+    let padding = Padding::new(input, None);
+    let align_end = align_bits(
+        None,
+        input.align.as_ref(),
+        input.align_input,
+        |bit_size, align_input| emit_alignment(bit_size, align_input, &padding),
+    );
+
+    (align_start, align_end)
+}
+
+/// `ctx` arguments with the name of a field
+///
+/// The fields are bound before writing any field, shadowing these arguments, while
+/// they're only bound once read when reading.
+fn shadowed_ctx(input: &DekuData) -> Vec<Ident> {
+    let ctx = match &input.ctx {
+        Some(ctx) => ctx,
+        None => return Vec::new(),
+    };
+    let field_names: Vec<String> = match &input.data {
+        Data::Struct(fields) => fields
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| !f.temp)
+            .map(|(i, f)| f.get_ident(i, true).to_string())
+            .collect(),
+        Data::Enum(variants) => variants
+            .iter()
+            .flat_map(|v| v.fields.iter().enumerate())
+            .filter(|(_, f)| !f.temp)
+            .map(|(i, f)| f.get_ident(i, true).to_string())
+            .collect(),
+    };
+
+    ctx.iter()
+        .filter_map(|arg| match arg {
+            syn::FnArg::Typed(pat_type) => match pat_type.pat.as_ref() {
+                syn::Pat::Ident(pat) => Some(pat.ident.clone()),
+                _ => None,
+            },
+            syn::FnArg::Receiver(_) => None,
+        })
+        .filter(|ctx_ident| field_names.contains(&ctx_ident.to_string()))
+        .collect()
+}
+
+/// Keep the `ctx` arguments shadowed by fields, to evaluate `cond` like when reading
+fn emit_ctx_save(input: &DekuData) -> TokenStream {
+    let saves = shadowed_ctx(input).into_iter().map(|ctx_ident| {
+        let saved = format_ident!("__deku_ctx_{}", ctx_ident);
+        quote! { let #saved = &#ctx_ident; }
+    });
+    quote! { #(#saves)* }
+}
+
 fn emit_field_writes(
     input: &DekuData,
     fields: &Fields<&FieldData>,
     object_prefix: Option<TokenStream>,
     ident: &TokenStream,
 ) -> Result<Vec<TokenStream>, syn::Error> {
-    fields
-        .iter()
-        .enumerate()
-        .map(|(i, f)| emit_field_write(input, i, f, &object_prefix, ident))
+    // fields with `at` are written after the sequential fields, at their offset
+    let (sequential, at): (Vec<_>, Vec<_>) =
+        fields.iter().enumerate().partition(|(_, f)| f.at.is_none());
+
+    // `ctx` arguments shadowed by this field or the following ones, which aren't read yet
+    let shadowed_ctx = shadowed_ctx(input);
+    let restored_ctx = |i: usize| -> Vec<Ident> {
+        shadowed_ctx
+            .iter()
+            .filter(|ctx_ident| {
+                fields.iter().enumerate().skip(i).any(|(j, f)| {
+                    !f.temp && f.get_ident(j, true).to_string() == ctx_ident.to_string()
+                })
+            })
+            .cloned()
+            .collect()
+    };
+
+    // the offset fields of the fields with `at` are filled in once the output is laid out
+    let fields_vec: Vec<&FieldData> = fields.iter().copied().collect();
+    let at_patch = |i: usize| -> Option<Ident> {
+        let offset_field = match fields_vec[i].at {
+            Some(_) => at_offset_field(&fields_vec, fields_vec[i])?,
+            None if fields_vec
+                .iter()
+                .any(|f| at_offset_field(&fields_vec, f) == Some(i)) =>
+            {
+                i
+            }
+            None => return None,
+        };
+        let offset_ident = fields_vec[offset_field].get_ident(offset_field, true);
+        Some(format_ident!(
+            "__deku_at_patch_{}",
+            offset_ident.to_string()
+        ))
+    };
+
+    sequential
+        .into_iter()
+        .chain(at)
+        .map(|(i, f)| {
+            emit_field_write(
+                input,
+                i,
+                f,
+                &object_prefix,
+                ident,
+                &restored_ctx(i),
+                at_patch(i),
+            )
+        })
         .collect()
 }
 
@@ -445,8 +656,9 @@ fn emit_bit_byte_offsets(
     (bit_offset, byte_offset)
 }
 
-fn emit_padding(bit_size: &TokenStream) -> TokenStream {
+fn emit_padding(bit_size: &TokenStream, padding: &Padding) -> TokenStream {
     let crate_ = super::get_crate_name();
+    let write_padding = emit_write_padding(padding);
     quote! {
         {
             use core::convert::TryFrom;
@@ -456,8 +668,92 @@ fn emit_padding(bit_size: &TokenStream) -> TokenStream {
                     stringify!(#bit_size)
                 ))
             )?;
+
+            #write_padding
+        }
+    }
+}
+
+/// The offset is from the start of the struct/enum, or of the outermost output with `align_input`
+fn emit_alignment(bit_size: &TokenStream, align_input: bool, padding: &Padding) -> TokenStream {
+    let crate_ = super::get_crate_name();
+    let offset = if align_input {
+        quote! { __deku_layout.position(__deku_output) }
+    } else {
+        quote! { (__deku_output.len() - __deku_align_start) }
+    };
+    let write_padding = emit_write_padding(padding);
+    quote! {
+        {
+            use core::convert::TryFrom;
+            let __deku_align = usize::try_from(#bit_size).map_err(|e|
+                ::#crate_::DekuError::InvalidParam(format!(
+                    "Invalid alignment param \"({})\": cannot convert to usize",
+                    stringify!(#bit_size)
+                ))
+            )?;
+            if __deku_align == 0 {
+                return Err(::#crate_::DekuError::InvalidParam(format!(
+                    "Invalid alignment param \"({})\": must be greater than 0",
+                    stringify!(#bit_size)
+                )));
+            }
+
+            let __deku_pad = (__deku_align - #offset % __deku_align) % __deku_align;
+
+            #write_padding
+        }
+    }
+}
+
+/// Write `__deku_pad` bits of the fill value
+fn emit_write_padding(padding: &Padding) -> TokenStream {
+    let crate_ = super::get_crate_name();
+    match padding.fill_bytes() {
+        Some(fill) => quote! {
+            let __deku_fill: &[u8] = #fill;
+            let __deku_fill_bits = <[u8] as ::#crate_::bitvec::BitView>::view_bits::<::#crate_::bitvec::Msb0>(__deku_fill);
+            for __deku_i in 0..__deku_pad {
+                __deku_output.push(__deku_fill_bits[__deku_i % __deku_fill_bits.len()]);
+            }
+        },
+        None => quote! {
             let new_len = __deku_output.len() + __deku_pad;
             __deku_output.resize(new_len, false);
+        },
+    }
+}
+
+/// Write the value of the reserved range before a field
+fn emit_reserved(f: &FieldData) -> TokenStream {
+    let crate_ = super::get_crate_name();
+    let bit_size = match reserved_bits(f) {
+        Some(bit_size) => bit_size,
+        None => return quote! {},
+    };
+    let value = f.reserved_value.clone().unwrap_or_else(|| quote! { 0 });
+
+    quote! {
+        {
+            use core::convert::TryFrom;
+            let __deku_reserved_size = usize::try_from(#bit_size).map_err(|e|
+                ::#crate_::DekuError::InvalidParam(format!(
+                    "Invalid reserved param \"({})\": cannot convert to usize",
+                    stringify!(#bit_size)
+                ))
+            )?;
+            let __deku_reserved_value: u64 = #value;
+            if __deku_reserved_size < 64 && (__deku_reserved_value >> __deku_reserved_size) != 0 {
+                return Err(::#crate_::DekuError::InvalidParam(format!(
+                    "Invalid reserved value {:#x}: does not fit in {} bits",
+                    __deku_reserved_value,
+                    __deku_reserved_size
+                )));
+            }
+
+            for __deku_shift in (0..__deku_reserved_size).rev() {
+                __deku_output.push(__deku_shift < 64 && (__deku_reserved_value >> __deku_shift) & 1 == 1);
+            }
         }
     }
 }
@@ -468,13 +764,21 @@ fn emit_field_write(
     f: &FieldData,
     object_prefix: &Option<TokenStream>,
     ident: &TokenStream,
+    restored_ctx: &[Ident],
+    at_patch: Option<Ident>,
 ) -> Result<TokenStream, syn::Error> {
     let crate_ = super::get_crate_name();
-    let field_endian = f.endian.as_ref().or(input.endian.as_ref());
+    let container_endian = container_endian(input, i);
+    let field_endian = f.endian.as_ref().or(container_endian.as_ref());
 
     // fields to check usage of bit/byte offset
     let field_check_vars = [
         &f.writer,
+        &f.write_map,
+        &f.terminator,
+        &f.until_exclusive,
+        &f.window_bytes,
+        &f.at,
         &f.cond,
         &f.ctx.as_ref().map(|v| quote!(#v)),
         &f.assert,
@@ -489,15 +793,22 @@ fn emit_field_write(
     let field_ident_str = field_ident.to_string();
 
     let field_assert = f.assert.as_ref().map(|v| {
+        let error = gen_assert_error(
+            f.assert_msg.as_ref(),
+            f.assert_err.as_ref(),
+            quote! {
+                format!(
+                    "{}.{} field failed assertion: {}",
+                    #ident,
+                    #field_ident_str,
+                    stringify!(#v)
+                )
+            },
+        );
         quote! {
             if (!(#v)) {
                 // assertion is false, raise error
-                return Err(::#crate_::DekuError::Assertion(format!(
-                            "{}.{} field failed assertion: {}",
-                            #ident,
-                            #field_ident_str,
-                            stringify!(#v)
-                        )));
+                return Err(#error);
             } else {
                 // do nothing
             }
@@ -505,62 +816,228 @@ fn emit_field_write(
     });
 
     let field_assert_eq = f.assert_eq.as_ref().map(|v| {
+        let error = gen_assert_error(
+            f.assert_msg.as_ref(),
+            f.assert_err.as_ref(),
+            quote! {
+                format!(
+                    "{}.{} field failed assertion: {}",
+                    #ident,
+                    #field_ident_str,
+                    stringify!(#field_ident == #v)
+                )
+            },
+        );
         quote! {
             if (!(*(#field_ident) == (#v))) {
                 // assertion is false, raise error
-                return Err(::#crate_::DekuError::Assertion(format!(
-                            "{}.{} field failed assertion: {}",
-                            #ident,
-                            #field_ident_str,
-                            stringify!(#field_ident == #v)
-                        )));
+                return Err(#error);
             } else {
                 // do nothing
             }
         }
     });
 
+    let write_args = gen_field_args(
+        field_endian,
+        f.bits.as_ref(),
+        f.bytes.as_ref(),
+        f.ctx.as_ref(),
+    )?;
+
     let field_write_func = if field_writer.is_some() {
         quote! { #field_writer }
     } else {
-        let write_args = gen_field_args(
-            field_endian,
-            f.bits.as_ref(),
-            f.bytes.as_ref(),
-            f.ctx.as_ref(),
-        )?;
+        // convert the field value to the on-wire value
+        let write_map = match &f.as_type {
+            Some(as_type) => Some(emit_as_type_map(
+                ident,
+                &field_ident_str,
+                as_type,
+                &f.ty,
+                f.try_from,
+            )),
+            None => f.write_map.clone(),
+        };
+        let write_value = |value: TokenStream| match &write_map {
+            Some(write_map) => quote! {
+                {
+                    let __deku_mapped = (#write_map)(#value)?;
+                    ::#crate_::DekuWrite::write_in(&__deku_mapped, __deku_layout, __deku_output, (#write_args))
+                }
+            },
+            None => {
+                quote! { ::#crate_::DekuWrite::write_in(#value, __deku_layout, __deku_output, (#write_args)) }
+            }
+        };
 
         if f.temp {
             if let Some(temp_value) = &f.temp_value {
                 let field_type = &f.ty;
+                let write = write_value(quote! { #object_prefix &#field_ident });
                 quote! {
                     let #field_ident: #field_type = #temp_value;
-                    ::#crate_::DekuWrite::write(#object_prefix &#field_ident, __deku_output, (#write_args))
+                    #write
                 }
             } else {
                 quote! { core::result::Result::<(), ::#crate_::DekuError>::Ok(()) }
             }
+        } else if let Some(terminator) = &f.terminator {
+            // the terminator is written after the elements, which can't contain it
+            let field_type = &f.ty;
+            let write = write_value(quote! { #object_prefix #field_ident });
+            let is_terminator = match &f.until_exclusive {
+                Some(until_exclusive) => quote! { (#until_exclusive)(__deku_element) },
+                None => quote! { *__deku_element == __deku_terminator },
+            };
+            quote! {
+                {
+                    let __deku_terminator: <#field_type as core::iter::IntoIterator>::Item = #terminator;
+                    for __deku_element in core::iter::IntoIterator::into_iter(#object_prefix #field_ident) {
+                        if #is_terminator {
+                            return Err(::#crate_::DekuError::InvalidParam(format!(
+                                "{}.{} field contains the terminator",
+                                #ident,
+                                #field_ident_str
+                            )));
+                        }
+                    }
+                    #write?;
+                    ::#crate_::DekuWrite::write_in(&__deku_terminator, __deku_layout, __deku_output, (#write_args))
+                }
+            }
         } else {
-            quote! { ::#crate_::DekuWrite::write(#object_prefix #field_ident, __deku_output, (#write_args)) }
+            write_value(quote! { #object_prefix #field_ident })
         }
     };
 
+    let reserved = emit_reserved(f);
+    let padding = Padding::new(input, Some((f, field_ident_str.clone())));
     let pad_bits_before = pad_bits(
         f.pad_bits_before.as_ref(),
         f.pad_bytes_before.as_ref(),
-        emit_padding,
+        |bit_size| emit_padding(bit_size, &padding),
     );
     let pad_bits_after = pad_bits(
         f.pad_bits_after.as_ref(),
         f.pad_bytes_after.as_ref(),
-        emit_padding,
+        |bit_size| emit_padding(bit_size, &padding),
+    );
+    let align_bits_before = align_bits(
+        f.align_bits_before.as_ref(),
+        f.align_before.as_ref(),
+        input.align_input,
+        |bit_size, align_input| emit_alignment(bit_size, align_input, &padding),
+    );
+    let align_bits_after = align_bits(
+        f.align_bits_after.as_ref(),
+        f.align_after.as_ref(),
+        input.align_input,
+        |bit_size, align_input| emit_alignment(bit_size, align_input, &padding),
     );
 
-    let field_write_normal = quote! {
-        #field_write_func ?;
+    let field_write_normal = match &f.window_bytes {
+        Some(window_bytes) => {
+            // the field is written to a window of `window_bytes`, filled up with padding
+            let window_fill = match f.window_rest().as_str() {
+                "skip" => emit_padding(
+                    &quote! { __deku_window_bits - __deku_window_written },
+                    &padding,
+                ),
+                "keep" => quote! {},
+                _ => quote! {
+                    if __deku_window_written != __deku_window_bits {
+                        return Err(::#crate_::DekuError::InvalidParam(format!(
+                            "{}.{} field doesn't fill its window: {} of {} bits",
+                            #ident,
+                            #field_ident_str,
+                            __deku_window_written,
+                            __deku_window_bits
+                        )));
+                    }
+                },
+            };
+            quote! {
+                let __deku_window_bits = {
+                    use core::borrow::Borrow;
+                    use core::convert::TryFrom;
+                    usize::try_from(*((#window_bytes).borrow()))? * 8
+                };
+                let __deku_window_start = __deku_output.len();
+                #field_write_func ?;
+                let __deku_window_written = __deku_output.len() - __deku_window_start;
+                if __deku_window_written > __deku_window_bits {
+                    return Err(::#crate_::DekuError::InvalidParam(format!(
+                        "{}.{} field is larger than its window: {} of {} bits",
+                        #ident,
+                        #field_ident_str,
+                        __deku_window_written,
+                        __deku_window_bits
+                    )));
+                }
+                #window_fill
+            }
+        }
+        None => quote! {
+            #field_write_func ?;
+        },
+    };
+
+    // a field which isn't written is read back as the default, error if the value differs
+    let check_default = |cond: &TokenStream, msg: &str| {
+        let field_type = &f.ty;
+        let field_default = &f.default;
+        quote! {
+            let __deku_default: #field_type = #field_default;
+            if *(#field_ident) != __deku_default {
+                return Err(::#crate_::DekuError::InvalidParam(format!(
+                    "{}.{} field {}: {}",
+                    #ident,
+                    #field_ident_str,
+                    #msg,
+                    stringify!(#cond)
+                )));
+            }
+        }
     };
 
-    let field_write_tokens = match (f.skip, &f.cond) {
+    // the field isn't bound when evaluating `cond` when reading, the `ctx` argument is
+    let field_cond = f.cond.as_ref().map(|field_cond| {
+        let restores = restored_ctx.iter().map(|ctx_ident| {
+            let saved = format_ident!("__deku_ctx_{}", ctx_ident);
+            quote! { let #ctx_ident = *#saved; }
+        });
+        quote! {
+            {
+                #(#restores)*
+                #field_cond
+            }
+        }
+    });
+
+    // `cond` refers to the input, which doesn't exist when writing
+    let cond_reads_input = [
+        "__deku_rest",
+        "__deku_input",
+        "__deku_bit_offset",
+        "__deku_byte_offset",
+    ]
+    .iter()
+    .any(|var| token_contains_string(&f.cond, var));
+    if cond_reads_input && (f.skip || f.cond_strict) {
+        return Err(syn::Error::new(
+            f.ident.span(),
+            "DekuWrite: `cond` of a `skip` or `cond_strict` field can't use `deku::rest`, `deku::input_bits`, `deku::bit_offset` or `deku::byte_offset`",
+        ));
+    }
+
+    let field_write_tokens = match (f.skip, &field_cond) {
+        (false, Some(_)) if cond_reads_input => {
+            // can't be evaluated, always write the field
+            quote! {
+                #field_write_normal
+            }
+        }
         (true, Some(field_cond)) => {
             // #[deku(skip, cond = "...")] ==> `skip` if `cond`
             quote! {
@@ -577,15 +1054,81 @@ fn emit_field_write(
                 // skipping, no write
             }
         }
-        (false, _) => {
+        (false, Some(field_cond)) => {
+            // #[deku(cond = "...")] ==> write if `cond`
+            let field_cond_strict = if f.cond_strict || input.cond_strict {
+                let cond = f.cond.as_ref().unwrap_or(field_cond);
+                check_default(cond, "differs from the default with false condition")
+            } else {
+                quote! {}
+            };
+            quote! {
+                if (#field_cond) {
+                    #field_write_normal
+                } else {
+                    #field_cond_strict
+                }
+            }
+        }
+        (false, None) => {
             quote! {
                 #field_write_normal
             }
         }
     };
 
+    // only write the field if present in the `version`, a value other than the
+    // default can't be represented in versions without the field
+    let field_write_tokens = match f.version_cond() {
+        Some(version_cond) => {
+            let field_version_check = check_default(&version_cond, "is not present in version");
+            quote! {
+                if (#version_cond) {
+                    #field_write_tokens
+                } else {
+                    #field_version_check
+                }
+            }
+        }
+        None => field_write_tokens,
+    };
+
+    let endian_from = emit_endian_from(input, i, &field_ident);
+
+    // the offset field is filled in with the offset of the data once laid out
+    let (offset_field_start, offset_field_end) = match &at_patch {
+        Some(at_patch) if f.at.is_none() => {
+            let field_type = &f.ty;
+            (
+                quote! {
+                    let __deku_at_position = __deku_layout.position(__deku_output);
+                },
+                quote! {
+                    let #at_patch = __deku_layout.offset_field(__deku_at_position, __deku_output, {
+                        let __deku_ctx = (#write_args);
+                        move |__deku_at_offset: usize, __deku_output: &mut ::#crate_::bitvec::BitVec<u8, ::#crate_::bitvec::Msb0>| {
+                            use core::convert::TryFrom;
+                            let __deku_value = <#field_type as TryFrom<usize>>::try_from(__deku_at_offset).map_err(|_|
+                                ::#crate_::DekuError::InvalidParam(format!(
+                                    "{}.{} field can't hold the offset {}",
+                                    #ident,
+                                    #field_ident_str,
+                                    __deku_at_offset
+                                ))
+                            )?;
+                            ::#crate_::DekuWrite::write(&__deku_value, __deku_output, __deku_ctx)
+                        }
+                    });
+                },
+            )
+        }
+        _ => (quote! {}, quote! {}),
+    };
+
     let field_write = quote! {
+        #reserved
         #pad_bits_before
+        #align_bits_before
 
         #bit_offset
         #byte_offset
@@ -593,14 +1136,77 @@ fn emit_field_write(
         #field_assert
         #field_assert_eq
 
+        #offset_field_start
         #field_write_tokens
+        #offset_field_end
 
         #pad_bits_after
+        #align_bits_after
+        #endian_from
+    };
+
+    let field_write = match &f.at {
+        Some(at) => {
+            // the data is laid out once the output is written, after it unless the offset is
+            // given by an expression
+            let at_offset = match &at_patch {
+                Some(at_patch) => quote! { ::#crate_::layout::AtOffset::Field(#at_patch) },
+                None => quote! {
+                    ::#crate_::layout::AtOffset::Bits({
+                        use core::borrow::Borrow;
+                        use core::convert::TryFrom;
+                        usize::try_from(*((#at).borrow()))? * 8
+                    })
+                },
+            };
+            quote! {
+                __deku_layout.write_at(
+                    concat!(#ident, ".", #field_ident_str),
+                    #at_offset,
+                    |__deku_layout, __deku_output| -> core::result::Result<(), ::#crate_::DekuError> {
+                        #field_write
+                        Ok(())
+                    },
+                )?;
+            }
+        }
+        None => field_write,
     };
 
     Ok(field_write)
 }
 
+/// Conversion from the field type to the `as_type` on-wire value, like a `write_map` closure
+fn emit_as_type_map(
+    ident: &str,
+    field_ident_str: &str,
+    as_type: &TokenStream,
+    field_type: &syn::Type,
+    try_from: bool,
+) -> TokenStream {
+    let crate_ = super::get_crate_name();
+    if try_from {
+        quote! {
+            (|__deku_value: &#field_type| -> core::result::Result<#as_type, ::#crate_::DekuError> {
+                <#field_type as core::convert::TryInto<#as_type>>::try_into(core::clone::Clone::clone(__deku_value)).map_err(|_| {
+                    ::#crate_::DekuError::InvalidParam(format!(
+                        "Invalid {}.{}: cannot convert into {}",
+                        #ident,
+                        #field_ident_str,
+                        stringify!(#as_type)
+                    ))
+                })
+            })
+        }
+    } else {
+        quote! {
+            (|__deku_value: &#field_type| -> core::result::Result<#as_type, ::#crate_::DekuError> {
+                Ok(<#field_type as core::convert::Into<#as_type>>::into(core::clone::Clone::clone(__deku_value)))
+            })
+        }
+    }
+}
+
 /// avoid outputing `use core::convert::TryInto` if update() function is generated with empty Vec
 fn check_update_use<T>(vec: &[T]) -> TokenStream {
     if !vec.is_empty() {
@@ -1,4 +1,5 @@
-use crate::Num;
+use crate::{DekuData, FieldData, Id, Num, VariantData};
+use darling::ast::Data;
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::{quote, ToTokens};
 use syn::parse::Parser;
@@ -7,6 +8,7 @@ use syn::spanned::Spanned;
 use syn::token::Comma;
 
 pub(crate) mod deku_read;
+pub(crate) mod deku_schema;
 pub(crate) mod deku_write;
 
 #[cfg(feature = "proc-macro-crate")]
@@ -30,7 +32,7 @@ fn get_crate_name() -> Ident {
 
 /// Generate enum initialization TokenStream
 /// Cases:
-/// - No fields: `MyEnum`
+/// - No fields: `MyEnum`, or `MyEnum {}` if named
 /// - Named: `MyEnum { field_idents }`
 /// - Unnamed:  `MyEnum ( field_idents )`
 fn gen_enum_init<V: ToTokens, I: ToTokens>(
@@ -40,7 +42,12 @@ fn gen_enum_init<V: ToTokens, I: ToTokens>(
 ) -> TokenStream {
     let mut field_idents = field_idents.peekable();
     if field_idents.peek().is_none() {
-        return quote! { #enum_variant };
+        // named variants whose fields are all temporary
+        return if is_named {
+            quote! { #enum_variant {} }
+        } else {
+            quote! { #enum_variant }
+        };
     }
 
     if is_named {
@@ -330,7 +337,7 @@ fn token_contains_string(tok: &Option<TokenStream>, s: &str) -> bool {
 fn pad_bits(
     bits: Option<&TokenStream>,
     bytes: Option<&TokenStream>,
-    emit_padding: fn(&TokenStream) -> TokenStream,
+    emit_padding: impl Fn(&TokenStream) -> TokenStream,
 ) -> TokenStream {
     match (bits, bytes) {
         (Some(pad_bits), Some(pad_bytes)) => {
@@ -341,3 +348,189 @@ fn pad_bits(
         (None, None) => quote!(),
     }
 }
+
+fn align_bits(
+    bits: Option<&TokenStream>,
+    bytes: Option<&TokenStream>,
+    align_input: bool,
+    emit_alignment: impl Fn(&TokenStream, bool) -> TokenStream,
+) -> TokenStream {
+    match (bits, bytes) {
+        (Some(align_bits), _) => emit_alignment(align_bits, align_input),
+        (None, Some(align_bytes)) => emit_alignment(&quote! {((#align_bytes) * 8)}, align_input),
+        (None, None) => quote!(),
+    }
+}
+
+/// Size in bits of the reserved range before a field
+fn reserved_bits(f: &FieldData) -> Option<TokenStream> {
+    match (&f.reserved_bits, &f.reserved_bytes) {
+        (Some(bits), _) => Some(quote! { #bits }),
+        (None, Some(bytes)) => Some(quote! { ((#bytes) * 8) }),
+        (None, None) => None,
+    }
+}
+
+/// Fill value and checking of the padding of a field or of the struct/enum
+struct Padding<'a> {
+    fill: Option<&'a Id>,
+    strict: bool,
+    /// `Type.field` or `Type`, used in error messages
+    name: String,
+}
+
+impl<'a> Padding<'a> {
+    fn new(input: &'a DekuData, field: Option<(&'a FieldData, String)>) -> Self {
+        let ident = input.ident.to_string();
+        match field {
+            Some((f, field_ident)) => Self {
+                fill: f.pad_fill.as_ref().or(input.pad_fill.as_ref()),
+                strict: f.pad_strict || input.pad_strict,
+                name: format!("{}.{}", ident, field_ident),
+            },
+            None => Self {
+                fill: input.pad_fill.as_ref(),
+                strict: input.pad_strict,
+                name: ident,
+            },
+        }
+    }
+
+    /// Fill pattern as a `&[u8]` expression, `None` for zeros
+    fn fill_bytes(&self) -> Option<TokenStream> {
+        self.fill.map(|fill| match fill {
+            Id::TokenStream(v) => quote! { &[#v] },
+            Id::LitByteStr(v) => quote! { #v },
+        })
+    }
+
+    /// Fill pattern as written in the attribute
+    fn fill_str(&self) -> String {
+        match self.fill {
+            Some(fill) => fill.to_string(),
+            None => "0".to_string(),
+        }
+    }
+}
+
+/// Returns true if the start of the struct/enum is needed to compute alignment padding
+fn needs_align_start(input: &DekuData) -> bool {
+    let field_aligned = |f: &FieldData| {
+        f.align_bits_before.is_some()
+            || f.align_before.is_some()
+            || f.align_bits_after.is_some()
+            || f.align_after.is_some()
+    };
+
+    let aligned = input.align.is_some()
+        || match &input.data {
+            Data::Struct(fields) => fields.iter().any(field_aligned),
+            Data::Enum(variants) => variants.iter().any(|v| v.fields.iter().any(field_aligned)),
+        };
+
+    aligned
+}
+
+/// Index of the struct field named by `endian_from`
+fn endian_from_index(input: &DekuData) -> Option<usize> {
+    let name = input.endian_from.as_ref()?.value();
+    match &input.data {
+        Data::Struct(fields) => fields
+            .iter()
+            .enumerate()
+            .position(|(i, f)| f.get_ident(i, false).to_string() == name),
+        Data::Enum(_) => None,
+    }
+}
+
+/// Default endianness of the `i`th field: the endianness selected by the `endian_from`
+/// field for the fields following it, otherwise the top-level `endian`
+fn container_endian(input: &DekuData, i: usize) -> Option<syn::LitStr> {
+    match endian_from_index(input) {
+        Some(index) if i > index => Some(syn::LitStr::new(
+            "__deku_endian_from",
+            input.endian_from.span(),
+        )),
+        _ => input.endian.clone(),
+    }
+}
+
+/// Select the endianness of the following fields after the `endian_from` field
+fn emit_endian_from(input: &DekuData, i: usize, field_ident: &TokenStream) -> TokenStream {
+    let crate_ = get_crate_name();
+    if endian_from_index(input) == Some(i) {
+        quote! {
+            let __deku_endian_from = <::#crate_::ctx::Endian as core::convert::From<_>>::from(#field_ident);
+        }
+    } else {
+        quote! {}
+    }
+}
+
+/// Error of a failed assertion: the user error from `assert_err`, the message from
+/// `assert_msg` formatted with the fields in scope, or the default message
+fn gen_assert_error(
+    assert_msg: Option<&syn::LitStr>,
+    assert_err: Option<&TokenStream>,
+    default_msg: TokenStream,
+) -> TokenStream {
+    let crate_ = get_crate_name();
+    match (assert_err, assert_msg) {
+        (Some(assert_err), _) => quote! { ::#crate_::__deku_assert_err!(#assert_err) },
+        (None, Some(assert_msg)) => {
+            quote! { ::#crate_::DekuError::Assertion(format!(#assert_msg)) }
+        }
+        (None, None) => quote! { ::#crate_::DekuError::Assertion(#default_msg) },
+    }
+}
+
+/// Check the top-level `assert`, with the fields in scope
+fn emit_container_assert(input: &DekuData) -> TokenStream {
+    let ident = input.ident.to_string();
+    match &input.assert {
+        Some(assert) => {
+            let error = gen_assert_error(
+                input.assert_msg.as_ref(),
+                input.assert_err.as_ref(),
+                quote! { format!("{} failed assertion: {}", #ident, stringify!(#assert)) },
+            );
+            quote! {
+                if !(#assert) {
+                    return Err(#error);
+                }
+            }
+        }
+        None => quote! {},
+    }
+}
+
+/// Check the whole value with the top-level `validate` function
+fn emit_container_validate(input: &DekuData, value: TokenStream) -> TokenStream {
+    let ident = input.ident.to_string();
+    match &input.validate {
+        Some(validate) => {
+            let error = gen_assert_error(
+                input.assert_msg.as_ref(),
+                input.assert_err.as_ref(),
+                quote! { format!("{} failed validation: {}", #ident, stringify!(#validate)) },
+            );
+            quote! {
+                if !(#validate)(#value) {
+                    return Err(#error);
+                }
+            }
+        }
+        None => quote! {},
+    }
+}
+
+/// Bind the variant `ctx` values for the variant fields
+fn emit_variant_ctx(variant: &VariantData) -> TokenStream {
+    let bindings = variant.ctx_bindings().into_iter().map(|(ident, expr)| {
+        quote! {
+            #[allow(unused_variables)]
+            let #ident = #expr;
+        }
+    });
+    quote! { #(#bindings)* }
+}
@@ -0,0 +1,771 @@
+/*!
+ASN.1 BER and DER tag-length-value encoding (ITU-T X.690)
+
+Requires the `asn1` feature.
+
+Every BER value is a [Tag], a [Length] and the contents. [Tlv] reads the tag and the
+length, then reads its value within exactly `length` bytes, so the value can be a type
+deriving [DekuRead]/[DekuWrite] such as the fields of a SEQUENCE. The contents of
+primitive values (INTEGER, OBJECT IDENTIFIER, OCTET STRING, ...) are read as raw
+[Contents], the elements of a SEQUENCE OF or SET OF as a [SequenceOf].
+
+Values are read with the encoding [Rules] as context, [Rules::Ber] with the `()`
+context. BER accepts the indefinite length form, ended by end-of-contents octets, and
+long form lengths with more octets than needed. [Rules::Der] rejects them and
+requires the short form of a tag number when possible. Values are always written with
+the minimal definite length form, which is valid DER.
+
+To read DER, a [Tlv] is read with [Rules::Der] as context and passes the rules to its
+value, so the fields of a SEQUENCE take the rules as [ctx](crate::attributes#ctx).
+[ctx_default](crate::attributes#ctx_default) keeps the `()` implementations, used to
+read BER.
+
+# Example
+
+```rust
+use deku::asn1::{Contents, Rules, Tag, Tlv};
+use deku::bitvec::BitView;
+use deku::prelude::*;
+
+/// AlgorithmIdentifier ::= SEQUENCE { algorithm OBJECT IDENTIFIER, parameters NULL }
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(ctx = "rules: Rules", ctx_default = "Rules::Ber")]
+struct AlgorithmIdentifier {
+    #[deku(ctx = "rules", assert = "algorithm.tag == Tag::OBJECT_IDENTIFIER")]
+    algorithm: Tlv<Contents>,
+    #[deku(ctx = "rules", assert = "parameters.tag == Tag::NULL")]
+    parameters: Tlv<()>,
+}
+
+// sha256WithRSAEncryption
+let data = b"\x30\x0D\x06\x09\x2A\x86\x48\x86\xF7\x0D\x01\x01\x0B\x05\x00";
+let (_rest, value) = Tlv::<AlgorithmIdentifier>::from_bytes((data, 0)).unwrap();
+assert_eq!(Tag::SEQUENCE, value.tag);
+assert_eq!(
+    b"\x2A\x86\x48\x86\xF7\x0D\x01\x01\x0B".to_vec(),
+    value.value.algorithm.value.0
+);
+assert_eq!(data.to_vec(), value.to_bytes().unwrap());
+assert!(Tlv::<AlgorithmIdentifier>::read(data.view_bits(), Rules::Der).is_ok());
+
+// a non-minimal long form length is valid BER but not DER
+let data = b"\x30\x0E\x06\x81\x09\x2A\x86\x48\x86\xF7\x0D\x01\x01\x0B\x05\x00";
+let (_rest, ber) = Tlv::<AlgorithmIdentifier>::from_bytes((data, 0)).unwrap();
+assert_eq!(value, ber);
+assert!(Tlv::<AlgorithmIdentifier>::read(data.view_bits(), Rules::Der).is_err());
+```
+*/
+
+use crate::{DekuContainerRead, DekuContainerWrite, DekuError, DekuRead, DekuWrite};
+use alloc::{format, vec::Vec};
+use bitvec::prelude::*;
+use core::fmt;
+
+/// Encoding rules of the values
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Rules {
+    /// Basic Encoding Rules
+    #[default]
+    Ber,
+    /// Distinguished Encoding Rules, a single encoding of each value
+    Der,
+}
+
+/// Class of a [Tag]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Class {
+    /// Types defined by X.680, such as INTEGER or SEQUENCE
+    Universal,
+    /// Types of an application
+    Application,
+    /// Types whose meaning depends on the enclosing type, such as `[0]`
+    ContextSpecific,
+    /// Types of an organization
+    Private,
+}
+
+impl Class {
+    fn from_bits(bits: u8) -> Self {
+        match bits & 0b11 {
+            0 => Class::Universal,
+            1 => Class::Application,
+            2 => Class::ContextSpecific,
+            _ => Class::Private,
+        }
+    }
+
+    fn bits(self) -> u8 {
+        match self {
+            Class::Universal => 0,
+            Class::Application => 1,
+            Class::ContextSpecific => 2,
+            Class::Private => 3,
+        }
+    }
+}
+
+/// Identifier octets of a value
+///
+/// Tag numbers from 31 are encoded in base 128 in the following octets.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Tag {
+    /// Class of the tag
+    pub class: Class,
+    /// True if the contents are values, false if they are primitive
+    pub constructed: bool,
+    /// Tag number
+    pub number: u32,
+}
+
+impl Tag {
+    /// BOOLEAN
+    pub const BOOLEAN: Tag = Tag::new(Class::Universal, false, 1);
+    /// INTEGER
+    pub const INTEGER: Tag = Tag::new(Class::Universal, false, 2);
+    /// BIT STRING
+    pub const BIT_STRING: Tag = Tag::new(Class::Universal, false, 3);
+    /// OCTET STRING
+    pub const OCTET_STRING: Tag = Tag::new(Class::Universal, false, 4);
+    /// NULL
+    pub const NULL: Tag = Tag::new(Class::Universal, false, 5);
+    /// OBJECT IDENTIFIER
+    pub const OBJECT_IDENTIFIER: Tag = Tag::new(Class::Universal, false, 6);
+    /// ENUMERATED
+    pub const ENUMERATED: Tag = Tag::new(Class::Universal, false, 10);
+    /// UTF8String
+    pub const UTF8_STRING: Tag = Tag::new(Class::Universal, false, 12);
+    /// SEQUENCE and SEQUENCE OF
+    pub const SEQUENCE: Tag = Tag::new(Class::Universal, true, 16);
+    /// SET and SET OF
+    pub const SET: Tag = Tag::new(Class::Universal, true, 17);
+    /// PrintableString
+    pub const PRINTABLE_STRING: Tag = Tag::new(Class::Universal, false, 19);
+    /// IA5String
+    pub const IA5_STRING: Tag = Tag::new(Class::Universal, false, 22);
+    /// UTCTime
+    pub const UTC_TIME: Tag = Tag::new(Class::Universal, false, 23);
+    /// GeneralizedTime
+    pub const GENERALIZED_TIME: Tag = Tag::new(Class::Universal, false, 24);
+
+    /// Create a tag
+    pub const fn new(class: Class, constructed: bool, number: u32) -> Self {
+        Self {
+            class,
+            constructed,
+            number,
+        }
+    }
+
+    /// Create a context-specific tag, `[number]`
+    pub const fn context_specific(number: u32, constructed: bool) -> Self {
+        Self::new(Class::ContextSpecific, constructed, number)
+    }
+}
+
+/// ASN.1 notation of the tag, such as `[UNIVERSAL 16]` or `[0]`
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.class {
+            Class::Universal => write!(f, "[UNIVERSAL {}]", self.number),
+            Class::Application => write!(f, "[APPLICATION {}]", self.number),
+            Class::ContextSpecific => write!(f, "[{}]", self.number),
+            Class::Private => write!(f, "[PRIVATE {}]", self.number),
+        }
+    }
+}
+
+impl<'a> DekuRead<'a, Rules> for Tag {
+    fn read(
+        input: &'a BitSlice<u8, Msb0>,
+        rules: Rules,
+    ) -> Result<(&'a BitSlice<u8, Msb0>, Self), DekuError> {
+        let (mut rest, first) = u8::read(input, ())?;
+        let class = Class::from_bits(first >> 6);
+        let constructed = first & 0x20 != 0;
+        if first & 0x1F != 0x1F {
+            return Ok((rest, Tag::new(class, constructed, u32::from(first & 0x1F))));
+        }
+
+        let mut number: u32 = 0;
+        loop {
+            let (new_rest, byte) = u8::read(rest, ())?;
+            rest = new_rest;
+            if number == 0 && byte == 0x80 {
+                return Err(DekuError::Parse(
+                    "tag number with a leading zero octet".into(),
+                ));
+            }
+            number = number
+                .checked_mul(128)
+                .map(|number| number | u32::from(byte & 0x7F))
+                .ok_or_else(|| DekuError::Parse("tag number does not fit in 32 bits".into()))?;
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+        if rules == Rules::Der && number < 0x1F {
+            return Err(DekuError::Parse(format!(
+                "DER: tag number {number} must use the single octet form"
+            )));
+        }
+        Ok((rest, Tag::new(class, constructed, number)))
+    }
+}
+
+impl<'a> DekuRead<'a, ()> for Tag {
+    fn read(
+        input: &'a BitSlice<u8, Msb0>,
+        _: (),
+    ) -> Result<(&'a BitSlice<u8, Msb0>, Self), DekuError> {
+        Self::read(input, Rules::Ber)
+    }
+}
+
+impl DekuWrite<()> for Tag {
+    fn write(&self, output: &mut BitVec<u8, Msb0>, _: ()) -> Result<(), DekuError> {
+        let first = (self.class.bits() << 6) | (u8::from(self.constructed) << 5);
+        if self.number < 0x1F {
+            output.extend_from_bitslice((first | self.number as u8).view_bits::<Msb0>());
+            return Ok(());
+        }
+
+        output.extend_from_bitslice((first | 0x1F).view_bits::<Msb0>());
+        let groups = (32 - self.number.leading_zeros() as usize).div_ceil(7);
+        for i in (0..groups).rev() {
+            let more = if i == 0 { 0 } else { 0x80 };
+            let byte = ((self.number >> (7 * i)) & 0x7F) as u8 | more;
+            output.extend_from_bitslice(byte.view_bits::<Msb0>());
+        }
+        Ok(())
+    }
+}
+
+impl DekuWrite<Rules> for Tag {
+    fn write(&self, output: &mut BitVec<u8, Msb0>, _: Rules) -> Result<(), DekuError> {
+        self.write(output, ())
+    }
+}
+
+/// Length octets of a value
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Length {
+    /// Length of the contents, in bytes
+    Definite(usize),
+    /// Contents ended by end-of-contents octets, only allowed by BER for constructed
+    /// values
+    Indefinite,
+}
+
+impl<'a> DekuRead<'a, Rules> for Length {
+    fn read(
+        input: &'a BitSlice<u8, Msb0>,
+        rules: Rules,
+    ) -> Result<(&'a BitSlice<u8, Msb0>, Self), DekuError> {
+        let (mut rest, first) = u8::read(input, ())?;
+        match first {
+            0x00..=0x7F => return Ok((rest, Length::Definite(usize::from(first)))),
+            0x80 if rules == Rules::Der => {
+                return Err(DekuError::Parse("DER: indefinite length".into()))
+            }
+            0x80 => return Ok((rest, Length::Indefinite)),
+            0xFF => return Err(DekuError::Parse("reserved length octet 0xFF".into())),
+            _ => {}
+        }
+
+        let count = first & 0x7F;
+        let mut len: usize = 0;
+        for i in 0..count {
+            let (new_rest, byte) = u8::read(rest, ())?;
+            rest = new_rest;
+            if i == 0 && byte == 0 && rules == Rules::Der {
+                return Err(DekuError::Parse(
+                    "DER: length with a leading zero octet".into(),
+                ));
+            }
+            len = len
+                .checked_mul(256)
+                .map(|len| len | usize::from(byte))
+                .ok_or_else(|| DekuError::Parse(format!("length of {count} octets is too long")))?;
+        }
+        if len < 0x80 && rules == Rules::Der {
+            return Err(DekuError::Parse(format!(
+                "DER: length {len} must use the short form"
+            )));
+        }
+        Ok((rest, Length::Definite(len)))
+    }
+}
+
+impl<'a> DekuRead<'a, ()> for Length {
+    fn read(
+        input: &'a BitSlice<u8, Msb0>,
+        _: (),
+    ) -> Result<(&'a BitSlice<u8, Msb0>, Self), DekuError> {
+        Self::read(input, Rules::Ber)
+    }
+}
+
+/// Write the minimal form of a definite length
+impl DekuWrite<()> for Length {
+    fn write(&self, output: &mut BitVec<u8, Msb0>, _: ()) -> Result<(), DekuError> {
+        match *self {
+            Length::Definite(len) if len < 0x80 => {
+                output.extend_from_bitslice((len as u8).view_bits::<Msb0>());
+            }
+            Length::Definite(len) => {
+                let bytes = len.to_be_bytes();
+                let skip = len.leading_zeros() as usize / 8;
+                let count = (bytes.len() - skip) as u8;
+                output.extend_from_bitslice((0x80 | count).view_bits::<Msb0>());
+                output.extend_from_bitslice(bytes[skip..].view_bits::<Msb0>());
+            }
+            Length::Indefinite => output.extend_from_bitslice(0x80u8.view_bits::<Msb0>()),
+        }
+        Ok(())
+    }
+}
+
+impl DekuWrite<Rules> for Length {
+    fn write(&self, output: &mut BitVec<u8, Msb0>, rules: Rules) -> Result<(), DekuError> {
+        if *self == Length::Indefinite && rules == Rules::Der {
+            return Err(DekuError::InvalidParam("DER: indefinite length".into()));
+        }
+        self.write(output, ())
+    }
+}
+
+/// A value with its tag and length
+///
+/// The value is read within the contents, and must use all of them. With the
+/// indefinite length form, it is read until the end-of-contents octets, such as with a
+/// [SequenceOf]. The tag is not checked, use [assert](crate::attributes#assert) to
+/// expect a tag.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Tlv<T> {
+    /// Tag of the value
+    pub tag: Tag,
+    /// The value
+    pub value: T,
+}
+
+impl<T> Tlv<T> {
+    /// Create a value with its tag
+    pub fn new(tag: Tag, value: T) -> Self {
+        Self { tag, value }
+    }
+}
+
+/// Read the contents of a value with `read`, then the end-of-contents octets if the
+/// length is indefinite
+fn read_tlv<'a, T>(
+    input: &'a BitSlice<u8, Msb0>,
+    rules: Rules,
+    read: impl FnOnce(&'a BitSlice<u8, Msb0>) -> Result<(&'a BitSlice<u8, Msb0>, T), DekuError>,
+) -> Result<(&'a BitSlice<u8, Msb0>, Tlv<T>), DekuError> {
+    let (rest, tag) = Tag::read(input, rules)?;
+    let (rest, length) = Length::read(rest, rules)?;
+    match length {
+        Length::Definite(len) => {
+            let bits = len
+                .checked_mul(8)
+                .ok_or_else(|| DekuError::Parse(format!("length {len} of {tag} is too long")))?;
+            if rest.len() < bits {
+                return Err(DekuError::Incomplete(crate::error::NeedSize::new(bits)));
+            }
+            let (contents, rest) = rest.split_at(bits);
+            let (left, value) = read(contents)?;
+            if !left.is_empty() {
+                return Err(DekuError::Parse(format!(
+                    "{} bits left in the contents of {tag}",
+                    left.len()
+                )));
+            }
+            Ok((rest, Tlv { tag, value }))
+        }
+        Length::Indefinite if !tag.constructed => Err(DekuError::Parse(format!(
+            "indefinite length of the primitive {tag}"
+        ))),
+        Length::Indefinite => {
+            let (rest, value) = read(rest)?;
+            let (rest, end) = u16::read(rest, crate::ctx::Endian::Big)?;
+            if end != 0 {
+                return Err(DekuError::Parse(format!(
+                    "missing end-of-contents of {tag}"
+                )));
+            }
+            Ok((rest, Tlv { tag, value }))
+        }
+    }
+}
+
+/// Write the tag, the minimal definite length and the value written with `write`
+fn write_tlv(
+    output: &mut BitVec<u8, Msb0>,
+    tag: Tag,
+    write: impl FnOnce(&mut BitVec<u8, Msb0>) -> Result<(), DekuError>,
+) -> Result<(), DekuError> {
+    let mut contents = BitVec::new();
+    write(&mut contents)?;
+    if !contents.len().is_multiple_of(8) {
+        return Err(DekuError::InvalidParam(format!(
+            "contents of {tag} are not a whole number of bytes: {} bits",
+            contents.len()
+        )));
+    }
+    tag.write(output, ())?;
+    Length::Definite(contents.len() / 8).write(output, ())?;
+    output.extend_from_bitslice(&contents);
+    Ok(())
+}
+
+/// Read the value with the rules as context
+impl<'a, T: DekuRead<'a, Rules>> DekuRead<'a, Rules> for Tlv<T> {
+    fn read(
+        input: &'a BitSlice<u8, Msb0>,
+        rules: Rules,
+    ) -> Result<(&'a BitSlice<u8, Msb0>, Self), DekuError> {
+        read_tlv(input, rules, |contents| T::read(contents, rules))
+    }
+}
+
+/// Read BER, the value with the `()` context
+impl<'a, T: DekuRead<'a, ()>> DekuRead<'a, ()> for Tlv<T> {
+    fn read(
+        input: &'a BitSlice<u8, Msb0>,
+        _: (),
+    ) -> Result<(&'a BitSlice<u8, Msb0>, Self), DekuError> {
+        read_tlv(input, Rules::Ber, |contents| T::read(contents, ()))
+    }
+}
+
+/// Write the value with the rules as context
+impl<T: DekuWrite<Rules>> DekuWrite<Rules> for Tlv<T> {
+    fn write(&self, output: &mut BitVec<u8, Msb0>, rules: Rules) -> Result<(), DekuError> {
+        write_tlv(output, self.tag, |contents| {
+            self.value.write(contents, rules)
+        })
+    }
+}
+
+/// Write the value with the `()` context
+impl<T: DekuWrite<()>> DekuWrite<()> for Tlv<T> {
+    fn write(&self, output: &mut BitVec<u8, Msb0>, _: ()) -> Result<(), DekuError> {
+        write_tlv(output, self.tag, |contents| self.value.write(contents, ()))
+    }
+}
+
+impl<'a, T: DekuRead<'a, ()>> DekuContainerRead<'a> for Tlv<T> {
+    fn from_bytes(input: (&'a [u8], usize)) -> Result<((&'a [u8], usize), Self), DekuError> {
+        let bits = input.0.view_bits::<Msb0>();
+        let (rest, value) = Self::read(&bits[input.1..], ())?;
+        let read = bits.len() - rest.len();
+        Ok(((&input.0[read / 8..], read % 8), value))
+    }
+}
+
+impl<T: DekuWrite<()>> DekuContainerWrite for Tlv<T> {
+    fn to_bytes(&self) -> Result<Vec<u8>, DekuError> {
+        Ok(self.to_bits()?.into_vec())
+    }
+
+    fn to_bits(&self) -> Result<BitVec<u8, Msb0>, DekuError> {
+        let mut output = BitVec::new();
+        self.write(&mut output, ())?;
+        Ok(output)
+    }
+}
+
+/// Contents octets of a primitive value, all the remaining bytes
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Contents(pub Vec<u8>);
+
+impl<'a> DekuRead<'a, ()> for Contents {
+    fn read(
+        input: &'a BitSlice<u8, Msb0>,
+        _: (),
+    ) -> Result<(&'a BitSlice<u8, Msb0>, Self), DekuError> {
+        if !input.len().is_multiple_of(8) {
+            return Err(DekuError::Parse(format!(
+                "contents are not a whole number of bytes: {} bits",
+                input.len()
+            )));
+        }
+        let bytes = input.chunks(8).map(|byte| byte.load_be::<u8>()).collect();
+        Ok((&input[input.len()..], Contents(bytes)))
+    }
+}
+
+impl<'a> DekuRead<'a, Rules> for Contents {
+    fn read(
+        input: &'a BitSlice<u8, Msb0>,
+        _: Rules,
+    ) -> Result<(&'a BitSlice<u8, Msb0>, Self), DekuError> {
+        Self::read(input, ())
+    }
+}
+
+impl DekuWrite<()> for Contents {
+    fn write(&self, output: &mut BitVec<u8, Msb0>, _: ()) -> Result<(), DekuError> {
+        output.extend_from_bitslice(self.0.view_bits::<Msb0>());
+        Ok(())
+    }
+}
+
+impl DekuWrite<Rules> for Contents {
+    fn write(&self, output: &mut BitVec<u8, Msb0>, _: Rules) -> Result<(), DekuError> {
+        self.write(output, ())
+    }
+}
+
+/// Values until the end of the contents, the elements of a SEQUENCE OF or SET OF
+///
+/// With the indefinite length form, the values are read until the end-of-contents
+/// octets, which are left to the enclosing [Tlv].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct SequenceOf<T>(pub Vec<T>);
+
+/// Returns true if the input starts with end-of-contents octets
+fn is_end_of_contents(input: &BitSlice<u8, Msb0>) -> bool {
+    input.len() >= 16 && input[..16].not_any()
+}
+
+/// Read values with `read` until the end of the input or the end-of-contents octets
+fn read_values<'a, T>(
+    mut input: &'a BitSlice<u8, Msb0>,
+    read: impl Fn(&'a BitSlice<u8, Msb0>) -> Result<(&'a BitSlice<u8, Msb0>, T), DekuError>,
+) -> Result<(&'a BitSlice<u8, Msb0>, SequenceOf<T>), DekuError> {
+    let mut values = Vec::new();
+    while !input.is_empty() && !is_end_of_contents(input) {
+        let (rest, value) = read(input)?;
+        input = rest;
+        values.push(value);
+    }
+    Ok((input, SequenceOf(values)))
+}
+
+impl<'a, T: DekuRead<'a, Rules>> DekuRead<'a, Rules> for SequenceOf<T> {
+    fn read(
+        input: &'a BitSlice<u8, Msb0>,
+        rules: Rules,
+    ) -> Result<(&'a BitSlice<u8, Msb0>, Self), DekuError> {
+        read_values(input, |rest| T::read(rest, rules))
+    }
+}
+
+impl<'a, T: DekuRead<'a, ()>> DekuRead<'a, ()> for SequenceOf<T> {
+    fn read(
+        input: &'a BitSlice<u8, Msb0>,
+        _: (),
+    ) -> Result<(&'a BitSlice<u8, Msb0>, Self), DekuError> {
+        read_values(input, |rest| T::read(rest, ()))
+    }
+}
+
+impl<T: DekuWrite<Rules>> DekuWrite<Rules> for SequenceOf<T> {
+    fn write(&self, output: &mut BitVec<u8, Msb0>, rules: Rules) -> Result<(), DekuError> {
+        for value in &self.0 {
+            value.write(output, rules)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: DekuWrite<()>> DekuWrite<()> for SequenceOf<T> {
+    fn write(&self, output: &mut BitVec<u8, Msb0>, _: ()) -> Result<(), DekuError> {
+        for value in &self.0 {
+            value.write(output, ())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hexlit::hex;
+    use rstest::rstest;
+
+    #[rstest(input, expected,
+        case(&hex!("02"), Tag::INTEGER),
+        case(&hex!("30"), Tag::SEQUENCE),
+        case(&hex!("A0"), Tag::context_specific(0, true)),
+        case(&hex!("5E"), Tag::new(Class::Application, false, 30)),
+        case(&hex!("DF1F"), Tag::new(Class::Private, false, 31)),
+        case(&hex!("7F8140"), Tag::new(Class::Application, true, 0xC0)),
+        case(&hex!("9F8FFFFFFF7F"), Tag::context_specific(u32::MAX, false)),
+
+        #[should_panic(expected = "tag number with a leading zero octet")]
+        case(&hex!("1F8001"), Tag::INTEGER),
+        #[should_panic(expected = "tag number does not fit in 32 bits")]
+        case(&hex!("1F9080808000"), Tag::INTEGER),
+        #[should_panic(expected = "Incomplete(NeedSize { bits: 8 })")]
+        case(&hex!("1F81"), Tag::INTEGER),
+    )]
+    fn test_tag(input: &[u8], expected: Tag) {
+        for rules in [Rules::Ber, Rules::Der] {
+            let (rest, tag) = Tag::read(input.view_bits(), rules).unwrap();
+            assert!(rest.is_empty());
+            assert_eq!(expected, tag);
+        }
+
+        let mut output = BitVec::new();
+        expected.write(&mut output, ()).unwrap();
+        assert_eq!(input.to_vec(), output.into_vec());
+    }
+
+    #[test]
+    fn test_tag_der() {
+        let input = hex!("1F02");
+        let (_, tag) = Tag::read(input.view_bits(), Rules::Ber).unwrap();
+        assert_eq!(Tag::INTEGER, tag);
+        assert_eq!(
+            Err(DekuError::Parse(
+                "DER: tag number 2 must use the single octet form".into()
+            )),
+            Tag::read(input.view_bits(), Rules::Der)
+        );
+    }
+
+    #[test]
+    fn test_tag_display() {
+        assert_eq!("[UNIVERSAL 16]", Tag::SEQUENCE.to_string());
+        assert_eq!(
+            "[APPLICATION 1]",
+            Tag::new(Class::Application, true, 1).to_string()
+        );
+        assert_eq!("[3]", Tag::context_specific(3, false).to_string());
+        assert_eq!(
+            "[PRIVATE 7]",
+            Tag::new(Class::Private, false, 7).to_string()
+        );
+    }
+
+    #[rstest(input, expected, minimal,
+        case(&hex!("00"), Length::Definite(0), true),
+        case(&hex!("7F"), Length::Definite(127), true),
+        case(&hex!("8180"), Length::Definite(128), true),
+        case(&hex!("820100"), Length::Definite(256), true),
+        case(&hex!("83010000"), Length::Definite(0x10000), true),
+        case(&hex!("8105"), Length::Definite(5), false),
+        case(&hex!("820080"), Length::Definite(128), false),
+        case(&hex!("80"), Length::Indefinite, false),
+
+        #[should_panic(expected = "reserved length octet 0xFF")]
+        case(&hex!("FF"), Length::Indefinite, false),
+        #[should_panic(expected = "length of 9 octets is too long")]
+        case(&hex!("89 010000000000000000"), Length::Indefinite, false),
+        #[should_panic(expected = "Incomplete(NeedSize { bits: 8 })")]
+        case(&hex!("8201"), Length::Indefinite, false),
+    )]
+    fn test_length(input: &[u8], expected: Length, minimal: bool) {
+        let (rest, length) = Length::read(input.view_bits(), Rules::Ber).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(expected, length);
+
+        assert_eq!(minimal, Length::read(input.view_bits(), Rules::Der).is_ok());
+
+        let mut output = BitVec::new();
+        expected.write(&mut output, ()).unwrap();
+        assert_eq!(
+            minimal || expected == Length::Indefinite,
+            input.to_vec() == output.into_vec()
+        );
+    }
+
+    #[rstest(input, expected,
+        case(&hex!("8105"), "DER: length 5 must use the short form"),
+        case(&hex!("820080"), "DER: length with a leading zero octet"),
+        case(&hex!("80"), "DER: indefinite length"),
+    )]
+    fn test_length_der(input: &[u8], expected: &str) {
+        assert_eq!(
+            Err(DekuError::Parse(expected.into())),
+            Length::read(input.view_bits(), Rules::Der)
+        );
+    }
+
+    #[test]
+    fn test_length_write_der() {
+        let mut output = BitVec::new();
+        assert_eq!(
+            Err(DekuError::InvalidParam("DER: indefinite length".into())),
+            Length::Indefinite.write(&mut output, Rules::Der)
+        );
+    }
+
+    #[rstest(input, expected, expected_rest,
+        case(&hex!("0201 05"), Tlv::new(Tag::INTEGER, Contents(vec![5])), &[]),
+        case(&hex!("0500 FF"), Tlv::new(Tag::NULL, Contents(vec![])), &[0xFF]),
+        case(&hex!("0481 02 AABB"), Tlv::new(Tag::OCTET_STRING, Contents(hex!("AABB").to_vec())), &[]),
+
+        #[should_panic(expected = "Incomplete(NeedSize { bits: 24 })")]
+        case(&hex!("0403 AABB"), Tlv::new(Tag::OCTET_STRING, Contents(vec![])), &[]),
+        #[should_panic(expected = "indefinite length of the primitive [UNIVERSAL 4]")]
+        case(&hex!("0480 AABB 0000"), Tlv::new(Tag::OCTET_STRING, Contents(vec![])), &[]),
+    )]
+    fn test_tlv_contents(input: &[u8], expected: Tlv<Contents>, expected_rest: &[u8]) {
+        let (rest, tlv) = Tlv::<Contents>::from_bytes((input, 0)).unwrap();
+        assert_eq!(expected, tlv);
+        assert_eq!((expected_rest, 0), rest);
+    }
+
+    #[test]
+    fn test_tlv_bounds_value() {
+        // the integer is read within the 2 bytes of the contents
+        let input = hex!("A003 020100");
+        let (rest, tlv) = Tlv::<Tlv<Contents>>::from_bytes((&input, 0)).unwrap();
+        assert!(rest.0.is_empty());
+        assert_eq!(Tlv::new(Tag::INTEGER, Contents(vec![0])), tlv.value);
+
+        let input = hex!("A004 020100 FF");
+        assert_eq!(
+            Err(DekuError::Parse(
+                "8 bits left in the contents of [0]".into()
+            )),
+            Tlv::<Tlv<Contents>>::from_bytes((&input, 0))
+        );
+    }
+
+    #[test]
+    fn test_sequence_of() {
+        let ints = |values: &[u8]| {
+            SequenceOf(
+                values
+                    .iter()
+                    .map(|&v| Tlv::new(Tag::INTEGER, Contents(vec![v])))
+                    .collect::<Vec<_>>(),
+            )
+        };
+
+        let definite = hex!("3006 020101 020102");
+        let (_, value) = Tlv::<SequenceOf<Tlv<Contents>>>::from_bytes((&definite, 0)).unwrap();
+        assert_eq!(ints(&[1, 2]), value.value);
+        assert_eq!(definite.to_vec(), value.to_bytes().unwrap());
+
+        // BER indefinite length, written with a definite length
+        let indefinite = hex!("3080 020101 020102 0000 FF");
+        let (rest, value) = Tlv::<SequenceOf<Tlv<Contents>>>::from_bytes((&indefinite, 0)).unwrap();
+        assert_eq!((&[0xFF][..], 0), rest);
+        assert_eq!(ints(&[1, 2]), value.value);
+        assert_eq!(definite.to_vec(), value.to_bytes().unwrap());
+
+        assert!(
+            Tlv::<SequenceOf<Tlv<Contents>>>::read(indefinite.view_bits(), Rules::Der).is_err()
+        );
+
+        let empty = hex!("3080 0000");
+        let (_, value) = Tlv::<SequenceOf<Tlv<Contents>>>::from_bytes((&empty, 0)).unwrap();
+        assert_eq!(ints(&[]), value.value);
+    }
+
+    #[test]
+    fn test_tlv_long_length() {
+        let value = Tlv::new(Tag::OCTET_STRING, Contents(vec![0xAB; 300]));
+        let output = value.to_bytes().unwrap();
+        assert_eq!(hex!("04 82012C AB").to_vec(), output[..5].to_vec());
+        assert_eq!(304, output.len());
+
+        let (_, read) = Tlv::<Contents>::read(output.view_bits(), Rules::Der).unwrap();
+        assert_eq!(value, read);
+    }
+}
@@ -32,16 +32,25 @@ enum DekuEnum {
 
 | Attribute | Scope | Description
 |-----------|------------------|------------
-| [endian](#endian) | top-level, field | Set the endianness
-| [magic](#magic) | top-level | A magic value that must be present at the start of this struct/enum
-| [assert](#assert) | field | Assert a condition
+| [endian](#endian) | top-level, variant, field | Set the endianness
+| [endian_from](#endian_from) | top-level | Set the endianness of the following fields from a field
+| [magic](#magic) | top-level, variant | A magic value that must be present at the start of this struct/enum, or after the variant `id`
+| [assert](#assert) | top-level, field | Assert a condition
 | [assert_eq](#assert_eq) | field | Assert equals on the field
+| [validate](#validate) | top-level | Validate the struct/enum with a function
+| [assert_msg](#assert_msg) | top-level, field | Custom message of a failed assertion
+| [assert_err](#assert_err) | top-level, field | Custom error of a failed assertion
 | [bits](#bits) | field | Set the bit-size of the field
 | [bytes](#bytes) | field | Set the byte-size of the field
 | [count](#count) | field | Set the field representing the element count of a container
 | [bits_read](#bits_read) | field | Set the field representing the number of bits to read into a container
 | [bytes_read](#bytes_read) | field | Set the field representing the number of bytes to read into a container
 | [until](#until) | field | Set a predicate returning when to stop reading elements into a container
+| [terminator](#terminator) | field | Element ending a container, consumed but not stored
+| [until_exclusive](#until_exclusive) | field | Like [until](#until), without storing the matching element
+| [window_bytes](#window_bytes) | field | Read/write the field within a window of N bytes
+| [rest](#window_bytes) | field | Handling of the bytes of the window unused by the field
+| [at](#at) | field | Read/write the field at a byte offset from the start of the input
 | [update](#update) | field | Apply code over the field when `.update()` is called
 | [temp](#temp) | field | Read the field but exclude it from the struct/enum
 | [temp_value](#temp_value) | field | Write the field but exclude it from the struct/enum
@@ -50,18 +59,40 @@ enum DekuEnum {
 | [pad_bits_before](#pad_bits_before) | field | Skip bits before reading, pad before writing
 | [pad_bytes_after](#pad_bytes_after) | field | Skip bytes after reading, pad after writing
 | [pad_bits_after](#pad_bits_after) | field | Skip bits after reading, pad after writing
+| [align_before](#align_beforealign_after) | field | Skip/pad bytes before the field up to a multiple of N bytes
+| [align_after](#align_beforealign_after) | field | Skip/pad bytes after the field up to a multiple of N bytes
+| [align_bits_before](#align_bits_beforealign_bits_after) | field | Skip/pad bits before the field up to a multiple of N bits
+| [align_bits_after](#align_bits_beforealign_bits_after) | field | Skip/pad bits after the field up to a multiple of N bits
+| [align](#align) | top-level | Skip/pad bytes after the struct/enum up to a multiple of N bytes
+| [align_input](#align_input) | top-level | Align relative to the input instead of the start of the struct/enum
+| [pad_fill](#pad_fill) | top-level, field | Value written to padding bits
+| [pad_strict](#pad_strict) | top-level, field | Error when reading padding bits which don't match the fill value
+| [reserved_bits](#reserved_bitsreserved_bytes) | field | Reserved bits before the field, not stored in the struct
+| [reserved_bytes](#reserved_bitsreserved_bytes) | field | Reserved bytes before the field, not stored in the struct
+| [reserved_value](#reserved_value) | field | Value of the reserved bits/bytes
+| [reserved_strict](#reserved_strict) | top-level, field | Error when reading reserved bits which don't match the reserved value
 | [cond](#cond) | field | Conditional expression for the field
+| [cond_strict](#cond_strict) | top-level, field | Error when writing a field with a false [cond](#cond) which isn't the default
+| [version](#version) | top-level | Version of the data, used by [since](#sinceuntil_version) and [until_version](#sinceuntil_version)
+| [since](#sinceuntil_version) | field | First version the field is present in
+| [until_version](#sinceuntil_version) | field | Last version the field is present in
 | [default](#default) | field | Provide default value. Used with [skip](#skip) or [cond](#cond)
 | [map](#map) | field | Apply a function over the result of reading
+| [write_map](#write_map) | field | Apply a function over the field before writing
+| [as_type](#as_type) | field | Read/write another type, converted with `From`/`Into`
+| [try_from](#try_from) | field | Convert [as_type](#as_type) with `TryFrom`/`TryInto`
 | [reader](#readerwriter) | variant, field | Custom reader code
 | [writer](#readerwriter) | variant, field | Custom writer code
-| [ctx](#ctx) | top-level, field| Context list for context sensitive parsing
+| [ctx](#ctx) | top-level, variant, field| Context list for context sensitive parsing, or bindings on a variant
 | [ctx_default](#ctx_default) | top-level, field| Default context values
 | enum: [id](#id) | top-level, variant | enum or variant id value
 | enum: [id_pat](#id_pat) | variant | variant id match pattern
-| enum: [type](#type) | top-level | Set the type of the variant `id`
+| enum: [unknown](#unknown) | variant | variant capturing unknown ids and the remaining bytes
+| enum: [type](#type) | top-level | Set the type of the variant `id`, defaults to the `#[repr(...)]`
 | enum: [bits](#bits-1) | top-level | Set the bit-size of the variant `id`
 | enum: [bytes](#bytes-1) | top-level | Set the byte-size of the variant `id`
+| enum: [id_reader](#id_readerid_writer) | top-level | Custom reader code for the variant `id`
+| enum: [id_writer](#id_readerid_writer) | top-level | Custom writer code for the variant `id`
 
 # endian
 
@@ -69,7 +100,7 @@ Set to read/write bytes in a specific byte order.
 
 Values: `big`, `little` or an expression which returns a [`Endian`](super::ctx::Endian)
 
-Precedence: field > top-level > system endianness (default)
+Precedence: field > variant > top-level > system endianness (default)
 
 Example:
 ```rust
@@ -140,6 +171,61 @@ let value: Vec<u8> = value.try_into().unwrap();
 assert_eq!(data, value);
 ```
 
+# endian_from
+
+Set the endianness of the fields following the named field from its value, for formats
+where a byte order mark decides the byte order of the rest of the data.
+
+The endianness is converted from a reference to the field with
+`From<&T> for` [`Endian`](super::ctx::Endian), after the field is read or written. It
+is the default of the following fields and is passed as a context argument to sub-types,
+as with [endian](#endian). Fields before it use the top-level `endian`.
+
+For a tuple struct, the field is named by its index, e.g. `endian_from = "0"`
+
+Example:
+```rust
+# use deku::prelude::*;
+# use deku::ctx::Endian;
+# use std::convert::{TryInto, TryFrom};
+# #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(type = "[u8; 2]")]
+enum ByteOrder {
+    #[deku(id = b"II")]
+    Little,
+    #[deku(id = b"MM")]
+    Big,
+}
+
+impl From<&ByteOrder> for Endian {
+    fn from(byte_order: &ByteOrder) -> Self {
+        match byte_order {
+            ByteOrder::Little => Endian::Little,
+            ByteOrder::Big => Endian::Big,
+        }
+    }
+}
+
+# #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian_from = "byte_order")]
+struct DekuTest {
+    byte_order: ByteOrder,
+    value: u16,
+}
+
+let data: Vec<u8> = vec![b'M', b'M', 0xAB, 0xCD];
+
+let value = DekuTest::try_from(data.as_ref()).unwrap();
+
+assert_eq!(
+    DekuTest { byte_order: ByteOrder::Big, value: 0xABCD },
+    value
+);
+
+let value: Vec<u8> = value.try_into().unwrap();
+assert_eq!(data, value);
+```
+
 # magic
 
 Sets a "magic" value that must be present in the data at the start of
@@ -169,6 +255,36 @@ let value: Vec<u8> = value.try_into().unwrap();
 assert_eq!(data, value);
 ```
 
+On an enum variant, the magic value follows the variant `id`. Combined with a variant
+[endian](#endian), this describes formats where each variant has its own signature and
+byte order.
+
+**Note**: Cannot be used with [id_pat](#id_pat), [unknown](#unknown) or a custom
+[reader/writer](#readerwriter)
+
+Example:
+```rust
+# use deku::prelude::*;
+# use std::convert::{TryInto, TryFrom};
+# #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(type = "[u8; 2]")]
+enum TiffHeader {
+    #[deku(id = b"II", magic = b"\x2a\x00", endian = "little")]
+    Little { offset: u32 },
+    #[deku(id = b"MM", magic = b"\x00\x2a", endian = "big")]
+    Big { offset: u32 },
+}
+
+let data: Vec<u8> = vec![b'M', b'M', 0x00, 0x2a, 0x00, 0x00, 0x00, 0x08];
+
+let value = TiffHeader::try_from(data.as_ref()).unwrap();
+
+assert_eq!(TiffHeader::Big { offset: 8 }, value);
+
+let value: Vec<u8> = value.try_into().unwrap();
+assert_eq!(data, value);
+```
+
 # assert
 
 Assert a condition after reading and before writing a field
@@ -193,6 +309,67 @@ assert_eq!(
 );
 ```
 
+On a struct, the condition can use all fields and is checked after reading all of
+them and before writing any of them.
+
+Example:
+```rust
+# use deku::prelude::*;
+# use std::convert::{TryInto, TryFrom};
+# #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(assert = "*header_len <= *total_len")]
+struct DekuTest {
+    header_len: u8,
+    total_len: u8,
+}
+
+let data: Vec<u8> = vec![0x04, 0x02];
+
+let value = DekuTest::try_from(data.as_ref());
+
+assert_eq!(
+    Err(DekuError::Assertion("DekuTest failed assertion: * header_len <= * total_len".into())),
+    value
+);
+```
+
+# validate
+
+Validate the struct/enum with a function `fn(&Self) -> bool`, called after reading
+the whole value and before writing it
+
+Example:
+```rust
+# use deku::prelude::*;
+# use std::convert::{TryInto, TryFrom};
+fn is_sorted(value: &DekuTest) -> bool {
+    value.data.windows(2).all(|w| w[0] <= w[1])
+}
+
+# #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(validate = "is_sorted")]
+struct DekuTest {
+    count: u8,
+    #[deku(count = "count")]
+    data: Vec<u8>,
+}
+
+let data: Vec<u8> = vec![0x02, 0x02, 0x01];
+
+let value = DekuTest::try_from(data.as_ref());
+
+assert_eq!(
+    Err(DekuError::Assertion("DekuTest failed validation: is_sorted".into())),
+    value
+);
+
+let value = DekuTest { count: 2, data: vec![0x02, 0x01] };
+assert_eq!(
+    Err(DekuError::Assertion("DekuTest failed validation: is_sorted".into())),
+    value.to_bytes()
+);
+```
+
 # assert_eq
 
 Assert equals after reading and before writing a field
@@ -226,6 +403,78 @@ assert_eq!(
 );
 ```
 
+# assert_msg
+
+Message of the [DekuError::Assertion](crate::DekuError::Assertion) returned when an
+[assert](#assert), [assert_eq](#assert_eq) or [validate](#validate) fails, instead of
+the default one. The message is a format string, fields read so far can be used as
+inline arguments.
+
+Example:
+```rust
+# use deku::prelude::*;
+# use std::convert::{TryInto, TryFrom};
+# #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+struct DekuTest {
+    len: u8,
+    #[deku(assert = "*len <= 4", assert_msg = "length {len} exceeds maximum of 4")]
+    data: u8,
+}
+
+let data: Vec<u8> = vec![0x05, 0x01];
+
+let value = DekuTest::try_from(data.as_ref());
+
+assert_eq!(
+    Err(DekuError::Assertion("length 5 exceeds maximum of 4".into())),
+    value
+);
+```
+
+# assert_err
+
+Error returned when an [assert](#assert), [assert_eq](#assert_eq) or [validate](#validate)
+fails, instead of [DekuError::Assertion](crate::DekuError::Assertion). The expression
+is converted into a [DekuError::Custom](crate::DekuError::Custom), it can be any type
+implementing `std::error::Error + Send + Sync`, and can be retrieved with
+[CustomError::downcast_ref](crate::error::CustomError::downcast_ref).
+
+**Note**: Requires the `std` feature, as [DekuError::Custom](crate::DekuError::Custom) only
+exists with it. Without it, `assert_err` is a compile error.
+
+Example:
+```rust
+# use deku::prelude::*;
+# use std::convert::{TryInto, TryFrom};
+#[derive(Debug, PartialEq)]
+struct VersionError(u8);
+
+impl std::fmt::Display for VersionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unsupported version {}", self.0)
+    }
+}
+
+impl std::error::Error for VersionError {}
+
+# #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+struct DekuTest {
+    #[deku(assert_eq = "1", assert_err = "VersionError(*version)")]
+    version: u8,
+}
+
+let data: Vec<u8> = vec![0x02];
+
+let value = DekuTest::try_from(data.as_ref());
+
+match value {
+    Err(DekuError::Custom(err)) => {
+        assert_eq!(Some(&VersionError(2)), err.downcast_ref::<VersionError>());
+    }
+    _ => panic!("expected a custom error"),
+}
+```
+
 # bits
 
 Set the bit-size of the field
@@ -412,6 +661,165 @@ assert_eq!(
 );
 ```
 
+# terminator
+
+Read elements into the container until an element equal to the terminator, the
+terminator is consumed but not stored. On write, the terminator is written after the
+elements, and it is an error for the container to contain it.
+
+Useful for lists ending with a sentinel value or an end-marker, for `Vec` and `HashSet`
+containers of a type implementing `PartialEq`.
+
+**Note**: Cannot be used in combination with [count](#count), [bits_read](#bits_read),
+[bytes_read](#bytes_read) or [until](#until)
+
+Example:
+```rust
+# use deku::prelude::*;
+# use std::convert::{TryInto, TryFrom};
+# #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+struct DekuTest {
+    #[deku(terminator = "0")]
+    name: Vec<u8>,
+    #[deku(endian = "big", terminator = "0xFFFF")]
+    ports: Vec<u16>,
+}
+
+let data: Vec<u8> = vec![b'd', b'k', 0, 0x00, 0x50, 0x01, 0xBB, 0xFF, 0xFF];
+let value = DekuTest::try_from(data.as_ref()).unwrap();
+
+assert_eq!(
+    DekuTest {
+        name: b"dk".to_vec(),
+        ports: vec![80, 443],
+    },
+    value
+);
+
+assert_eq!(data, value.to_bytes().unwrap());
+```
+
+# until_exclusive
+
+Specifies a predicate which sets when to stop reading values into the container, like
+[until](#until), but the value matching the predicate is consumed and not stored.
+
+As the matching value is not stored, [terminator](#terminator) must be given as the value
+written after the elements. It is an error to write a container with a value matching
+the predicate.
+
+Example:
+```rust
+# use deku::prelude::*;
+# use std::convert::{TryInto, TryFrom};
+# #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+struct DekuTest {
+    #[deku(until_exclusive = "|v: &u8| *v & 0x80 != 0", terminator = "0x80")]
+    data: Vec<u8>,
+}
+
+let data: Vec<u8> = vec![0x01, 0x02, 0xFF];
+let value = DekuTest::try_from(data.as_ref()).unwrap();
+
+assert_eq!(DekuTest { data: vec![0x01, 0x02] }, value);
+
+assert_eq!(vec![0x01, 0x02, 0x80], value.to_bytes().unwrap());
+```
+
+# window_bytes
+
+Read the field from a window of the next N bytes, the field can't read past the window.
+Useful when a length gives the size of a nested structure.
+
+`rest` sets the handling of the bytes of the window the field doesn't use:
+- `"error"` (default): error if the field doesn't read the whole window, or doesn't fill
+  it when writing
+- `"skip"`: continue after the window, skipping the unread bytes. When writing, the
+  window is filled with padding, see [pad_fill](#pad_fill)
+- `"keep"`: the unread bytes are left for the following fields
+
+Writing a field larger than its window is an error.
+
+Example:
+```rust
+# use deku::prelude::*;
+# use std::convert::{TryInto, TryFrom};
+# #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+struct Extension {
+    kind: u8,
+}
+
+# #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+struct DekuTest {
+    len: u8,
+    #[deku(window_bytes = "len", rest = "skip")]
+    extension: Extension,
+    checksum: u8,
+}
+
+// the extension is given 3 bytes, its unknown trailing bytes are skipped
+let data: Vec<u8> = vec![0x03, 0x01, 0xAA, 0xBB, 0xFF];
+let value = DekuTest::try_from(data.as_ref()).unwrap();
+
+assert_eq!(
+    DekuTest { len: 3, extension: Extension { kind: 0x01 }, checksum: 0xFF },
+    value
+);
+
+assert_eq!(vec![0x03, 0x01, 0x00, 0x00, 0xFF], value.to_bytes().unwrap());
+```
+
+# at
+
+Read the field at a byte offset from the start of the input, usually given by a previously
+read field. The following fields are read from where the previous field ended, as if the
+field wasn't there.
+
+The offset is relative to the start of the whole input, also when the struct/enum is nested
+in another one or the field is itself in data read at an offset. The rest of the input
+starts after both the other fields and the data read at an offset.
+
+When writing, the data of the fields with `at` is laid out after all the other data of the
+whole output, in order. When `at` names a field of the same struct/enum, that field is
+filled in with the offset, whatever its value. Otherwise the data is written at the offset
+given by the expression, which can't overlap the other data. Any gap is filled with zeros.
+
+The offset field is only filled in when it's always written as-is: without `writer`,
+`write_map`, `as_type`, `ctx`, `cond`, `skip`, `temp`, `window_bytes`, `terminator` or a
+version. Fields with `at` can't share an offset field.
+
+Example:
+```rust
+# use deku::prelude::*;
+# use std::convert::{TryInto, TryFrom};
+# #[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+struct DekuTest {
+    name_offset: u8,
+    #[deku(update = "self.name.len()")]
+    name_len: u8,
+    #[deku(at = "name_offset", count = "name_len")]
+    name: Vec<u8>,
+    kind: u8,
+}
+
+let data: Vec<u8> = vec![0x04, 0x02, 0xAA, 0xFF, 0x68, 0x69];
+let (_rest, mut value) = DekuTest::from_bytes((data.as_ref(), 0)).unwrap();
+
+assert_eq!(
+    DekuTest { name_offset: 4, name_len: 2, name: b"hi".to_vec(), kind: 0xAA },
+    value
+);
+
+value.name = b"deku".to_vec();
+value.update().unwrap();
+
+// `name` is laid out after `kind`, `name_offset` is filled in
+assert_eq!(
+    vec![0x03, 0x04, 0xAA, 0x64, 0x65, 0x6B, 0x75],
+    value.to_bytes().unwrap()
+);
+```
+
 
 # update
 
@@ -686,11 +1094,14 @@ let value: Vec<u8> = value.try_into().unwrap();
 assert_eq!(vec![0b10_00_1001], value);
 ```
 
-# cond
+# align_before/align_after
 
-Specify a condition to parse or skip a field
+Skip bytes before/after reading, pad with 0x00s before/after writing, until
+the offset is a multiple of the given number of bytes.
 
-**Note**: Can be paired with [default](#default)
+The offset is relative to the start of the struct/enum (before its [magic](#magic)),
+see [align_input](#align_input) to align relative to the input instead.
+Alignment is applied after [pad_bytes_before](#pad_bytes_before)/[pad_bytes_after](#pad_bytes_after).
 
 Example:
 
@@ -700,34 +1111,35 @@ Example:
 #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
 struct DekuTest {
     field_a: u8,
-    #[deku(cond = "*field_a == 0x01")]
-    field_b: Option<u8>,
-    #[deku(cond = "*field_b == Some(0xFF)", default = "Some(0x05)")]
-    field_c: Option<u8>,
-    #[deku(skip, cond = "*field_a == 0x01", default = "Some(0x06)")]
-    field_d: Option<u8>,
+    #[deku(align_before = "4", align_after = "4")]
+    field_b: u8,
+    field_c: u8,
 }
 
-let data: Vec<u8> = vec![0x01, 0x02];
+let data: Vec<u8> = vec![0xAA, 0xFF, 0xFF, 0xFF, 0xBB, 0xFF, 0xFF, 0xFF, 0xCC];
 
 let value = DekuTest::try_from(data.as_ref()).unwrap();
 
 assert_eq!(
-    DekuTest { field_a: 0x01, field_b: Some(0x02), field_c: Some(0x05), field_d: Some(0x06)},
+    DekuTest {
+        field_a: 0xAA,
+        field_b: 0xBB,
+        field_c: 0xCC,
+    },
     value
 );
 
+let value: Vec<u8> = value.try_into().unwrap();
 assert_eq!(
-    vec![0x01, 0x02, 0x05],
-    value.to_bytes().unwrap(),
-)
+    vec![0xAA, 0x00, 0x00, 0x00, 0xBB, 0x00, 0x00, 0x00, 0xCC],
+    value
+);
 ```
 
-# default
-
-Default code tokens used with [skip](#skip) or [cond](#cond)
+# align_bits_before/align_bits_after
 
-Defaults to `Default::default()`
+Same as [align_before/align_after](#align_beforealign_after), with the
+alignment given in bits.
 
 Example:
 
@@ -736,35 +1148,467 @@ Example:
 # use std::convert::{TryInto, TryFrom};
 #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
 struct DekuTest {
+    #[deku(bits = 3, align_bits_after = "4")]
     field_a: u8,
-    #[deku(skip, default = "Some(*field_a)")]
-    field_b: Option<u8>,
-    field_c: u8,
+    #[deku(bits = 4)]
+    field_b: u8,
 }
 
-let data: Vec<u8> = vec![0x01, 0x02];
+let data: Vec<u8> = vec![0b101_1_1001];
 
 let value = DekuTest::try_from(data.as_ref()).unwrap();
 
 assert_eq!(
-    DekuTest { field_a: 0x01, field_b: Some(0x01), field_c: 0x02 },
+    DekuTest {
+        field_a: 0b101,
+        field_b: 0b1001,
+    },
     value
 );
+
+let value: Vec<u8> = value.try_into().unwrap();
+assert_eq!(vec![0b101_0_1001], value);
 ```
 
-# map
+# align
 
-Specify a function or lambda to apply to the result of the read
+Skip bytes after reading, pad with 0x00s after writing, until the size of
+the struct/enum is a multiple of the given number of bytes.
 
 Example:
 
-Read a `u8` and apply a function to convert it to a `String`.
-
 ```rust
 # use deku::prelude::*;
 # use std::convert::{TryInto, TryFrom};
-#[derive(PartialEq, Debug, DekuRead)]
-struct DekuTest {
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+#[deku(align = "4")]
+struct AlignedOption {
+    kind: u8,
+    len: u8,
+    #[deku(count = "len")]
+    value: Vec<u8>,
+}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+struct DekuTest {
+    #[deku(count = "2")]
+    options: Vec<AlignedOption>,
+}
+
+let data: Vec<u8> = vec![0x01, 0x01, 0xAA, 0xFF, 0x02, 0x02, 0xBB, 0xCC];
+
+let value = DekuTest::try_from(data.as_ref()).unwrap();
+
+assert_eq!(
+    DekuTest {
+        options: vec![
+            AlignedOption { kind: 1, len: 1, value: vec![0xAA] },
+            AlignedOption { kind: 2, len: 2, value: vec![0xBB, 0xCC] },
+        ],
+    },
+    value
+);
+
+let value: Vec<u8> = value.try_into().unwrap();
+assert_eq!(vec![0x01, 0x01, 0xAA, 0x00, 0x02, 0x02, 0xBB, 0xCC], value);
+```
+
+# align_input
+
+Compute the padding of [align_before/align_after](#align_beforealign_after),
+[align_bits_before/align_bits_after](#align_bits_beforealign_bits_after) and [align](#align)
+from the offset in the whole input instead of the offset from the start of the struct/enum.
+
+The whole input is the data given to `from_bytes` (including its bit offset) or to
+`DekuRead::read`, and the output from the start of `DekuWrite::write`/`to_bytes`, also when
+the struct/enum is nested in another one. Within the data of a field with [at](#at), the
+offset is from the start of that data, which is laid out at a byte offset when writing.
+
+Example:
+
+```rust
+# use deku::prelude::*;
+# use std::convert::{TryInto, TryFrom};
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+#[deku(align_input)]
+struct DekuTest {
+    #[deku(bits = 4)]
+    field_a: u8,
+    #[deku(align_before = "2")]
+    field_b: u8,
+}
+
+let data: Vec<u8> = vec![0xFA, 0xFF, 0xBB];
+
+// the struct starts at bit 4, `field_b` is aligned to the input
+let (_rest, value) = DekuTest::from_bytes((data.as_ref(), 4)).unwrap();
+
+assert_eq!(
+    DekuTest {
+        field_a: 0xA,
+        field_b: 0xBB,
+    },
+    value
+);
+
+let value: Vec<u8> = value.try_into().unwrap();
+assert_eq!(vec![0xA0, 0x00, 0xBB], value);
+```
+
+# pad_fill
+
+Value written to the padding of [pad_bytes_before](#pad_bytes_before),
+[pad_bits_before](#pad_bits_before), [pad_bytes_after](#pad_bytes_after),
+[pad_bits_after](#pad_bits_after), the `align_*` attributes and [align](#align)
+instead of zeros.
+
+The value is a `u8` expression or a byte string, repeated from the start of each
+padding, most significant bit first. It can be set on the struct/enum as the
+default for all its padding and overridden by a field.
+
+Padding isn't checked when reading unless [pad_strict](#pad_strict) is set.
+
+Example:
+
+```rust
+# use deku::prelude::*;
+# use std::convert::{TryInto, TryFrom};
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+#[deku(pad_fill = b"\xAA\x55")]
+struct DekuTest {
+    #[deku(pad_bytes_after = "3")]
+    field_a: u8,
+    #[deku(bits = 4, pad_bits_after = "4", pad_fill = "0xFF")]
+    field_b: u8,
+}
+
+let data: Vec<u8> = vec![0x01, 0x00, 0x00, 0x00, 0b1010_0000];
+
+let value = DekuTest::try_from(data.as_ref()).unwrap();
+
+assert_eq!(
+    DekuTest {
+        field_a: 0x01,
+        field_b: 0b1010,
+    },
+    value
+);
+
+let value: Vec<u8> = value.try_into().unwrap();
+assert_eq!(vec![0x01, 0xAA, 0x55, 0xAA, 0b1010_1111], value);
+```
+
+# pad_strict
+
+Return an error when reading padding bits which don't match the [pad_fill](#pad_fill)
+value (zeros by default), to detect corrupted or mis-framed data.
+
+It can be set on the struct/enum for all its padding or on a field.
+
+Example:
+
+```rust
+# use deku::prelude::*;
+# use std::convert::{TryInto, TryFrom};
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+#[deku(pad_strict)]
+struct DekuTest {
+    #[deku(bits = 4, pad_bits_after = "4")]
+    field_a: u8,
+    field_b: u8,
+}
+
+let value = DekuTest::try_from([0b1010_0000, 0xBB].as_ref()).unwrap();
+assert_eq!(
+    DekuTest {
+        field_a: 0b1010,
+        field_b: 0xBB,
+    },
+    value
+);
+
+let value = DekuTest::try_from([0b1010_0001, 0xBB].as_ref());
+assert_eq!(
+    Err(DekuError::Parse(
+        "Invalid padding in DekuTest.field_a: expected fill 0".to_string()
+    )),
+    value
+);
+```
+
+# reserved_bits/reserved_bytes
+
+Reserved (or "must be zero") bits/bytes located before the field. They are
+not stored in the struct: on read they are skipped, on write the
+[reserved_value](#reserved_value) (zero by default) is written.
+
+Reserved ranges after the last field can be expressed with
+[pad_bits_after](#pad_bits_after) and [pad_strict](#pad_strict).
+
+Example:
+
+```rust
+# use deku::prelude::*;
+# use std::convert::{TryInto, TryFrom};
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+struct DekuTest {
+    #[deku(bits = 4)]
+    field_a: u8,
+    #[deku(reserved_bits = "3", bits = 1)]
+    field_b: u8,
+    #[deku(reserved_bytes = "1")]
+    field_c: u8,
+}
+
+let data: &[u8] = &[0b1010_1111, 0xFF, 0xCC];
+let value = DekuTest::try_from(data).unwrap();
+
+assert_eq!(
+    DekuTest {
+        field_a: 0b1010,
+        field_b: 0b1,
+        field_c: 0xCC,
+    },
+    value
+);
+
+let value: Vec<u8> = value.try_into().unwrap();
+assert_eq!(vec![0b1010_0001, 0x00, 0xCC], value);
+```
+
+# reserved_value
+
+Value of the reserved bits/bytes, right-aligned as an integer of up to 64 bits.
+It is written on write and checked on read with [reserved_strict](#reserved_strict).
+
+Example:
+
+```rust
+# use deku::prelude::*;
+# use std::convert::{TryInto, TryFrom};
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+struct DekuTest {
+    #[deku(reserved_bits = "4", reserved_value = "0b1111", bits = 4)]
+    field_a: u8,
+}
+
+let value = DekuTest { field_a: 0b0011 };
+let value: Vec<u8> = value.try_into().unwrap();
+assert_eq!(vec![0b1111_0011], value);
+```
+
+# reserved_strict
+
+Return an error when reading reserved bits which don't match the
+[reserved_value](#reserved_value).
+
+It can be set on the struct/enum for all its reserved ranges or on a field.
+
+Example:
+
+```rust
+# use deku::prelude::*;
+# use std::convert::{TryInto, TryFrom};
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+#[deku(reserved_strict)]
+struct DekuTest {
+    #[deku(reserved_bits = "4", bits = 4)]
+    field_a: u8,
+}
+
+let value = DekuTest::try_from([0b0000_0011].as_ref()).unwrap();
+assert_eq!(DekuTest { field_a: 0b0011 }, value);
+
+let value = DekuTest::try_from([0b0100_0011].as_ref());
+assert_eq!(
+    Err(DekuError::Parse(
+        "Invalid reserved bits before DekuTest.field_a: expected 0x0".to_string()
+    )),
+    value
+);
+```
+
+# cond
+
+Specify a condition to parse or skip a field. The condition is also evaluated when
+writing, the field is only written when it is true.
+
+When writing, the condition sees the same variables as when reading: a `ctx` argument
+with the name of the field or of a following field is used rather than the field. A
+condition using `deku::rest`, `deku::input_bits`, `deku::bit_offset` or
+`deku::byte_offset` can't be evaluated when writing, the field is always written.
+
+**Note**: Can be paired with [default](#default) and [cond_strict](#cond_strict)
+
+Example:
+
+```rust
+# use deku::prelude::*;
+# use std::convert::{TryInto, TryFrom};
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+struct DekuTest {
+    field_a: u8,
+    #[deku(cond = "*field_a == 0x01")]
+    field_b: Option<u8>,
+    #[deku(cond = "*field_b == Some(0xFF)", default = "Some(0x05)")]
+    field_c: Option<u8>,
+    #[deku(skip, cond = "*field_a == 0x01", default = "Some(0x06)")]
+    field_d: Option<u8>,
+}
+
+let data: Vec<u8> = vec![0x01, 0x02];
+
+let value = DekuTest::try_from(data.as_ref()).unwrap();
+
+assert_eq!(
+    DekuTest { field_a: 0x01, field_b: Some(0x02), field_c: Some(0x05), field_d: Some(0x06)},
+    value
+);
+
+assert_eq!(
+    vec![0x01, 0x02],
+    value.to_bytes().unwrap(),
+)
+```
+
+# cond_strict
+
+Error when writing a field whose [cond](#cond) is false and whose value differs from
+the [default](#default). The value would be dropped from the output and read back as the
+default, this makes sure the field round-trips.
+
+When used at the top-level, applies to all fields with a [cond](#cond) which can be
+evaluated when writing. On a field, the condition can't use `deku::rest`,
+`deku::input_bits`, `deku::bit_offset` or `deku::byte_offset`.
+
+Example:
+
+```rust
+# use deku::prelude::*;
+# use std::convert::{TryInto, TryFrom};
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+struct DekuTest {
+    field_a: u8,
+    #[deku(cond = "*field_a == 0x01", cond_strict)]
+    field_b: Option<u8>,
+}
+
+let value = DekuTest { field_a: 0x01, field_b: Some(0x02) };
+assert_eq!(vec![0x01, 0x02], value.to_bytes().unwrap());
+
+let value = DekuTest { field_a: 0x00, field_b: None };
+assert_eq!(vec![0x00], value.to_bytes().unwrap());
+
+let value = DekuTest { field_a: 0x00, field_b: Some(0x02) };
+assert_eq!(
+    Err(DekuError::InvalidParam(
+        "DekuTest.field_b field differs from the default with false condition: * field_a == 0x01".into()
+    )),
+    value.to_bytes(),
+);
+```
+
+# version
+
+Version of the data, an expression on a previous field or a [ctx](#ctx) parameter.
+Fields with [since](#sinceuntil_version) or [until_version](#sinceuntil_version) are
+compared against it.
+
+# since/until_version
+
+Version gated field, present from version `since` up to and including version
+`until_version`. When the field is absent from the [version](#version), it isn't read and
+uses [default](#default). Writing a field absent from the version errors if its value
+isn't the default, as the version can't represent it.
+
+**Note**: Can be paired with [cond](#cond) and [default](#default)
+
+Example:
+
+```rust
+# use deku::prelude::*;
+# use std::convert::{TryInto, TryFrom};
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+#[deku(version = "*version")]
+struct DekuTest {
+    version: u8,
+    #[deku(since = "2")]
+    flags: u8,
+    #[deku(until_version = "2", default = "0xFF")]
+    legacy: u8,
+}
+
+let data: Vec<u8> = vec![0x01, 0x02];
+
+let value = DekuTest::try_from(data.as_ref()).unwrap();
+
+assert_eq!(
+    DekuTest { version: 1, flags: 0x00, legacy: 0x02 },
+    value
+);
+
+let data: Vec<u8> = vec![0x03, 0x04];
+
+let value = DekuTest::try_from(data.as_ref()).unwrap();
+
+assert_eq!(
+    DekuTest { version: 3, flags: 0x04, legacy: 0xFF },
+    value
+);
+
+let value = DekuTest { version: 1, flags: 0x04, legacy: 0x02 };
+
+assert_eq!(
+    Err(DekuError::InvalidParam(
+        "DekuTest.flags field is not present in version: (* version) >= (2)".into()
+    )),
+    value.to_bytes(),
+);
+```
+
+# default
+
+Default code tokens used with [skip](#skip), [cond](#cond) or
+[since/until_version](#sinceuntil_version)
+
+Defaults to `Default::default()`
+
+Example:
+
+```rust
+# use deku::prelude::*;
+# use std::convert::{TryInto, TryFrom};
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+struct DekuTest {
+    field_a: u8,
+    #[deku(skip, default = "Some(*field_a)")]
+    field_b: Option<u8>,
+    field_c: u8,
+}
+
+let data: Vec<u8> = vec![0x01, 0x02];
+
+let value = DekuTest::try_from(data.as_ref()).unwrap();
+
+assert_eq!(
+    DekuTest { field_a: 0x01, field_b: Some(0x01), field_c: 0x02 },
+    value
+);
+```
+
+# map
+
+Specify a function or lambda to apply to the result of the read
+
+Example:
+
+Read a `u8` and apply a function to convert it to a `String`.
+
+```rust
+# use deku::prelude::*;
+# use std::convert::{TryInto, TryFrom};
+#[derive(PartialEq, Debug, DekuRead)]
+struct DekuTest {
     #[deku(map = "|field: u8| -> Result<_, DekuError> { Ok(field.to_string()) }")]
     field_a: String,
     #[deku(map = "DekuTest::map_field_b")]
@@ -787,6 +1631,138 @@ assert_eq!(
 );
 ```
 
+# write_map
+
+Specify a function or lambda to apply to a reference of the field before writing,
+the inverse of [map](#map). The returned value is written with the field's
+attributes such as [bits](#bits), [endian](#endian) or [ctx](#ctx).
+
+Example:
+
+Write a `String` as a `u8`, read back with [map](#map).
+
+```rust
+# use deku::prelude::*;
+# use std::convert::{TryInto, TryFrom};
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+struct DekuTest {
+    #[deku(
+        map = "|field: u8| -> Result<_, DekuError> { Ok(field.to_string()) }",
+        write_map = "DekuTest::write_map_field_a"
+    )]
+    field_a: String,
+}
+
+impl DekuTest {
+    fn write_map_field_a(field_a: &String) -> Result<u8, DekuError> {
+        field_a
+            .parse()
+            .map_err(|_| DekuError::InvalidParam(format!("invalid field_a: {}", field_a)))
+    }
+}
+
+let data: Vec<u8> = vec![0x01];
+
+let value = DekuTest::try_from(data.as_ref()).unwrap();
+assert_eq!(DekuTest { field_a: "1".to_string() }, value);
+
+let value: Vec<u8> = value.try_into().unwrap();
+assert_eq!(data, value);
+```
+
+# as_type
+
+Read and write the field as another type, converting it with `From` on read and
+`Into` on write. Attributes such as [bits](#bits), [endian](#endian) or [ctx](#ctx)
+apply to the on-wire type.
+
+The field type must implement `Clone`, its value is cloned before conversion.
+
+Example:
+
+```rust
+# use deku::prelude::*;
+# use std::convert::{TryInto, TryFrom};
+#[derive(PartialEq, Debug, Clone, Copy)]
+struct Port(u16);
+
+impl From<u16> for Port {
+    fn from(v: u16) -> Self {
+        Port(v)
+    }
+}
+
+impl From<Port> for u16 {
+    fn from(v: Port) -> Self {
+        v.0
+    }
+}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+struct DekuTest {
+    #[deku(as_type = "u16", endian = "big")]
+    port: Port,
+}
+
+let data: Vec<u8> = vec![0x1F, 0x90];
+
+let value = DekuTest::try_from(data.as_ref()).unwrap();
+assert_eq!(DekuTest { port: Port(8080) }, value);
+
+let value: Vec<u8> = value.try_into().unwrap();
+assert_eq!(data, value);
+```
+
+# try_from
+
+Convert the [as_type](#as_type) value with `TryFrom` on read and `TryInto` on write.
+A failed conversion returns `DekuError::Parse` on read and `DekuError::InvalidParam`
+on write.
+
+Example:
+
+```rust
+# use deku::prelude::*;
+# use std::convert::{TryInto, TryFrom};
+#[derive(PartialEq, Debug, Clone, Copy)]
+struct Percent(u8);
+
+impl TryFrom<u8> for Percent {
+    type Error = ();
+
+    fn try_from(v: u8) -> Result<Self, Self::Error> {
+        if v <= 100 {
+            Ok(Percent(v))
+        } else {
+            Err(())
+        }
+    }
+}
+
+impl From<Percent> for u8 {
+    fn from(v: Percent) -> Self {
+        v.0
+    }
+}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+struct DekuTest {
+    #[deku(as_type = "u8", try_from)]
+    level: Percent,
+}
+
+let value = DekuTest::try_from([50].as_ref()).unwrap();
+assert_eq!(DekuTest { level: Percent(50) }, value);
+
+let value = DekuTest::try_from([200].as_ref());
+assert_eq!(
+    Err(DekuError::Parse(
+        "Invalid DekuTest.level: cannot convert from u8".to_string()
+    )),
+    value
+);
+```
+
 # reader/writer
 
 Specify custom reader or writer tokens for reading a field or variant
@@ -847,6 +1823,10 @@ for example `#[deku(ctx = "a: u8, b: String")]`
 **field-level**: The value of the ctx attribute is a list of expressions,
 for example `#[deku("a, b")]`
 
+**variant-level**: The value of the ctx attribute is a list of bindings,
+for example `#[deku(ctx = "a = 1, b = a + 1")]`. These can be used by the
+attributes of the fields of the variant.
+
 **Visibility**: The following can be accessed:
 1. All former fields which have been parsed (given as a reference).
 2. `endian`, `bytes`, `bits` attributes declared on the top-level
@@ -876,6 +1856,31 @@ assert_eq!(value.a, 0x01);
 assert_eq!(value.sub.b, 0x01 + 0x02)
 ```
 
+Example: variant-level
+```rust
+# use deku::prelude::*;
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(ctx = "a: u8")]
+struct Subtype {
+    #[deku(map = "|b: u8| -> Result<_, DekuError> { Ok(b + a) }")]
+    b: u8
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(type = "u8")]
+enum Test {
+    #[deku(id = "1", ctx = "a = 1")]
+    VariantA(#[deku(ctx = "a")] Subtype, #[deku(ctx = "a + 1")] Subtype),
+    #[deku(id = "2", ctx = "a = 10")]
+    VariantB(#[deku(ctx = "a")] Subtype),
+}
+
+let data: Vec<u8> = vec![0x02, 0x05];
+
+let (rest, value) = Test::from_bytes((&data[..], 0)).unwrap();
+assert_eq!(value, Test::VariantB(Subtype { b: 10 + 0x05 }))
+```
+
 **Note**: In addition, `endian`, `bytes` and `bits` use the `ctx` concept internally, examples below are equivalent:
 
 Example:
@@ -1120,10 +2125,106 @@ let variant_bytes: Vec<u8> = value.try_into().unwrap();
 assert_eq!(vec![0xFF], variant_bytes);
 ```
 
+# unknown
+
+Catch-all variant for ids which don't match any other variant. Its first field
+holds the id, and an optional second field (such as `Vec<u8>`) the remaining bytes
+of the input. Both are written back unchanged and the id is returned by
+[DekuEnumExt::deku_id](crate::DekuEnumExt::deku_id), for lossless round-trips.
+It only matches ids of no other variant, wherever it is declared in the enum.
+
+The remaining bytes are everything left in the input, the enum should be read from
+a bounded payload.
+
+Example:
+```rust
+# use deku::prelude::*;
+# use std::convert::{TryInto, TryFrom};
+# #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+#[deku(type = "u8")]
+enum DekuTest {
+    #[deku(id = "0x01")]
+    VariantA(u8),
+    #[deku(unknown)]
+    Unknown { id: u8, rest: Vec<u8> },
+}
+
+let data: Vec<u8> = vec![0x05, 0xAB, 0xCD];
+
+let value = DekuTest::try_from(data.as_ref()).unwrap();
+assert_eq!(
+    DekuTest::Unknown { id: 0x05, rest: vec![0xAB, 0xCD] },
+    value
+);
+assert_eq!(0x05, value.deku_id().unwrap());
+
+let variant_bytes: Vec<u8> = value.try_into().unwrap();
+assert_eq!(data, variant_bytes);
+```
+
 # type
 
 Specify the type of the enum variant id to consume, see [example](#id-variant)
 
+If neither `type` nor [id (top-level)](#id-top-level) is given, the type is taken
+from an integer `#[repr(...)]` of the enum, such as `#[repr(u8)]`. `isize` and `usize`
+are platform dependent and are not used. [bits](#bits-1) or [bytes](#bytes-1) must
+fit in the `repr`.
+
+Variants must not share the same literal `id` or discriminant.
+
+The type can be any type implementing `DekuRead`, `DekuWrite` and `PartialEq`, such as
+a tuple or a struct, to match a composite key with `id` or `id_pat`:
+
+```rust
+# use deku::prelude::*;
+# use std::convert::{TryInto, TryFrom};
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+struct Key {
+    class: u8,
+    op: u8,
+}
+
+# #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+#[deku(type = "Key")]
+enum DekuTest {
+    #[deku(id = "Key { class: 1, op: 2 }")]
+    VariantA(u8),
+    #[deku(id_pat = "Key { class: 2, .. }")]
+    VariantB(Key),
+}
+
+let data: Vec<u8> = vec![0x01, 0x02, 0xFF];
+
+let value = DekuTest::try_from(data.as_ref()).unwrap();
+assert_eq!(DekuTest::VariantA(0xFF), value);
+
+let variant_bytes: Vec<u8> = value.try_into().unwrap();
+assert_eq!(data, variant_bytes);
+```
+
+Example:
+```rust
+# use deku::prelude::*;
+# use std::convert::{TryInto, TryFrom};
+# #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+#[repr(u16)]
+enum DekuTest {
+    VariantA = 0x0100,
+    VariantB,
+}
+
+let data: Vec<u8> = vec![0x01, 0x01];
+
+let (rest, value) = DekuTest::from_bytes((data.as_ref(), 0)).unwrap();
+assert_eq!(DekuTest::VariantB, value);
+assert_eq!(0x0101, value.deku_id().unwrap());
+
+let variant_bytes: Vec<u8> = value.try_into().unwrap();
+assert_eq!(data, variant_bytes);
+```
+
 # bits
 
 Set the bit size of the enum variant `id`
@@ -1184,5 +2285,57 @@ let value: Vec<u8> = value.try_into().unwrap();
 assert_eq!(data, value);
 ```
 
+# id_reader/id_writer
+
+Specify custom code to read and write the enum variant `id`, for ids which can't be
+described with [type](#type), [bits](#bits-1) and [bytes](#bytes-1). Must be paired
+with [type](#type).
+
+`id_reader` returns `Result<(&BitSlice<u8, Msb0>, Type), DekuError>` from `deku::rest`,
+`id_writer` writes `deku::id`, the id of the variant, to `deku::output`.
+
+Example:
+```rust
+# use deku::prelude::*;
+# use deku::bitvec::{BitSlice, BitVec, Msb0};
+# use deku::ctx::{BitSize, Endian};
+# use std::convert::{TryInto, TryFrom};
+/// 7 bit id, or 15 bit id if the first bit is set
+# #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+#[deku(
+    type = "u16",
+    id_reader = "DekuTest::read_id(deku::rest)",
+    id_writer = "DekuTest::write_id(deku::output, deku::id)"
+)]
+enum DekuTest {
+    #[deku(id = "0x01")]
+    VariantA(u8),
+    #[deku(id = "0x1234")]
+    VariantB,
+}
+
+impl DekuTest {
+    fn read_id(rest: &BitSlice<u8, Msb0>) -> Result<(&BitSlice<u8, Msb0>, u16), DekuError> {
+        let (rest, long) = bool::read(rest, BitSize(1))?;
+        let size = if long { 15 } else { 7 };
+        u16::read(rest, (Endian::Big, BitSize(size)))
+    }
+
+    fn write_id(output: &mut BitVec<u8, Msb0>, id: u16) -> Result<(), DekuError> {
+        let long = id >= 0x80;
+        long.write(output, BitSize(1))?;
+        let size = if long { 15 } else { 7 };
+        id.write(output, (Endian::Big, BitSize(size)))
+    }
+}
+
+let data: Vec<u8> = vec![0x92, 0x34];
+
+let value = DekuTest::try_from(data.as_ref()).unwrap();
+assert_eq!(DekuTest::VariantB, value);
+
+let value: Vec<u8> = value.try_into().unwrap();
+assert_eq!(data, value);
+```
 
 */
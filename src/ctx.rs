@@ -84,6 +84,10 @@ pub enum Limit<T, Predicate: FnMut(&T) -> bool> {
     /// Read until a given predicate holds true
     Until(Predicate, PhantomData<T>),
 
+    /// Read until a given predicate holds true, the element matching it is neither stored nor
+    /// consumed: it is left in the rest
+    UntilExclusive(Predicate, PhantomData<T>),
+
     /// Read until a given quantity of bytes have been read
     ByteSize(ByteSize),
 
@@ -122,6 +126,12 @@ impl<T, Predicate: for<'a> FnMut(&'a T) -> bool> Limit<T, Predicate> {
     pub fn new_until(predicate: Predicate) -> Self {
         predicate.into()
     }
+
+    /// Constructs a new Limit that reads until the given predicate returns true
+    /// The value matching the predicate is not part of the result and is left in the rest
+    pub fn new_until_exclusive(predicate: Predicate) -> Self {
+        Limit::UntilExclusive(predicate, PhantomData)
+    }
 }
 
 impl<T> Limit<T, fn(&T) -> bool> {
@@ -139,6 +149,15 @@ impl<T> Limit<T, fn(&T) -> bool> {
     pub fn new_byte_size(size: ByteSize) -> Self {
         size.into()
     }
+
+    /// Constructs a new Limit that reads until a value equal to `terminator`
+    /// The terminator is not part of the result and is left in the rest
+    pub fn new_terminator(terminator: T) -> Limit<T, impl FnMut(&T) -> bool>
+    where
+        T: PartialEq,
+    {
+        Limit::new_until_exclusive(move |value: &T| *value == terminator)
+    }
 }
 
 /// The size of field in bytes
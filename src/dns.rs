@@ -0,0 +1,470 @@
+/*!
+DNS domain names with message compression
+
+Requires the `dns` feature.
+
+A [DomainName] is a sequence of length-prefixed labels ended by an empty label. To
+save space, DNS (and mDNS, LLMNR, ...) messages replace the end of a name by a
+pointer to an earlier occurrence of the same labels, as a byte offset from the start
+of the message. Following a pointer needs access to the whole message, so
+[DomainName] is read with the bits of the message as context, usually
+`deku::input_bits` of the type representing the message. Pointer loops are detected
+and reported as errors.
+
+When writing, a name is written uncompressed with the `()` context, or compressed
+against the names previously written to the message with a shared [Compressor]. The
+offsets recorded by the [Compressor] are relative to the start of the output, which
+must be the start of the message.
+
+As the context of a field is used for both reading and writing, the names of a
+message are read and written with [reader/writer](crate::attributes#readerwriter),
+with a [Compressor] created for each message by [ctx_default](crate::attributes#ctx_default).
+The [Compressor] is only used when writing, hence the `_names` binding.
+
+# Example
+
+```rust
+use deku::bitvec::{BitVec, Msb0};
+use deku::dns::{Compressor, DomainName};
+use deku::prelude::*;
+use std::convert::TryFrom;
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(endian = "big", ctx = "_names: Compressor", ctx_default = "Compressor::new()")]
+struct Message {
+    id: u16,
+    #[deku(
+        reader = "DomainName::read(deku::rest, deku::input_bits)",
+        writer = "question.write(deku::output, &_names)"
+    )]
+    question: DomainName,
+    #[deku(
+        reader = "DomainName::read(deku::rest, deku::input_bits)",
+        writer = "answer.write(deku::output, &_names)"
+    )]
+    answer: DomainName,
+}
+
+let message = Message {
+    id: 0x1234,
+    question: DomainName::try_from("www.example.com").unwrap(),
+    answer: DomainName::try_from("example.com").unwrap(),
+};
+
+// `example.com` in the answer is a pointer to offset 6, in the question
+let data = message.to_bytes().unwrap();
+assert_eq!(
+    b"\x12\x34\x03www\x07example\x03com\x00\xC0\x06".to_vec(),
+    data
+);
+
+let (_rest, value) = Message::from_bytes((data.as_ref(), 0)).unwrap();
+assert_eq!(message, value);
+assert_eq!("example.com", value.answer.to_string());
+```
+*/
+
+use crate::{ctx::Limit, DekuError, DekuRead, DekuWrite};
+use alloc::{format, string::String, vec::Vec};
+use bitvec::prelude::*;
+use core::cell::RefCell;
+use core::convert::TryFrom;
+
+/// Maximum length of a label, in bytes
+pub const MAX_LABEL_LEN: usize = 63;
+
+/// Maximum length of an encoded name, in bytes
+pub const MAX_NAME_LEN: usize = 255;
+
+/// Largest offset a compression pointer can hold
+const MAX_POINTER: usize = 0x3FFF;
+
+/// Domain name, a list of labels
+///
+/// The root name `.` has no labels.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct DomainName {
+    labels: Vec<Vec<u8>>,
+}
+
+impl DomainName {
+    /// Create the root name
+    pub fn root() -> Self {
+        Self::default()
+    }
+
+    /// Create a name from its labels
+    ///
+    /// Errors if a label is empty or longer than [MAX_LABEL_LEN], or if the encoded
+    /// name is longer than [MAX_NAME_LEN].
+    pub fn from_labels(labels: Vec<Vec<u8>>) -> Result<Self, DekuError> {
+        for label in &labels {
+            if label.is_empty() || label.len() > MAX_LABEL_LEN {
+                return Err(DekuError::InvalidParam(format!(
+                    "invalid domain name label length: {}",
+                    label.len()
+                )));
+            }
+        }
+        let name = Self { labels };
+        if name.encoded_len() > MAX_NAME_LEN {
+            return Err(DekuError::InvalidParam(format!(
+                "domain name is too long: {} bytes",
+                name.encoded_len()
+            )));
+        }
+        Ok(name)
+    }
+
+    /// Labels of the name
+    pub fn labels(&self) -> &[Vec<u8>] {
+        &self.labels
+    }
+
+    /// Returns true for the root name
+    pub fn is_root(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    /// Length of the uncompressed name, in bytes
+    pub fn encoded_len(&self) -> usize {
+        self.labels.iter().map(|l| 1 + l.len()).sum::<usize>() + 1
+    }
+
+    fn write_labels(labels: &[Vec<u8>], output: &mut BitVec<u8, Msb0>) {
+        for label in labels {
+            output.extend_from_bitslice((label.len() as u8).view_bits::<Msb0>());
+            output.extend_from_bitslice(label.view_bits::<Msb0>());
+        }
+    }
+}
+
+/// Parse a dotted name, such as `www.example.com`, a trailing dot is allowed
+impl TryFrom<&str> for DomainName {
+    type Error = DekuError;
+
+    fn try_from(name: &str) -> Result<Self, Self::Error> {
+        let name = name.strip_suffix('.').unwrap_or(name);
+        if name.is_empty() {
+            return Ok(Self::root());
+        }
+        Self::from_labels(name.split('.').map(|l| l.as_bytes().to_vec()).collect())
+    }
+}
+
+/// Dotted name, bytes outside of printable ASCII, `.` and `\` are escaped as `\DDD`
+impl core::fmt::Display for DomainName {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        if self.is_root() {
+            return f.write_str(".");
+        }
+        for (i, label) in self.labels.iter().enumerate() {
+            if i != 0 {
+                f.write_str(".")?;
+            }
+            for &b in label {
+                if b.is_ascii_graphic() && b != b'.' && b != b'\\' {
+                    write!(f, "{}", b as char)?;
+                } else {
+                    write!(f, "\\{b:03}")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Read a name starting at `input`, following pointers within `message`
+fn read_name<'a>(
+    input: &'a BitSlice<u8, Msb0>,
+    message: Option<&BitSlice<u8, Msb0>>,
+) -> Result<(&'a BitSlice<u8, Msb0>, DomainName), DekuError> {
+    let mut labels = Vec::new();
+    let mut name_len = 1;
+    let mut pointers = Vec::new();
+
+    // the name ends in the input after the terminating label or the first pointer
+    let mut rest = None;
+    let mut cursor: &BitSlice<u8, Msb0> = input;
+
+    let rest = loop {
+        let (new_cursor, len) = u8::read(cursor, ())?;
+        match len & 0xC0 {
+            0x00 if len == 0 => {
+                break rest.unwrap_or_else(|| &input[input.len() - new_cursor.len()..]);
+            }
+            0x00 => {
+                let (new_cursor, label) =
+                    Vec::<u8>::read(new_cursor, (Limit::new_count(len as usize), ()))?;
+                name_len += 1 + label.len();
+                if name_len > MAX_NAME_LEN {
+                    return Err(DekuError::Parse(format!(
+                        "domain name is longer than {MAX_NAME_LEN} bytes"
+                    )));
+                }
+                labels.push(label);
+                cursor = new_cursor;
+            }
+            0xC0 => {
+                let message = message.ok_or_else(|| {
+                    DekuError::Parse(String::from(
+                        "domain name compression pointer without the message",
+                    ))
+                })?;
+                let (new_cursor, low) = u8::read(new_cursor, ())?;
+                if rest.is_none() {
+                    rest = Some(&input[input.len() - new_cursor.len()..]);
+                }
+
+                let offset = (usize::from(len & 0x3F) << 8) | usize::from(low);
+                if pointers.contains(&offset) {
+                    return Err(DekuError::Parse(format!(
+                        "domain name compression pointer loop at offset {offset}"
+                    )));
+                }
+                pointers.push(offset);
+
+                if offset * 8 >= message.len() {
+                    return Err(DekuError::Parse(format!(
+                        "domain name compression pointer {offset} is past the end of the message"
+                    )));
+                }
+                cursor = &message[offset * 8..];
+            }
+            _ => {
+                return Err(DekuError::Parse(format!(
+                    "unsupported domain name label type: 0x{len:02X}"
+                )))
+            }
+        }
+    };
+
+    Ok((rest, DomainName { labels }))
+}
+
+/// Read an uncompressed name, a compression pointer is an error
+impl<'a> DekuRead<'a, ()> for DomainName {
+    fn read(
+        input: &'a BitSlice<u8, Msb0>,
+        _: (),
+    ) -> Result<(&'a BitSlice<u8, Msb0>, Self), DekuError> {
+        read_name(input, None)
+    }
+}
+
+/// Read a name, following compression pointers within the message given as context
+impl<'a, 'm> DekuRead<'a, &'m BitSlice<u8, Msb0>> for DomainName {
+    fn read(
+        input: &'a BitSlice<u8, Msb0>,
+        message: &'m BitSlice<u8, Msb0>,
+    ) -> Result<(&'a BitSlice<u8, Msb0>, Self), DekuError> {
+        read_name(input, Some(message))
+    }
+}
+
+/// Write the name uncompressed
+impl DekuWrite<()> for DomainName {
+    fn write(&self, output: &mut BitVec<u8, Msb0>, _: ()) -> Result<(), DekuError> {
+        Self::write_labels(&self.labels, output);
+        output.extend_from_bitslice(0u8.view_bits::<Msb0>());
+        Ok(())
+    }
+}
+
+/// Write the name compressed against the names previously written with the [Compressor]
+impl DekuWrite<&Compressor> for DomainName {
+    fn write(
+        &self,
+        output: &mut BitVec<u8, Msb0>,
+        compressor: &Compressor,
+    ) -> Result<(), DekuError> {
+        for i in 0..self.labels.len() {
+            let suffix = &self.labels[i..];
+            if let Some(offset) = compressor.find(output, suffix) {
+                output.extend_from_bitslice((0xC000 | offset as u16).view_bits::<Msb0>());
+                return Ok(());
+            }
+            compressor.insert(output);
+            Self::write_labels(&self.labels[i..i + 1], output);
+        }
+        output.extend_from_bitslice(0u8.view_bits::<Msb0>());
+        Ok(())
+    }
+}
+
+/// Offsets of the names written to a message, used to compress the following names
+///
+/// A pointer is only written to an offset where the output holds the same labels, so a
+/// [Compressor] used for a previous message never produces an invalid pointer, but
+/// should be [cleared](Self::clear) to not waste time on stale offsets.
+#[derive(Debug, Clone, Default)]
+pub struct Compressor {
+    offsets: RefCell<Vec<usize>>,
+}
+
+impl Compressor {
+    /// Create an empty [Compressor]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forget the names written so far
+    pub fn clear(&self) {
+        self.offsets.borrow_mut().clear();
+    }
+
+    /// Record a name (or the end of a name) starting at the end of `output`
+    fn insert(&self, output: &BitVec<u8, Msb0>) {
+        let offset = output.len() / 8;
+        let mut offsets = self.offsets.borrow_mut();
+        if output.len().is_multiple_of(8) && offset <= MAX_POINTER && !offsets.contains(&offset) {
+            offsets.push(offset);
+        }
+    }
+
+    /// Offset of a name written to `output` with the given labels
+    fn find(&self, output: &BitVec<u8, Msb0>, labels: &[Vec<u8>]) -> Option<usize> {
+        let message = output.as_bitslice();
+        self.offsets
+            .borrow()
+            .iter()
+            .copied()
+            .filter(|offset| offset * 8 < message.len())
+            .find(
+                |offset| match read_name(&message[offset * 8..], Some(message)) {
+                    Ok((_, name)) => name.labels == labels,
+                    Err(_) => false,
+                },
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn name(s: &str) -> DomainName {
+        DomainName::try_from(s).unwrap()
+    }
+
+    #[rstest(input, offset, expected, expected_rest,
+        case(b"\x03www\x07example\x03com\x00", 0, name("www.example.com"), bits![u8, Msb0;]),
+        case(b"\x00\xFF", 0, DomainName::root(), bits![u8, Msb0; 1, 1, 1, 1, 1, 1, 1, 1]),
+        case(b"\x07example\x03com\x00\x03www\xC0\x00\xFF", 13, name("www.example.com"), bits![u8, Msb0; 1, 1, 1, 1, 1, 1, 1, 1]),
+        case(b"\x03com\x00\x07example\xC0\x00\x03www\xC0\x05", 15, name("www.example.com"), bits![u8, Msb0;]),
+
+        #[should_panic(expected = "domain name compression pointer loop at offset 0")]
+        case(b"\xC0\x00", 0, DomainName::root(), bits![u8, Msb0;]),
+        #[should_panic(expected = "domain name compression pointer loop at offset 4")]
+        case(b"\x01a\xC0\x04\xC0\x00", 0, DomainName::root(), bits![u8, Msb0;]),
+        #[should_panic(expected = "domain name compression pointer 16 is past the end of the message")]
+        case(b"\xC0\x10", 0, DomainName::root(), bits![u8, Msb0;]),
+        #[should_panic(expected = "unsupported domain name label type: 0x40")]
+        case(b"\x40", 0, DomainName::root(), bits![u8, Msb0;]),
+        #[should_panic(expected = "Incomplete(NeedSize { bits: 8 })")]
+        case(b"\x03www", 0, DomainName::root(), bits![u8, Msb0;]),
+    )]
+    fn test_read(
+        input: &[u8],
+        offset: usize,
+        expected: DomainName,
+        expected_rest: &BitSlice<u8, Msb0>,
+    ) {
+        let message = input.view_bits::<Msb0>();
+        let (rest, res_read) = DomainName::read(&message[offset * 8..], message).unwrap();
+        assert_eq!(expected, res_read);
+        assert_eq!(expected_rest, rest);
+    }
+
+    #[test]
+    fn test_read_uncompressed() {
+        let input = b"\x03www\xC0\x00".view_bits::<Msb0>();
+        assert_eq!(
+            Err(DekuError::Parse(
+                "domain name compression pointer without the message".into()
+            )),
+            DomainName::read(input, ())
+        );
+    }
+
+    #[test]
+    fn test_read_too_long() {
+        let mut input = Vec::new();
+        for _ in 0..5 {
+            input.push(63);
+            input.extend_from_slice(&[b'a'; 63]);
+        }
+        input.push(0);
+        assert_eq!(
+            Err(DekuError::Parse(
+                "domain name is longer than 255 bytes".into()
+            )),
+            DomainName::read(input.view_bits::<Msb0>(), ())
+        );
+    }
+
+    #[rstest(input, expected,
+        case(".", Ok(DomainName::root())),
+        case("", Ok(DomainName::root())),
+        case("example.com.", Ok(name("example.com"))),
+        case("example..com", Err(DekuError::InvalidParam("invalid domain name label length: 0".into()))),
+    )]
+    fn test_try_from(input: &str, expected: Result<DomainName, DekuError>) {
+        assert_eq!(expected, DomainName::try_from(input));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(".", DomainName::root().to_string());
+        assert_eq!(
+            "a\\046b.c\\000",
+            DomainName::from_labels(vec![b"a.b".to_vec(), b"c\0".to_vec()])
+                .unwrap()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn test_write_compressed() {
+        let compressor = Compressor::new();
+        let mut output = bitvec![u8, Msb0;];
+
+        name("www.example.com")
+            .write(&mut output, &compressor)
+            .unwrap();
+        name("mail.example.com")
+            .write(&mut output, &compressor)
+            .unwrap();
+        name("www.example.com")
+            .write(&mut output, &compressor)
+            .unwrap();
+        name("example.org").write(&mut output, &compressor).unwrap();
+        DomainName::root().write(&mut output, &compressor).unwrap();
+
+        assert_eq!(
+            b"\x03www\x07example\x03com\x00\x04mail\xC0\x04\xC0\x00\x07example\x03org\x00\x00"
+                .to_vec(),
+            output.clone().into_vec()
+        );
+
+        // stale offsets of a previous message aren't used
+        let mut output = bitvec![u8, Msb0;];
+        name("example.com").write(&mut output, &compressor).unwrap();
+        assert_eq!(
+            b"\x07example\x03com\x00".to_vec(),
+            output.clone().into_vec()
+        );
+    }
+
+    #[test]
+    fn test_write_uncompressed() {
+        let mut output = bitvec![u8, Msb0;];
+        name("example.com").write(&mut output, ()).unwrap();
+        name("example.com").write(&mut output, ()).unwrap();
+        assert_eq!(
+            b"\x07example\x03com\x00\x07example\x03com\x00".to_vec(),
+            output.into_vec()
+        );
+    }
+}
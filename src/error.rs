@@ -27,6 +27,51 @@ impl NeedSize {
     }
 }
 
+/// User defined error held by [DekuError::Custom]
+///
+/// The error is shared when cloned, two custom errors are equal if their messages are equal
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct CustomError(std::sync::Arc<dyn std::error::Error + Send + Sync>);
+
+#[cfg(feature = "std")]
+impl CustomError {
+    /// Create new [CustomError] from an error or a message
+    pub fn new<E>(error: E) -> Self
+    where
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        Self(error.into().into())
+    }
+
+    /// The user defined error
+    pub fn get_ref(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+        &*self.0
+    }
+
+    /// The user defined error, if it is of type `E`
+    pub fn downcast_ref<E: std::error::Error + 'static>(&self) -> Option<&E> {
+        self.0.downcast_ref()
+    }
+}
+
+#[cfg(feature = "std")]
+impl PartialEq for CustomError {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_string() == other.0.to_string()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Eq for CustomError {}
+
+#[cfg(feature = "std")]
+impl core::fmt::Display for CustomError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
 /// Deku errors
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[non_exhaustive]
@@ -39,10 +84,44 @@ pub enum DekuError {
     InvalidParam(String),
     /// Unexpected error
     Unexpected(String),
-    /// Assertion error from `assert` or `assert_eq` attributes
+    /// Assertion error from `assert`, `assert_eq` or `validate` attributes
     Assertion(String),
     /// Could not resolve `id` for variant
     IdVariantNotFound,
+    /// User defined error, from `assert_err` or returned by user code
+    #[cfg(feature = "std")]
+    Custom(CustomError),
+}
+
+#[cfg(feature = "std")]
+impl DekuError {
+    /// Create a [DekuError::Custom] from a user defined error or a message
+    pub fn custom<E>(error: E) -> Self
+    where
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        DekuError::Custom(CustomError::new(error))
+    }
+}
+
+/// Error of `assert_err`, used by the derive macros
+#[cfg(feature = "std")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __deku_assert_err {
+    ($error:expr) => {
+        $crate::DekuError::custom($error)
+    };
+}
+
+/// Error of `assert_err`, used by the derive macros
+#[cfg(not(feature = "std"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __deku_assert_err {
+    ($error:expr) => {
+        compile_error!("`assert_err` requires the `std` feature of deku")
+    };
 }
 
 impl From<core::num::TryFromIntError> for DekuError {
@@ -77,6 +156,8 @@ impl core::fmt::Display for DekuError {
             DekuError::Unexpected(ref err) => write!(f, "Unexpected error: {err}"),
             DekuError::Assertion(ref err) => write!(f, "Assertion error: {err}"),
             DekuError::IdVariantNotFound => write!(f, "Could not resolve `id` for variant"),
+            #[cfg(feature = "std")]
+            DekuError::Custom(ref err) => write!(f, "Custom error: {err}"),
         }
     }
 }
@@ -86,6 +167,13 @@ impl std::error::Error for DekuError {
     fn cause(&self) -> Option<&dyn std::error::Error> {
         Some(self)
     }
+
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            DekuError::Custom(ref err) => Some(err.get_ref()),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(feature = "std")]
@@ -99,6 +187,7 @@ impl From<DekuError> for std::io::Error {
             DekuError::Unexpected(_) => io::Error::new(io::ErrorKind::Other, error),
             DekuError::Assertion(_) => io::Error::new(io::ErrorKind::InvalidData, error),
             DekuError::IdVariantNotFound => io::Error::new(io::ErrorKind::NotFound, error),
+            DekuError::Custom(_) => io::Error::new(io::ErrorKind::InvalidData, error),
         }
     }
 }
@@ -0,0 +1,9 @@
+/*!
+Ready-made file formats built on deku
+
+Each format is enabled by its feature:
+- `pcap`: [pcap] and pcapng capture files
+*/
+
+#[cfg(feature = "pcap")]
+pub mod pcap;
@@ -0,0 +1,276 @@
+/*!
+pcap and pcapng capture files
+
+Requires the `pcap` feature.
+
+A pcap file is a [GlobalHeader] followed by [Record]s, each holding a captured
+packet. The byte order of the file and the resolution of the timestamps are given
+by the [Magic] number at the start of the header. [PcapReader] iterates over the
+records of a file and [PcapWriter] creates one.
+
+pcapng files are handled by the [ng] module.
+
+# Example
+
+```rust
+use deku::formats::pcap::{GlobalHeader, Magic, PcapReader, PcapWriter, Record};
+
+// write a capture of two Ethernet frames
+let mut writer = PcapWriter::new(&GlobalHeader::new(Magic::LittleMicros, 65535, 1)).unwrap();
+writer.write(&Record::new(1, 500, vec![0xAA; 14])).unwrap();
+writer.write(&Record::new(2, 0, vec![0xBB; 20])).unwrap();
+let data = writer.finish();
+
+let reader = PcapReader::new(&data).unwrap();
+assert_eq!(1, reader.header().network);
+
+let records = reader.collect::<Result<Vec<_>, _>>().unwrap();
+assert_eq!(2, records.len());
+assert_eq!(vec![0xBB; 20], records[1].data);
+```
+*/
+
+pub mod ng;
+
+use crate::bitvec::{BitSlice, BitVec, BitView, Msb0};
+use crate::ctx::Endian;
+use crate::prelude::*;
+use alloc::{format, vec::Vec};
+
+/// Magic number of a pcap file, giving its byte order and timestamp resolution
+#[derive(Debug, Copy, Clone, PartialEq, Eq, DekuRead, DekuWrite)]
+#[deku(type = "[u8; 4]")]
+pub enum Magic {
+    /// Little endian, timestamps in microseconds
+    #[deku(id = "[0xD4, 0xC3, 0xB2, 0xA1]")]
+    LittleMicros,
+    /// Big endian, timestamps in microseconds
+    #[deku(id = "[0xA1, 0xB2, 0xC3, 0xD4]")]
+    BigMicros,
+    /// Little endian, timestamps in nanoseconds
+    #[deku(id = "[0x4D, 0x3C, 0xB2, 0xA1]")]
+    LittleNanos,
+    /// Big endian, timestamps in nanoseconds
+    #[deku(id = "[0xA1, 0xB2, 0x3C, 0x4D]")]
+    BigNanos,
+}
+
+impl Magic {
+    /// Byte order of the file
+    pub fn endian(&self) -> Endian {
+        match self {
+            Magic::LittleMicros | Magic::LittleNanos => Endian::Little,
+            Magic::BigMicros | Magic::BigNanos => Endian::Big,
+        }
+    }
+
+    /// Returns true if the fraction of the timestamps is in nanoseconds, instead of
+    /// microseconds
+    pub fn is_nanos(&self) -> bool {
+        matches!(self, Magic::LittleNanos | Magic::BigNanos)
+    }
+}
+
+impl From<&Magic> for Endian {
+    fn from(magic: &Magic) -> Self {
+        magic.endian()
+    }
+}
+
+/// Header at the start of a pcap file
+#[derive(Debug, Clone, PartialEq, Eq, DekuRead, DekuWrite)]
+#[deku(endian_from = "magic")]
+pub struct GlobalHeader {
+    /// Byte order and timestamp resolution
+    pub magic: Magic,
+    /// Major version, 2
+    pub version_major: u16,
+    /// Minor version, 4
+    pub version_minor: u16,
+    /// Time zone correction, in seconds, usually 0
+    pub thiszone: i32,
+    /// Accuracy of the timestamps, usually 0
+    pub sigfigs: u32,
+    /// Maximum length of the captured packets
+    pub snaplen: u32,
+    /// Link-layer header type of the packets, such as `1` for Ethernet
+    pub network: u32,
+}
+
+impl GlobalHeader {
+    /// Create a version 2.4 header
+    pub fn new(magic: Magic, snaplen: u32, network: u32) -> Self {
+        Self {
+            magic,
+            version_major: 2,
+            version_minor: 4,
+            thiszone: 0,
+            sigfigs: 0,
+            snaplen,
+            network,
+        }
+    }
+}
+
+/// Header of a captured packet
+#[derive(Debug, Copy, Clone, PartialEq, Eq, DekuRead, DekuWrite)]
+#[deku(ctx = "endian: Endian", endian = "endian")]
+pub struct RecordHeader {
+    /// Timestamp, in seconds
+    pub ts_sec: u32,
+    /// Fraction of the timestamp, in microseconds or nanoseconds, see [Magic::is_nanos]
+    pub ts_frac: u32,
+    /// Length of the captured data
+    pub incl_len: u32,
+    /// Length of the packet, the captured data can be truncated to the `snaplen`
+    pub orig_len: u32,
+}
+
+/// Captured packet
+#[derive(Debug, Clone, PartialEq, Eq, DekuRead, DekuWrite)]
+#[deku(ctx = "endian: Endian", endian = "endian")]
+pub struct Record {
+    /// Timestamp and lengths
+    pub header: RecordHeader,
+    /// Captured data
+    #[deku(
+        count = "header.incl_len",
+        assert = "data.len() == header.incl_len as usize"
+    )]
+    pub data: Vec<u8>,
+}
+
+impl Record {
+    /// Create a record of a packet captured whole
+    pub fn new(ts_sec: u32, ts_frac: u32, data: Vec<u8>) -> Self {
+        let len = data.len() as u32;
+        Self {
+            header: RecordHeader {
+                ts_sec,
+                ts_frac,
+                incl_len: len,
+                orig_len: len,
+            },
+            data,
+        }
+    }
+}
+
+/// Iterator over the records of a pcap file
+///
+/// Iteration stops after the first error.
+#[derive(Debug, Clone)]
+pub struct PcapReader<'a> {
+    header: GlobalHeader,
+    rest: &'a BitSlice<u8, Msb0>,
+}
+
+impl<'a> PcapReader<'a> {
+    /// Read the header of a pcap file
+    pub fn new(data: &'a [u8]) -> Result<Self, DekuError> {
+        let (rest, header) = GlobalHeader::read(data.view_bits::<Msb0>(), ())?;
+        Ok(Self { header, rest })
+    }
+
+    /// Header of the file
+    pub fn header(&self) -> &GlobalHeader {
+        &self.header
+    }
+}
+
+impl<'a> Iterator for PcapReader<'a> {
+    type Item = Result<Record, DekuError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.is_empty() {
+            return None;
+        }
+        match Record::read(self.rest, self.header.magic.endian()) {
+            Ok((rest, record)) => {
+                self.rest = rest;
+                Some(Ok(record))
+            }
+            Err(e) => {
+                self.rest = &self.rest[self.rest.len()..];
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Writer of a pcap file
+#[derive(Debug, Clone)]
+pub struct PcapWriter {
+    endian: Endian,
+    output: BitVec<u8, Msb0>,
+}
+
+impl PcapWriter {
+    /// Start a pcap file with its header
+    pub fn new(header: &GlobalHeader) -> Result<Self, DekuError> {
+        Ok(Self {
+            endian: header.magic.endian(),
+            output: header.to_bits()?,
+        })
+    }
+
+    /// Append a record
+    pub fn write(&mut self, record: &Record) -> Result<(), DekuError> {
+        record.write(&mut self.output, self.endian)
+    }
+
+    /// Bytes of the file
+    pub fn finish(self) -> Vec<u8> {
+        self.output.into_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hexlit::hex;
+    use rstest::rstest;
+
+    #[rstest(input, expected,
+        case(&hex!("D4C3B2A1 0200 0400 00000000 00000000 FFFF0000 01000000"), GlobalHeader::new(Magic::LittleMicros, 65535, 1)),
+        case(&hex!("A1B2C3D4 0002 0004 00000000 00000000 0000FFFF 00000001"), GlobalHeader::new(Magic::BigMicros, 65535, 1)),
+        case(&hex!("4D3CB2A1 0200 0400 00000000 00000000 FFFF0000 01000000"), GlobalHeader::new(Magic::LittleNanos, 65535, 1)),
+        case(&hex!("A1B23C4D 0002 0004 00000000 00000000 0000FFFF 00000001"), GlobalHeader::new(Magic::BigNanos, 65535, 1)),
+
+        #[should_panic(expected = "Parse(\"Could not match enum variant id = [161, 178, 195, 195] on enum `Magic`\")")]
+        case(&hex!("A1B2C3C3 0002 0004 00000000 00000000 0000FFFF 00000001"), GlobalHeader::new(Magic::BigNanos, 65535, 1)),
+    )]
+    fn test_global_header(input: &[u8], expected: GlobalHeader) {
+        let (rest, header) = GlobalHeader::from_bytes((input, 0)).unwrap();
+        assert_eq!(expected, header);
+        assert!(rest.0.is_empty());
+
+        assert_eq!(input.to_vec(), header.to_bytes().unwrap());
+    }
+
+    #[test]
+    fn test_record_len_mismatch() {
+        let mut record = Record::new(0, 0, vec![0xAA]);
+        record.header.incl_len = 2;
+
+        let mut writer =
+            PcapWriter::new(&GlobalHeader::new(Magic::LittleMicros, 65535, 1)).unwrap();
+        assert_eq!(
+            Err(DekuError::Assertion(
+                "Record.data field failed assertion: data.len() == header.incl_len as usize".into()
+            )),
+            writer.write(&record)
+        );
+    }
+
+    #[test]
+    fn test_reader_error() {
+        let input = hex!("D4C3B2A1 0200 0400 00000000 00000000 FFFF0000 01000000 00000000 00000000 04000000 04000000 AABB");
+        let mut reader = PcapReader::new(&input).unwrap();
+        assert_eq!(
+            Some(Err(DekuError::Incomplete(NeedSize::new(8)))),
+            reader.next()
+        );
+        assert_eq!(None, reader.next());
+    }
+}
@@ -0,0 +1,459 @@
+/*!
+pcapng capture files
+
+A pcapng file is a sequence of blocks, each starting with its type and length. A
+section starts with a [SectionHeader] block giving the byte order of the blocks of
+the section, followed by [InterfaceDescription] blocks and the captured packets, as
+[EnhancedPacket] blocks. Blocks end with a list of [BlockOption]s, aligned to 32 bits.
+
+Blocks are read and written as a [RawBlock], the type and body of the block, which
+[Block] interprets. Other block types are kept as a [RawBlock].
+
+# Example
+
+```rust
+use deku::ctx::Endian;
+use deku::formats::pcap::ng::{
+    Block, BlockOption, EnhancedPacket, InterfaceDescription, PcapngReader, PcapngWriter,
+    SectionHeader,
+};
+
+let mut writer = PcapngWriter::new(Endian::Big, &SectionHeader::new()).unwrap();
+let mut interface = InterfaceDescription::new(1, 65535);
+interface.options.push(BlockOption::new(2, b"eth0".to_vec()));
+writer.write(&Block::InterfaceDescription(interface)).unwrap();
+writer
+    .write(&Block::EnhancedPacket(EnhancedPacket::new(0, 1_000_000, vec![0xAA; 15])))
+    .unwrap();
+let data = writer.finish();
+
+let packets: Vec<EnhancedPacket> = PcapngReader::new(&data)
+    .filter_map(|block| match block {
+        Ok(Block::EnhancedPacket(packet)) => Some(packet),
+        _ => None,
+    })
+    .collect();
+assert_eq!(1, packets.len());
+assert_eq!(1_000_000, packets[0].timestamp());
+assert_eq!(vec![0xAA; 15], packets[0].data);
+```
+*/
+
+use crate::bitvec::{BitSlice, BitVec, BitView, Msb0};
+use crate::ctx::Endian;
+use crate::prelude::*;
+use alloc::{format, vec::Vec};
+use core::convert::TryFrom;
+
+/// Block type of a [SectionHeader]
+pub const SECTION_HEADER: u32 = 0x0A0D_0D0A;
+
+/// Block type of an [InterfaceDescription]
+pub const INTERFACE_DESCRIPTION: u32 = 0x0000_0001;
+
+/// Block type of an [EnhancedPacket]
+pub const ENHANCED_PACKET: u32 = 0x0000_0006;
+
+/// Byte order magic of a [SectionHeader], as read in the byte order of the section
+pub const BYTE_ORDER_MAGIC: u32 = 0x1A2B_3C4D;
+
+/// Option code ending the list of options
+pub const OPT_ENDOFOPT: u16 = 0;
+
+/// Block type, length and body
+#[deku_derive(DekuRead, DekuWrite)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[deku(ctx = "endian: Endian", endian = "endian")]
+pub struct RawBlock {
+    /// Type of the block
+    pub block_type: u32,
+    #[deku(temp, temp_value = "u32::try_from(body.len() + 12)?")]
+    block_total_length: u32,
+    /// Body of the block, its length is a multiple of 4
+    #[deku(
+        count = "body_len(*block_total_length)?",
+        assert = "body.len() % 4 == 0"
+    )]
+    pub body: Vec<u8>,
+    #[deku(
+        temp,
+        temp_value = "block_total_length",
+        map = "|trailer: u32| check_trailer(trailer, *block_total_length)"
+    )]
+    block_total_length_trailer: u32,
+}
+
+/// Length of the body of a block of `total` bytes
+fn body_len(total: u32) -> Result<usize, DekuError> {
+    if total < 12 || !total.is_multiple_of(4) {
+        return Err(DekuError::Parse(format!(
+            "invalid block total length: {total}"
+        )));
+    }
+    Ok(total as usize - 12)
+}
+
+/// Check the total length repeated at the end of a block
+fn check_trailer(trailer: u32, total: u32) -> Result<u32, DekuError> {
+    if trailer != total {
+        return Err(DekuError::Parse(format!(
+            "block total length mismatch: {total} and {trailer}"
+        )));
+    }
+    Ok(trailer)
+}
+
+/// Option of a block, its value is padded to 32 bits
+#[deku_derive(DekuRead, DekuWrite)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[deku(ctx = "endian: Endian", endian = "endian")]
+pub struct BlockOption {
+    /// Option code, such as [OPT_ENDOFOPT]
+    pub code: u16,
+    #[deku(temp, temp_value = "u16::try_from(value.len())?")]
+    length: u16,
+    /// Value of the option, without the padding
+    #[deku(count = "length", align_after = "4")]
+    pub value: Vec<u8>,
+}
+
+impl BlockOption {
+    /// Create an option
+    pub fn new(code: u16, value: Vec<u8>) -> Self {
+        Self { code, value }
+    }
+
+    /// Create the option ending the list of options
+    pub fn end() -> Self {
+        Self::new(OPT_ENDOFOPT, Vec::new())
+    }
+}
+
+/// Body of a section header block
+#[deku_derive(DekuRead, DekuWrite)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[deku(ctx = "endian: Endian", endian = "endian")]
+pub struct SectionHeader {
+    #[deku(temp, temp_value = "BYTE_ORDER_MAGIC", map = "check_byte_order")]
+    byte_order_magic: u32,
+    /// Major version, 1
+    pub major_version: u16,
+    /// Minor version, 0
+    pub minor_version: u16,
+    /// Length of the section after this block, or -1 if unknown
+    pub section_length: i64,
+    /// Options of the block
+    #[deku(bits_read = "deku::rest.len()")]
+    pub options: Vec<BlockOption>,
+}
+
+fn check_byte_order(magic: u32) -> Result<u32, DekuError> {
+    if magic != BYTE_ORDER_MAGIC {
+        return Err(DekuError::Parse(format!(
+            "invalid byte order magic: 0x{magic:08X}"
+        )));
+    }
+    Ok(magic)
+}
+
+impl SectionHeader {
+    /// Create a version 1.0 section header of unknown length, without options
+    pub fn new() -> Self {
+        Self {
+            major_version: 1,
+            minor_version: 0,
+            section_length: -1,
+            options: Vec::new(),
+        }
+    }
+}
+
+impl Default for SectionHeader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Body of an interface description block
+#[derive(Debug, Clone, PartialEq, Eq, DekuRead, DekuWrite)]
+#[deku(ctx = "endian: Endian", endian = "endian")]
+pub struct InterfaceDescription {
+    /// Link-layer header type of the packets, such as `1` for Ethernet
+    pub link_type: u16,
+    /// Maximum length of the captured packets, 0 for no limit
+    #[deku(reserved_bytes = "2")]
+    pub snap_len: u32,
+    /// Options of the block
+    #[deku(bits_read = "deku::rest.len()")]
+    pub options: Vec<BlockOption>,
+}
+
+impl InterfaceDescription {
+    /// Create an interface description without options
+    pub fn new(link_type: u16, snap_len: u32) -> Self {
+        Self {
+            link_type,
+            snap_len,
+            options: Vec::new(),
+        }
+    }
+}
+
+/// Body of an enhanced packet block
+#[deku_derive(DekuRead, DekuWrite)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[deku(ctx = "endian: Endian", endian = "endian")]
+pub struct EnhancedPacket {
+    /// Index of the interface the packet was captured on, in the section
+    pub interface_id: u32,
+    /// Upper 32 bits of the timestamp
+    pub timestamp_high: u32,
+    /// Lower 32 bits of the timestamp
+    pub timestamp_low: u32,
+    #[deku(temp, temp_value = "u32::try_from(data.len())?")]
+    captured_len: u32,
+    /// Length of the packet, the captured data can be truncated
+    pub original_len: u32,
+    /// Captured data, without the padding
+    #[deku(count = "captured_len", align_after = "4")]
+    pub data: Vec<u8>,
+    /// Options of the block
+    #[deku(bits_read = "deku::rest.len()")]
+    pub options: Vec<BlockOption>,
+}
+
+impl EnhancedPacket {
+    /// Create a packet captured whole, without options
+    ///
+    /// The unit of the timestamp is given by the `if_tsresol` option of the
+    /// interface, microseconds by default.
+    pub fn new(interface_id: u32, timestamp: u64, data: Vec<u8>) -> Self {
+        Self {
+            interface_id,
+            timestamp_high: (timestamp >> 32) as u32,
+            timestamp_low: timestamp as u32,
+            original_len: data.len() as u32,
+            data,
+            options: Vec::new(),
+        }
+    }
+
+    /// Timestamp of the packet
+    pub fn timestamp(&self) -> u64 {
+        (u64::from(self.timestamp_high) << 32) | u64::from(self.timestamp_low)
+    }
+}
+
+/// Block of a pcapng file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Block {
+    /// Start of a section
+    SectionHeader(SectionHeader),
+    /// Interface the packets are captured on
+    InterfaceDescription(InterfaceDescription),
+    /// Captured packet
+    EnhancedPacket(EnhancedPacket),
+    /// Block of another type
+    Other(RawBlock),
+}
+
+impl Block {
+    /// Interpret a raw block of a section of the given byte order
+    pub fn from_raw(raw: RawBlock, endian: Endian) -> Result<Self, DekuError> {
+        let body = raw.body.view_bits::<Msb0>();
+        Ok(match raw.block_type {
+            SECTION_HEADER => Block::SectionHeader(SectionHeader::read(body, endian)?.1),
+            INTERFACE_DESCRIPTION => {
+                Block::InterfaceDescription(InterfaceDescription::read(body, endian)?.1)
+            }
+            ENHANCED_PACKET => Block::EnhancedPacket(EnhancedPacket::read(body, endian)?.1),
+            _ => Block::Other(raw),
+        })
+    }
+
+    /// Raw block in the given byte order
+    pub fn to_raw(&self, endian: Endian) -> Result<RawBlock, DekuError> {
+        let mut body = BitVec::new();
+        let block_type = match self {
+            Block::SectionHeader(block) => {
+                block.write(&mut body, endian)?;
+                SECTION_HEADER
+            }
+            Block::InterfaceDescription(block) => {
+                block.write(&mut body, endian)?;
+                INTERFACE_DESCRIPTION
+            }
+            Block::EnhancedPacket(block) => {
+                block.write(&mut body, endian)?;
+                ENHANCED_PACKET
+            }
+            Block::Other(raw) => return Ok(raw.clone()),
+        };
+        Ok(RawBlock {
+            block_type,
+            body: body.into_vec(),
+        })
+    }
+}
+
+/// Iterator over the blocks of a pcapng file
+///
+/// The byte order of each section is taken from its section header block.
+/// Iteration stops after the first error.
+#[derive(Debug, Clone)]
+pub struct PcapngReader<'a> {
+    rest: &'a BitSlice<u8, Msb0>,
+    endian: Option<Endian>,
+}
+
+impl<'a> PcapngReader<'a> {
+    /// Read the blocks of a pcapng file
+    pub fn new(data: &'a [u8]) -> Self {
+        Self {
+            rest: data.view_bits::<Msb0>(),
+            endian: None,
+        }
+    }
+
+    /// Byte order of the current section, once its header is read
+    pub fn endian(&self) -> Option<Endian> {
+        self.endian
+    }
+
+    fn read_block(&mut self) -> Result<Block, DekuError> {
+        // the byte order is given by the magic following the type and length of the section header
+        let (_, block_type) = <[u8; 4]>::read(self.rest, ())?;
+        if u32::from_be_bytes(block_type) == SECTION_HEADER {
+            if self.rest.len() < 12 * 8 {
+                return Err(DekuError::Incomplete(NeedSize::new(12 * 8)));
+            }
+            let (_, magic) = u32::read(&self.rest[8 * 8..], Endian::Big)?;
+            self.endian = Some(match magic {
+                BYTE_ORDER_MAGIC => Endian::Big,
+                _ if magic.swap_bytes() == BYTE_ORDER_MAGIC => Endian::Little,
+                _ => return Err(check_byte_order(magic).unwrap_err()),
+            });
+        }
+
+        let endian = self.endian.ok_or_else(|| {
+            DekuError::Parse(format!(
+                "block type 0x{:08X} before the section header",
+                u32::from_be_bytes(block_type)
+            ))
+        })?;
+        let (rest, raw) = RawBlock::read(self.rest, endian)?;
+        self.rest = rest;
+        Block::from_raw(raw, endian)
+    }
+}
+
+impl<'a> Iterator for PcapngReader<'a> {
+    type Item = Result<Block, DekuError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.is_empty() {
+            return None;
+        }
+        let block = self.read_block();
+        if block.is_err() {
+            self.rest = &self.rest[self.rest.len()..];
+        }
+        Some(block)
+    }
+}
+
+/// Writer of a pcapng file
+#[derive(Debug, Clone)]
+pub struct PcapngWriter {
+    endian: Endian,
+    output: BitVec<u8, Msb0>,
+}
+
+impl PcapngWriter {
+    /// Start a pcapng file with a section of the given byte order
+    pub fn new(endian: Endian, section: &SectionHeader) -> Result<Self, DekuError> {
+        let mut writer = Self {
+            endian,
+            output: BitVec::new(),
+        };
+        writer.write(&Block::SectionHeader(section.clone()))?;
+        Ok(writer)
+    }
+
+    /// Append a block, in the byte order of the file
+    pub fn write(&mut self, block: &Block) -> Result<(), DekuError> {
+        block
+            .to_raw(self.endian)?
+            .write(&mut self.output, self.endian)
+    }
+
+    /// Bytes of the file
+    pub fn finish(self) -> Vec<u8> {
+        self.output.into_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hexlit::hex;
+    use rstest::rstest;
+
+    #[rstest(input, endian, expected,
+        case(&hex!("0002 0004 65746830"), Endian::Big, BlockOption::new(2, b"eth0".to_vec())),
+        case(&hex!("0002 0003 657468 00"), Endian::Big, BlockOption::new(2, b"eth".to_vec())),
+        case(&hex!("0200 0300 657468 00"), Endian::Little, BlockOption::new(2, b"eth".to_vec())),
+        case(&hex!("0900 0100 06 000000"), Endian::Little, BlockOption::new(9, vec![6])),
+        case(&hex!("0000 0000"), Endian::Little, BlockOption::end()),
+    )]
+    fn test_option(input: &[u8], endian: Endian, expected: BlockOption) {
+        let (rest, option) = BlockOption::read(input.view_bits::<Msb0>(), endian).unwrap();
+        assert_eq!(expected, option);
+        assert!(rest.is_empty());
+
+        let mut output = BitVec::new();
+        option.write(&mut output, endian).unwrap();
+        assert_eq!(input.to_vec(), output.into_vec());
+    }
+
+    #[rstest(input, expected,
+        case(&hex!("00000001 0000000C 0000000C"), RawBlock { block_type: 1, body: vec![] }),
+        case(&hex!("00000BAD 00000010 AABBCCDD 00000010"), RawBlock { block_type: 0xBAD, body: vec![0xAA, 0xBB, 0xCC, 0xDD] }),
+
+        #[should_panic(expected = "block total length mismatch: 16 and 12")]
+        case(&hex!("00000BAD 00000010 AABBCCDD 0000000C"), RawBlock { block_type: 0, body: vec![] }),
+        #[should_panic(expected = "invalid block total length: 14")]
+        case(&hex!("00000BAD 0000000E AABB 0000000E"), RawBlock { block_type: 0, body: vec![] }),
+    )]
+    fn test_raw_block(input: &[u8], expected: RawBlock) {
+        let (rest, block) = RawBlock::read(input.view_bits::<Msb0>(), Endian::Big).unwrap();
+        assert_eq!(expected, block);
+        assert!(rest.is_empty());
+
+        let mut output = BitVec::new();
+        block.write(&mut output, Endian::Big).unwrap();
+        assert_eq!(input.to_vec(), output.into_vec());
+    }
+
+    #[test]
+    fn test_reader_errors() {
+        let input = hex!("00000001 0000000C 0000000C");
+        let mut reader = PcapngReader::new(&input);
+        assert_eq!(
+            Some(Err(DekuError::Parse(
+                "block type 0x00000001 before the section header".into()
+            ))),
+            reader.next()
+        );
+        assert_eq!(None, reader.next());
+
+        let input = hex!("0A0D0D0A 0000001C 11223344");
+        let mut reader = PcapngReader::new(&input);
+        assert_eq!(
+            Some(Err(DekuError::Parse(
+                "invalid byte order magic: 0x11223344".into()
+            ))),
+            reader.next()
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use crate::layout::{self, ReadLayout, WriteLayout};
 use crate::{ctx::Limit, DekuError, DekuRead, DekuWrite};
 use alloc::{boxed::Box, vec::Vec};
 use bitvec::prelude::*;
@@ -18,6 +19,18 @@ where
         let (rest, val) = <T>::read(input, inner_ctx)?;
         Ok((rest, Box::new(val)))
     }
+
+    fn read_in(
+        layout: &ReadLayout<'a>,
+        input: &'a BitSlice<u8, Msb0>,
+        inner_ctx: Ctx,
+    ) -> Result<(&'a BitSlice<u8, Msb0>, Self), DekuError>
+    where
+        Self: Sized,
+    {
+        let (rest, val) = <T>::read_in(layout, input, inner_ctx)?;
+        Ok((rest, Box::new(val)))
+    }
 }
 
 impl<T, Ctx> DekuWrite<Ctx> for Box<T>
@@ -29,6 +42,15 @@ where
     fn write(&self, output: &mut BitVec<u8, Msb0>, inner_ctx: Ctx) -> Result<(), DekuError> {
         self.as_ref().write(output, inner_ctx)
     }
+
+    fn write_in(
+        &self,
+        layout: &mut WriteLayout,
+        output: &mut BitVec<u8, Msb0>,
+        inner_ctx: Ctx,
+    ) -> Result<(), DekuError> {
+        self.as_ref().write_in(layout, output, inner_ctx)
+    }
 }
 
 impl<'a, T, Ctx, Predicate> DekuRead<'a, (Limit<T, Predicate>, Ctx)> for Box<[T]>
@@ -49,6 +71,18 @@ where
         let (rest, val) = <Vec<T>>::read(input, (limit, inner_ctx))?;
         Ok((rest, val.into_boxed_slice()))
     }
+
+    fn read_in(
+        layout: &ReadLayout<'a>,
+        input: &'a BitSlice<u8, Msb0>,
+        (limit, inner_ctx): (Limit<T, Predicate>, Ctx),
+    ) -> Result<(&'a BitSlice<u8, Msb0>, Self), DekuError>
+    where
+        Self: Sized,
+    {
+        let (rest, val) = <Vec<T>>::read_in(layout, input, (limit, inner_ctx))?;
+        Ok((rest, val.into_boxed_slice()))
+    }
 }
 
 impl<T, Ctx> DekuWrite<Ctx> for Box<[T]>
@@ -58,8 +92,17 @@ where
 {
     /// Write all `T`s to bits
     fn write(&self, output: &mut BitVec<u8, Msb0>, ctx: Ctx) -> Result<(), DekuError> {
+        layout::write(self, output, ctx)
+    }
+
+    fn write_in(
+        &self,
+        layout: &mut WriteLayout,
+        output: &mut BitVec<u8, Msb0>,
+        ctx: Ctx,
+    ) -> Result<(), DekuError> {
         for v in self.as_ref() {
-            v.write(output, ctx)?;
+            v.write_in(layout, output, ctx)?;
         }
         Ok(())
     }
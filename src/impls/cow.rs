@@ -1,3 +1,4 @@
+use crate::layout::{ReadLayout, WriteLayout};
 use crate::{DekuError, DekuRead, DekuWrite};
 use bitvec::prelude::*;
 use std::borrow::{Borrow, Cow};
@@ -18,6 +19,18 @@ where
         let (rest, val) = <T>::read(input, inner_ctx)?;
         Ok((rest, Cow::Owned(val)))
     }
+
+    fn read_in(
+        layout: &ReadLayout<'a>,
+        input: &'a BitSlice<u8, Msb0>,
+        inner_ctx: Ctx,
+    ) -> Result<(&'a BitSlice<u8, Msb0>, Self), DekuError>
+    where
+        Self: Sized,
+    {
+        let (rest, val) = <T>::read_in(layout, input, inner_ctx)?;
+        Ok((rest, Cow::Owned(val)))
+    }
 }
 
 impl<T, Ctx> DekuWrite<Ctx> for Cow<'_, T>
@@ -29,6 +42,15 @@ where
     fn write(&self, output: &mut BitVec<u8, Msb0>, inner_ctx: Ctx) -> Result<(), DekuError> {
         (self.borrow() as &T).write(output, inner_ctx)
     }
+
+    fn write_in(
+        &self,
+        layout: &mut WriteLayout,
+        output: &mut BitVec<u8, Msb0>,
+        inner_ctx: Ctx,
+    ) -> Result<(), DekuError> {
+        (self.borrow() as &T).write_in(layout, output, inner_ctx)
+    }
 }
 
 #[cfg(test)]
@@ -23,15 +23,9 @@ where
     where
         Self: Sized,
     {
-        let (rest, mut bytes) = Vec::read(input, (Limit::from(|b: &u8| *b == 0x00), ctx))?;
-
-        // TODO: use from_vec_with_nul instead once stable
-
-        // Remove null byte
-        let nul_byte = bytes.pop();
-        if nul_byte != Some(0x00) {
-            return Err(DekuError::Unexpected("Expected nul byte".to_string()));
-        }
+        let (rest, bytes) = Vec::read(input, (Limit::new_terminator(0x00), ctx))?;
+        // consume the nul terminator left in the rest
+        let (rest, _) = u8::read(rest, ctx)?;
 
         let value = CString::new(bytes)
             .map_err(|e| DekuError::Parse(format!("Failed to convert Vec to CString: {e}")))?;
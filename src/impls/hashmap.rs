@@ -1,10 +1,13 @@
+use crate::layout::{self, ReadLayout, WriteLayout};
 use crate::{ctx::*, DekuError, DekuRead, DekuWrite};
 use bitvec::prelude::*;
 use std::collections::HashMap;
 use std::hash::{BuildHasher, Hash};
 
 /// Read `K, V`s into a hashmap until a given predicate returns true
+/// * `layout` - the layout of the outermost input, passed to every `K, V`
 /// * `capacity` - an optional capacity to pre-allocate the hashmap with
+/// * `exclusive` - don't read the value for which the predicate returned true, it is left in the rest
 /// * `ctx` - The context required by `K, V`. It will be passed to every `K, V` when constructing.
 /// * `predicate` - the predicate that decides when to stop reading `K, V`s
 /// The predicate takes two parameters: the number of bits that have been read so far,
@@ -19,8 +22,10 @@ fn read_hashmap_with_predicate<
     Ctx: Copy,
     Predicate: FnMut(usize, &(K, V)) -> bool,
 >(
+    layout: &ReadLayout<'a>,
     input: &'a BitSlice<u8, Msb0>,
     capacity: Option<usize>,
+    exclusive: bool,
     ctx: Ctx,
     mut predicate: Predicate,
 ) -> Result<(&'a BitSlice<u8, Msb0>, HashMap<K, V, S>), DekuError> {
@@ -30,11 +35,15 @@ fn read_hashmap_with_predicate<
     let mut found_predicate = false;
 
     while !found_predicate {
-        let (new_rest, kv) = <(K, V)>::read(rest, ctx)?;
+        let (new_rest, kv) = <(K, V)>::read_in(layout, rest, ctx)?;
         found_predicate = predicate(
             unsafe { new_rest.as_bitptr().offset_from(input.as_bitptr()) } as usize,
             &kv,
         );
+        if exclusive && found_predicate {
+            // the value is left in the rest
+            break;
+        }
         res.insert(kv.0, kv.1);
         rest = new_rest;
     }
@@ -68,6 +77,17 @@ impl<
     /// assert_eq!(expected, map)
     /// ```
     fn read(
+        input: &'a BitSlice<u8, Msb0>,
+        ctx: (Limit<(K, V), Predicate>, Ctx),
+    ) -> Result<(&'a BitSlice<u8, Msb0>, Self), DekuError>
+    where
+        Self: Sized,
+    {
+        layout::read(input, ctx)
+    }
+
+    fn read_in(
+        layout: &ReadLayout<'a>,
         input: &'a BitSlice<u8, Msb0>,
         (limit, inner_ctx): (Limit<(K, V), Predicate>, Ctx),
     ) -> Result<(&'a BitSlice<u8, Msb0>, Self), DekuError>
@@ -83,15 +103,31 @@ impl<
                 }
 
                 // Otherwise, read until we have read `count` elements
-                read_hashmap_with_predicate(input, Some(count), inner_ctx, move |_, _| {
-                    count -= 1;
-                    count == 0
-                })
+                read_hashmap_with_predicate(
+                    layout,
+                    input,
+                    Some(count),
+                    false,
+                    inner_ctx,
+                    move |_, _| {
+                        count -= 1;
+                        count == 0
+                    },
+                )
             }
 
             // Read until a given predicate returns true
             Limit::Until(mut predicate, _) => {
-                read_hashmap_with_predicate(input, None, inner_ctx, move |_, kv| predicate(kv))
+                read_hashmap_with_predicate(layout, input, None, false, inner_ctx, move |_, kv| {
+                    predicate(kv)
+                })
+            }
+
+            // Read until a given predicate returns true, leaving the last value in the rest
+            Limit::UntilExclusive(mut predicate, _) => {
+                read_hashmap_with_predicate(layout, input, None, true, inner_ctx, move |_, kv| {
+                    predicate(kv)
+                })
             }
 
             // Read until a given quantity of bits have been read
@@ -103,9 +139,14 @@ impl<
                     return Ok((input, HashMap::<K, V, S>::default()));
                 }
 
-                read_hashmap_with_predicate(input, None, inner_ctx, move |read_bits, _| {
-                    read_bits == bit_size
-                })
+                read_hashmap_with_predicate(
+                    layout,
+                    input,
+                    None,
+                    false,
+                    inner_ctx,
+                    move |read_bits, _| read_bits == bit_size,
+                )
             }
 
             // Read until a given quantity of bits have been read
@@ -117,9 +158,14 @@ impl<
                     return Ok((input, HashMap::<K, V, S>::default()));
                 }
 
-                read_hashmap_with_predicate(input, None, inner_ctx, move |read_bits, _| {
-                    read_bits == bit_size
-                })
+                read_hashmap_with_predicate(
+                    layout,
+                    input,
+                    None,
+                    false,
+                    inner_ctx,
+                    move |read_bits, _| read_bits == bit_size,
+                )
             }
         }
     }
@@ -143,6 +189,17 @@ impl<
     {
         Self::read(input, (limit, ()))
     }
+
+    fn read_in(
+        layout: &ReadLayout<'a>,
+        input: &'a BitSlice<u8, Msb0>,
+        limit: Limit<(K, V), Predicate>,
+    ) -> Result<(&'a BitSlice<u8, Msb0>, Self), DekuError>
+    where
+        Self: Sized,
+    {
+        Self::read_in(layout, input, (limit, ()))
+    }
 }
 
 impl<K: DekuWrite<Ctx>, V: DekuWrite<Ctx>, S, Ctx: Copy> DekuWrite<Ctx> for HashMap<K, V, S> {
@@ -164,8 +221,17 @@ impl<K: DekuWrite<Ctx>, V: DekuWrite<Ctx>, S, Ctx: Copy> DekuWrite<Ctx> for Hash
     /// assert_eq!(expected, output.into_vec())
     /// ```
     fn write(&self, output: &mut BitVec<u8, Msb0>, inner_ctx: Ctx) -> Result<(), DekuError> {
+        layout::write(self, output, inner_ctx)
+    }
+
+    fn write_in(
+        &self,
+        layout: &mut WriteLayout,
+        output: &mut BitVec<u8, Msb0>,
+        inner_ctx: Ctx,
+    ) -> Result<(), DekuError> {
         for kv in self {
-            kv.write(output, inner_ctx)?;
+            kv.write_in(layout, output, inner_ctx)?;
         }
         Ok(())
     }
@@ -1,3 +1,4 @@
+use crate::layout::{ReadLayout, WriteLayout};
 use crate::{DekuError, DekuRead, DekuWrite};
 use bitvec::prelude::*;
 
@@ -24,6 +25,18 @@ impl<'a, T: DekuRead<'a, Ctx>, Ctx: Copy> DekuRead<'a, Ctx> for Option<T> {
         let (rest, val) = <T>::read(input, inner_ctx)?;
         Ok((rest, Some(val)))
     }
+
+    fn read_in(
+        layout: &ReadLayout<'a>,
+        input: &'a BitSlice<u8, Msb0>,
+        inner_ctx: Ctx,
+    ) -> Result<(&'a BitSlice<u8, Msb0>, Self), DekuError>
+    where
+        Self: Sized,
+    {
+        let (rest, val) = <T>::read_in(layout, input, inner_ctx)?;
+        Ok((rest, Some(val)))
+    }
 }
 
 impl<T: DekuWrite<Ctx>, Ctx: Copy> DekuWrite<Ctx> for Option<T> {
@@ -41,4 +54,14 @@ impl<T: DekuWrite<Ctx>, Ctx: Copy> DekuWrite<Ctx> for Option<T> {
     fn write(&self, output: &mut BitVec<u8, Msb0>, inner_ctx: Ctx) -> Result<(), DekuError> {
         self.as_ref().map_or(Ok(()), |v| v.write(output, inner_ctx))
     }
+
+    fn write_in(
+        &self,
+        layout: &mut WriteLayout,
+        output: &mut BitVec<u8, Msb0>,
+        inner_ctx: Ctx,
+    ) -> Result<(), DekuError> {
+        self.as_ref()
+            .map_or(Ok(()), |v| v.write_in(layout, output, inner_ctx))
+    }
 }
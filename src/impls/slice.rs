@@ -5,6 +5,7 @@ use bitvec::prelude::*;
 pub use deku_derive::*;
 
 /// Read `u8`s and returns a byte slice up until a given predicate returns true
+/// * `exclusive` - don't read the value for which the predicate returned true, it is left in the rest
 /// * `ctx` - The context required by `u8`. It will be passed to every `u8` when constructing.
 /// * `predicate` - the predicate that decides when to stop reading `u8`s
 /// The predicate takes two parameters: the number of bits that have been read so far,
@@ -12,6 +13,7 @@ pub use deku_derive::*;
 /// should now stop, and `false` otherwise
 fn read_slice_with_predicate<'a, Ctx: Copy, Predicate: FnMut(usize, &u8) -> bool>(
     input: &'a BitSlice<u8, Msb0>,
+    exclusive: bool,
     ctx: Ctx,
     mut predicate: Predicate,
 ) -> Result<(&'a BitSlice<u8, Msb0>, &[u8]), DekuError>
@@ -19,21 +21,23 @@ where
     u8: DekuRead<'a, Ctx>,
 {
     let mut rest = input;
-    let mut value;
 
     loop {
         let (new_rest, val) = u8::read(rest, ctx)?;
-        rest = new_rest;
-
-        let read_idx = unsafe { rest.as_bitptr().offset_from(input.as_bitptr()) } as usize;
-        value = input[..read_idx].domain().region().unwrap().1;
 
+        let read_idx = unsafe { new_rest.as_bitptr().offset_from(input.as_bitptr()) } as usize;
         if predicate(read_idx, &val) {
+            // with `exclusive`, the value is left in the rest
+            if !exclusive {
+                rest = new_rest;
+            }
             break;
         }
+        rest = new_rest;
     }
 
-    Ok((rest, value))
+    let read_idx = unsafe { rest.as_bitptr().offset_from(input.as_bitptr()) } as usize;
+    Ok((rest, input[..read_idx].domain().region().unwrap().1))
 }
 
 impl<'a, Ctx: Copy, Predicate: FnMut(&u8) -> bool> DekuRead<'a, (Limit<u8, Predicate>, Ctx)>
@@ -67,7 +71,7 @@ where
                 }
 
                 // Otherwise, read until we have read `count` elements
-                read_slice_with_predicate(input, inner_ctx, move |_, _| {
+                read_slice_with_predicate(input, false, inner_ctx, move |_, _| {
                     count -= 1;
                     count == 0
                 })
@@ -75,7 +79,12 @@ where
 
             // Read until a given predicate returns true
             Limit::Until(mut predicate, _) => {
-                read_slice_with_predicate(input, inner_ctx, move |_, value| predicate(value))
+                read_slice_with_predicate(input, false, inner_ctx, move |_, value| predicate(value))
+            }
+
+            // Read until a given predicate returns true, leaving the last element in the rest
+            Limit::UntilExclusive(mut predicate, _) => {
+                read_slice_with_predicate(input, true, inner_ctx, move |_, value| predicate(value))
             }
 
             // Read until a given quantity of bits have been read
@@ -87,7 +96,7 @@ where
                     return Ok((input, &input.domain().region().unwrap().1[..0]));
                 }
 
-                read_slice_with_predicate(input, inner_ctx, move |read_bits, _| {
+                read_slice_with_predicate(input, false, inner_ctx, move |read_bits, _| {
                     read_bits == bit_size
                 })
             }
@@ -101,7 +110,7 @@ where
                     return Ok((input, &input.domain().region().unwrap().1[..0]));
                 }
 
-                read_slice_with_predicate(input, inner_ctx, move |read_bits, _| {
+                read_slice_with_predicate(input, false, inner_ctx, move |read_bits, _| {
                     read_bits == bit_size
                 })
             }
@@ -186,6 +195,7 @@ mod pre_const_generics_impl {
 #[cfg(feature = "const_generics")]
 mod const_generics_impl {
     use super::*;
+    use crate::layout::{self, ReadLayout, WriteLayout};
 
     use core::mem::MaybeUninit;
 
@@ -197,6 +207,17 @@ mod const_generics_impl {
             input: &'a BitSlice<u8, Msb0>,
             ctx: Ctx,
         ) -> Result<(&'a BitSlice<u8, Msb0>, Self), DekuError>
+        where
+            Self: Sized,
+        {
+            layout::read(input, ctx)
+        }
+
+        fn read_in(
+            layout: &ReadLayout<'a>,
+            input: &'a BitSlice<u8, Msb0>,
+            ctx: Ctx,
+        ) -> Result<(&'a BitSlice<u8, Msb0>, Self), DekuError>
         where
             Self: Sized,
         {
@@ -206,7 +227,7 @@ mod const_generics_impl {
             let mut slice: [MaybeUninit<T>; N] = unsafe { MaybeUninit::uninit().assume_init() };
             let mut rest = input;
             for (n, item) in slice.iter_mut().enumerate() {
-                let (new_rest, value) = match T::read(rest, ctx) {
+                let (new_rest, value) = match T::read_in(layout, rest, ctx) {
                     Ok(it) => it,
                     Err(err) => {
                         // For each item in the array, drop if we allocated it.
@@ -234,8 +255,17 @@ mod const_generics_impl {
         T: DekuWrite<Ctx>,
     {
         fn write(&self, output: &mut BitVec<u8, Msb0>, ctx: Ctx) -> Result<(), DekuError> {
+            layout::write(self, output, ctx)
+        }
+
+        fn write_in(
+            &self,
+            layout: &mut WriteLayout,
+            output: &mut BitVec<u8, Msb0>,
+            ctx: Ctx,
+        ) -> Result<(), DekuError> {
             for v in self {
-                v.write(output, ctx)?;
+                v.write_in(layout, output, ctx)?;
             }
             Ok(())
         }
@@ -246,8 +276,17 @@ mod const_generics_impl {
         T: DekuWrite<Ctx>,
     {
         fn write(&self, output: &mut BitVec<u8, Msb0>, ctx: Ctx) -> Result<(), DekuError> {
+            layout::write(self, output, ctx)
+        }
+
+        fn write_in(
+            &self,
+            layout: &mut WriteLayout,
+            output: &mut BitVec<u8, Msb0>,
+            ctx: Ctx,
+        ) -> Result<(), DekuError> {
             for v in *self {
-                v.write(output, ctx)?;
+                v.write_in(layout, output, ctx)?;
             }
             Ok(())
         }
@@ -278,6 +317,23 @@ mod tests {
         assert_eq!(expected_rest, rest);
     }
 
+    #[rstest(input,limit,expected,expected_rest,
+        case::until([0xAA, 0, 0xBB].as_ref(), (|v: &u8| *v == 0u8).into(), [0xAA, 0].as_ref(), bits![u8, Msb0; 1, 0, 1, 1, 1, 0, 1, 1]),
+        case::until_exclusive([0xAA, 0, 0xBB].as_ref(), Limit::new_until_exclusive(|v: &u8| *v == 0u8), [0xAA].as_ref(), bits![u8, Msb0; 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 1, 1, 1, 0, 1, 1]),
+    )]
+    fn test_slice_read_limit<Predicate: FnMut(&u8) -> bool>(
+        input: &[u8],
+        limit: Limit<u8, Predicate>,
+        expected: &[u8],
+        expected_rest: &BitSlice<u8, Msb0>,
+    ) {
+        let bit_slice = input.view_bits::<Msb0>();
+
+        let (rest, res_read) = <&[u8]>::read(bit_slice, (limit, ())).unwrap();
+        assert_eq!(expected, res_read);
+        assert_eq!(expected_rest, rest);
+    }
+
     #[rstest(input,endian,expected,
         case::normal_le([0xDDCC, 0xBBAA], Endian::Little, vec![0xCC, 0xDD, 0xAA, 0xBB]),
         case::normal_be([0xDDCC, 0xBBAA], Endian::Big, vec![0xDD, 0xCC, 0xBB, 0xAA]),
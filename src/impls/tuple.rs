@@ -1,5 +1,6 @@
 //! Implementations of DekuRead and DekuWrite for tuples of length 1 to 11
 
+use crate::layout::{self, ReadLayout, WriteLayout};
 use crate::{DekuError, DekuRead, DekuWrite};
 use bitvec::prelude::*;
 
@@ -40,13 +41,24 @@ macro_rules! ImplDekuTupleTraits {
                 input: &'a BitSlice<u8, Msb0>,
                 ctx: Ctx,
             ) -> Result<(&'a BitSlice<u8, Msb0>, Self), DekuError>
+            where
+                Self: Sized,
+            {
+                layout::read(input, ctx)
+            }
+
+            fn read_in(
+                layout: &ReadLayout<'a>,
+                input: &'a BitSlice<u8, Msb0>,
+                ctx: Ctx,
+            ) -> Result<(&'a BitSlice<u8, Msb0>, Self), DekuError>
             where
                 Self: Sized,
             {
                 let tuple = ();
                 let mut rest = input;
                 $(
-                    let read = <$T>::read(rest, ctx)?;
+                    let read = <$T>::read_in(layout, rest, ctx)?;
                     rest = read.0;
                     let tuple = tuple.append(read.1);
                 )+
@@ -56,11 +68,15 @@ macro_rules! ImplDekuTupleTraits {
 
         impl<Ctx: Copy, $($T:DekuWrite<Ctx>),+> DekuWrite<Ctx> for ($($T,)+)
         {
-            #[allow(non_snake_case)]
             fn write(&self, output: &mut BitVec<u8, Msb0>, ctx: Ctx) -> Result<(), DekuError> {
+                layout::write(self, output, ctx)
+            }
+
+            #[allow(non_snake_case)]
+            fn write_in(&self, layout: &mut WriteLayout, output: &mut BitVec<u8, Msb0>, ctx: Ctx) -> Result<(), DekuError> {
                 let ($(ref $T,)+) = *self;
                 $(
-                    $T.write(output, ctx)?;
+                    $T.write_in(layout, output, ctx)?;
                 )+
                 Ok(())
             }
@@ -1,3 +1,4 @@
+use crate::layout::{self, ReadLayout, WriteLayout};
 use crate::{ctx::*, DekuError, DekuRead, DekuWrite};
 use bitvec::prelude::*;
 
@@ -5,7 +6,9 @@ use bitvec::prelude::*;
 use alloc::vec::Vec;
 
 /// Read `T`s into a vec until a given predicate returns true
+/// * `layout` - the layout of the outermost input, passed to every `T`
 /// * `capacity` - an optional capacity to pre-allocate the vector with
+/// * `exclusive` - don't read the value for which the predicate returned true, it is left in the rest
 /// * `ctx` - The context required by `T`. It will be passed to every `T` when constructing.
 /// * `predicate` - the predicate that decides when to stop reading `T`s
 /// The predicate takes two parameters: the number of bits that have been read so far,
@@ -17,8 +20,10 @@ fn read_vec_with_predicate<
     Ctx: Copy,
     Predicate: FnMut(usize, &T) -> bool,
 >(
+    layout: &ReadLayout<'a>,
     input: &'a BitSlice<u8, Msb0>,
     capacity: Option<usize>,
+    exclusive: bool,
     ctx: Ctx,
     mut predicate: Predicate,
 ) -> Result<(&'a BitSlice<u8, Msb0>, Vec<T>), DekuError> {
@@ -27,16 +32,19 @@ fn read_vec_with_predicate<
     let mut rest = input;
 
     loop {
-        let (new_rest, val) = <T>::read(rest, ctx)?;
+        let (new_rest, val) = <T>::read_in(layout, rest, ctx)?;
+        let found_predicate = predicate(
+            unsafe { new_rest.as_bitptr().offset_from(input.as_bitptr()) } as usize,
+            &val,
+        );
+        if exclusive && found_predicate {
+            // the value is left in the rest
+            break;
+        }
         res.push(val);
         rest = new_rest;
 
-        // This unwrap is safe as we are pushing to the vec immediately before it,
-        // so there will always be a last element
-        if predicate(
-            unsafe { rest.as_bitptr().offset_from(input.as_bitptr()) } as usize,
-            res.last().unwrap(),
-        ) {
+        if found_predicate {
             break;
         }
     }
@@ -61,6 +69,17 @@ impl<'a, T: DekuRead<'a, Ctx>, Ctx: Copy, Predicate: FnMut(&T) -> bool>
     /// assert_eq!(vec![0x04030201], v)
     /// ```
     fn read(
+        input: &'a BitSlice<u8, Msb0>,
+        ctx: (Limit<T, Predicate>, Ctx),
+    ) -> Result<(&'a BitSlice<u8, Msb0>, Self), DekuError>
+    where
+        Self: Sized,
+    {
+        layout::read(input, ctx)
+    }
+
+    fn read_in(
+        layout: &ReadLayout<'a>,
         input: &'a BitSlice<u8, Msb0>,
         (limit, inner_ctx): (Limit<T, Predicate>, Ctx),
     ) -> Result<(&'a BitSlice<u8, Msb0>, Self), DekuError>
@@ -76,15 +95,31 @@ impl<'a, T: DekuRead<'a, Ctx>, Ctx: Copy, Predicate: FnMut(&T) -> bool>
                 }
 
                 // Otherwise, read until we have read `count` elements
-                read_vec_with_predicate(input, Some(count), inner_ctx, move |_, _| {
-                    count -= 1;
-                    count == 0
-                })
+                read_vec_with_predicate(
+                    layout,
+                    input,
+                    Some(count),
+                    false,
+                    inner_ctx,
+                    move |_, _| {
+                        count -= 1;
+                        count == 0
+                    },
+                )
             }
 
             // Read until a given predicate returns true
             Limit::Until(mut predicate, _) => {
-                read_vec_with_predicate(input, None, inner_ctx, move |_, value| predicate(value))
+                read_vec_with_predicate(layout, input, None, false, inner_ctx, move |_, value| {
+                    predicate(value)
+                })
+            }
+
+            // Read until a given predicate returns true, leaving the last element in the rest
+            Limit::UntilExclusive(mut predicate, _) => {
+                read_vec_with_predicate(layout, input, None, true, inner_ctx, move |_, value| {
+                    predicate(value)
+                })
             }
 
             // Read until a given quantity of bits have been read
@@ -96,9 +131,14 @@ impl<'a, T: DekuRead<'a, Ctx>, Ctx: Copy, Predicate: FnMut(&T) -> bool>
                     return Ok((input, Vec::new()));
                 }
 
-                read_vec_with_predicate(input, None, inner_ctx, move |read_bits, _| {
-                    read_bits == bit_size
-                })
+                read_vec_with_predicate(
+                    layout,
+                    input,
+                    None,
+                    false,
+                    inner_ctx,
+                    move |read_bits, _| read_bits == bit_size,
+                )
             }
 
             // Read until a given quantity of bits have been read
@@ -110,9 +150,14 @@ impl<'a, T: DekuRead<'a, Ctx>, Ctx: Copy, Predicate: FnMut(&T) -> bool>
                     return Ok((input, Vec::new()));
                 }
 
-                read_vec_with_predicate(input, None, inner_ctx, move |read_bits, _| {
-                    read_bits == bit_size
-                })
+                read_vec_with_predicate(
+                    layout,
+                    input,
+                    None,
+                    false,
+                    inner_ctx,
+                    move |read_bits, _| read_bits == bit_size,
+                )
             }
         }
     }
@@ -131,6 +176,17 @@ impl<'a, T: DekuRead<'a>, Predicate: FnMut(&T) -> bool> DekuRead<'a, Limit<T, Pr
     {
         Vec::read(input, (limit, ()))
     }
+
+    fn read_in(
+        layout: &ReadLayout<'a>,
+        input: &'a BitSlice<u8, Msb0>,
+        limit: Limit<T, Predicate>,
+    ) -> Result<(&'a BitSlice<u8, Msb0>, Self), DekuError>
+    where
+        Self: Sized,
+    {
+        Vec::read_in(layout, input, (limit, ()))
+    }
 }
 
 impl<T: DekuWrite<Ctx>, Ctx: Copy> DekuWrite<Ctx> for Vec<T> {
@@ -146,8 +202,17 @@ impl<T: DekuWrite<Ctx>, Ctx: Copy> DekuWrite<Ctx> for Vec<T> {
     /// assert_eq!(output, bitvec![u8, Msb0; 0, 0, 0, 0, 0, 0, 0, 1])
     /// ```
     fn write(&self, output: &mut BitVec<u8, Msb0>, inner_ctx: Ctx) -> Result<(), DekuError> {
+        layout::write(self, output, inner_ctx)
+    }
+
+    fn write_in(
+        &self,
+        layout: &mut WriteLayout,
+        output: &mut BitVec<u8, Msb0>,
+        inner_ctx: Ctx,
+    ) -> Result<(), DekuError> {
         for v in self {
-            v.write(output, inner_ctx)?;
+            v.write_in(layout, output, inner_ctx)?;
         }
         Ok(())
     }
@@ -164,6 +229,9 @@ mod tests {
         case::count_2([0xAA, 0xBB, 0xCC].as_ref(), Endian::Little, Some(8), 2.into(), vec![0xAA, 0xBB], bits![u8, Msb0; 1, 1, 0, 0, 1, 1, 0, 0]),
         case::until_null([0xAA, 0, 0xBB].as_ref(), Endian::Little, None, (|v: &u8| *v == 0u8).into(), vec![0xAA, 0], bits![u8, Msb0; 1, 0, 1, 1, 1, 0, 1, 1]),
         case::until_bits([0xAA, 0xBB].as_ref(), Endian::Little, None, BitSize(8).into(), vec![0xAA], bits![u8, Msb0; 1, 0, 1, 1, 1, 0, 1, 1]),
+        case::until_exclusive([0xAA, 0, 0xBB].as_ref(), Endian::Little, None, Limit::new_until_exclusive(|v: &u8| *v == 0u8), vec![0xAA], bits![u8, Msb0; 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 1, 1, 1, 0, 1, 1]),
+        case::terminator([0xAA, 0, 0xBB].as_ref(), Endian::Little, None, Limit::new_terminator(0u8), vec![0xAA], bits![u8, Msb0; 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 1, 1, 1, 0, 1, 1]),
+        case::terminator_empty([0, 0xBB].as_ref(), Endian::Little, None, Limit::new_terminator(0u8), vec![], bits![u8, Msb0; 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 1, 1, 1, 0, 1, 1]),
         case::bits_6([0b0110_1001, 0b1110_1001].as_ref(), Endian::Little, Some(6), 2.into(), vec![0b00_011010, 0b00_011110], bits![u8, Msb0; 1, 0, 0, 1]),
         #[should_panic(expected = "Parse(\"too much data: container of 8 bits cannot hold 9 bits\")")]
         case::not_enough_data([].as_ref(), Endian::Little, Some(9), 1.into(), vec![], bits![u8, Msb0;]),
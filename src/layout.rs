@@ -0,0 +1,309 @@
+//! Layout of the outermost input and output
+//!
+//! [at](crate::attributes#at) offsets are relative to the whole input, not to the
+//! struct/enum declaring the field. The layout is passed on to nested types with
+//! [DekuRead::read_in] and [DekuWrite::write_in], [read] and [write] start a new one.
+//!
+//! When writing, the data of the fields with `at` is laid out after all the other data and
+//! the fields holding their offsets are filled in once the layout is known.
+
+#![cfg(feature = "alloc")]
+
+use crate::{DekuError, DekuRead, DekuWrite};
+use alloc::{boxed::Box, format, vec::Vec};
+use bitvec::prelude::*;
+use core::cell::Cell;
+
+/// Read `T` from `input` as the outermost input, including the data read at an offset
+///
+/// The rest starts after both the sequential data and the data of the fields with `at`.
+pub fn read<'a, T, Ctx>(
+    input: &'a BitSlice<u8, Msb0>,
+    ctx: Ctx,
+) -> Result<(&'a BitSlice<u8, Msb0>, T), DekuError>
+where
+    T: DekuRead<'a, Ctx>,
+{
+    let layout = ReadLayout::new(input);
+    let (rest, value) = T::read_in(&layout, input, ctx)?;
+    Ok((layout.rest(rest), value))
+}
+
+/// Write `value` to `output` as the outermost output, laying out the data written at an offset
+pub fn write<T, Ctx>(value: &T, output: &mut BitVec<u8, Msb0>, ctx: Ctx) -> Result<(), DekuError>
+where
+    T: DekuWrite<Ctx> + ?Sized,
+{
+    let mut layout = WriteLayout::new(output);
+    value.write_in(&mut layout, output, ctx)?;
+    layout.finish(output)
+}
+
+/// Layout of the outermost input of a read
+#[derive(Debug)]
+pub struct ReadLayout<'a> {
+    input: &'a BitSlice<u8, Msb0>,
+    /// Start of the data of the field with `at` being read
+    base: Cell<usize>,
+    /// End of the data read at an offset
+    end: Cell<usize>,
+}
+
+impl<'a> ReadLayout<'a> {
+    /// Layout of `input`, the outermost input
+    pub fn new(input: &'a BitSlice<u8, Msb0>) -> Self {
+        Self {
+            input,
+            base: Cell::new(0),
+            end: Cell::new(0),
+        }
+    }
+
+    /// The outermost input
+    pub fn input(&self) -> &'a BitSlice<u8, Msb0> {
+        self.input
+    }
+
+    /// Bit offset of `rest` from the start of the outermost input, or from the start of the
+    /// data of the field with `at` being read
+    pub fn offset(&self, rest: &'a BitSlice<u8, Msb0>) -> Result<usize, DekuError> {
+        let range = self.input.as_bitptr_range();
+        if !range.contains(&rest.as_bitptr()) && rest.as_bitptr() != range.end {
+            return Err(DekuError::InvalidParam(
+                "input is not a part of the outermost input".into(),
+            ));
+        }
+        // SAFETY: `rest` points within the outermost input
+        let offset = unsafe { rest.as_bitptr().offset_from(self.input.as_bitptr()) };
+        Ok(offset as usize - self.base.get())
+    }
+
+    /// The rest of the outermost input after `rest`, the sequential rest, and after the data
+    /// read at an offset
+    pub fn rest(&self, rest: &'a BitSlice<u8, Msb0>) -> &'a BitSlice<u8, Msb0> {
+        let read = self.input.len() - rest.len();
+        if self.end.get() > read {
+            &self.input[self.end.get()..]
+        } else {
+            rest
+        }
+    }
+
+    /// Start reading the data of a field with `at` at `offset` bits, returns the previous start
+    #[doc(hidden)]
+    pub fn enter(&self, offset: usize) -> usize {
+        self.base.replace(offset)
+    }
+
+    /// Done reading the data of a field with `at` up to `rest`, restoring the previous start
+    #[doc(hidden)]
+    pub fn leave(&self, base: usize, rest: &'a BitSlice<u8, Msb0>) {
+        self.base.set(base);
+        let end = self.input.len() - rest.len();
+        if end > self.end.get() {
+            self.end.set(end);
+        }
+    }
+}
+
+/// Where the data of a field with `at` is written
+#[doc(hidden)]
+pub enum AtOffset {
+    /// At a bit offset of the outermost output
+    Bits(usize),
+    /// After the output, the offset is written to the offset field
+    Field(OffsetField),
+}
+
+/// Writes the offset of the data it references, in bytes
+type OffsetWrite = Box<dyn FnOnce(usize, &mut BitVec<u8, Msb0>) -> Result<(), DekuError>>;
+
+/// A field holding the offset of the data of a field with `at`, filled in once laid out
+#[doc(hidden)]
+pub struct OffsetField {
+    /// Data of the field with `at` containing the offset field, `None` for the output
+    parent: Option<usize>,
+    position: usize,
+    len: usize,
+    write: OffsetWrite,
+}
+
+/// Data of a field with `at`
+struct Entry {
+    name: &'static str,
+    offset: Option<usize>,
+    data: BitVec<u8, Msb0>,
+}
+
+/// Layout of the outermost output of a write
+pub struct WriteLayout {
+    /// Start of the outermost output in the output
+    start: usize,
+    entries: Vec<Entry>,
+    fields: Vec<(usize, OffsetField)>,
+    /// Data of the field with `at` being written, `None` for the output
+    current: Option<usize>,
+}
+
+impl WriteLayout {
+    /// Layout of the output written after the current end of `output`
+    pub fn new(output: &BitVec<u8, Msb0>) -> Self {
+        Self {
+            start: output.len(),
+            entries: Vec::new(),
+            fields: Vec::new(),
+            current: None,
+        }
+    }
+
+    /// Bit position of the end of `output` from the start of the outermost output, or from
+    /// the start of the data of the field with `at` being written
+    pub fn position(&self, output: &BitVec<u8, Msb0>) -> usize {
+        match self.current {
+            Some(_) => output.len(),
+            None => output.len() - self.start,
+        }
+    }
+
+    /// The offset field written from `position` to the end of `output`
+    #[doc(hidden)]
+    pub fn offset_field<F>(
+        &self,
+        position: usize,
+        output: &BitVec<u8, Msb0>,
+        write: F,
+    ) -> OffsetField
+    where
+        F: FnOnce(usize, &mut BitVec<u8, Msb0>) -> Result<(), DekuError> + 'static,
+    {
+        OffsetField {
+            parent: self.current,
+            position,
+            len: self.position(output) - position,
+            write: Box::new(write),
+        }
+    }
+
+    /// Write the data of the field `name` with `write`, laid out once the output is written
+    #[doc(hidden)]
+    pub fn write_at<F>(
+        &mut self,
+        name: &'static str,
+        offset: AtOffset,
+        write: F,
+    ) -> Result<(), DekuError>
+    where
+        F: FnOnce(&mut Self, &mut BitVec<u8, Msb0>) -> Result<(), DekuError>,
+    {
+        let index = self.entries.len();
+        let (offset, field) = match offset {
+            AtOffset::Bits(bits) => (Some(bits), None),
+            AtOffset::Field(field) => (None, Some(field)),
+        };
+        self.entries.push(Entry {
+            name,
+            offset,
+            data: BitVec::new(),
+        });
+        if let Some(field) = field {
+            self.fields.push((index, field));
+        }
+
+        let mut data = BitVec::new();
+        let parent = self.current.replace(index);
+        write(self, &mut data)?;
+        self.current = parent;
+        self.entries[index].data = data;
+
+        Ok(())
+    }
+
+    /// Lay out the data of the fields with `at` after `output` and fill in their offsets
+    pub fn finish(&mut self, output: &mut BitVec<u8, Msb0>) -> Result<(), DekuError> {
+        let mut entries = core::mem::take(&mut self.entries);
+        let fields = core::mem::take(&mut self.fields);
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        // the data at a given offset can't overlap the other fields
+        let end = output.len() - self.start;
+        let mut positions = Vec::with_capacity(entries.len());
+        let mut next = end;
+        for entry in &entries {
+            if let Some(offset) = entry.offset {
+                if offset < end && !entry.data.is_empty() {
+                    return Err(DekuError::InvalidParam(format!(
+                        "{} field offset overlaps the preceding fields: {} of {} bits",
+                        entry.name, offset, end
+                    )));
+                }
+                next = next.max(offset + entry.data.len());
+            }
+            positions.push(entry.offset);
+        }
+
+        // the other data is laid out in order after it, at byte offsets
+        let positions: Vec<usize> = positions
+            .into_iter()
+            .zip(&entries)
+            .map(|(offset, entry)| {
+                offset.unwrap_or_else(|| {
+                    let position = (next + 7) / 8 * 8;
+                    next = position + entry.data.len();
+                    position
+                })
+            })
+            .collect();
+
+        for (i, entry) in entries.iter().enumerate() {
+            for (j, other) in entries.iter().enumerate().skip(i + 1) {
+                if entry.data.is_empty() || other.data.is_empty() {
+                    continue;
+                }
+                if positions[i] < positions[j] + other.data.len()
+                    && positions[j] < positions[i] + entry.data.len()
+                {
+                    return Err(DekuError::InvalidParam(format!(
+                        "{} field offset overlaps the {} field: {} of {}..{} bits",
+                        other.name,
+                        entry.name,
+                        positions[j],
+                        positions[i],
+                        positions[i] + entry.data.len()
+                    )));
+                }
+            }
+        }
+
+        for (index, field) in fields {
+            let mut value = BitVec::new();
+            (field.write)(positions[index] / 8, &mut value)?;
+            if value.len() != field.len {
+                return Err(DekuError::InvalidParam(format!(
+                    "{} field offset {} changes the size of its offset field",
+                    entries[index].name,
+                    positions[index] / 8
+                )));
+            }
+            let target = match field.parent {
+                Some(parent) => &mut entries[parent].data[field.position..],
+                None => &mut output[self.start + field.position..],
+            };
+            target[..field.len].copy_from_bitslice(&value);
+        }
+
+        let len = positions
+            .iter()
+            .zip(&entries)
+            .map(|(position, entry)| position + entry.data.len())
+            .fold(end, usize::max);
+        output.resize(self.start + len, false);
+        for (position, entry) in positions.iter().zip(&entries) {
+            let position = self.start + position;
+            output[position..position + entry.data.len()].copy_from_bitslice(&entry.data);
+        }
+
+        Ok(())
+    }
+}
@@ -215,6 +215,13 @@ assert_eq!(value.a, 0x01);
 assert_eq!(value.sub.b, 0x01 + 0x02)
 ```
 
+# Schema
+
+Deriving `DekuSchema` provides a static description of the layout of a type,
+see the [schema](crate::schema) module. With the `ksy`, `wireshark` and `c_header`
+features, a schema can be exported as a Kaitai Struct description, a Wireshark Lua
+dissector or a C header.
+
 # Internal variables and previously read fields
 
 Along similar lines to [Context](#context) variables, previously read variables
@@ -280,11 +287,24 @@ pub mod bitvec {
 
 pub use deku_derive::*;
 
+// the derive macros refer to the crate as `deku`, also within the crate
+extern crate self as deku;
+
+#[cfg(feature = "asn1")]
+pub mod asn1;
 pub mod attributes;
 pub mod ctx;
+#[cfg(feature = "dns")]
+pub mod dns;
 pub mod error;
+#[cfg(feature = "pcap")]
+pub mod formats;
 mod impls;
+pub mod layout;
 pub mod prelude;
+#[cfg(feature = "protocols")]
+pub mod protocols;
+pub mod schema;
 
 pub use crate::error::DekuError;
 
@@ -302,6 +322,25 @@ pub trait DekuRead<'a, Ctx = ()> {
     ) -> Result<(&'a bitvec::BitSlice<u8, bitvec::Msb0>, Self), DekuError>
     where
         Self: Sized;
+
+    /// Read bits and construct type, as a part of a larger input
+    /// * **layout** - Layout of the outermost input, which `input` is a part of
+    /// * **input** - Input as bits
+    /// * **ctx** - A context required by context-sensitive reading
+    ///
+    /// [at](attributes#at) offsets are relative to the outermost input. Types reading other
+    /// types pass the layout on, by default the type is only read.
+    fn read_in(
+        layout: &layout::ReadLayout<'a>,
+        input: &'a bitvec::BitSlice<u8, bitvec::Msb0>,
+        ctx: Ctx,
+    ) -> Result<(&'a bitvec::BitSlice<u8, bitvec::Msb0>, Self), DekuError>
+    where
+        Self: Sized,
+    {
+        let _ = layout;
+        Self::read(input, ctx)
+    }
 }
 
 /// "Reader" trait: implemented on DekuRead struct and enum containers. A `container` is a type which
@@ -327,6 +366,23 @@ pub trait DekuWrite<Ctx = ()> {
         output: &mut bitvec::BitVec<u8, bitvec::Msb0>,
         ctx: Ctx,
     ) -> Result<(), DekuError>;
+
+    /// Write type to bits, as a part of a larger output
+    /// * **layout** - Layout of the outermost output, which `output` is a part of
+    /// * **output** - Sink to store resulting bits
+    /// * **ctx** - A context required by context-sensitive writing
+    ///
+    /// The data of [at](attributes#at) fields is laid out after the outermost output. Types
+    /// writing other types pass the layout on, by default the type is only written.
+    fn write_in(
+        &self,
+        layout: &mut layout::WriteLayout,
+        output: &mut bitvec::BitVec<u8, bitvec::Msb0>,
+        ctx: Ctx,
+    ) -> Result<(), DekuError> {
+        let _ = layout;
+        self.write(output, ctx)
+    }
 }
 
 /// "Writer" trait: implemented on DekuWrite struct and enum containers. A `container` is a type which
@@ -351,6 +407,12 @@ pub trait DekuEnumExt<'a, T> {
     fn deku_id(&self) -> Result<T, DekuError>;
 }
 
+/// "Schema" trait: static description of a type's layout
+pub trait DekuSchema {
+    /// Layout of the type as described by its `#[deku]` attributes
+    const SCHEMA: schema::Schema;
+}
+
 /// Implements DekuWrite for references of types that implement DekuWrite
 impl<T, Ctx> DekuWrite<Ctx> for &T
 where
@@ -366,6 +428,15 @@ where
         <T>::write(self, output, ctx)?;
         Ok(())
     }
+
+    fn write_in(
+        &self,
+        layout: &mut layout::WriteLayout,
+        output: &mut bitvec::BitVec<u8, bitvec::Msb0>,
+        ctx: Ctx,
+    ) -> Result<(), DekuError> {
+        <T>::write_in(self, layout, output, ctx)
+    }
 }
 
 #[cfg(test)]
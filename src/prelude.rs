@@ -4,5 +4,5 @@
 */
 pub use crate::{
     deku_derive, error::DekuError, error::NeedSize, DekuContainerRead, DekuContainerWrite,
-    DekuEnumExt, DekuRead, DekuUpdate, DekuWrite,
+    DekuEnumExt, DekuRead, DekuSchema, DekuUpdate, DekuWrite,
 };
@@ -0,0 +1,158 @@
+//! Internet checksum (RFC 1071) and the pseudo-headers of UDP, TCP and ICMPv6
+
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+/// Incremental computation of the Internet checksum
+///
+/// The ones' complement sum of the data, as 16-bit big-endian words, can be fed in
+/// chunks of any length.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Checksum {
+    sum: u64,
+    odd: Option<u8>,
+}
+
+impl Checksum {
+    /// Start a checksum
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add bytes to the checksum
+    pub fn add_bytes(&mut self, data: &[u8]) -> &mut Self {
+        let mut data = data;
+        if let (Some(high), Some((low, rest))) = (self.odd, data.split_first()) {
+            self.sum += u64::from(u16::from_be_bytes([high, *low]));
+            self.odd = None;
+            data = rest;
+        }
+
+        let mut words = data.chunks_exact(2);
+        for word in &mut words {
+            self.sum += u64::from(u16::from_be_bytes([word[0], word[1]]));
+        }
+        if let [last] = words.remainder() {
+            self.odd = Some(*last);
+        }
+        self
+    }
+
+    /// Add a pseudo-header, for a segment of `length` bytes
+    pub fn add_pseudo_header(&mut self, pseudo_header: &PseudoHeader, length: usize) -> &mut Self {
+        match pseudo_header {
+            PseudoHeader::V4 { src, dst, protocol } => self
+                .add_bytes(&src.octets())
+                .add_bytes(&dst.octets())
+                .add_bytes(&[0, *protocol])
+                .add_bytes(&(length as u16).to_be_bytes()),
+            PseudoHeader::V6 {
+                src,
+                dst,
+                next_header,
+            } => self
+                .add_bytes(&src.octets())
+                .add_bytes(&dst.octets())
+                .add_bytes(&(length as u32).to_be_bytes())
+                .add_bytes(&[0, 0, 0, *next_header]),
+        }
+    }
+
+    /// Ones' complement of the sum, the value of a checksum field
+    ///
+    /// An odd byte at the end is padded with zero.
+    pub fn finish(&self) -> u16 {
+        let mut sum = self.sum;
+        if let Some(last) = self.odd {
+            sum += u64::from(last) << 8;
+        }
+        while sum > 0xFFFF {
+            sum = (sum & 0xFFFF) + (sum >> 16);
+        }
+        !(sum as u16)
+    }
+}
+
+/// Internet checksum of `data`
+pub fn checksum(data: &[u8]) -> u16 {
+    Checksum::new().add_bytes(data).finish()
+}
+
+/// Addresses and upper-layer protocol covered by the checksum of a UDP, TCP or
+/// ICMPv6 header
+///
+/// The upper-layer length is the length of the checksummed segment, header and
+/// payload.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PseudoHeader {
+    /// IPv4 pseudo-header (RFC 793)
+    V4 {
+        /// Source address
+        src: Ipv4Addr,
+        /// Destination address
+        dst: Ipv4Addr,
+        /// Protocol of the segment, see [ip_protocol](super::ip_protocol)
+        protocol: u8,
+    },
+    /// IPv6 pseudo-header (RFC 8200)
+    V6 {
+        /// Source address
+        src: Ipv6Addr,
+        /// Final destination address
+        dst: Ipv6Addr,
+        /// Protocol of the segment, see [ip_protocol](super::ip_protocol)
+        next_header: u8,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hexlit::hex;
+    use rstest::rstest;
+
+    #[rstest(input, expected,
+        case(&[], 0xFFFF),
+        // RFC 1071 example, the sum is 0xDDF2
+        case(&hex!("0001 F203 F4F5 F6F7"), !0xDDF2),
+        case(&hex!("4500 0073 0000 4000 4011 0000 C0A8 0001 C0A8 00C7"), 0xB861),
+        case(&hex!("01"), !0x0100),
+        case(&hex!("FFFF FFFF"), 0x0000),
+    )]
+    fn test_checksum(input: &[u8], expected: u16) {
+        assert_eq!(expected, checksum(input));
+
+        // chunks of every length give the same result
+        for chunk_len in 1..=input.len() {
+            let mut sum = Checksum::new();
+            for chunk in input.chunks(chunk_len) {
+                sum.add_bytes(chunk);
+            }
+            assert_eq!(expected, sum.finish());
+        }
+    }
+
+    #[test]
+    fn test_pseudo_header() {
+        let v4 = PseudoHeader::V4 {
+            src: Ipv4Addr::new(192, 168, 0, 1),
+            dst: Ipv4Addr::new(192, 168, 0, 199),
+            protocol: 17,
+        };
+        assert_eq!(
+            checksum(&hex!("C0A8 0001 C0A8 00C7 0011 0010")),
+            Checksum::new().add_pseudo_header(&v4, 16).finish()
+        );
+
+        let v6 = PseudoHeader::V6 {
+            src: "2001:db8::1".parse().unwrap(),
+            dst: "2001:db8::2".parse().unwrap(),
+            next_header: 58,
+        };
+        assert_eq!(
+            checksum(&hex!(
+                "20010DB8 00000000 00000000 00000001 20010DB8 00000000 00000000 00000002 00000010 0000003A"
+            )),
+            Checksum::new().add_pseudo_header(&v6, 16).finish()
+        );
+    }
+}
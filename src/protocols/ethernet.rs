@@ -0,0 +1,157 @@
+//! Ethernet II header with IEEE 802.1Q VLAN tags
+
+use crate::bitvec::{BitSlice, Msb0};
+use crate::ctx::Endian;
+use crate::prelude::*;
+use core::fmt;
+use std::vec::Vec;
+
+/// EtherType of IPv4
+pub const ETHERTYPE_IPV4: u16 = 0x0800;
+/// EtherType of ARP
+pub const ETHERTYPE_ARP: u16 = 0x0806;
+/// EtherType of IPv6
+pub const ETHERTYPE_IPV6: u16 = 0x86DD;
+/// Tag protocol identifier of a 802.1Q VLAN tag
+pub const TPID_VLAN: u16 = 0x8100;
+/// Tag protocol identifier of a 802.1ad service VLAN tag
+pub const TPID_SERVICE_VLAN: u16 = 0x88A8;
+
+/// MAC address
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, DekuRead, DekuWrite)]
+#[deku(ctx = "_endian: Endian", ctx_default = "Endian::Big")]
+pub struct MacAddress(pub [u8; 6]);
+
+impl MacAddress {
+    /// Broadcast address, `ff:ff:ff:ff:ff:ff`
+    pub const BROADCAST: MacAddress = MacAddress([0xFF; 6]);
+}
+
+impl fmt::Display for MacAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [a, b, c, d, e, g] = self.0;
+        write!(f, "{a:02x}:{b:02x}:{c:02x}:{d:02x}:{e:02x}:{g:02x}")
+    }
+}
+
+/// VLAN tag, a tag protocol identifier and a tag control information
+#[derive(Debug, Copy, Clone, PartialEq, Eq, DekuRead, DekuWrite)]
+#[deku(ctx = "endian: Endian", ctx_default = "Endian::Big", endian = "endian")]
+pub struct VlanTag {
+    /// Tag protocol identifier, [TPID_VLAN] or [TPID_SERVICE_VLAN]
+    pub tpid: u16,
+    /// Priority code point
+    #[deku(bits = "3")]
+    pub pcp: u8,
+    /// Drop eligible indicator
+    #[deku(bits = "1")]
+    pub dei: bool,
+    /// VLAN identifier
+    #[deku(bits = "12")]
+    pub vid: u16,
+}
+
+impl VlanTag {
+    /// Create a 802.1Q tag of the given VLAN
+    pub fn new(vid: u16) -> Self {
+        Self {
+            tpid: TPID_VLAN,
+            pcp: 0,
+            dei: false,
+            vid,
+        }
+    }
+}
+
+/// Ethernet II header
+///
+/// VLAN tags are read while the EtherType is a tag protocol identifier.
+#[derive(Debug, Clone, PartialEq, Eq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct EthernetHeader {
+    /// Destination address
+    pub dst: MacAddress,
+    /// Source address
+    pub src: MacAddress,
+    /// VLAN tags, outermost first
+    #[deku(reader = "read_vlan_tags(deku::rest)")]
+    pub vlan_tags: Vec<VlanTag>,
+    /// Protocol of the payload, such as [ETHERTYPE_IPV4]
+    pub ethertype: u16,
+}
+
+fn read_vlan_tags(
+    mut rest: &BitSlice<u8, Msb0>,
+) -> Result<(&BitSlice<u8, Msb0>, Vec<VlanTag>), DekuError> {
+    let mut tags = Vec::new();
+    loop {
+        let (_, tpid) = u16::read(rest, Endian::Big)?;
+        if tpid != TPID_VLAN && tpid != TPID_SERVICE_VLAN {
+            return Ok((rest, tags));
+        }
+        let (new_rest, tag) = VlanTag::read(rest, Endian::Big)?;
+        rest = new_rest;
+        tags.push(tag);
+    }
+}
+
+impl EthernetHeader {
+    /// Create an untagged header
+    pub fn new(dst: MacAddress, src: MacAddress, ethertype: u16) -> Self {
+        Self {
+            dst,
+            src,
+            vlan_tags: Vec::new(),
+            ethertype,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hexlit::hex;
+    use rstest::rstest;
+    use std::convert::TryFrom;
+
+    #[rstest(input, expected,
+        case(
+            &hex!("FFFFFFFFFFFF 001122334455 0806"),
+            EthernetHeader::new(MacAddress::BROADCAST, MacAddress(hex!("001122334455")), ETHERTYPE_ARP)
+        ),
+        case(
+            &hex!("001122334455 66778899AABB 8100 A064 86DD"),
+            EthernetHeader {
+                vlan_tags: vec![VlanTag { tpid: TPID_VLAN, pcp: 5, dei: false, vid: 100 }],
+                ..EthernetHeader::new(MacAddress(hex!("001122334455")), MacAddress(hex!("66778899AABB")), ETHERTYPE_IPV6)
+            }
+        ),
+        case(
+            &hex!("001122334455 66778899AABB 88A8 100A 8100 0FFF 0800"),
+            EthernetHeader {
+                vlan_tags: vec![
+                    VlanTag { tpid: TPID_SERVICE_VLAN, pcp: 0, dei: true, vid: 10 },
+                    VlanTag::new(0xFFF),
+                ],
+                ..EthernetHeader::new(MacAddress(hex!("001122334455")), MacAddress(hex!("66778899AABB")), ETHERTYPE_IPV4)
+            }
+        ),
+
+        #[should_panic(expected = "Incomplete(NeedSize { bits: 16 })")]
+        case(&hex!("001122334455 66778899AABB 8100 0001"), EthernetHeader::new(MacAddress::BROADCAST, MacAddress::BROADCAST, 0)),
+    )]
+    fn test_ethernet(input: &[u8], expected: EthernetHeader) {
+        let header = EthernetHeader::try_from(input).unwrap();
+        assert_eq!(expected, header);
+
+        assert_eq!(input.to_vec(), header.to_bytes().unwrap());
+    }
+
+    #[test]
+    fn test_mac_address_display() {
+        assert_eq!(
+            "00:11:22:aa:bb:cc",
+            MacAddress(hex!("001122AABBCC")).to_string()
+        );
+    }
+}
@@ -0,0 +1,172 @@
+//! ICMP header (RFC 792)
+
+use super::segment_checksum;
+use crate::ctx::Endian;
+use crate::prelude::*;
+use std::net::Ipv4Addr;
+
+/// Type of an echo reply
+pub const ECHO_REPLY: u8 = 0;
+/// Type of a destination unreachable message
+pub const DESTINATION_UNREACHABLE: u8 = 3;
+/// Type of a redirect message
+pub const REDIRECT: u8 = 5;
+/// Type of an echo request
+pub const ECHO_REQUEST: u8 = 8;
+/// Type of a time exceeded message
+pub const TIME_EXCEEDED: u8 = 11;
+/// Type of a parameter problem message
+pub const PARAMETER_PROBLEM: u8 = 12;
+
+/// Rest of an ICMP header, identified by the type of the message
+#[derive(Debug, Copy, Clone, PartialEq, Eq, DekuRead, DekuWrite)]
+#[deku(
+    ctx = "endian: Endian, icmp_type: u8",
+    id = "icmp_type",
+    endian = "endian"
+)]
+pub enum IcmpBody {
+    /// Echo request or reply
+    #[deku(id_pat = "&ECHO_REPLY | &ECHO_REQUEST")]
+    Echo {
+        /// Identifier of the requests
+        identifier: u16,
+        /// Sequence number of the request
+        sequence: u16,
+    },
+    /// Destination unreachable
+    #[deku(id = "DESTINATION_UNREACHABLE")]
+    DestinationUnreachable {
+        /// Unused, 0
+        unused: u16,
+        /// MTU of the next hop, if the code is "fragmentation needed" (RFC 1191)
+        next_hop_mtu: u16,
+    },
+    /// Redirect
+    #[deku(id = "REDIRECT")]
+    Redirect {
+        /// Address of the gateway to use
+        gateway: Ipv4Addr,
+    },
+    /// Time exceeded
+    #[deku(id = "TIME_EXCEEDED")]
+    TimeExceeded {
+        /// Unused, 0
+        unused: u32,
+    },
+    /// Parameter problem
+    #[deku(id = "PARAMETER_PROBLEM")]
+    ParameterProblem {
+        /// Offset of the error in the original datagram
+        pointer: u8,
+        /// Unused, 0
+        unused: [u8; 3],
+    },
+    /// Message of another type
+    #[deku(id_pat = "_")]
+    Other([u8; 4]),
+}
+
+/// ICMP header
+///
+/// Error messages are followed by the header and the start of the original
+/// datagram, echo messages by their data.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct IcmpHeader {
+    /// Type of the message, such as [ECHO_REQUEST]
+    pub icmp_type: u8,
+    /// Code of the message, a subtype
+    pub code: u8,
+    /// Checksum of the header and payload
+    pub checksum: u16,
+    /// Rest of the header
+    #[deku(ctx = "*icmp_type")]
+    pub body: IcmpBody,
+}
+
+impl IcmpHeader {
+    /// Create an echo request, without checksum
+    pub fn echo_request(identifier: u16, sequence: u16) -> Self {
+        Self {
+            icmp_type: ECHO_REQUEST,
+            code: 0,
+            checksum: 0,
+            body: IcmpBody::Echo {
+                identifier,
+                sequence,
+            },
+        }
+    }
+
+    /// Create the reply to an echo request, without checksum
+    pub fn echo_reply(request: &IcmpHeader) -> Self {
+        Self {
+            icmp_type: ECHO_REPLY,
+            checksum: 0,
+            ..*request
+        }
+    }
+
+    /// Checksum of the message
+    pub fn compute_checksum(&self, payload: &[u8]) -> Result<u16, DekuError> {
+        let header = Self {
+            checksum: 0,
+            ..*self
+        };
+        Ok(segment_checksum(None, &header.to_bytes()?, payload))
+    }
+
+    /// Returns true if the checksum of the message is valid
+    pub fn verify_checksum(&self, payload: &[u8]) -> Result<bool, DekuError> {
+        Ok(self.compute_checksum(payload)? == self.checksum)
+    }
+
+    /// Set the checksum of the message
+    pub fn update_checksum(&mut self, payload: &[u8]) -> Result<(), DekuError> {
+        self.checksum = self.compute_checksum(payload)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hexlit::hex;
+    use rstest::rstest;
+    use std::convert::TryFrom;
+
+    #[rstest(input, expected,
+        case(&hex!("0800 0000 1234 0001"), IcmpBody::Echo { identifier: 0x1234, sequence: 1 }),
+        case(&hex!("0000 0000 1234 0001"), IcmpBody::Echo { identifier: 0x1234, sequence: 1 }),
+        case(&hex!("0304 0000 0000 05DC"), IcmpBody::DestinationUnreachable { unused: 0, next_hop_mtu: 1500 }),
+        case(&hex!("0501 0000 C0A80001"), IcmpBody::Redirect { gateway: Ipv4Addr::new(192, 168, 0, 1) }),
+        case(&hex!("0B00 0000 00000000"), IcmpBody::TimeExceeded { unused: 0 }),
+        case(&hex!("0C00 0000 14000000"), IcmpBody::ParameterProblem { pointer: 20, unused: [0; 3] }),
+        case(&hex!("0D00 0000 AABBCCDD"), IcmpBody::Other(hex!("AABBCCDD"))),
+    )]
+    fn test_icmp(input: &[u8], expected: IcmpBody) {
+        let header = IcmpHeader::try_from(input).unwrap();
+        assert_eq!(expected, header.body);
+
+        assert_eq!(input.to_vec(), header.to_bytes().unwrap());
+    }
+
+    #[test]
+    fn test_icmp_checksum() {
+        let payload = b"abcdefghijklmnopqrstuvwabcdefghi";
+        let mut request = IcmpHeader::echo_request(1, 0x21);
+        request.update_checksum(payload).unwrap();
+        assert_eq!(0x4D3A, request.checksum);
+        assert!(request.verify_checksum(payload).unwrap());
+
+        let mut reply = IcmpHeader::echo_reply(&request);
+        assert!(!reply.verify_checksum(payload).unwrap());
+        reply.update_checksum(payload).unwrap();
+        assert_eq!(0x553A, reply.checksum);
+        assert_eq!(
+            hex!("0000 553A 0001 0021").to_vec(),
+            reply.to_bytes().unwrap()
+        );
+    }
+}
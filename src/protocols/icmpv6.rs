@@ -0,0 +1,187 @@
+//! ICMPv6 header (RFC 4443)
+
+use super::checksum::PseudoHeader;
+use super::segment_checksum;
+use crate::ctx::Endian;
+use crate::prelude::*;
+
+/// Type of a destination unreachable message
+pub const DESTINATION_UNREACHABLE: u8 = 1;
+/// Type of a packet too big message
+pub const PACKET_TOO_BIG: u8 = 2;
+/// Type of a time exceeded message
+pub const TIME_EXCEEDED: u8 = 3;
+/// Type of a parameter problem message
+pub const PARAMETER_PROBLEM: u8 = 4;
+/// Type of an echo request
+pub const ECHO_REQUEST: u8 = 128;
+/// Type of an echo reply
+pub const ECHO_REPLY: u8 = 129;
+
+/// Rest of an ICMPv6 header, identified by the type of the message
+#[derive(Debug, Copy, Clone, PartialEq, Eq, DekuRead, DekuWrite)]
+#[deku(
+    ctx = "endian: Endian, icmp_type: u8",
+    id = "icmp_type",
+    endian = "endian"
+)]
+pub enum Icmpv6Body {
+    /// Destination unreachable
+    #[deku(id = "DESTINATION_UNREACHABLE")]
+    DestinationUnreachable {
+        /// Unused, 0
+        unused: u32,
+    },
+    /// Packet too big
+    #[deku(id = "PACKET_TOO_BIG")]
+    PacketTooBig {
+        /// MTU of the next hop
+        mtu: u32,
+    },
+    /// Time exceeded
+    #[deku(id = "TIME_EXCEEDED")]
+    TimeExceeded {
+        /// Unused, 0
+        unused: u32,
+    },
+    /// Parameter problem
+    #[deku(id = "PARAMETER_PROBLEM")]
+    ParameterProblem {
+        /// Offset of the error in the original packet
+        pointer: u32,
+    },
+    /// Echo request or reply
+    #[deku(id_pat = "&ECHO_REQUEST | &ECHO_REPLY")]
+    Echo {
+        /// Identifier of the requests
+        identifier: u16,
+        /// Sequence number of the request
+        sequence: u16,
+    },
+    /// Message of another type, such as the Neighbor Discovery messages
+    #[deku(id_pat = "_")]
+    Other([u8; 4]),
+}
+
+/// ICMPv6 header
+///
+/// Error messages are followed by the start of the original packet, echo messages
+/// by their data.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct Icmpv6Header {
+    /// Type of the message, such as [ECHO_REQUEST]
+    pub icmp_type: u8,
+    /// Code of the message, a subtype
+    pub code: u8,
+    /// Checksum of the pseudo-header, header and payload
+    pub checksum: u16,
+    /// Rest of the header
+    #[deku(ctx = "*icmp_type")]
+    pub body: Icmpv6Body,
+}
+
+impl Icmpv6Header {
+    /// Create an echo request, without checksum
+    pub fn echo_request(identifier: u16, sequence: u16) -> Self {
+        Self {
+            icmp_type: ECHO_REQUEST,
+            code: 0,
+            checksum: 0,
+            body: Icmpv6Body::Echo {
+                identifier,
+                sequence,
+            },
+        }
+    }
+
+    /// Create the reply to an echo request, without checksum
+    pub fn echo_reply(request: &Icmpv6Header) -> Self {
+        Self {
+            icmp_type: ECHO_REPLY,
+            checksum: 0,
+            ..*request
+        }
+    }
+
+    /// Returns true if the message is an error message
+    pub fn is_error(&self) -> bool {
+        self.icmp_type < 128
+    }
+
+    /// Checksum of the message
+    pub fn compute_checksum(
+        &self,
+        pseudo_header: &PseudoHeader,
+        payload: &[u8],
+    ) -> Result<u16, DekuError> {
+        let header = Self {
+            checksum: 0,
+            ..*self
+        };
+        Ok(segment_checksum(
+            Some(pseudo_header),
+            &header.to_bytes()?,
+            payload,
+        ))
+    }
+
+    /// Returns true if the checksum of the message is valid
+    pub fn verify_checksum(
+        &self,
+        pseudo_header: &PseudoHeader,
+        payload: &[u8],
+    ) -> Result<bool, DekuError> {
+        Ok(self.compute_checksum(pseudo_header, payload)? == self.checksum)
+    }
+
+    /// Set the checksum of the message
+    pub fn update_checksum(
+        &mut self,
+        pseudo_header: &PseudoHeader,
+        payload: &[u8],
+    ) -> Result<(), DekuError> {
+        self.checksum = self.compute_checksum(pseudo_header, payload)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocols::ip_protocol::ICMPV6;
+    use hexlit::hex;
+    use rstest::rstest;
+    use std::convert::TryFrom;
+
+    #[rstest(input, expected,
+        case(&hex!("0104 0000 00000000"), Icmpv6Body::DestinationUnreachable { unused: 0 }),
+        case(&hex!("0200 0000 000005DC"), Icmpv6Body::PacketTooBig { mtu: 1500 }),
+        case(&hex!("0300 0000 00000000"), Icmpv6Body::TimeExceeded { unused: 0 }),
+        case(&hex!("0400 0000 00000028"), Icmpv6Body::ParameterProblem { pointer: 40 }),
+        case(&hex!("8000 0000 1234 0001"), Icmpv6Body::Echo { identifier: 0x1234, sequence: 1 }),
+        case(&hex!("8100 0000 1234 0001"), Icmpv6Body::Echo { identifier: 0x1234, sequence: 1 }),
+        case(&hex!("8700 0000 00000000"), Icmpv6Body::Other([0; 4])),
+    )]
+    fn test_icmpv6(input: &[u8], expected: Icmpv6Body) {
+        let header = Icmpv6Header::try_from(input).unwrap();
+        assert_eq!(expected, header.body);
+        assert_eq!(input[0] < 128, header.is_error());
+
+        assert_eq!(input.to_vec(), header.to_bytes().unwrap());
+    }
+
+    #[test]
+    fn test_icmpv6_checksum() {
+        let pseudo_header = PseudoHeader::V6 {
+            src: "fe80::1".parse().unwrap(),
+            dst: "fe80::2".parse().unwrap(),
+            next_header: ICMPV6,
+        };
+        let mut request = Icmpv6Header::echo_request(0x1234, 1);
+        request.update_checksum(&pseudo_header, b"ping").unwrap();
+        assert_eq!(0x91AE, request.checksum);
+        assert!(request.verify_checksum(&pseudo_header, b"ping").unwrap());
+        assert!(!request.verify_checksum(&pseudo_header, b"pong").unwrap());
+    }
+}
@@ -0,0 +1,281 @@
+//! IPv4 header with options (RFC 791)
+
+use super::checksum::{checksum, PseudoHeader};
+use super::options_words;
+use crate::ctx::Endian;
+use crate::prelude::*;
+use core::convert::TryFrom;
+use std::net::Ipv4Addr;
+use std::vec::Vec;
+
+/// Option kind ending the list of options
+pub const OPTION_END: u8 = 0;
+/// Option kind of a single byte of padding
+pub const OPTION_NOP: u8 = 1;
+
+/// Option of an IPv4 header
+#[deku_derive(DekuRead, DekuWrite)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[deku(
+    type = "u8",
+    ctx = "endian: Endian",
+    ctx_default = "Endian::Big",
+    endian = "endian"
+)]
+pub enum Ipv4Option {
+    /// End of the options, followed by zero padding
+    #[deku(id = "OPTION_END")]
+    End,
+    /// Padding between options
+    #[deku(id = "OPTION_NOP")]
+    Nop,
+    /// Option with a length and a value
+    #[deku(id_pat = "_")]
+    Other {
+        /// Kind of the option, including the copied flag and the class
+        kind: u8,
+        #[deku(temp, temp_value = "u8::try_from(data.len() + 2)?")]
+        length: u8,
+        /// Value of the option
+        #[deku(count = "option_data_len(*length)?")]
+        data: Vec<u8>,
+    },
+}
+
+/// Length of the value of an option of `length` bytes, kind and length included
+fn option_data_len(length: u8) -> Result<usize, DekuError> {
+    (length as usize)
+        .checked_sub(2)
+        .ok_or_else(|| DekuError::Parse(format!("invalid option length: {length}")))
+}
+
+/// IPv4 header
+///
+/// ```text
+///     0                   1                   2                   3
+///     0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+///    +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///    |Version|  IHL  |    DSCP   |ECN|         Total Length          |
+///    +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///    |         Identification        |Flags|      Fragment Offset    |
+///    +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///    |  Time to Live |    Protocol   |         Header Checksum       |
+///    +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///    |                       Source Address                          |
+///    +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///    |                    Destination Address                        |
+///    +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///    |                    Options                    |    Padding    |
+///    +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// ```
+///
+/// `ihl` and `checksum` are kept as read, [DekuUpdate::update] sets them from the
+/// options and the other fields.
+#[derive(Debug, Clone, PartialEq, Eq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct Ipv4Header {
+    /// Version, 4
+    #[deku(bits = "4", assert_eq = "4")]
+    pub version: u8,
+    /// Length of the header, in 32-bit words
+    #[deku(bits = "4", update = "options_words(&self.options)? + 5")]
+    pub ihl: u8,
+    /// Differentiated services code point
+    #[deku(bits = "6")]
+    pub dscp: u8,
+    /// Explicit congestion notification
+    #[deku(bits = "2")]
+    pub ecn: u8,
+    /// Length of the packet, header and payload
+    pub total_length: u16,
+    /// Identification of the fragments of a packet
+    pub identification: u16,
+    /// Flags, [FLAG_DONT_FRAGMENT](Ipv4Header::FLAG_DONT_FRAGMENT) and
+    /// [FLAG_MORE_FRAGMENTS](Ipv4Header::FLAG_MORE_FRAGMENTS)
+    #[deku(bits = "3")]
+    pub flags: u8,
+    /// Offset of the fragment, in 8-byte units
+    #[deku(bits = "13")]
+    pub fragment_offset: u16,
+    /// Time to live
+    pub ttl: u8,
+    /// Protocol of the payload, see [ip_protocol](super::ip_protocol)
+    pub protocol: u8,
+    /// Header checksum
+    #[deku(update = "self.compute_checksum()?")]
+    pub checksum: u16,
+    /// Source address
+    pub src: Ipv4Addr,
+    /// Destination address
+    pub dst: Ipv4Addr,
+    /// Options, padded to 32 bits with [Ipv4Option::End]
+    #[deku(bytes_read = "options_len(*ihl)?")]
+    pub options: Vec<Ipv4Option>,
+}
+
+/// Length of the options of a header of `ihl` 32-bit words
+fn options_len(ihl: u8) -> Result<usize, DekuError> {
+    (ihl as usize)
+        .checked_sub(5)
+        .map(|words| words * 4)
+        .ok_or_else(|| DekuError::Parse(format!("invalid IPv4 header length: {ihl}")))
+}
+
+impl Ipv4Header {
+    /// Don't fragment flag
+    pub const FLAG_DONT_FRAGMENT: u8 = 0b010;
+    /// More fragments flag
+    pub const FLAG_MORE_FRAGMENTS: u8 = 0b001;
+
+    /// Create a header without options, of a payload of `payload_len` bytes
+    ///
+    /// The checksum is set.
+    pub fn new(
+        src: Ipv4Addr,
+        dst: Ipv4Addr,
+        protocol: u8,
+        payload_len: u16,
+    ) -> Result<Self, DekuError> {
+        let mut header = Self {
+            version: 4,
+            ihl: 5,
+            dscp: 0,
+            ecn: 0,
+            total_length: payload_len
+                .checked_add(20)
+                .ok_or_else(|| DekuError::InvalidParam("IPv4 payload too long".into()))?,
+            identification: 0,
+            flags: Self::FLAG_DONT_FRAGMENT,
+            fragment_offset: 0,
+            ttl: 64,
+            protocol,
+            checksum: 0,
+            src,
+            dst,
+            options: Vec::new(),
+        };
+        header.checksum = header.compute_checksum()?;
+        Ok(header)
+    }
+
+    /// Length of the header, in bytes
+    pub fn header_len(&self) -> usize {
+        self.ihl as usize * 4
+    }
+
+    /// Length of the payload, in bytes
+    pub fn payload_len(&self) -> usize {
+        (self.total_length as usize).saturating_sub(self.header_len())
+    }
+
+    /// Checksum of the header, with a zero checksum field
+    pub fn compute_checksum(&self) -> Result<u16, DekuError> {
+        let header = Self {
+            checksum: 0,
+            ..self.clone()
+        };
+        Ok(checksum(&header.to_bytes()?))
+    }
+
+    /// Returns true if the checksum of the header is valid
+    pub fn verify_checksum(&self) -> Result<bool, DekuError> {
+        Ok(checksum(&self.to_bytes()?) == 0)
+    }
+
+    /// Pseudo-header of the checksum of the payload
+    pub fn pseudo_header(&self) -> PseudoHeader {
+        PseudoHeader::V4 {
+            src: self.src,
+            dst: self.dst,
+            protocol: self.protocol,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocols::ip_protocol;
+    use hexlit::hex;
+    use rstest::rstest;
+
+    #[rstest(input, expected_options,
+        case(&hex!("4500004b0f490000801163a591fea0ed91fd02cb"), vec![]),
+        // record route and router alert
+        case(
+            &hex!("4A00 0030 0001 0000 4001 0000 0A000001 0A000002 070B04 0A000001 00000000 940400 00 00 00000000"),
+            vec![
+                Ipv4Option::Other { kind: 7, data: hex!("04 0A000001 00000000").to_vec() },
+                Ipv4Option::Other { kind: 0x94, data: vec![0, 0] },
+                Ipv4Option::End,
+                Ipv4Option::End,
+                Ipv4Option::End,
+                Ipv4Option::End,
+                Ipv4Option::End,
+            ]
+        ),
+        case(&hex!("4600 0018 0001 0000 4001 0000 0A000001 0A000002 01010100"), vec![Ipv4Option::Nop, Ipv4Option::Nop, Ipv4Option::Nop, Ipv4Option::End]),
+
+        #[should_panic(expected = "invalid IPv4 header length: 4")]
+        case(&hex!("4400 0014 0001 0000 4001 0000 0A000001 0A000002"), vec![]),
+        #[should_panic(expected = "invalid option length: 1")]
+        case(&hex!("4600 0018 0001 0000 4001 0000 0A000001 0A000002 07010000"), vec![]),
+        #[should_panic(expected = "Ipv4Header.version field failed assertion")]
+        case(&hex!("6500 0014 0001 0000 4001 0000 0A000001 0A000002"), vec![]),
+    )]
+    fn test_ipv4(input: &[u8], expected_options: Vec<Ipv4Option>) {
+        let header = Ipv4Header::try_from(input).unwrap();
+        assert_eq!(expected_options, header.options);
+        assert_eq!(input.len(), header.header_len());
+
+        assert_eq!(input.to_vec(), header.to_bytes().unwrap());
+    }
+
+    #[test]
+    fn test_ipv4_checksum() {
+        let input = hex!("4500004b0f490000801163a591fea0ed91fd02cb");
+        let mut header = Ipv4Header::try_from(input.as_ref()).unwrap();
+        assert_eq!(55, header.payload_len());
+        assert!(header.verify_checksum().unwrap());
+        assert_eq!(0x63A5, header.compute_checksum().unwrap());
+
+        header.ttl = 127;
+        assert!(!header.verify_checksum().unwrap());
+        header.update().unwrap();
+        assert!(header.verify_checksum().unwrap());
+        assert_eq!(0x64A5, header.checksum);
+    }
+
+    #[test]
+    fn test_ipv4_update() {
+        let mut header = Ipv4Header::new(
+            Ipv4Addr::new(10, 0, 0, 1),
+            Ipv4Addr::new(10, 0, 0, 2),
+            ip_protocol::ICMP,
+            8,
+        )
+        .unwrap();
+        assert!(header.verify_checksum().unwrap());
+        assert_eq!(
+            hex!("4500 001C 0000 4000 4001 26DF 0A000001 0A000002").to_vec(),
+            header.to_bytes().unwrap()
+        );
+
+        header.options = vec![Ipv4Option::Nop, Ipv4Option::Nop];
+        assert_eq!(
+            Err(DekuError::InvalidParam(
+                "options must be padded to 32 bits, up to 40 bytes: 2 bytes".into()
+            )),
+            header.update()
+        );
+
+        header.options.extend([Ipv4Option::Nop, Ipv4Option::End]);
+        header.update().unwrap();
+        assert_eq!(6, header.ihl);
+        assert!(header.verify_checksum().unwrap());
+        assert_eq!(
+            header,
+            Ipv4Header::try_from(header.to_bytes().unwrap().as_ref()).unwrap()
+        );
+    }
+}
@@ -0,0 +1,371 @@
+//! IPv6 header with extension headers (RFC 8200)
+
+use super::checksum::PseudoHeader;
+use super::encoded_len;
+use super::ip_protocol::{DESTINATION_OPTIONS, FRAGMENT, HOP_BY_HOP, ROUTING};
+use crate::bitvec::{BitSlice, BitVec, Msb0};
+use crate::ctx::Endian;
+use crate::prelude::*;
+use core::convert::TryFrom;
+use std::net::Ipv6Addr;
+use std::vec::Vec;
+
+/// Option type of a single byte of padding
+pub const OPTION_PAD1: u8 = 0;
+/// Option type of padding of several bytes
+pub const OPTION_PADN: u8 = 1;
+
+/// Option of a Hop-by-Hop or Destination Options header
+#[deku_derive(DekuRead, DekuWrite)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[deku(
+    type = "u8",
+    ctx = "endian: Endian",
+    ctx_default = "Endian::Big",
+    endian = "endian"
+)]
+pub enum Ipv6Option {
+    /// Single byte of padding
+    #[deku(id = "OPTION_PAD1")]
+    Pad1,
+    /// Option with a length and a value, including [OPTION_PADN]
+    #[deku(id_pat = "_")]
+    Other {
+        /// Type of the option
+        option_type: u8,
+        #[deku(temp, temp_value = "u8::try_from(data.len())?")]
+        length: u8,
+        /// Value of the option
+        #[deku(count = "length")]
+        data: Vec<u8>,
+    },
+}
+
+/// Length in 8-byte units, not counting the first 8 bytes, of an extension header
+/// with `len` bytes following its length field
+fn header_ext_len(len: usize, first_len: usize) -> Result<u8, DekuError> {
+    if !(len + 2).is_multiple_of(8) || len < first_len {
+        return Err(DekuError::InvalidParam(format!(
+            "IPv6 extension header must be padded to 64 bits: {} bytes",
+            len + 2
+        )));
+    }
+    u8::try_from((len + 2) / 8 - 1)
+        .map_err(|_| DekuError::InvalidParam("IPv6 extension header too long".into()))
+}
+
+/// Hop-by-Hop or Destination Options header
+#[deku_derive(DekuRead, DekuWrite)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[deku(ctx = "endian: Endian", endian = "endian")]
+pub struct OptionsHeader {
+    /// Protocol of the next header
+    pub next_header: u8,
+    #[deku(temp, temp_value = "header_ext_len(encoded_len(options)?, 6)?")]
+    hdr_ext_len: u8,
+    /// Options, padded to 64 bits with [Ipv6Option::Pad1] or [OPTION_PADN]
+    #[deku(bytes_read = "*hdr_ext_len as usize * 8 + 6")]
+    pub options: Vec<Ipv6Option>,
+}
+
+/// Routing header
+#[deku_derive(DekuRead, DekuWrite)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[deku(ctx = "endian: Endian", endian = "endian")]
+pub struct RoutingHeader {
+    /// Protocol of the next header
+    pub next_header: u8,
+    #[deku(temp, temp_value = "header_ext_len(data.len() + 2, 6)?")]
+    hdr_ext_len: u8,
+    /// Type of routing
+    pub routing_type: u8,
+    /// Number of remaining route segments
+    pub segments_left: u8,
+    /// Data of the routing type
+    #[deku(count = "*hdr_ext_len as usize * 8 + 4")]
+    pub data: Vec<u8>,
+}
+
+/// Fragment header
+#[derive(Debug, Copy, Clone, PartialEq, Eq, DekuRead, DekuWrite)]
+#[deku(ctx = "endian: Endian", endian = "endian")]
+pub struct FragmentHeader {
+    /// Protocol of the next header
+    pub next_header: u8,
+    /// Offset of the fragment, in 8-byte units
+    #[deku(reserved_bytes = "1", bits = "13")]
+    pub fragment_offset: u16,
+    /// More fragments flag
+    #[deku(reserved_bits = "2", bits = "1")]
+    pub more_fragments: bool,
+    /// Identification of the fragments of a packet
+    pub identification: u32,
+}
+
+/// Extension header, identified by the next header of the previous header
+#[derive(Debug, Clone, PartialEq, Eq, DekuRead, DekuWrite)]
+#[deku(
+    ctx = "endian: Endian, header_type: u8",
+    id = "header_type",
+    endian = "endian"
+)]
+pub enum ExtensionHeader {
+    /// Hop-by-Hop Options
+    #[deku(id = "HOP_BY_HOP")]
+    HopByHop(OptionsHeader),
+    /// Routing header
+    #[deku(id = "ROUTING")]
+    Routing(RoutingHeader),
+    /// Fragment header
+    #[deku(id = "FRAGMENT")]
+    Fragment(FragmentHeader),
+    /// Destination Options
+    #[deku(id = "DESTINATION_OPTIONS")]
+    DestinationOptions(OptionsHeader),
+}
+
+impl ExtensionHeader {
+    /// Returns true if `next_header` is the protocol of an extension header
+    pub fn is_extension(next_header: u8) -> bool {
+        matches!(
+            next_header,
+            HOP_BY_HOP | ROUTING | FRAGMENT | DESTINATION_OPTIONS
+        )
+    }
+
+    /// Protocol of this header, the next header of the previous one
+    pub fn header_type(&self) -> u8 {
+        match self {
+            ExtensionHeader::HopByHop(_) => HOP_BY_HOP,
+            ExtensionHeader::Routing(_) => ROUTING,
+            ExtensionHeader::Fragment(_) => FRAGMENT,
+            ExtensionHeader::DestinationOptions(_) => DESTINATION_OPTIONS,
+        }
+    }
+
+    /// Protocol of the header following this one
+    pub fn next_header(&self) -> u8 {
+        match self {
+            ExtensionHeader::HopByHop(header) | ExtensionHeader::DestinationOptions(header) => {
+                header.next_header
+            }
+            ExtensionHeader::Routing(header) => header.next_header,
+            ExtensionHeader::Fragment(header) => header.next_header,
+        }
+    }
+}
+
+/// IPv6 header and its extension headers
+///
+/// ```text
+///    +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///    |Version| Traffic Class |           Flow Label                  |
+///    +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///    |         Payload Length        |  Next Header  |   Hop Limit   |
+///    +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///    |                                                               |
+///    +                         Source Address                        +
+///    |                                                               |
+///    +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///    |                                                               |
+///    +                      Destination Address                      +
+///    |                                                               |
+///    +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// ```
+///
+/// Extension headers are read while the next header is one of
+/// [ExtensionHeader::is_extension], the `next_header` of the last one is the
+/// [upper-layer protocol](Ipv6Header::upper_layer_protocol).
+#[derive(Debug, Clone, PartialEq, Eq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct Ipv6Header {
+    /// Version, 6
+    #[deku(bits = "4", assert_eq = "6")]
+    pub version: u8,
+    /// Traffic class
+    #[deku(bits = "8")]
+    pub traffic_class: u8,
+    /// Flow label
+    #[deku(bits = "20")]
+    pub flow_label: u32,
+    /// Length of the extension headers and the payload
+    pub payload_length: u16,
+    /// Protocol of the first extension header or of the payload
+    pub next_header: u8,
+    /// Hop limit
+    pub hop_limit: u8,
+    /// Source address
+    pub src: Ipv6Addr,
+    /// Destination address
+    pub dst: Ipv6Addr,
+    /// Extension headers, in order
+    #[deku(
+        reader = "read_extensions(*next_header, deku::rest)",
+        writer = "write_extensions(*next_header, extensions, deku::output)"
+    )]
+    pub extensions: Vec<ExtensionHeader>,
+}
+
+fn read_extensions(
+    mut next_header: u8,
+    mut rest: &BitSlice<u8, Msb0>,
+) -> Result<(&BitSlice<u8, Msb0>, Vec<ExtensionHeader>), DekuError> {
+    let mut extensions = Vec::new();
+    while ExtensionHeader::is_extension(next_header) {
+        let (new_rest, extension) = ExtensionHeader::read(rest, (Endian::Big, next_header))?;
+        rest = new_rest;
+        next_header = extension.next_header();
+        extensions.push(extension);
+    }
+    Ok((rest, extensions))
+}
+
+fn write_extensions(
+    mut next_header: u8,
+    extensions: &[ExtensionHeader],
+    output: &mut BitVec<u8, Msb0>,
+) -> Result<(), DekuError> {
+    for extension in extensions {
+        let header_type = extension.header_type();
+        if header_type != next_header {
+            return Err(DekuError::InvalidParam(format!(
+                "IPv6 extension header {header_type} follows next header {next_header}"
+            )));
+        }
+        extension.write(output, (Endian::Big, header_type))?;
+        next_header = extension.next_header();
+    }
+    Ok(())
+}
+
+impl Ipv6Header {
+    /// Create a header without extension headers, of a payload of `payload_length` bytes
+    pub fn new(src: Ipv6Addr, dst: Ipv6Addr, next_header: u8, payload_length: u16) -> Self {
+        Self {
+            version: 6,
+            traffic_class: 0,
+            flow_label: 0,
+            payload_length,
+            next_header,
+            hop_limit: 64,
+            src,
+            dst,
+            extensions: Vec::new(),
+        }
+    }
+
+    /// Protocol of the payload, after the extension headers
+    pub fn upper_layer_protocol(&self) -> u8 {
+        self.extensions
+            .last()
+            .map_or(self.next_header, ExtensionHeader::next_header)
+    }
+
+    /// Length of the extension headers, in bytes
+    pub fn extensions_len(&self) -> Result<usize, DekuError> {
+        let mut output = BitVec::new();
+        write_extensions(self.next_header, &self.extensions, &mut output)?;
+        Ok(output.len() / 8)
+    }
+
+    /// Pseudo-header of the checksum of the payload
+    ///
+    /// The destination is the final one, routing headers aren't considered.
+    pub fn pseudo_header(&self) -> PseudoHeader {
+        PseudoHeader::V6 {
+            src: self.src,
+            dst: self.dst,
+            next_header: self.upper_layer_protocol(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocols::ip_protocol::{ICMPV6, TCP, UDP};
+    use hexlit::hex;
+    use rstest::rstest;
+
+    const ADDRS: [u8; 32] =
+        hex!("20010DB8000000000000000000000001 20010DB8000000000000000000000002");
+
+    #[rstest(extensions, expected, upper_layer_protocol,
+        case(&[], vec![], ICMPV6),
+        // hop-by-hop with a router alert and padding
+        case(
+            &hex!("3A00 05020000 0100"),
+            vec![ExtensionHeader::HopByHop(OptionsHeader {
+                next_header: ICMPV6,
+                options: vec![
+                    Ipv6Option::Other { option_type: 5, data: vec![0, 0] },
+                    Ipv6Option::Other { option_type: OPTION_PADN, data: vec![] },
+                ],
+            })],
+            ICMPV6
+        ),
+        // destination options, fragment, routing
+        case(
+            &hex!("2C00 00000000 0000 2B00 0009 12345678 1102 0001 00000000 20010DB8000000000000000000000003"),
+            vec![
+                ExtensionHeader::DestinationOptions(OptionsHeader {
+                    next_header: FRAGMENT,
+                    options: vec![Ipv6Option::Pad1; 6],
+                }),
+                ExtensionHeader::Fragment(FragmentHeader {
+                    next_header: ROUTING,
+                    fragment_offset: 1,
+                    more_fragments: true,
+                    identification: 0x12345678,
+                }),
+                ExtensionHeader::Routing(RoutingHeader {
+                    next_header: UDP,
+                    routing_type: 0,
+                    segments_left: 1,
+                    data: hex!("00000000 20010DB8000000000000000000000003").to_vec(),
+                }),
+            ],
+            UDP
+        ),
+    )]
+    fn test_ipv6(extensions: &[u8], expected: Vec<ExtensionHeader>, upper_layer_protocol: u8) {
+        let next_header = expected
+            .first()
+            .map_or(ICMPV6, ExtensionHeader::header_type);
+        let mut input = vec![0x60, 0x00, 0x00, 0x00];
+        input.extend((extensions.len() as u16 + 8).to_be_bytes());
+        input.extend([next_header, 0x40]);
+        input.extend(ADDRS);
+        input.extend(extensions);
+
+        let header = Ipv6Header::try_from(input.as_ref()).unwrap();
+        assert_eq!(expected, header.extensions);
+        assert_eq!(upper_layer_protocol, header.upper_layer_protocol());
+        assert_eq!(extensions.len(), header.extensions_len().unwrap());
+
+        assert_eq!(input, header.to_bytes().unwrap());
+    }
+
+    #[test]
+    fn test_ipv6_write_errors() {
+        let mut header = Ipv6Header::new(Ipv6Addr::LOCALHOST, Ipv6Addr::LOCALHOST, TCP, 20);
+        header.extensions = vec![ExtensionHeader::HopByHop(OptionsHeader {
+            next_header: TCP,
+            options: vec![],
+        })];
+        assert_eq!(
+            Err(DekuError::InvalidParam(
+                "IPv6 extension header 0 follows next header 6".into()
+            )),
+            header.to_bytes()
+        );
+
+        header.next_header = HOP_BY_HOP;
+        assert_eq!(
+            Err(DekuError::InvalidParam(
+                "IPv6 extension header must be padded to 64 bits: 2 bytes".into()
+            )),
+            header.to_bytes()
+        );
+    }
+}
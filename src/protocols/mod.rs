@@ -0,0 +1,108 @@
+/*!
+Network protocol headers
+
+Requires the `protocols` feature.
+
+Headers of Ethernet II with VLAN tags, IPv4 with options, IPv6 with extension
+headers, UDP, TCP with options, ICMP and ICMPv6. The headers are read and written
+without their payload, which follows in the input.
+
+Checksums are computed over the header as written, with a zero checksum field, and
+the payload, see the `compute_checksum` and `verify_checksum` methods.
+`update_checksum`, or [update](crate::DekuUpdate::update) for IPv4, sets the checksum
+field. UDP, TCP and ICMPv6 checksums also cover a
+[PseudoHeader](checksum::PseudoHeader), given by the IP header.
+
+# Example
+
+```rust
+use deku::prelude::*;
+use deku::protocols::ethernet::{EthernetHeader, ETHERTYPE_IPV4};
+use deku::protocols::ipv4::Ipv4Header;
+use deku::protocols::udp::UdpHeader;
+
+let frame = [
+    0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xAA, 0xBB, 0x08, 0x00, // Ethernet
+    0x45, 0x00, 0x00, 0x1E, 0x00, 0x00, 0x40, 0x00, 0x40, 0x11, 0xB8, 0xB6, 0xC0, 0xA8, 0x00, 0x01,
+    0xC0, 0xA8, 0x00, 0xC7, // IPv4
+    0x04, 0x00, 0x00, 0x35, 0x00, 0x0A, 0x11, 0x23, // UDP
+    0x68, 0x69, // payload
+];
+
+let ((rest, _), ethernet) = EthernetHeader::from_bytes((&frame, 0)).unwrap();
+assert_eq!(ETHERTYPE_IPV4, ethernet.ethertype);
+
+let ((rest, _), ip) = Ipv4Header::from_bytes((rest, 0)).unwrap();
+assert!(ip.verify_checksum().unwrap());
+
+let ((payload, _), udp) = UdpHeader::from_bytes((rest, 0)).unwrap();
+assert_eq!(53, udp.dst_port);
+assert!(udp.verify_checksum(&ip.pseudo_header(), payload).unwrap());
+```
+*/
+
+pub mod checksum;
+pub mod ethernet;
+pub mod icmp;
+pub mod icmpv6;
+pub mod ipv4;
+pub mod ipv6;
+pub mod tcp;
+pub mod udp;
+
+use crate::bitvec::BitVec;
+use crate::ctx::Endian;
+use crate::{DekuError, DekuWrite};
+use checksum::{Checksum, PseudoHeader};
+use std::format;
+
+/// IP protocol numbers, the `protocol` of IPv4 and the `next_header` of IPv6
+pub mod ip_protocol {
+    /// IPv6 Hop-by-Hop Options
+    pub const HOP_BY_HOP: u8 = 0;
+    /// Internet Control Message Protocol
+    pub const ICMP: u8 = 1;
+    /// Transmission Control Protocol
+    pub const TCP: u8 = 6;
+    /// User Datagram Protocol
+    pub const UDP: u8 = 17;
+    /// Routing Header for IPv6
+    pub const ROUTING: u8 = 43;
+    /// Fragment Header for IPv6
+    pub const FRAGMENT: u8 = 44;
+    /// ICMP for IPv6
+    pub const ICMPV6: u8 = 58;
+    /// No Next Header for IPv6
+    pub const NO_NEXT_HEADER: u8 = 59;
+    /// Destination Options for IPv6
+    pub const DESTINATION_OPTIONS: u8 = 60;
+}
+
+/// Length in bytes of `items` once written
+fn encoded_len<T: DekuWrite<Endian>>(items: &[T]) -> Result<usize, DekuError> {
+    let mut output = BitVec::new();
+    for item in items {
+        item.write(&mut output, Endian::Big)?;
+    }
+    Ok(output.len() / 8)
+}
+
+/// Length in 32-bit words of the options of an IPv4 or TCP header
+fn options_words<T: DekuWrite<Endian>>(options: &[T]) -> Result<u8, DekuError> {
+    let len = encoded_len(options)?;
+    if len % 4 != 0 || len > 40 {
+        return Err(DekuError::InvalidParam(format!(
+            "options must be padded to 32 bits, up to 40 bytes: {len} bytes"
+        )));
+    }
+    Ok((len / 4) as u8)
+}
+
+/// Checksum of a header, with a zero checksum field, and its payload
+fn segment_checksum(pseudo_header: Option<&PseudoHeader>, header: &[u8], payload: &[u8]) -> u16 {
+    let mut sum = Checksum::new();
+    if let Some(pseudo_header) = pseudo_header {
+        sum.add_pseudo_header(pseudo_header, header.len() + payload.len());
+    }
+    sum.add_bytes(header).add_bytes(payload).finish()
+}
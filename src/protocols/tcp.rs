@@ -0,0 +1,371 @@
+//! TCP header with options (RFC 9293)
+
+use super::checksum::PseudoHeader;
+use super::{options_words, segment_checksum};
+use crate::ctx::Endian;
+use crate::prelude::*;
+use core::convert::TryFrom;
+use std::vec::Vec;
+
+/// Option kind ending the list of options
+pub const OPTION_END: u8 = 0;
+/// Option kind of a single byte of padding
+pub const OPTION_NOP: u8 = 1;
+/// Option kind of the maximum segment size
+pub const OPTION_MSS: u8 = 2;
+/// Option kind of the window scale
+pub const OPTION_WINDOW_SCALE: u8 = 3;
+/// Option kind of the selective acknowledgment permitted
+pub const OPTION_SACK_PERMITTED: u8 = 4;
+/// Option kind of the selective acknowledgment
+pub const OPTION_SACK: u8 = 5;
+/// Option kind of the timestamps
+pub const OPTION_TIMESTAMPS: u8 = 8;
+
+/// Block of a selective acknowledgment
+#[derive(Debug, Copy, Clone, PartialEq, Eq, DekuRead, DekuWrite)]
+#[deku(ctx = "endian: Endian", endian = "endian")]
+pub struct SackBlock {
+    /// First sequence number of the block
+    pub left_edge: u32,
+    /// Sequence number following the block
+    pub right_edge: u32,
+}
+
+/// Option of a TCP header
+#[deku_derive(DekuRead, DekuWrite)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[deku(
+    type = "u8",
+    ctx = "endian: Endian",
+    ctx_default = "Endian::Big",
+    endian = "endian"
+)]
+pub enum TcpOption {
+    /// End of the options, followed by zero padding
+    #[deku(id = "OPTION_END")]
+    End,
+    /// Padding between options
+    #[deku(id = "OPTION_NOP")]
+    Nop,
+    /// Maximum segment size
+    #[deku(id = "OPTION_MSS")]
+    MaxSegmentSize {
+        #[deku(temp, temp_value = "4", map = "|length: u8| check_length(length, 4)")]
+        length: u8,
+        /// Maximum segment size, in bytes
+        mss: u16,
+    },
+    /// Window scale
+    #[deku(id = "OPTION_WINDOW_SCALE")]
+    WindowScale {
+        #[deku(temp, temp_value = "3", map = "|length: u8| check_length(length, 3)")]
+        length: u8,
+        /// Shift count of the window
+        shift: u8,
+    },
+    /// Selective acknowledgment permitted
+    #[deku(id = "OPTION_SACK_PERMITTED")]
+    SackPermitted {
+        #[deku(temp, temp_value = "2", map = "|length: u8| check_length(length, 2)")]
+        length: u8,
+    },
+    /// Selective acknowledgment
+    #[deku(id = "OPTION_SACK")]
+    Sack {
+        #[deku(temp, temp_value = "u8::try_from(blocks.len() * 8 + 2)?")]
+        length: u8,
+        /// Received blocks
+        #[deku(count = "sack_blocks(*length)?")]
+        blocks: Vec<SackBlock>,
+    },
+    /// Timestamps
+    #[deku(id = "OPTION_TIMESTAMPS")]
+    Timestamps {
+        #[deku(temp, temp_value = "10", map = "|length: u8| check_length(length, 10)")]
+        length: u8,
+        /// Timestamp value of the sender
+        value: u32,
+        /// Echoed timestamp value of the peer
+        echo_reply: u32,
+    },
+    /// Option of another kind
+    #[deku(id_pat = "_")]
+    Other {
+        /// Kind of the option
+        kind: u8,
+        #[deku(temp, temp_value = "u8::try_from(data.len() + 2)?")]
+        length: u8,
+        /// Value of the option
+        #[deku(count = "option_data_len(*length)?")]
+        data: Vec<u8>,
+    },
+}
+
+fn invalid_length(length: u8) -> DekuError {
+    DekuError::Parse(format!("invalid option length: {length}"))
+}
+
+/// Length of the value of an option of `length` bytes, kind and length included
+fn option_data_len(length: u8) -> Result<usize, DekuError> {
+    (length as usize)
+        .checked_sub(2)
+        .ok_or_else(|| invalid_length(length))
+}
+
+fn check_length(length: u8, expected: u8) -> Result<u8, DekuError> {
+    if length != expected {
+        return Err(invalid_length(length));
+    }
+    Ok(length)
+}
+
+/// Number of blocks of a selective acknowledgment option of `length` bytes
+fn sack_blocks(length: u8) -> Result<usize, DekuError> {
+    match (length as usize).checked_sub(2) {
+        Some(len) if len % 8 == 0 => Ok(len / 8),
+        _ => Err(invalid_length(length)),
+    }
+}
+
+/// TCP header
+///
+/// ```text
+///     0                   1                   2                   3
+///     0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+///    +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///    |          Source Port          |       Destination Port        |
+///    +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///    |                        Sequence Number                        |
+///    +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///    |                    Acknowledgment Number                      |
+///    +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///    |  Data |     |N|C|E|U|A|P|R|S|F|                               |
+///    | Offset| Rsv |S|W|C|R|C|S|S|Y|I|            Window             |
+///    |       |     | |R|E|G|K|H|T|N|N|                               |
+///    +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///    |           Checksum            |         Urgent Pointer        |
+///    +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///    |                    Options                    |    Padding    |
+///    +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// ```
+///
+/// `data_offset` is kept as read, [DekuUpdate::update] sets it from the options.
+#[derive(Debug, Clone, PartialEq, Eq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct TcpHeader {
+    /// Source port
+    pub src_port: u16,
+    /// Destination port
+    pub dst_port: u16,
+    /// Sequence number
+    pub sequence: u32,
+    /// Acknowledgment number, if [ACK](TcpHeader::ACK) is set
+    pub acknowledgment: u32,
+    /// Length of the header, in 32-bit words
+    #[deku(bits = "4", update = "options_words(&self.options)? + 5")]
+    pub data_offset: u8,
+    /// Flags, such as [SYN](TcpHeader::SYN)
+    #[deku(reserved_bits = "3", bits = "9")]
+    pub flags: u16,
+    /// Receive window
+    pub window: u16,
+    /// Checksum of the pseudo-header, header and payload
+    pub checksum: u16,
+    /// Urgent pointer, if [URG](TcpHeader::URG) is set
+    pub urgent_pointer: u16,
+    /// Options, padded to 32 bits with [TcpOption::End] or [TcpOption::Nop]
+    #[deku(bytes_read = "options_len(*data_offset)?")]
+    pub options: Vec<TcpOption>,
+}
+
+/// Length of the options of a header of `data_offset` 32-bit words
+fn options_len(data_offset: u8) -> Result<usize, DekuError> {
+    (data_offset as usize)
+        .checked_sub(5)
+        .map(|words| words * 4)
+        .ok_or_else(|| DekuError::Parse(format!("invalid TCP data offset: {data_offset}")))
+}
+
+impl TcpHeader {
+    /// No more data from sender
+    pub const FIN: u16 = 0x001;
+    /// Synchronize sequence numbers
+    pub const SYN: u16 = 0x002;
+    /// Reset the connection
+    pub const RST: u16 = 0x004;
+    /// Push function
+    pub const PSH: u16 = 0x008;
+    /// Acknowledgment number is significant
+    pub const ACK: u16 = 0x010;
+    /// Urgent pointer is significant
+    pub const URG: u16 = 0x020;
+    /// ECN echo
+    pub const ECE: u16 = 0x040;
+    /// Congestion window reduced
+    pub const CWR: u16 = 0x080;
+    /// ECN nonce, historic
+    pub const NS: u16 = 0x100;
+
+    /// Create a header without options nor checksum
+    pub fn new(src_port: u16, dst_port: u16, sequence: u32, flags: u16) -> Self {
+        Self {
+            src_port,
+            dst_port,
+            sequence,
+            acknowledgment: 0,
+            data_offset: 5,
+            flags,
+            window: 65535,
+            checksum: 0,
+            urgent_pointer: 0,
+            options: Vec::new(),
+        }
+    }
+
+    /// Length of the header, in bytes
+    pub fn header_len(&self) -> usize {
+        self.data_offset as usize * 4
+    }
+
+    /// Returns true if all the given flags are set
+    pub fn has_flags(&self, flags: u16) -> bool {
+        self.flags & flags == flags
+    }
+
+    /// Checksum of the segment
+    pub fn compute_checksum(
+        &self,
+        pseudo_header: &PseudoHeader,
+        payload: &[u8],
+    ) -> Result<u16, DekuError> {
+        let header = Self {
+            checksum: 0,
+            ..self.clone()
+        };
+        Ok(segment_checksum(
+            Some(pseudo_header),
+            &header.to_bytes()?,
+            payload,
+        ))
+    }
+
+    /// Returns true if the checksum of the segment is valid
+    pub fn verify_checksum(
+        &self,
+        pseudo_header: &PseudoHeader,
+        payload: &[u8],
+    ) -> Result<bool, DekuError> {
+        Ok(self.compute_checksum(pseudo_header, payload)? == self.checksum)
+    }
+
+    /// Set the checksum of the segment
+    pub fn update_checksum(
+        &mut self,
+        pseudo_header: &PseudoHeader,
+        payload: &[u8],
+    ) -> Result<(), DekuError> {
+        self.checksum = self.compute_checksum(pseudo_header, payload)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitvec::{BitVec, BitView, Msb0};
+    use hexlit::hex;
+    use rstest::rstest;
+
+    #[rstest(input, expected,
+        case(&hex!("0000"), vec![TcpOption::End, TcpOption::End]),
+        case(&hex!("0204 05B4"), vec![TcpOption::MaxSegmentSize { mss: 1460 }]),
+        case(&hex!("01 030307"), vec![TcpOption::Nop, TcpOption::WindowScale { shift: 7 }]),
+        case(&hex!("0402"), vec![TcpOption::SackPermitted {}]),
+        case(
+            &hex!("080A 00000001 00000002"),
+            vec![TcpOption::Timestamps { value: 1, echo_reply: 2 }]
+        ),
+        case(
+            &hex!("0512 00000010 00000020 00000030 00000040"),
+            vec![TcpOption::Sack {
+                blocks: vec![
+                    SackBlock { left_edge: 0x10, right_edge: 0x20 },
+                    SackBlock { left_edge: 0x30, right_edge: 0x40 },
+                ],
+            }]
+        ),
+        case(&hex!("1E03 AA"), vec![TcpOption::Other { kind: 30, data: vec![0xAA] }]),
+
+        #[should_panic(expected = "invalid option length: 5")]
+        case(&hex!("0205 05B400"), vec![]),
+        #[should_panic(expected = "invalid option length: 12")]
+        case(&hex!("050C 00000010 00000020"), vec![]),
+        #[should_panic(expected = "invalid option length: 1")]
+        case(&hex!("1E01"), vec![]),
+    )]
+    fn test_tcp_options(input: &[u8], expected: Vec<TcpOption>) {
+        let mut rest = input.view_bits::<Msb0>();
+        let mut options = vec![];
+        while !rest.is_empty() {
+            let (new_rest, option) = TcpOption::read(rest, Endian::Big).unwrap();
+            rest = new_rest;
+            options.push(option);
+        }
+        assert_eq!(expected, options);
+
+        let mut output = BitVec::new();
+        for option in &options {
+            option.write(&mut output, Endian::Big).unwrap();
+        }
+        assert_eq!(input.to_vec(), output.into_vec());
+    }
+
+    #[test]
+    fn test_tcp() {
+        // SYN with MSS, SACK permitted, timestamps, NOP and window scale
+        let input = hex!(
+            "C350 0050 00000001 00000000 A002 FAF0 0000 0000 020405B4 0402080A 0000007B 00000000 01030307"
+        );
+        let header = TcpHeader::try_from(input.as_ref()).unwrap();
+        assert_eq!(
+            TcpHeader {
+                data_offset: 10,
+                window: 64240,
+                options: vec![
+                    TcpOption::MaxSegmentSize { mss: 1460 },
+                    TcpOption::SackPermitted {},
+                    TcpOption::Timestamps {
+                        value: 123,
+                        echo_reply: 0
+                    },
+                    TcpOption::Nop,
+                    TcpOption::WindowScale { shift: 7 },
+                ],
+                ..TcpHeader::new(50000, 80, 1, TcpHeader::SYN)
+            },
+            header
+        );
+        assert_eq!(40, header.header_len());
+        assert!(header.has_flags(TcpHeader::SYN));
+        assert!(!header.has_flags(TcpHeader::SYN | TcpHeader::ACK));
+
+        assert_eq!(input.to_vec(), header.to_bytes().unwrap());
+    }
+
+    #[test]
+    fn test_tcp_update() {
+        let mut header = TcpHeader::new(50000, 80, 1, TcpHeader::SYN);
+        header.options = vec![TcpOption::MaxSegmentSize { mss: 1460 }];
+        header.update().unwrap();
+        assert_eq!(6, header.data_offset);
+
+        header.options.push(TcpOption::Nop);
+        assert_eq!(
+            Err(DekuError::InvalidParam(
+                "options must be padded to 32 bits, up to 40 bytes: 5 bytes".into()
+            )),
+            header.update()
+        );
+    }
+}
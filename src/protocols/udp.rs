@@ -0,0 +1,121 @@
+//! UDP header (RFC 768)
+
+use super::checksum::PseudoHeader;
+use super::segment_checksum;
+use crate::prelude::*;
+
+/// UDP header
+#[derive(Debug, Copy, Clone, PartialEq, Eq, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+pub struct UdpHeader {
+    /// Source port
+    pub src_port: u16,
+    /// Destination port
+    pub dst_port: u16,
+    /// Length of the datagram, header and payload
+    pub length: u16,
+    /// Checksum of the pseudo-header, header and payload, 0 if unused over IPv4
+    pub checksum: u16,
+}
+
+impl UdpHeader {
+    /// Create a header of a payload of `payload_len` bytes, without checksum
+    pub fn new(src_port: u16, dst_port: u16, payload_len: u16) -> Result<Self, DekuError> {
+        Ok(Self {
+            src_port,
+            dst_port,
+            length: payload_len
+                .checked_add(8)
+                .ok_or_else(|| DekuError::InvalidParam("UDP payload too long".into()))?,
+            checksum: 0,
+        })
+    }
+
+    /// Checksum of the datagram
+    ///
+    /// A checksum of 0 is sent as `0xFFFF`, 0 meaning that the checksum is unused.
+    pub fn compute_checksum(
+        &self,
+        pseudo_header: &PseudoHeader,
+        payload: &[u8],
+    ) -> Result<u16, DekuError> {
+        let header = Self {
+            checksum: 0,
+            ..*self
+        };
+        match segment_checksum(Some(pseudo_header), &header.to_bytes()?, payload) {
+            0 => Ok(0xFFFF),
+            checksum => Ok(checksum),
+        }
+    }
+
+    /// Returns true if the checksum of the datagram is valid or unused over IPv4
+    pub fn verify_checksum(
+        &self,
+        pseudo_header: &PseudoHeader,
+        payload: &[u8],
+    ) -> Result<bool, DekuError> {
+        if self.checksum == 0 && matches!(pseudo_header, PseudoHeader::V4 { .. }) {
+            return Ok(true);
+        }
+        Ok(self.compute_checksum(pseudo_header, payload)? == self.checksum)
+    }
+
+    /// Set the checksum of the datagram
+    pub fn update_checksum(
+        &mut self,
+        pseudo_header: &PseudoHeader,
+        payload: &[u8],
+    ) -> Result<(), DekuError> {
+        self.checksum = self.compute_checksum(pseudo_header, payload)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocols::ip_protocol::UDP;
+    use hexlit::hex;
+    use std::convert::TryFrom;
+    use std::net::Ipv4Addr;
+
+    const PSEUDO_HEADER: PseudoHeader = PseudoHeader::V4 {
+        src: Ipv4Addr::new(192, 168, 0, 1),
+        dst: Ipv4Addr::new(192, 168, 0, 199),
+        protocol: UDP,
+    };
+
+    #[test]
+    fn test_udp() {
+        let input = hex!("0400 0035 000A 1123");
+        let header = UdpHeader::try_from(input.as_ref()).unwrap();
+        assert_eq!(
+            UdpHeader {
+                checksum: 0x1123,
+                ..UdpHeader::new(1024, 53, 2).unwrap()
+            },
+            header
+        );
+        assert_eq!(input.to_vec(), header.to_bytes().unwrap());
+
+        assert!(header.verify_checksum(&PSEUDO_HEADER, b"hi").unwrap());
+        assert!(!header.verify_checksum(&PSEUDO_HEADER, b"ho").unwrap());
+    }
+
+    #[test]
+    fn test_udp_checksum() {
+        let mut header = UdpHeader::new(1024, 53, 2).unwrap();
+        assert!(header.verify_checksum(&PSEUDO_HEADER, b"hi").unwrap());
+
+        header.update_checksum(&PSEUDO_HEADER, b"hi").unwrap();
+        assert_eq!(0x1123, header.checksum);
+
+        // a computed checksum of 0 is sent as 0xFFFF
+        let payload = hex!("798C");
+        let mut header = UdpHeader::new(1024, 53, 2).unwrap();
+        header.update_checksum(&PSEUDO_HEADER, &payload).unwrap();
+        assert_eq!(0xFFFF, header.checksum);
+        assert!(header.verify_checksum(&PSEUDO_HEADER, &payload).unwrap());
+    }
+}
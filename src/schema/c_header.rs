@@ -0,0 +1,1049 @@
+/*!
+Generate a C header from the [Schema] of fixed-size types
+
+Requires the `c_header` feature.
+
+The header declares a struct per type, using bitfields for fields with a `bits`
+attribute, constants for enum ids and `pack`/`unpack` functions following the bit
+layout of deku: fields are packed most significant bit first and little endian
+values are made of 8 bit chunks, the least significant one first.
+
+Only types with a size known from their attributes are supported: fields using
+`count`, `cond`, `map`, `write_map`, custom readers, `Vec` or `Option` are rejected with
+[DekuError::InvalidParam]. Padding, alignment and `pad_fill` must be literals,
+alignment relative to the input (`align_input`) isn't supported. Enums are represented as a struct holding the id and a
+union of the variant fields, all variants must have the same size.
+
+Deku types referenced by fields must be given in the `types` argument of
+[to_c_header], they are emitted before the types using them.
+
+# Example
+
+```rust
+use deku::prelude::*;
+
+#[derive(DekuRead, DekuWrite, DekuSchema)]
+#[deku(endian = "big")]
+struct Header {
+    #[deku(bits = "4")]
+    version: u8,
+    #[deku(bits = "12")]
+    length: u16,
+}
+
+let header = deku::schema::c_header::to_c_header(&Header::SCHEMA, &[]).unwrap();
+assert!(header.contains("#define HEADER_SIZE 2\n"));
+assert!(header.contains("    uint16_t length : 12;\n"));
+assert!(header.contains("    value->length = (uint16_t)deku_read_bits(in, offset + 4, 12, 0);\n"));
+```
+*/
+
+use super::{parse_int, EnumSchema, FieldSchema, Primitive, Schema, SchemaData, TypeShape};
+use crate::DekuError;
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::convert::TryFrom;
+
+/// Functions used by the generated code, shared between headers
+const HELPERS: &str = r#"#ifndef DEKU_HELPERS
+#define DEKU_HELPERS
+
+#ifndef DEKU_NATIVE_LITTLE
+#define DEKU_NATIVE_LITTLE (__BYTE_ORDER__ == __ORDER_LITTLE_ENDIAN__)
+#endif
+
+/* Read `bits` bits at the bit `offset` of `buf`, most significant bit first.
+ * Little endian values are made of 8 bit chunks, the least significant first. */
+static inline uint64_t deku_read_bits(const uint8_t *buf, size_t offset, unsigned bits, int little)
+{
+    uint64_t value = 0;
+    if (little) {
+        unsigned shift = 0;
+        while (bits > 0) {
+            unsigned n = bits < 8 ? bits : 8;
+            value |= deku_read_bits(buf, offset, n, 0) << shift;
+            offset += n;
+            bits -= n;
+            shift += 8;
+        }
+        return value;
+    }
+    for (unsigned i = 0; i < bits; i++) {
+        size_t bit = offset + i;
+        value = (value << 1) | ((buf[bit / 8] >> (7 - bit % 8)) & 1u);
+    }
+    return value;
+}
+
+/* Write the low `bits` bits of `value` at the bit `offset` of `buf` */
+static inline void deku_write_bits(uint8_t *buf, size_t offset, unsigned bits, int little, uint64_t value)
+{
+    if (little) {
+        while (bits > 0) {
+            unsigned n = bits < 8 ? bits : 8;
+            deku_write_bits(buf, offset, n, 0, value);
+            value >>= 8;
+            offset += n;
+            bits -= n;
+        }
+        return;
+    }
+    for (unsigned i = 0; i < bits; i++) {
+        size_t bit = offset + i;
+        uint8_t mask = (uint8_t)(0x80u >> (bit % 8));
+        if ((value >> (bits - 1 - i)) & 1u) {
+            buf[bit / 8] |= mask;
+        } else {
+            buf[bit / 8] &= (uint8_t)~mask;
+        }
+    }
+}
+
+static inline int64_t deku_sign_extend(uint64_t value, unsigned bits)
+{
+    uint64_t sign = (uint64_t)1 << (bits - 1);
+    return (int64_t)((value ^ sign) - sign);
+}
+
+static inline float deku_f32(uint64_t bits)
+{
+    uint32_t v = (uint32_t)bits;
+    float f;
+    memcpy(&f, &v, sizeof(f));
+    return f;
+}
+
+static inline double deku_f64(uint64_t bits)
+{
+    double d;
+    memcpy(&d, &bits, sizeof(d));
+    return d;
+}
+
+static inline uint64_t deku_f32_bits(float f)
+{
+    uint32_t v;
+    memcpy(&v, &f, sizeof(v));
+    return v;
+}
+
+static inline uint64_t deku_f64_bits(double d)
+{
+    uint64_t v;
+    memcpy(&v, &d, sizeof(v));
+    return v;
+}
+
+#endif /* DEKU_HELPERS */
+"#;
+
+/// Generate a C header describing `root`
+///
+/// `types` are the schemas of deku types used by the fields of `root` (and
+/// recursively of those types).
+pub fn to_c_header(root: &Schema, types: &[Schema]) -> Result<String, DekuError> {
+    let mut gen = CHeader {
+        types,
+        emitted: Vec::new(),
+        in_progress: Vec::new(),
+        out: String::new(),
+    };
+    gen.emit_type(root)?;
+
+    let guard = format!("{}_H", upper_case(root.ident));
+    let mut out = format!("/* Generated by deku from `{}` */\n\n", root.ident);
+    out += &format!("#ifndef {guard}\n#define {guard}\n\n");
+    out +=
+        "#include <stdbool.h>\n#include <stddef.h>\n#include <stdint.h>\n#include <string.h>\n\n";
+    out += HELPERS;
+    out += &gen.out;
+    out += &format!("\n#endif /* {guard} */\n");
+
+    Ok(out)
+}
+
+/// A type which has been emitted
+struct Emitted {
+    ident: &'static str,
+    bits: usize,
+    /// The functions take the endianness as argument, from the `ctx` of the type
+    little_param: bool,
+}
+
+/// Code of a type: struct members, unpack and pack statements
+#[derive(Default)]
+struct Code {
+    members: String,
+    unpack: String,
+    pack: String,
+}
+
+struct CHeader<'a> {
+    types: &'a [Schema],
+    emitted: Vec<Emitted>,
+    in_progress: Vec<&'static str>,
+    out: String,
+}
+
+fn line(out: &mut String, indent: usize, s: &str) {
+    for _ in 0..indent {
+        out.push_str("    ");
+    }
+    out.push_str(s);
+    out.push('\n');
+}
+
+fn invalid(ident: &str, msg: &str) -> DekuError {
+    DekuError::InvalidParam(format!("cannot generate C for `{ident}`: {msg}"))
+}
+
+impl<'a> CHeader<'a> {
+    fn emit_type(&mut self, schema: &Schema) -> Result<(), DekuError> {
+        if self.emitted.iter().any(|e| e.ident == schema.ident) {
+            return Ok(());
+        }
+        if self.in_progress.contains(&schema.ident) {
+            return Err(invalid(schema.ident, "recursive type"));
+        }
+        self.in_progress.push(schema.ident);
+
+        // emit the types used by fields first
+        let fields = match schema.data {
+            SchemaData::Struct(fields) => fields.iter().collect::<Vec<_>>(),
+            SchemaData::Enum(ref data) => data.variants.iter().flat_map(|v| v.fields).collect(),
+        };
+        for f in fields {
+            let ty = match f.shape() {
+                TypeShape::Array(inner, _) => inner,
+                _ => f.wire_ty(),
+            };
+            if let TypeShape::Other(name) = TypeShape::parse(ty) {
+                if let Some(dep) = self.types.iter().find(|t| t.ident == name) {
+                    self.emit_type(dep)?;
+                }
+            }
+        }
+
+        let name = super::snake_case(schema.ident);
+        let upper = upper_case(schema.ident);
+        let little_param = is_endian_expr(schema.endian);
+
+        if schema.align_input {
+            return Err(invalid(schema.ident, "alignment relative to the input"));
+        }
+        if schema.endian_from.is_some() {
+            return Err(invalid(
+                schema.ident,
+                "endianness selected by `endian_from`",
+            ));
+        }
+
+        let mut code = Code::default();
+        let mut offset = 0;
+
+        if let Some(magic) = schema.magic {
+            emit_magic(&mut code, 1, &mut offset, magic);
+        }
+
+        let mut constants = String::new();
+        match schema.data {
+            SchemaData::Struct(fields) => {
+                self.emit_fields(&mut code, 1, "value->", fields, &mut offset, schema)?;
+            }
+            SchemaData::Enum(ref data) => {
+                self.emit_enum(&mut code, &mut constants, data, &mut offset, schema)?;
+            }
+        }
+
+        let pad = literal_align(offset, None, schema.align)
+            .ok_or_else(|| invalid(schema.ident, "alignment isn't fixed"))?;
+        emit_fill(
+            &mut code,
+            1,
+            &mut offset,
+            pad,
+            (schema.pad_fill, schema.pad_strict),
+        )
+        .map_err(|e| invalid(schema.ident, &e))?;
+
+        let size = (offset + 7) / 8;
+        let little_arg = if little_param { ", int little" } else { "" };
+        let little_call = if little_param { ", little" } else { "" };
+
+        let out = &mut self.out;
+        *out += &format!("\n/* `{}` */\n\n", schema.ident);
+        *out += &constants;
+        *out += &format!("#define {upper}_SIZE {size}\n\n");
+        *out += "typedef struct {\n";
+        *out += &code.members;
+        *out += &format!("}} {name}_t;\n\n");
+
+        *out += &format!(
+            "static inline int {name}_unpack_at({name}_t *value, const uint8_t *in, size_t offset{little_arg})\n{{\n"
+        );
+        *out += &code.unpack;
+        *out += "    return 0;\n}\n\n";
+
+        *out += &format!(
+            "static inline void {name}_pack_at(const {name}_t *value, uint8_t *out, size_t offset{little_arg})\n{{\n"
+        );
+        *out += &code.pack;
+        *out += "}\n\n";
+
+        *out += &format!("/* Unpack `{upper}_SIZE` bytes of `in`, returns 0 on success */\n");
+        *out += &format!(
+            "static inline int {name}_unpack({name}_t *value, const uint8_t *in{little_arg})\n{{\n"
+        );
+        *out += &format!("    return {name}_unpack_at(value, in, 0{little_call});\n}}\n\n");
+
+        *out += &format!("/* Pack `value` into `{upper}_SIZE` bytes of `out` */\n");
+        *out += &format!(
+            "static inline void {name}_pack(const {name}_t *value, uint8_t *out{little_arg})\n{{\n"
+        );
+        *out += &format!("    memset(out, 0, {upper}_SIZE);\n");
+        *out += &format!("    {name}_pack_at(value, out, 0{little_call});\n}}\n");
+
+        self.in_progress.pop();
+        self.emitted.push(Emitted {
+            ident: schema.ident,
+            bits: offset,
+            little_param,
+        });
+
+        Ok(())
+    }
+
+    fn emit_enum(
+        &mut self,
+        code: &mut Code,
+        constants: &mut String,
+        data: &EnumSchema,
+        offset: &mut usize,
+        schema: &Schema,
+    ) -> Result<(), DekuError> {
+        let ident = schema.ident;
+        if data.id.is_some() {
+            return Err(invalid(ident, "enum id from `id` isn't fixed"));
+        }
+        if data.custom_id {
+            return Err(invalid(ident, "custom enum id reader"));
+        }
+        let (p, ctype) = match data.id_type.map(TypeShape::parse) {
+            Some(TypeShape::Primitive(p @ (Primitive::Unsigned(n) | Primitive::Signed(n))))
+                if n <= 64 =>
+            {
+                (p, c_type(p))
+            }
+            _ => return Err(invalid(ident, "unsupported enum id type")),
+        };
+        let id_bits = data
+            .bits
+            .or(data.bytes.map(|b| b * 8))
+            .unwrap_or(p.bit_size());
+        let little = c_endian(schema.endian);
+        let start = *offset;
+        let upper = upper_case(ident);
+
+        line(
+            &mut code.members,
+            1,
+            &format!("{ctype} id{};", bit_width(p, Some(id_bits))),
+        );
+        let read = format!("deku_read_bits(in, {}, {id_bits}, {little})", at(start));
+        line(
+            &mut code.unpack,
+            1,
+            &format!("value->id = {};", from_raw(p, &read, id_bits)),
+        );
+
+        let mut union = String::new();
+        let mut size = None;
+        let mut catch_all = None;
+        let mut default = None;
+        let mut branches = Vec::new();
+
+        for variant in data.variants {
+            let consume_id = variant.id_pat.is_none();
+            let variant_name = super::snake_case(variant.ident);
+            let access = format!("value->variant.{variant_name}.");
+
+            let mut variant_code = Code::default();
+            let mut variant_offset = if consume_id { start + id_bits } else { start };
+            if consume_id {
+                line(
+                    &mut variant_code.pack,
+                    2,
+                    &format!(
+                        "deku_write_bits(out, {}, {id_bits}, {little}, {});",
+                        at(start),
+                        to_raw(p, "value->id")
+                    ),
+                );
+            }
+            if variant.custom {
+                return Err(invalid(ident, "custom reader"));
+            }
+            if variant.unknown && !variant.data_fields().is_empty() {
+                return Err(invalid(ident, "remaining bytes of an `unknown` variant"));
+            }
+            if let Some(magic) = variant.magic {
+                emit_magic(&mut variant_code, 2, &mut variant_offset, magic);
+            }
+            // the id of an `unknown` variant is kept in `value->id`
+            self.emit_fields(
+                &mut variant_code,
+                3,
+                &access,
+                variant.data_fields(),
+                &mut variant_offset,
+                schema,
+            )?;
+
+            match size {
+                None => size = Some(variant_offset),
+                Some(size) if size != variant_offset => {
+                    return Err(invalid(ident, "variants have different sizes"))
+                }
+                Some(_) => {}
+            }
+
+            if !variant.data_fields().is_empty() {
+                line(&mut union, 2, "struct {");
+                union += &variant_code.members;
+                line(&mut union, 2, &format!("}} {variant_name};"));
+            }
+
+            if variant.unknown {
+                catch_all = Some((variant, variant_code));
+                continue;
+            }
+            let condition = if let Some(pat) = variant.id_pat {
+                if pat == "_" {
+                    catch_all = Some((variant, variant_code));
+                    continue;
+                }
+                c_pattern(pat, "value->id")
+                    .ok_or_else(|| invalid(ident, &format!("unsupported id_pat `{pat}`")))?
+            } else {
+                let id = variant
+                    .id_str()
+                    .filter(|id| parse_int(id).is_some())
+                    .ok_or_else(|| invalid(ident, "variant ids must be integers"))?;
+                let constant = format!("{upper}_{}", upper_case(variant.ident));
+                *constants += &format!("#define {constant} {id}\n");
+                format!("value->id == {constant}")
+            };
+            if variant.default {
+                default = Some(branches.len());
+            }
+            branches.push((condition, variant, variant_code));
+        }
+        if !constants.is_empty() {
+            constants.push('\n');
+        }
+
+        if !union.is_empty() {
+            line(&mut code.members, 1, "union {");
+            code.members += &union;
+            line(&mut code.members, 1, "} variant;");
+        }
+
+        for (i, (condition, variant, variant_code)) in branches.iter().enumerate() {
+            let keyword = if i == 0 { "if" } else { "} else if" };
+            for out in [&mut code.unpack, &mut code.pack] {
+                line(out, 1, &format!("{keyword} ({condition}) {{"));
+                line(out, 2, &format!("/* {} */", variant.ident));
+            }
+            code.unpack += &variant_code.unpack;
+            code.pack += &variant_code.pack;
+        }
+
+        let (else_unpack, else_pack) = match (catch_all, default) {
+            (Some((variant, variant_code)), _) => (
+                format!("        /* {} */\n{}", variant.ident, variant_code.unpack),
+                format!("        /* {} */\n{}", variant.ident, variant_code.pack),
+            ),
+            // keep unknown ids when packing
+            (None, default) => (
+                match default {
+                    Some(i) => branches[i].2.unpack.clone(),
+                    None => "        return -1;\n".to_string(),
+                },
+                format!(
+                    "        deku_write_bits(out, {}, {id_bits}, {little}, {});\n",
+                    at(start),
+                    to_raw(p, "value->id")
+                ),
+            ),
+        };
+        if branches.is_empty() {
+            line(&mut code.unpack, 1, "{");
+            line(&mut code.pack, 1, "{");
+        } else {
+            line(&mut code.unpack, 1, "} else {");
+            line(&mut code.pack, 1, "} else {");
+        }
+        code.unpack += &else_unpack;
+        code.pack += &else_pack;
+        line(&mut code.unpack, 1, "}");
+        line(&mut code.pack, 1, "}");
+
+        *offset = size.unwrap_or(start + id_bits);
+        Ok(())
+    }
+
+    fn emit_fields(
+        &mut self,
+        code: &mut Code,
+        indent: usize,
+        access: &str,
+        fields: &[FieldSchema],
+        offset: &mut usize,
+        schema: &Schema,
+    ) -> Result<(), DekuError> {
+        // statements are one level deeper than members in enum variants
+        let stmt_indent = if indent == 1 { 1 } else { 2 };
+
+        for (i, f) in fields.iter().enumerate() {
+            let name = match f.ident {
+                Some(ident) => ident.trim_start_matches("r#").to_string(),
+                None => format!("field_{i}"),
+            };
+            let err = |msg: &str| invalid(schema.ident, &format!("field `{name}`: {msg}"));
+
+            if f.cond.is_some() {
+                return Err(err("`cond` isn't fixed size"));
+            }
+            if f.window_bytes.is_some() {
+                return Err(err("`window_bytes` isn't fixed size"));
+            }
+            if f.at.is_some() {
+                return Err(err("`at` isn't in the sequential layout"));
+            }
+            if f.skip {
+                continue;
+            }
+            if f.custom {
+                return Err(err("custom reader"));
+            }
+            if f.map.is_some() || f.write_map.is_some() {
+                return Err(err("mapped value"));
+            }
+
+            let reserved = literal_pad(f.reserved_bits, f.reserved_bytes)
+                .ok_or_else(|| err("reserved size isn't fixed"))?;
+            emit_reserved(
+                code,
+                stmt_indent,
+                offset,
+                reserved,
+                (
+                    f.reserved_value,
+                    f.reserved_strict || schema.reserved_strict,
+                ),
+            )
+            .map_err(|e| err(&e))?;
+
+            let fill = (
+                f.pad_fill.or(schema.pad_fill),
+                f.pad_strict || schema.pad_strict,
+            );
+            let pad = literal_pad(f.pad_bits_before, f.pad_bytes_before)
+                .ok_or_else(|| err("padding isn't fixed"))?;
+            emit_fill(code, stmt_indent, offset, pad, fill).map_err(|e| err(&e))?;
+            let pad = literal_align(*offset, f.align_bits_before, f.align_before)
+                .ok_or_else(|| err("alignment isn't fixed"))?;
+            emit_fill(code, stmt_indent, offset, pad, fill).map_err(|e| err(&e))?;
+
+            let little = c_endian(f.endian.or(schema.endian));
+            let has_endian = f.endian.or(schema.endian).is_some();
+            let lvalue = format!("{access}{name}");
+
+            match f.shape() {
+                TypeShape::Array(inner, len) => {
+                    let len = parse_int(len)
+                        .and_then(|n| usize::try_from(n).ok())
+                        .ok_or_else(|| err("array length isn't a literal"))?;
+                    let member = self
+                        .member(inner, f, &format!("{name}[{len}]"), false)
+                        .map_err(|e| err(&e))?;
+                    line(&mut code.members, indent, &member);
+
+                    let bits = self.value_bits(inner, f).map_err(|e| err(&e))?;
+                    let at = format!("{} + i * {bits}", at(*offset));
+                    for out in [&mut code.unpack, &mut code.pack] {
+                        line(
+                            out,
+                            stmt_indent,
+                            &format!("for (size_t i = 0; i < {len}; i++) {{"),
+                        );
+                    }
+                    self.emit_value(
+                        code,
+                        stmt_indent + 1,
+                        &format!("{lvalue}[i]"),
+                        &at,
+                        inner,
+                        f,
+                        (&little, has_endian),
+                    )
+                    .map_err(|e| err(&e))?;
+                    for out in [&mut code.unpack, &mut code.pack] {
+                        line(out, stmt_indent, "}");
+                    }
+                    *offset += bits * len;
+                }
+                TypeShape::Vec(_) | TypeShape::Option(_) => {
+                    return Err(err("`Vec` and `Option` aren't fixed size"));
+                }
+                _ => {
+                    let member = self
+                        .member(f.wire_ty(), f, &name, true)
+                        .map_err(|e| err(&e))?;
+                    line(&mut code.members, indent, &member);
+                    self.emit_value(
+                        code,
+                        stmt_indent,
+                        &lvalue,
+                        &at(*offset),
+                        f.wire_ty(),
+                        f,
+                        (&little, has_endian),
+                    )
+                    .map_err(|e| err(&e))?;
+                    *offset += self.value_bits(f.wire_ty(), f).map_err(|e| err(&e))?;
+                }
+            }
+
+            let pad = literal_pad(f.pad_bits_after, f.pad_bytes_after)
+                .ok_or_else(|| err("padding isn't fixed"))?;
+            emit_fill(code, stmt_indent, offset, pad, fill).map_err(|e| err(&e))?;
+            let pad = literal_align(*offset, f.align_bits_after, f.align_after)
+                .ok_or_else(|| err("alignment isn't fixed"))?;
+            emit_fill(code, stmt_indent, offset, pad, fill).map_err(|e| err(&e))?;
+        }
+
+        Ok(())
+    }
+
+    /// Declaration of a struct member
+    fn member(
+        &self,
+        ty: &str,
+        f: &FieldSchema,
+        name: &str,
+        bitfield: bool,
+    ) -> Result<String, String> {
+        Ok(match TypeShape::parse(ty) {
+            TypeShape::Primitive(p) => {
+                let width = if bitfield {
+                    bit_width(p, f.bit_size())
+                } else {
+                    String::new()
+                };
+                format!("{} {name}{width};", c_type(p))
+            }
+            TypeShape::Other("Ipv4Addr") => format!("uint32_t {name};"),
+            TypeShape::Other("Ipv6Addr") => format!("uint8_t {name}[16];"),
+            TypeShape::Other(other) => {
+                self.emitted_type(other)?;
+                format!("{}_t {name};", super::snake_case(other))
+            }
+            _ => return Err(format!("unsupported type `{ty}`")),
+        })
+    }
+
+    /// Size in bits of a single value
+    fn value_bits(&self, ty: &str, f: &FieldSchema) -> Result<usize, String> {
+        match TypeShape::parse(ty) {
+            TypeShape::Primitive(p) => Ok(f.bit_size().unwrap_or(p.bit_size())),
+            TypeShape::Other("Ipv4Addr") => Ok(32),
+            TypeShape::Other("Ipv6Addr") => Ok(128),
+            TypeShape::Other(other) => Ok(self.emitted_type(other)?.bits),
+            _ => Err(format!("unsupported type `{ty}`")),
+        }
+    }
+
+    fn emitted_type(&self, ident: &str) -> Result<&Emitted, String> {
+        self.emitted
+            .iter()
+            .find(|e| e.ident == ident)
+            .ok_or_else(|| format!("unknown type `{ident}`"))
+    }
+
+    /// Emit unpack and pack statements of a single value at the bit offset `at`
+    #[allow(clippy::too_many_arguments)]
+    fn emit_value(
+        &self,
+        code: &mut Code,
+        indent: usize,
+        lvalue: &str,
+        at: &str,
+        ty: &str,
+        f: &FieldSchema,
+        (little, has_endian): (&str, bool),
+    ) -> Result<(), String> {
+        match TypeShape::parse(ty) {
+            TypeShape::Primitive(p) => {
+                if p.bit_size() > 64 {
+                    return Err(format!("unsupported type `{ty}`"));
+                }
+                let bits = self.value_bits(ty, f)?;
+                let read = format!("deku_read_bits(in, {at}, {bits}, {little})");
+                line(
+                    &mut code.unpack,
+                    indent,
+                    &format!("{lvalue} = {};", from_raw(p, &read, bits)),
+                );
+                line(
+                    &mut code.pack,
+                    indent,
+                    &format!(
+                        "deku_write_bits(out, {at}, {bits}, {little}, {});",
+                        to_raw(p, lvalue)
+                    ),
+                );
+            }
+            TypeShape::Other("Ipv4Addr") => {
+                return self.emit_value(code, indent, lvalue, at, "u32", f, (little, has_endian));
+            }
+            TypeShape::Other("Ipv6Addr") => {
+                // written as a `u128`, the bytes are reversed in little endian
+                let index = match little {
+                    "0" => "j".to_string(),
+                    "1" => "15 - j".to_string(),
+                    little => format!("{little} ? 15 - j : j"),
+                };
+                for out in [&mut code.unpack, &mut code.pack] {
+                    line(out, indent, "for (size_t j = 0; j < 16; j++) {");
+                }
+                line(
+                    &mut code.unpack,
+                    indent + 1,
+                    &format!(
+                        "{lvalue}[j] = (uint8_t)deku_read_bits(in, {at} + 8 * ({index}), 8, 0);"
+                    ),
+                );
+                line(
+                    &mut code.pack,
+                    indent + 1,
+                    &format!("deku_write_bits(out, {at} + 8 * ({index}), 8, 0, {lvalue}[j]);"),
+                );
+                for out in [&mut code.unpack, &mut code.pack] {
+                    line(out, indent, "}");
+                }
+            }
+            TypeShape::Other(other) => {
+                let emitted = self.emitted_type(other)?;
+                let name = super::snake_case(other);
+                // the endianness is passed first in the `ctx` of the type
+                let little = match (emitted.little_param, has_endian) {
+                    (true, true) => format!(", {little}"),
+                    (true, false) => ", DEKU_NATIVE_LITTLE".to_string(),
+                    (false, _) => String::new(),
+                };
+                line(
+                    &mut code.unpack,
+                    indent,
+                    &format!("if ({name}_unpack_at(&{lvalue}, in, {at}{little}) != 0) {{"),
+                );
+                line(&mut code.unpack, indent + 1, "return -1;");
+                line(&mut code.unpack, indent, "}");
+                line(
+                    &mut code.pack,
+                    indent,
+                    &format!("{name}_pack_at(&{lvalue}, out, {at}{little});"),
+                );
+            }
+            _ => return Err(format!("unsupported type `{ty}`")),
+        }
+        Ok(())
+    }
+}
+
+/// `offset` or `offset + n`
+fn at(n: usize) -> String {
+    if n == 0 {
+        "offset".to_string()
+    } else {
+        format!("offset + {n}")
+    }
+}
+
+fn c_type(p: Primitive) -> String {
+    match p {
+        Primitive::Unsigned(n) => format!("uint{n}_t"),
+        Primitive::Signed(n) => format!("int{n}_t"),
+        Primitive::Float(32) => "float".to_string(),
+        Primitive::Float(_) => "double".to_string(),
+        Primitive::Bool => "bool".to_string(),
+    }
+}
+
+/// ` : n` for integers using less bits than their type
+fn bit_width(p: Primitive, bits: Option<usize>) -> String {
+    match (p, bits) {
+        (Primitive::Unsigned(n) | Primitive::Signed(n), Some(bits)) if bits < n => {
+            format!(" : {bits}")
+        }
+        _ => String::new(),
+    }
+}
+
+/// Convert the `uint64_t` read from the data into the C type
+fn from_raw(p: Primitive, read: &str, bits: usize) -> String {
+    match p {
+        Primitive::Unsigned(_) => format!("({}){read}", c_type(p)),
+        Primitive::Signed(_) => format!("({})deku_sign_extend({read}, {bits})", c_type(p)),
+        Primitive::Float(32) => format!("deku_f32({read})"),
+        Primitive::Float(_) => format!("deku_f64({read})"),
+        Primitive::Bool => format!("{read} != 0"),
+    }
+}
+
+/// Convert a C value into the `uint64_t` written to the data
+fn to_raw(p: Primitive, value: &str) -> String {
+    match p {
+        Primitive::Unsigned(_) => value.to_string(),
+        Primitive::Signed(_) => format!("(uint64_t){value}"),
+        Primitive::Float(32) => format!("deku_f32_bits({value})"),
+        Primitive::Float(_) => format!("deku_f64_bits({value})"),
+        Primitive::Bool => format!("{value} ? 1 : 0"),
+    }
+}
+
+/// C expression of the endianness: `1` for little endian
+fn c_endian(endian: Option<&str>) -> String {
+    match endian {
+        Some("big") => "0".to_string(),
+        Some("little") => "1".to_string(),
+        // an expression from the `ctx` of the type
+        Some(_) => "little".to_string(),
+        None => "DEKU_NATIVE_LITTLE".to_string(),
+    }
+}
+
+fn is_endian_expr(endian: Option<&str>) -> bool {
+    !matches!(endian, None | Some("big") | Some("little"))
+}
+
+/// Padding in bits, `None` if it isn't a literal
+fn literal_pad(bits: Option<&str>, bytes: Option<&str>) -> Option<usize> {
+    let literal = |s: Option<&str>| match s {
+        Some(s) => parse_int(s).and_then(|n| usize::try_from(n).ok()),
+        None => Some(0),
+    };
+    Some(literal(bits)? + literal(bytes)? * 8)
+}
+
+/// Skip `bits` padding bits at `offset`: write the fill value when packing and check
+/// it when unpacking in strict mode
+fn emit_fill(
+    code: &mut Code,
+    indent: usize,
+    offset: &mut usize,
+    bits: usize,
+    (fill, strict): (Option<&str>, bool),
+) -> Result<(), String> {
+    let fill = match fill {
+        Some(fill) => parse_int(fill)
+            .and_then(|n| u8::try_from(n).ok())
+            .ok_or_else(|| format!("padding fill `{fill}` isn't a literal byte"))?,
+        None => 0,
+    };
+
+    // `pack` starts from zeroed memory
+    if fill != 0 || strict {
+        for chunk in (0..bits).step_by(8) {
+            let n = (bits - chunk).min(8);
+            let value = fill >> (8 - n);
+            let at = at(*offset + chunk);
+            if fill != 0 {
+                line(
+                    &mut code.pack,
+                    indent,
+                    &format!("deku_write_bits(out, {at}, {n}, 0, {value:#04x});"),
+                );
+            }
+            if strict {
+                line(
+                    &mut code.unpack,
+                    indent,
+                    &format!("if (deku_read_bits(in, {at}, {n}, 0) != {value:#04x}) {{"),
+                );
+                line(&mut code.unpack, indent + 1, "return -1;");
+                line(&mut code.unpack, indent, "}");
+            }
+        }
+    }
+
+    *offset += bits;
+    Ok(())
+}
+
+/// Write the `magic` bytes at `offset` when packing and check them when unpacking
+fn emit_magic(code: &mut Code, indent: usize, offset: &mut usize, magic: &[u8]) {
+    let checks = magic
+        .iter()
+        .enumerate()
+        .map(|(i, b)| {
+            format!(
+                "deku_read_bits(in, {}, 8, 0) != {b:#04x}",
+                at(*offset + i * 8)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" || ");
+    line(&mut code.unpack, indent, &format!("if ({checks}) {{"));
+    line(&mut code.unpack, indent + 1, "return -1;");
+    line(&mut code.unpack, indent, "}");
+    for (i, b) in magic.iter().enumerate() {
+        line(
+            &mut code.pack,
+            indent,
+            &format!(
+                "deku_write_bits(out, {}, 8, 0, {b:#04x});",
+                at(*offset + i * 8)
+            ),
+        );
+    }
+    *offset += magic.len() * 8;
+}
+
+/// Skip `bits` reserved bits at `offset`: write their value when packing and check it
+/// when unpacking in strict mode
+fn emit_reserved(
+    code: &mut Code,
+    indent: usize,
+    offset: &mut usize,
+    bits: usize,
+    (value, strict): (Option<&str>, bool),
+) -> Result<(), String> {
+    let value = match value {
+        Some(value) => parse_int(value)
+            .and_then(|n| u64::try_from(n).ok())
+            .ok_or_else(|| format!("reserved value `{value}` isn't a literal"))?,
+        None => 0,
+    };
+
+    // the value is right-aligned, `deku_read_bits`/`deku_write_bits` handle up to 64 bits
+    for chunk in (0..bits).step_by(64) {
+        let n = (bits - chunk).min(64);
+        let shift = bits - chunk - n;
+        let chunk_value = if shift >= 64 {
+            0
+        } else {
+            (value >> shift) & (u64::MAX >> (64 - n))
+        };
+        let at = at(*offset + chunk);
+        if chunk_value != 0 {
+            line(
+                &mut code.pack,
+                indent,
+                &format!("deku_write_bits(out, {at}, {n}, 0, {chunk_value:#x});"),
+            );
+        }
+        if strict {
+            line(
+                &mut code.unpack,
+                indent,
+                &format!("if (deku_read_bits(in, {at}, {n}, 0) != {chunk_value:#x}) {{"),
+            );
+            line(&mut code.unpack, indent + 1, "return -1;");
+            line(&mut code.unpack, indent, "}");
+        }
+    }
+
+    *offset += bits;
+    Ok(())
+}
+
+/// Padding in bits to align `offset` to a literal number of bits/bytes
+fn literal_align(offset: usize, bits: Option<&str>, bytes: Option<&str>) -> Option<usize> {
+    let literal = |s: &str| parse_int(s).and_then(|n| usize::try_from(n).ok());
+    let align = match (bits, bytes) {
+        (Some(bits), _) => literal(bits)?,
+        (None, Some(bytes)) => literal(bytes)? * 8,
+        (None, None) => return Some(0),
+    };
+    if align == 0 {
+        return None;
+    }
+    Some((align - offset % align) % align)
+}
+
+/// Translate an `id_pat` such as `1 | 3 ..= 5` into a C condition on `id`
+fn c_pattern(pat: &str, id: &str) -> Option<String> {
+    let alternatives = pat
+        .split('|')
+        .map(|alt| {
+            let alt = alt.trim();
+            if let Some((start, end)) = alt.split_once("..=") {
+                let (start, end) = (parse_int(start)?, parse_int(end)?);
+                Some(format!("({id} >= {start} && {id} <= {end})"))
+            } else {
+                Some(format!("{id} == {}", parse_int(alt)?))
+            }
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(alternatives.join(" || "))
+}
+
+/// `Ipv4Header` -> `IPV4_HEADER`
+fn upper_case(s: &str) -> String {
+    super::snake_case(s).to_uppercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest(
+        input,
+        expected,
+        case("1", Some("id == 1")),
+        case("0x01 | 0x02", Some("id == 1 || id == 2")),
+        case("3 ..= 5", Some("(id >= 3 && id <= 5)")),
+        case("Foo", None)
+    )]
+    fn test_c_pattern(input: &str, expected: Option<&str>) {
+        assert_eq!(expected.map(String::from), c_pattern(input, "id"));
+    }
+
+    #[rstest(
+        bits,
+        bytes,
+        expected,
+        case(None, None, Some(0)),
+        case(Some("4"), Some("2"), Some(20)),
+        case(Some("count"), None, None)
+    )]
+    fn test_literal_pad(bits: Option<&str>, bytes: Option<&str>, expected: Option<usize>) {
+        assert_eq!(expected, literal_pad(bits, bytes));
+    }
+
+    #[rstest(
+        offset,
+        bits,
+        bytes,
+        expected,
+        case(3, None, None, Some(0)),
+        case(8, None, Some("4"), Some(24)),
+        case(32, None, Some("4"), Some(0)),
+        case(3, Some("4"), None, Some(1)),
+        case(3, None, Some("0"), None),
+        case(3, None, Some("n"), None)
+    )]
+    fn test_literal_align(
+        offset: usize,
+        bits: Option<&str>,
+        bytes: Option<&str>,
+        expected: Option<usize>,
+    ) {
+        assert_eq!(expected, literal_align(offset, bits, bytes));
+    }
+}
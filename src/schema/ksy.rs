@@ -0,0 +1,830 @@
+/*!
+Export a [Schema] as a [Kaitai Struct](https://kaitai.io) `.ksy` description
+
+Requires the `ksy` feature.
+
+The generated document covers sequential fields, bit-sized integers, endianness,
+`count`/`until` containers, enums with their ids, `magic` as `contents` and
+conditional fields from `cond`. Fields read at an offset with `at` become `instances`
+with a `pos`. Expressions are translated on a best-effort basis:
+dereferences are removed, `&&`/`||`/`!` become `and`/`or`/`not` and `.len()` becomes
+`.size`. Fields using custom readers, `map` or constructs without a Kaitai
+equivalent are annotated with a `doc` entry.
+
+Deku types referenced by fields are emitted under `types` when their schema is
+given in the `types` argument of [to_ksy].
+
+# Example
+
+```rust
+use deku::prelude::*;
+
+#[derive(DekuRead, DekuWrite, DekuSchema)]
+#[deku(endian = "big", magic = b"DK")]
+struct Header {
+    #[deku(bits = "4")]
+    version: u8,
+    #[deku(bits = "4")]
+    count: u8,
+    #[deku(count = "count")]
+    items: Vec<u16>,
+}
+
+let ksy = deku::schema::ksy::to_ksy(&Header::SCHEMA, &[]);
+assert_eq!(
+    ksy,
+    r#"meta:
+  id: header
+  endian: be
+  bit-endian: be
+seq:
+  - id: magic
+    contents: [0x44, 0x4b]
+  - id: version
+    type: b4
+  - id: count
+    type: b4
+  - id: items
+    type: u2
+    repeat: expr
+    repeat-expr: count
+"#
+);
+```
+*/
+
+use super::{
+    enum_table_of, parse_int, split_top_level, FieldSchema, Primitive, Schema, SchemaData,
+    TypeShape,
+};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Generate a `.ksy` document describing `root`
+///
+/// `types` are the schemas of deku types used by the fields of `root` (and
+/// recursively of those types), they are emitted as Kaitai `types`.
+pub fn to_ksy(root: &Schema, types: &[Schema]) -> String {
+    let meta_endian = match root.endian {
+        Some("big") => "be",
+        _ => "le",
+    };
+
+    let mut ksy = Ksy {
+        out: String::new(),
+        types,
+        meta_endian,
+    };
+
+    ksy.line(0, "meta:");
+    ksy.line(1, &format!("id: {}", super::snake_case(root.ident)));
+    ksy.line(1, &format!("endian: {meta_endian}"));
+    ksy.line(1, "bit-endian: be");
+
+    ksy.emit_type_body(0, root, types);
+
+    ksy.out
+}
+
+struct Ksy<'a> {
+    out: String,
+    types: &'a [Schema],
+    meta_endian: &'static str,
+}
+
+impl<'a> Ksy<'a> {
+    fn line(&mut self, indent: usize, s: &str) {
+        for _ in 0..indent {
+            self.out.push_str("  ");
+        }
+        self.out.push_str(s);
+        self.out.push('\n');
+    }
+
+    /// Emit `params`, `seq`, `types` and `enums` of a type
+    fn emit_type_body(&mut self, indent: usize, schema: &Schema, extra_types: &[Schema]) {
+        let name = super::snake_case(schema.ident);
+
+        let params = ctx_params(schema.ctx);
+        if params.iter().any(|(_, ty)| ty.is_some()) {
+            self.line(indent, "params:");
+            for (param, ty) in &params {
+                if let Some(ty) = ty {
+                    self.line(indent + 1, &format!("- id: {param}"));
+                    self.line(indent + 2, &format!("type: {ty}"));
+                }
+            }
+        }
+
+        self.line(indent, "seq:");
+        if let Some(magic) = schema.magic {
+            self.emit_magic(indent + 1, magic);
+        }
+
+        let mut variant_types = Vec::new();
+        let mut enum_table = Vec::new();
+
+        match schema.data {
+            SchemaData::Struct(fields) => {
+                for (i, f) in fields.iter().enumerate() {
+                    if f.at.is_none() {
+                        self.emit_field(indent + 1, schema, i, f);
+                    }
+                }
+            }
+            SchemaData::Enum(ref data) => {
+                let id_enum = format!("{name}_id");
+                enum_table = enum_table_of(data);
+                let use_enum = !enum_table.is_empty()
+                    && data.id.is_none()
+                    && matches!(
+                        data.id_type.map(TypeShape::parse),
+                        Some(TypeShape::Primitive(
+                            Primitive::Unsigned(_) | Primitive::Signed(_)
+                        ))
+                    );
+                if !use_enum {
+                    enum_table.clear();
+                }
+
+                let switch_on = if let Some(id) = data.id {
+                    kaitai_expr(id)
+                } else {
+                    let id_type = data.id_type.unwrap_or("u8");
+                    self.line(indent + 1, "- id: variant_id");
+                    self.emit_type(
+                        indent + 2,
+                        None,
+                        id_type,
+                        data.bits,
+                        data.bytes,
+                        schema.endian,
+                    );
+                    if use_enum {
+                        self.line(indent + 2, &format!("enum: {id_enum}"));
+                    }
+                    if data.custom_id {
+                        self.line(indent + 2, "doc: uses a custom deku reader");
+                    }
+                    "variant_id".to_string()
+                };
+
+                let mut cases = Vec::new();
+                for variant in data.variants {
+                    let is_default =
+                        variant.default || variant.unknown || variant.id_pat == Some("_");
+                    if variant.data_fields().is_empty()
+                        && !variant.custom
+                        && variant.magic.is_none()
+                    {
+                        continue;
+                    }
+
+                    let type_name = format!("{name}_{}", super::snake_case(variant.ident));
+                    let key = if is_default {
+                        Some("_".to_string())
+                    } else if use_enum {
+                        variant
+                            .id_str()
+                            .and_then(parse_int)
+                            .map(|_| format!("{id_enum}::{}", super::snake_case(variant.ident)))
+                    } else {
+                        variant.id_str().map(kaitai_expr)
+                    };
+
+                    if let Some(key) = key {
+                        cases.push((key, type_name.clone()));
+                    }
+                    variant_types.push((type_name, variant));
+                }
+
+                if !cases.is_empty() {
+                    self.line(indent + 1, "- id: body");
+                    self.line(indent + 2, "type:");
+                    self.line(indent + 3, &format!("switch-on: {}", yaml_str(&switch_on)));
+                    self.line(indent + 3, "cases:");
+                    for (key, ty) in cases {
+                        self.line(indent + 4, &format!("{}: {ty}", yaml_str(&key)));
+                    }
+                }
+            }
+        }
+
+        self.emit_alignment(indent + 1, schema, &name, "end", None, schema.align);
+
+        if let SchemaData::Struct(fields) = schema.data {
+            self.emit_instances(indent, schema, fields);
+        }
+
+        if !variant_types.is_empty() || !extra_types.is_empty() {
+            self.line(indent, "types:");
+            for (type_name, variant) in variant_types {
+                self.line(indent + 1, &format!("{type_name}:"));
+                if variant.custom {
+                    self.line(indent + 2, "doc: uses a custom deku reader");
+                }
+                self.line(indent + 2, "seq:");
+                if variant.unknown {
+                    // the id is `variant_id`, the remaining bytes are until the end
+                    if let Some(f) = variant.data_fields().first() {
+                        let name = f.ident.unwrap_or("field_1").trim_start_matches("r#");
+                        self.line(indent + 3, &format!("- id: {name}"));
+                        self.line(indent + 4, "size-eos: true");
+                    }
+                    continue;
+                }
+                if let Some(magic) = variant.magic {
+                    self.emit_magic(indent + 3, magic);
+                }
+                for (i, f) in variant.fields.iter().enumerate() {
+                    if f.at.is_none() {
+                        self.emit_field(indent + 3, schema, i, f);
+                    }
+                }
+                self.emit_instances(indent + 2, schema, variant.fields);
+            }
+            for ty in extra_types {
+                self.line(indent + 1, &format!("{}:", super::snake_case(ty.ident)));
+                self.emit_type_body(indent + 2, ty, &[]);
+            }
+        }
+
+        if !enum_table.is_empty() {
+            self.line(indent, "enums:");
+            self.line(indent + 1, &format!("{name}_id:"));
+            for (id, variant) in enum_table {
+                self.line(indent + 2, &format!("{id}: {}", super::snake_case(variant)));
+            }
+        }
+    }
+
+    /// Emit the fields read at an offset with `at` as Kaitai `instances`
+    fn emit_instances(&mut self, indent: usize, container: &Schema, fields: &[FieldSchema]) {
+        if fields.iter().all(|f| f.at.is_none()) {
+            return;
+        }
+        self.line(indent, "instances:");
+        for (i, f) in fields.iter().enumerate() {
+            if f.at.is_some() {
+                self.emit_field(indent + 1, container, i, f);
+            }
+        }
+    }
+
+    fn emit_field(&mut self, indent: usize, container: &Schema, i: usize, f: &FieldSchema) {
+        // skipped fields are not present in the data
+        if f.skip && f.cond.is_none() {
+            return;
+        }
+
+        let name = match f.ident {
+            Some(ident) => ident.trim_start_matches("r#").to_string(),
+            None => format!("field_{i}"),
+        };
+
+        self.emit_reserved(indent, container, &name, f);
+        self.emit_padding(
+            indent,
+            &name,
+            "before",
+            f.pad_bits_before,
+            f.pad_bytes_before,
+        );
+        self.emit_alignment(
+            indent,
+            container,
+            &name,
+            "before",
+            f.align_bits_before,
+            f.align_before,
+        );
+
+        match f.at {
+            Some(at) => {
+                self.line(indent, &format!("{name}:"));
+                self.line(indent + 1, &format!("pos: {}", yaml_str(&kaitai_expr(at))));
+            }
+            None => self.line(indent, &format!("- id: {name}")),
+        }
+
+        let mut docs = Vec::new();
+        if f.custom {
+            docs.push("uses a custom deku reader".to_string());
+        }
+        if let Some(map) = f.map {
+            docs.push(format!("mapped with `{map}`"));
+        }
+        if container.endian_from_index() == Some(i) {
+            docs.push("selects the endianness of the following fields".to_string());
+        }
+
+        let endian = f.endian.or(container.endian);
+        let element = match f.shape() {
+            TypeShape::Option(inner) => inner,
+            _ => f.wire_ty(),
+        };
+
+        match TypeShape::parse(element) {
+            TypeShape::Vec(inner) => {
+                self.emit_container(indent + 1, f, inner, None, endian, &mut docs)
+            }
+            TypeShape::Array(inner, len) => {
+                self.emit_container(indent + 1, f, inner, Some(len), endian, &mut docs)
+            }
+            _ => self.emit_type(indent + 1, f.ctx, element, f.bits, f.bytes, endian),
+        }
+
+        // the window is a substream, Kaitai skips the bytes not read from it
+        if let Some(window_bytes) = f.window_bytes {
+            match f.window_rest {
+                Some("keep") => docs.push(format!(
+                    "read from `{window_bytes}` bytes, the unread bytes are kept for the following fields"
+                )),
+                Some("error") => {
+                    self.line(
+                        indent + 1,
+                        &format!("size: {}", yaml_str(&kaitai_expr(window_bytes))),
+                    );
+                    docs.push("must read the whole window".to_string());
+                }
+                _ => self.line(
+                    indent + 1,
+                    &format!("size: {}", yaml_str(&kaitai_expr(window_bytes))),
+                ),
+            }
+        }
+
+        if let Some(cond) = f.cond {
+            let cond = kaitai_expr(cond);
+            let cond = if f.skip {
+                format!("not ({cond})")
+            } else {
+                cond
+            };
+            self.line(indent + 1, &format!("if: {}", yaml_str(&cond)));
+        }
+
+        if !docs.is_empty() {
+            self.line(indent + 1, &format!("doc: {}", yaml_str(&docs.join(", "))));
+        }
+
+        self.emit_padding(indent, &name, "after", f.pad_bits_after, f.pad_bytes_after);
+        self.emit_alignment(
+            indent,
+            container,
+            &name,
+            "after",
+            f.align_bits_after,
+            f.align_after,
+        );
+    }
+
+    /// Emit a `Vec` or array, either as raw bytes or as a repeated element
+    fn emit_container(
+        &mut self,
+        indent: usize,
+        f: &FieldSchema,
+        inner: &str,
+        len: Option<&str>,
+        endian: Option<&str>,
+        docs: &mut Vec<String>,
+    ) {
+        let is_bytes = inner == "u8" && f.bits.is_none() && f.until.is_none();
+        if let (true, Some(size)) = (is_bytes, f.count.or(f.bytes_read).or(len)) {
+            self.line(indent, &format!("size: {}", yaml_str(&kaitai_expr(size))));
+            return;
+        }
+        if let (true, None, Some(terminator)) = (is_bytes, f.until_exclusive, f.terminator) {
+            self.line(
+                indent,
+                &format!("terminator: {}", yaml_str(&kaitai_expr(terminator))),
+            );
+            return;
+        }
+
+        self.emit_type(indent, f.ctx, inner, f.bits, f.bytes, endian);
+
+        if let Some(count) = f.count.or(len) {
+            self.line(indent, "repeat: expr");
+            self.line(
+                indent,
+                &format!("repeat-expr: {}", yaml_str(&kaitai_expr(count))),
+            );
+        } else if let Some(until) = f.until {
+            self.line(indent, "repeat: until");
+            self.line(
+                indent,
+                &format!("repeat-until: {}", yaml_str(&kaitai_predicate(until))),
+            );
+        } else if let Some(terminator) = f.terminator {
+            let until = match f.until_exclusive {
+                Some(until) => kaitai_predicate(until),
+                None => format!("_ == {}", kaitai_expr(terminator)),
+            };
+            self.line(indent, "repeat: until");
+            self.line(indent, &format!("repeat-until: {}", yaml_str(&until)));
+            docs.push("the terminating element isn't stored by deku".to_string());
+        } else if let Some(size) = f.bytes_read {
+            self.line(indent, "repeat: eos");
+            docs.push(format!("limited to `{size}` bytes"));
+        } else if let Some(size) = f.bits_read {
+            self.line(indent, "repeat: eos");
+            docs.push(format!("limited to `{size}` bits"));
+        }
+    }
+
+    fn emit_padding(
+        &mut self,
+        indent: usize,
+        name: &str,
+        position: &str,
+        bits: Option<&str>,
+        bytes: Option<&str>,
+    ) {
+        if let Some(bits) = bits {
+            self.line(indent, &format!("- id: {name}_pad_bits_{position}"));
+            match bits.parse::<usize>() {
+                Ok(n) => self.line(indent + 1, &format!("type: b{n}")),
+                Err(_) => {
+                    self.line(indent + 1, "type: b1");
+                    self.line(indent + 1, "repeat: expr");
+                    self.line(
+                        indent + 1,
+                        &format!("repeat-expr: {}", yaml_str(&kaitai_expr(bits))),
+                    );
+                }
+            }
+        }
+        if let Some(bytes) = bytes {
+            self.line(indent, &format!("- id: {name}_pad_bytes_{position}"));
+            self.line(
+                indent + 1,
+                &format!("size: {}", yaml_str(&kaitai_expr(bytes))),
+            );
+        }
+    }
+
+    /// Emit the reserved range before a field, checking its value in strict mode
+    fn emit_reserved(&mut self, indent: usize, container: &Schema, name: &str, f: &FieldSchema) {
+        let bits = match (f.reserved_bits, f.reserved_bytes) {
+            (Some(bits), _) => bits.parse::<usize>().ok(),
+            (None, Some(bytes)) => bytes.parse::<usize>().ok().map(|n| n * 8),
+            (None, None) => return,
+        };
+
+        self.line(indent, &format!("- id: {name}_reserved"));
+        match (bits, f.reserved_bytes) {
+            (Some(n), _) if n <= 64 => {
+                self.line(indent + 1, &format!("type: b{n}"));
+                if f.reserved_strict || container.reserved_strict {
+                    let value = f.reserved_value.and_then(parse_int).unwrap_or(0);
+                    self.line(indent + 1, &format!("valid: {value}"));
+                }
+            }
+            (_, Some(bytes)) => {
+                self.line(
+                    indent + 1,
+                    &format!("size: {}", yaml_str(&kaitai_expr(bytes))),
+                );
+            }
+            (_, None) => {
+                let bits = f.reserved_bits.unwrap_or_default();
+                self.line(indent + 1, "type: b1");
+                self.line(indent + 1, "repeat: expr");
+                self.line(
+                    indent + 1,
+                    &format!("repeat-expr: {}", yaml_str(&kaitai_expr(bits))),
+                );
+            }
+        }
+    }
+
+    /// Emit a `magic` value as fixed `contents`
+    fn emit_magic(&mut self, indent: usize, magic: &[u8]) {
+        let bytes = magic
+            .iter()
+            .map(|b| format!("{b:#04x}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.line(indent, "- id: magic");
+        self.line(indent + 1, &format!("contents: [{bytes}]"));
+    }
+
+    /// Emit the padding up to a multiple of `bits` or `bytes`
+    fn emit_alignment(
+        &mut self,
+        indent: usize,
+        container: &Schema,
+        name: &str,
+        position: &str,
+        bits: Option<&str>,
+        bytes: Option<&str>,
+    ) {
+        if let Some(bits) = bits {
+            // `_io.pos` is in bytes
+            self.line(indent, &format!("- id: {name}_align_bits_{position}"));
+            self.line(indent + 1, "size: 0");
+            self.line(
+                indent + 1,
+                &format!(
+                    "doc: {}",
+                    yaml_str(&format!("deku aligns to `{bits}` bits"))
+                ),
+            );
+        } else if let Some(bytes) = bytes {
+            let bytes = kaitai_expr(bytes);
+            let bytes = if bytes.contains(' ') {
+                format!("({bytes})")
+            } else {
+                bytes
+            };
+            self.line(indent, &format!("- id: {name}_align_{position}"));
+            self.line(
+                indent + 1,
+                &format!(
+                    "size: {}",
+                    yaml_str(&format!("({bytes} - _io.pos % {bytes}) % {bytes}"))
+                ),
+            );
+            if !container.align_input {
+                self.line(
+                    indent + 1,
+                    &format!(
+                        "doc: {}",
+                        yaml_str(&format!(
+                            "deku aligns relative to the start of `{}`",
+                            container.ident
+                        ))
+                    ),
+                );
+            }
+        }
+    }
+
+    /// Emit `type` (and related keys) of a single value
+    fn emit_type(
+        &mut self,
+        indent: usize,
+        ctx: Option<&str>,
+        ty: &str,
+        bits: Option<usize>,
+        bytes: Option<usize>,
+        endian: Option<&str>,
+    ) {
+        let suffix = match endian {
+            Some("big") if self.meta_endian != "be" => "be",
+            Some("little") if self.meta_endian != "le" => "le",
+            _ => "",
+        };
+
+        match TypeShape::parse(ty) {
+            TypeShape::Primitive(p) => {
+                let size = bytes.map(|n| n * 8).unwrap_or_else(|| p.bit_size());
+                let ty = match (p, bits) {
+                    (_, Some(n)) => Some(format!("b{n}")),
+                    (Primitive::Float(n), None) => Some(format!("f{}{suffix}", n / 8)),
+                    (Primitive::Signed(_), None) => int_type('s', size, suffix),
+                    (_, None) => int_type('u', size, suffix),
+                };
+                match ty {
+                    Some(ty) => self.line(indent, &format!("type: {ty}")),
+                    None => self.line(indent, &format!("size: {}", size / 8)),
+                }
+            }
+            TypeShape::Other("CString") => {
+                self.line(indent, "type: strz");
+                self.line(indent, "encoding: ASCII");
+            }
+            TypeShape::Other("Ipv4Addr") => {
+                self.line(indent, &format!("type: u4{suffix}"));
+            }
+            TypeShape::Other("Ipv6Addr") => {
+                self.line(indent, "size: 16");
+            }
+            TypeShape::Other(name) => {
+                let type_name = super::snake_case(name);
+                // `endian`, `bits` and `bytes` are passed before `ctx`, see `gen_field_args`
+                let implicit = [endian, bits.map(|_| ""), bytes.map(|_| "")]
+                    .iter()
+                    .filter(|v| v.is_some())
+                    .count();
+                let args = self.ctx_args(name, implicit, ctx);
+                if args.is_empty() {
+                    self.line(indent, &format!("type: {type_name}"));
+                } else {
+                    let ty = format!("{type_name}({})", args.join(", "));
+                    self.line(indent, &format!("type: {}", yaml_str(&ty)));
+                }
+            }
+            TypeShape::Vec(_) | TypeShape::Array(..) | TypeShape::Option(_) => {
+                self.line(
+                    indent,
+                    &format!("doc: {}", yaml_str(&format!("nested `{ty}`"))),
+                );
+            }
+        }
+    }
+
+    /// Arguments passed to a user type, keeping only those with a Kaitai parameter
+    ///
+    /// `implicit` is the number of arguments deku passes before `ctx`
+    fn ctx_args(&self, type_name: &str, implicit: usize, ctx: Option<&str>) -> Vec<String> {
+        let args = ctx.map(split_top_level).unwrap_or_default();
+        match self.types.iter().find(|t| t.ident == type_name) {
+            Some(schema) => {
+                let mut all_args = alloc::vec![None; implicit];
+                all_args.extend(args.into_iter().map(Some));
+
+                ctx_params(schema.ctx)
+                    .iter()
+                    .zip(all_args)
+                    .filter_map(|((_, ty), arg)| ty.as_ref().and(arg))
+                    .map(kaitai_expr)
+                    .collect()
+            }
+            None => args.into_iter().map(kaitai_expr).collect(),
+        }
+    }
+}
+
+/// Integer type, using bit types for sizes Kaitai has no integer for
+fn int_type(sign: char, bits: usize, suffix: &str) -> Option<String> {
+    match bits {
+        8 => Some(format!("{sign}1")),
+        16 | 32 | 64 => Some(format!("{sign}{}{suffix}", bits / 8)),
+        n if n < 64 => Some(format!("b{n}")),
+        _ => None,
+    }
+}
+
+/// Kaitai types of the `ctx` parameters, `None` for those without an equivalent
+fn ctx_params(ctx: Option<&str>) -> Vec<(String, Option<String>)> {
+    super::ctx_params(ctx)
+        .into_iter()
+        .map(|(name, shape)| {
+            let ty = match shape {
+                TypeShape::Primitive(Primitive::Bool) => Some("bool".to_string()),
+                TypeShape::Primitive(Primitive::Float(n)) => Some(format!("f{}", n / 8)),
+                TypeShape::Primitive(Primitive::Signed(n)) => Some(format!("s{}", n / 8)),
+                TypeShape::Primitive(Primitive::Unsigned(n)) if n <= 64 => {
+                    Some(format!("u{}", n / 8))
+                }
+                _ => None,
+            };
+            (name.to_string(), ty)
+        })
+        .collect()
+}
+
+/// Translate a Rust expression into a Kaitai expression, on a best-effort basis
+fn kaitai_expr(expr: &str) -> String {
+    let tokens = super::expr_tokens(expr);
+    let tokens = tokens.iter().map(String::as_str).collect::<Vec<_>>();
+
+    let mut out: Vec<&str> = Vec::with_capacity(tokens.len());
+    // true if the previous token ends an operand, to tell unary from binary operators
+    let mut prev_is_value = false;
+
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = tokens[i];
+        let mut is_value = false;
+        match token {
+            // unary dereference/reference
+            "*" | "&" if !prev_is_value => {}
+            "&&" => out.push("and"),
+            "||" => out.push("or"),
+            "!" => out.push("not"),
+            // casts
+            "as" => {
+                i += 1;
+                is_value = true;
+            }
+            "__deku_byte_offset" => {
+                out.push("_io.pos");
+                is_value = true;
+            }
+            "__deku_bit_offset" => {
+                out.push("(_io.pos * 8)");
+                is_value = true;
+            }
+            _ => {
+                out.push(token);
+                is_value = token
+                    .chars()
+                    .last()
+                    .is_some_and(|c| c.is_alphanumeric() || c == '_' || c == ')' || c == ']');
+            }
+        }
+        prev_is_value = is_value;
+        i += 1;
+    }
+
+    let mut joined = String::with_capacity(expr.len());
+    let mut prev: Option<&str> = None;
+    for token in out {
+        let glue = match (prev, token) {
+            (None, _) => true,
+            (Some(_), ")" | "]" | "," | "." | "::") => true,
+            (Some("(" | "[" | "." | "::"), _) => true,
+            (Some(p), "(" | "[") => p.ends_with(|c: char| c.is_alphanumeric() || c == '_'),
+            _ => false,
+        };
+        if !glue {
+            joined.push(' ');
+        }
+        joined.push_str(token);
+        prev = Some(token);
+    }
+
+    joined.replace(".len()", ".size")
+}
+
+/// Translate an `until` closure into a Kaitai `repeat-until` expression using `_`
+fn kaitai_predicate(until: &str) -> String {
+    let mut parts = until.splitn(3, '|');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(""), Some(arg), Some(body)) => {
+            let arg = arg.split(':').next().unwrap_or("").trim();
+            let body = body
+                .split_whitespace()
+                .map(|t| if t == arg { "_" } else { t })
+                .collect::<Vec<_>>()
+                .join(" ");
+            kaitai_expr(&body)
+        }
+        _ => kaitai_expr(until),
+    }
+}
+
+/// Quote a YAML scalar when required
+fn yaml_str(s: &str) -> String {
+    let needs_quotes = s.is_empty()
+        || s.contains(": ")
+        || s.contains(" #")
+        || s.ends_with(':')
+        || s.starts_with(|c: char| "-?:,[]{}#&*!|>'\"%@`".contains(c) || c.is_whitespace());
+    if needs_quotes {
+        format!("'{}'", s.replace('\'', "''"))
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest(
+        input,
+        expected,
+        case("count", "count"),
+        case("* field_a == 0x01", "field_a == 0x01"),
+        case("* a & 0x0F", "a & 0x0F"),
+        case("data . len () > 2 && ! * flag", "data.size > 2 and not flag"),
+        case("(* len as usize) - 4", "(len) - 4"),
+        case("usize :: from(* len) * 2", "usize::from(len) * 2"),
+        case("__deku_byte_offset + 1", "_io.pos + 1")
+    )]
+    fn test_kaitai_expr(input: &str, expected: &str) {
+        assert_eq!(expected, kaitai_expr(input));
+    }
+
+    #[rstest(
+        input,
+        expected,
+        case("| v : & u8 | * v == 0", "_ == 0"),
+        case("| b | b . len () == 0", "_.size == 0")
+    )]
+    fn test_kaitai_predicate(input: &str, expected: &str) {
+        assert_eq!(expected, kaitai_predicate(input));
+    }
+
+    #[rstest(input, expected,
+        case("1", Some(1)),
+        case("0x10", Some(16)),
+        case("0b1_0", Some(2)),
+        case("- 1", Some(-1)),
+        case("Foo::Bar", None),
+    )]
+    fn test_parse_int(input: &str, expected: Option<i128>) {
+        assert_eq!(expected, parse_int(input));
+    }
+
+    #[rstest(
+        input,
+        expected,
+        case("a", "a"),
+        case("a::b", "a::b"),
+        case("_", "_"),
+        case("'x'", "'''x'''"),
+        case("*x", "'*x'")
+    )]
+    fn test_yaml_str(input: &str, expected: &str) {
+        assert_eq!(expected, yaml_str(input));
+    }
+}
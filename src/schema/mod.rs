@@ -0,0 +1,646 @@
+/*!
+Static layout description of derived types
+
+Deriving [DekuSchema](crate::DekuSchema) exposes the layout described by the
+`#[deku]` attributes of a struct or enum as a constant [Schema]. This can be used
+to build generic tooling on top of deku types, such as hex viewers, fuzzers or
+documentation generators.
+
+Expressions given to attributes such as `count`, `until` or `cond` are stored as
+the string representation of their tokens, as seen by the compiler.
+
+# Example
+
+```rust
+use deku::prelude::*;
+use deku::schema::SchemaData;
+
+#[derive(DekuRead, DekuWrite, DekuSchema)]
+#[deku(endian = "big")]
+struct DekuTest {
+    #[deku(bits = "4")]
+    field_a: u8,
+    #[deku(bits = "4")]
+    count: u8,
+    #[deku(count = "count")]
+    data: Vec<u16>,
+}
+
+let schema = DekuTest::SCHEMA;
+assert_eq!("DekuTest", schema.ident);
+assert_eq!(Some("big"), schema.endian);
+
+let fields = match schema.data {
+    SchemaData::Struct(fields) => fields,
+    SchemaData::Enum(_) => unreachable!(),
+};
+assert_eq!(Some("field_a"), fields[0].ident);
+assert_eq!(Some(4), fields[0].bits);
+assert_eq!("Vec<u16>", fields[2].ty);
+assert_eq!(Some("count"), fields[2].count);
+```
+*/
+
+#[cfg(feature = "c_header")]
+pub mod c_header;
+#[cfg(feature = "ksy")]
+pub mod ksy;
+#[cfg(feature = "wireshark")]
+pub mod wireshark;
+
+/// Description of a derived struct or enum
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Schema {
+    /// Name of the type
+    pub ident: &'static str,
+
+    /// Endianness for all fields: `big`, `little` or an expression
+    pub endian: Option<&'static str>,
+
+    /// Field selecting the endianness of the fields after it, see
+    /// [endian_from](crate::attributes#endian_from)
+    pub endian_from: Option<&'static str>,
+
+    /// Magic value that must appear at the start of the data
+    pub magic: Option<&'static [u8]>,
+
+    /// Context argument list, e.g. `a: u8, b: u16`
+    pub ctx: Option<&'static str>,
+
+    /// Default context values
+    pub ctx_default: Option<&'static str>,
+
+    /// Byte alignment of the end of the data
+    pub align: Option<&'static str>,
+
+    /// Alignment is relative to the input instead of the start of the data
+    pub align_input: bool,
+
+    /// Default value written to padding, a `u8` expression or a byte string
+    pub pad_fill: Option<&'static str>,
+
+    /// Padding is checked against the fill value when reading
+    pub pad_strict: bool,
+
+    /// Reserved bits are checked against their value when reading
+    pub reserved_strict: bool,
+
+    /// Struct fields or enum variants
+    pub data: SchemaData,
+}
+
+impl Schema {
+    /// Schema of `ident` without any attribute, the other fields can be set afterwards
+    pub const fn new(ident: &'static str, data: SchemaData) -> Self {
+        Self {
+            ident,
+            endian: None,
+            endian_from: None,
+            magic: None,
+            ctx: None,
+            ctx_default: None,
+            align: None,
+            align_input: false,
+            pad_fill: None,
+            pad_strict: false,
+            reserved_strict: false,
+            data,
+        }
+    }
+
+    /// Fields of a struct, `None` for an enum
+    pub fn fields(&self) -> Option<&'static [FieldSchema]> {
+        match self.data {
+            SchemaData::Struct(fields) => Some(fields),
+            SchemaData::Enum(_) => None,
+        }
+    }
+
+    /// Index of the field named by `endian_from`, the fields after it use the endianness it
+    /// selects
+    pub fn endian_from_index(&self) -> Option<usize> {
+        let name = self.endian_from?;
+        self.fields()?
+            .iter()
+            .enumerate()
+            .position(|(i, f)| f.ident.map_or_else(|| name.parse() == Ok(i), |v| v == name))
+    }
+
+    /// Enum description, `None` for a struct
+    pub fn as_enum(&self) -> Option<&EnumSchema> {
+        match self.data {
+            SchemaData::Struct(_) => None,
+            SchemaData::Enum(ref e) => Some(e),
+        }
+    }
+}
+
+/// Contents of a [Schema]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SchemaData {
+    /// Fields of a struct, in order
+    Struct(&'static [FieldSchema]),
+
+    /// Description of an enum
+    Enum(EnumSchema),
+}
+
+/// Description of an enum and its variants
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct EnumSchema {
+    /// Expression providing the variant id, see [id](crate::attributes#id-top-level)
+    pub id: Option<&'static str>,
+
+    /// Type of the variant id, see [type](crate::attributes#type)
+    pub id_type: Option<&'static str>,
+
+    /// Bit size of the variant id
+    pub bits: Option<usize>,
+
+    /// Byte size of the variant id
+    pub bytes: Option<usize>,
+
+    /// Variant id uses custom reader and/or writer code, see
+    /// [id_reader](crate::attributes#id_reader-id_writer)
+    pub custom_id: bool,
+
+    /// Variants, in order of declaration
+    pub variants: &'static [VariantSchema],
+}
+
+impl EnumSchema {
+    /// Enum schema without any attribute, the other fields can be set afterwards
+    pub const fn new(variants: &'static [VariantSchema]) -> Self {
+        Self {
+            id: None,
+            id_type: None,
+            bits: None,
+            bytes: None,
+            custom_id: false,
+            variants,
+        }
+    }
+}
+
+/// Description of an enum variant
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct VariantSchema {
+    /// Name of the variant
+    pub ident: &'static str,
+
+    /// Variant `id` value
+    pub id: Option<&'static str>,
+
+    /// Variant `id_pat` value
+    pub id_pat: Option<&'static str>,
+
+    /// Explicit discriminant of the variant
+    pub discriminant: Option<&'static str>,
+
+    /// Variant is read when no other variant matches
+    pub default: bool,
+
+    /// Variant captures unknown ids, its first field holds the id and an optional
+    /// second field the remaining bytes
+    pub unknown: bool,
+
+    /// Variant uses custom reader and/or writer code
+    pub custom: bool,
+
+    /// Magic value following the variant id
+    pub magic: Option<&'static [u8]>,
+
+    /// Default endianness of the variant fields, already applied to [fields](Self::fields)
+    pub endian: Option<&'static str>,
+
+    /// Context bindings available to the variant fields, as `name = expr`
+    pub ctx: Option<&'static str>,
+
+    /// Fields of the variant, in order
+    pub fields: &'static [FieldSchema],
+}
+
+impl VariantSchema {
+    /// Variant schema without any attribute, the other fields can be set afterwards
+    pub const fn new(ident: &'static str, fields: &'static [FieldSchema]) -> Self {
+        Self {
+            ident,
+            id: None,
+            id_pat: None,
+            discriminant: None,
+            default: false,
+            unknown: false,
+            custom: false,
+            magic: None,
+            endian: None,
+            ctx: None,
+            fields,
+        }
+    }
+
+    /// The id of the variant as written in the source, from `id`, `id_pat` or the discriminant
+    pub fn id_str(&self) -> Option<&'static str> {
+        self.id.or(self.id_pat).or(self.discriminant)
+    }
+
+    /// Fields read from the data after the id: all fields except the id of an `unknown` variant
+    pub fn data_fields(&self) -> &'static [FieldSchema] {
+        if self.unknown {
+            &self.fields[1..]
+        } else {
+            self.fields
+        }
+    }
+}
+
+/// Description of a struct or variant field
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FieldSchema {
+    /// Name of the field, `None` for un-named fields
+    pub ident: Option<&'static str>,
+
+    /// Rust type of the field
+    pub ty: &'static str,
+
+    /// Endianness of the field: `big`, `little` or an expression
+    pub endian: Option<&'static str>,
+
+    /// Bit size of the field
+    pub bits: Option<usize>,
+
+    /// Byte size of the field
+    pub bytes: Option<usize>,
+
+    /// Expression providing the element count of a container
+    pub count: Option<&'static str>,
+
+    /// Expression providing the number of bits to read into a container
+    pub bits_read: Option<&'static str>,
+
+    /// Expression providing the number of bytes to read into a container
+    pub bytes_read: Option<&'static str>,
+
+    /// Predicate deciding when to stop reading elements into a container
+    pub until: Option<&'static str>,
+
+    /// Predicate deciding when to stop reading elements into a container, the matching
+    /// element isn't stored
+    pub until_exclusive: Option<&'static str>,
+
+    /// Element ending a container, consumed but not stored
+    pub terminator: Option<&'static str>,
+
+    /// Expression providing the number of bytes the field is read from
+    pub window_bytes: Option<&'static str>,
+
+    /// Handling of the unread bytes of the window: `skip`, `error` or `keep`
+    pub window_rest: Option<&'static str>,
+
+    /// Expression providing the byte offset of the field from the start of the input
+    pub at: Option<&'static str>,
+
+    /// Context passed to the field
+    pub ctx: Option<&'static str>,
+
+    /// Function applied to the result of reading
+    pub map: Option<&'static str>,
+    /// Function applied to the value before writing
+    pub write_map: Option<&'static str>,
+    /// On-wire type the field is converted from/into
+    pub as_type: Option<&'static str>,
+    /// Conversion with `TryFrom`/`TryInto` instead of `From`/`Into`
+    pub try_from: bool,
+
+    /// Condition to read the field
+    pub cond: Option<&'static str>,
+
+    /// Value used when the field is skipped or `cond` is false
+    pub default: Option<&'static str>,
+
+    /// Field isn't read or written
+    pub skip: bool,
+
+    /// Field is read but not stored
+    pub temp: bool,
+
+    /// Bits skipped before the field
+    pub pad_bits_before: Option<&'static str>,
+
+    /// Bytes skipped before the field
+    pub pad_bytes_before: Option<&'static str>,
+
+    /// Bits skipped after the field
+    pub pad_bits_after: Option<&'static str>,
+
+    /// Bytes skipped after the field
+    pub pad_bytes_after: Option<&'static str>,
+
+    /// Bit alignment before the field
+    pub align_bits_before: Option<&'static str>,
+
+    /// Byte alignment before the field
+    pub align_before: Option<&'static str>,
+
+    /// Bit alignment after the field
+    pub align_bits_after: Option<&'static str>,
+
+    /// Byte alignment after the field
+    pub align_after: Option<&'static str>,
+
+    /// Value written to the padding of the field, a `u8` expression or a byte string
+    pub pad_fill: Option<&'static str>,
+
+    /// Padding of the field is checked against the fill value when reading
+    pub pad_strict: bool,
+
+    /// Reserved bits before the field
+    pub reserved_bits: Option<&'static str>,
+
+    /// Reserved bytes before the field
+    pub reserved_bytes: Option<&'static str>,
+
+    /// Value of the reserved bits, zero when `None`
+    pub reserved_value: Option<&'static str>,
+
+    /// Reserved bits are checked against their value when reading
+    pub reserved_strict: bool,
+
+    /// Assertion on the field
+    pub assert: Option<&'static str>,
+
+    /// Expected value of the field
+    pub assert_eq: Option<&'static str>,
+
+    /// Field uses custom reader and/or writer code
+    pub custom: bool,
+}
+
+impl FieldSchema {
+    /// Field schema without any attribute, the other fields can be set afterwards
+    pub const fn new(ident: Option<&'static str>, ty: &'static str) -> Self {
+        Self {
+            ident,
+            ty,
+            endian: None,
+            bits: None,
+            bytes: None,
+            count: None,
+            bits_read: None,
+            bytes_read: None,
+            until: None,
+            until_exclusive: None,
+            terminator: None,
+            window_bytes: None,
+            window_rest: None,
+            at: None,
+            ctx: None,
+            map: None,
+            write_map: None,
+            as_type: None,
+            try_from: false,
+            cond: None,
+            default: None,
+            skip: false,
+            temp: false,
+            pad_bits_before: None,
+            pad_bytes_before: None,
+            pad_bits_after: None,
+            pad_bytes_after: None,
+            align_bits_before: None,
+            align_before: None,
+            align_bits_after: None,
+            align_after: None,
+            pad_fill: None,
+            pad_strict: false,
+            reserved_bits: None,
+            reserved_bytes: None,
+            reserved_value: None,
+            reserved_strict: false,
+            assert: None,
+            assert_eq: None,
+            custom: false,
+        }
+    }
+
+    /// Fixed size of the field in bits, from `bits` or `bytes`
+    pub fn bit_size(&self) -> Option<usize> {
+        self.bits.or_else(|| self.bytes.map(|b| b * 8))
+    }
+
+    /// Type of the field in the data: `as_type` if present, otherwise `ty`
+    pub fn wire_ty(&self) -> &'static str {
+        self.as_type.unwrap_or(self.ty)
+    }
+
+    /// Shape of the field type in the data, see [TypeShape::parse]
+    pub fn shape(&self) -> TypeShape<'static> {
+        TypeShape::parse(self.wire_ty())
+    }
+}
+
+/// Primitive type of a field
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Primitive {
+    /// Unsigned integer of the given bit size
+    Unsigned(usize),
+    /// Signed integer of the given bit size
+    Signed(usize),
+    /// Floating point number of the given bit size
+    Float(usize),
+    /// Boolean, read as a `u8`
+    Bool,
+}
+
+impl Primitive {
+    /// Size of the type in bits
+    pub fn bit_size(self) -> usize {
+        match self {
+            Primitive::Unsigned(n) | Primitive::Signed(n) | Primitive::Float(n) => n,
+            Primitive::Bool => 8,
+        }
+    }
+}
+
+/// Shape of a field type, as far as it can be determined from its name
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TypeShape<'a> {
+    /// A primitive type such as `u8` or `f32`
+    Primitive(Primitive),
+    /// `Vec<T>`, with the element type
+    Vec(&'a str),
+    /// `[T; N]`, with the element type and length
+    Array(&'a str, &'a str),
+    /// `Option<T>`, with the inner type
+    Option(&'a str),
+    /// Any other type, with its path prefix removed
+    Other(&'a str),
+}
+
+impl<'a> TypeShape<'a> {
+    /// Determine the shape of a type as stored in [FieldSchema::ty]
+    ///
+    /// ```rust
+    /// # use deku::schema::{Primitive, TypeShape};
+    /// assert_eq!(TypeShape::Primitive(Primitive::Unsigned(16)), TypeShape::parse("u16"));
+    /// assert_eq!(TypeShape::Vec("u8"), TypeShape::parse("Vec<u8>"));
+    /// assert_eq!(TypeShape::Array("u8", "4"), TypeShape::parse("[u8; 4]"));
+    /// assert_eq!(TypeShape::Other("CString"), TypeShape::parse("std::ffi::CString"));
+    /// ```
+    pub fn parse(ty: &'a str) -> Self {
+        let ty = ty.trim();
+
+        if let Some(array) = ty.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+            if let Some((inner, len)) = array.rsplit_once(';') {
+                return TypeShape::Array(inner.trim(), len.trim());
+            }
+        }
+
+        if let Some((outer, inner)) = ty.strip_suffix('>').and_then(|t| t.split_once('<')) {
+            let inner = inner.trim();
+            match last_segment(outer) {
+                "Vec" => return TypeShape::Vec(inner),
+                "Option" => return TypeShape::Option(inner),
+                "Box" => return TypeShape::parse(inner),
+                _ => {}
+            }
+        }
+
+        let name = last_segment(ty);
+        let primitive = match name {
+            "u8" => Primitive::Unsigned(8),
+            "u16" => Primitive::Unsigned(16),
+            "u32" => Primitive::Unsigned(32),
+            "u64" => Primitive::Unsigned(64),
+            "u128" => Primitive::Unsigned(128),
+            "i8" => Primitive::Signed(8),
+            "i16" => Primitive::Signed(16),
+            "i32" => Primitive::Signed(32),
+            "i64" => Primitive::Signed(64),
+            "i128" => Primitive::Signed(128),
+            "f32" => Primitive::Float(32),
+            "f64" => Primitive::Float(64),
+            "bool" => Primitive::Bool,
+            _ => return TypeShape::Other(name),
+        };
+
+        TypeShape::Primitive(primitive)
+    }
+}
+
+/// Remove the path of a type outside of its generics: `std::ffi::CString` -> `CString`
+fn last_segment(ty: &str) -> &str {
+    let end = ty.find('<').unwrap_or(ty.len());
+    match ty[..end].rfind("::") {
+        Some(idx) => &ty[idx + 2..],
+        None => ty,
+    }
+}
+
+/// Convert a type or variant name to snake case: `Ipv4Header` -> `ipv4_header`
+#[cfg(any(feature = "c_header", feature = "ksy", feature = "wireshark"))]
+pub(crate) fn snake_case(s: &str) -> alloc::string::String {
+    let chars = s
+        .trim_start_matches("r#")
+        .chars()
+        .collect::<alloc::vec::Vec<_>>();
+    let mut out = alloc::string::String::with_capacity(s.len() + 4);
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i != 0 {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if prev.is_lowercase() || prev.is_numeric() || (prev.is_uppercase() && next_is_lower) {
+                out.push('_');
+            }
+        }
+        out.extend(c.to_lowercase());
+    }
+    out
+}
+
+/// `ctx = "a: u8, endian: Endian"` -> `[("a", Primitive(Unsigned(8))), ("endian", Other("Endian"))]`
+#[cfg(any(feature = "ksy", feature = "wireshark"))]
+pub(crate) fn ctx_params(ctx: Option<&str>) -> alloc::vec::Vec<(&str, TypeShape<'_>)> {
+    match ctx {
+        Some(ctx) => split_top_level(ctx)
+            .into_iter()
+            .filter_map(|arg| {
+                let (name, ty) = arg.split_once(':')?;
+                Some((name.trim(), TypeShape::parse(ty.trim())))
+            })
+            .collect(),
+        None => alloc::vec::Vec::new(),
+    }
+}
+
+/// Split on commas which aren't nested in brackets
+#[cfg(any(feature = "ksy", feature = "wireshark"))]
+pub(crate) fn split_top_level(s: &str) -> alloc::vec::Vec<&str> {
+    let mut parts = alloc::vec::Vec::new();
+    let mut depth = 0i32;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' | '[' | '<' | '{' => depth += 1,
+            ')' | ']' | '>' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(s[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if !s[start..].trim().is_empty() {
+        parts.push(s[start..].trim());
+    }
+    parts
+}
+
+/// Variants with an integer id: `[(1, "VarA"), (2, "VarB")]`
+#[cfg(any(feature = "ksy", feature = "wireshark"))]
+pub(crate) fn enum_table_of(data: &EnumSchema) -> alloc::vec::Vec<(i128, &'static str)> {
+    data.variants
+        .iter()
+        .filter(|v| v.id_pat.is_none())
+        .filter_map(|v| {
+            v.id.or(v.discriminant)
+                .and_then(parse_int)
+                .map(|id| (id, v.ident))
+        })
+        .collect()
+}
+
+/// Parse an integer literal: `12`, `0x0C`, `0b1100`, `0o14`, `-1`
+#[cfg(any(feature = "c_header", feature = "ksy", feature = "wireshark"))]
+pub(crate) fn parse_int(s: &str) -> Option<i128> {
+    let s = s.trim().replace('_', "");
+    let (neg, s) = match s.strip_prefix('-') {
+        Some(s) => (true, s.trim().into()),
+        None => (false, s),
+    };
+    let value = if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        i128::from_str_radix(hex, 16).ok()?
+    } else if let Some(bin) = s.strip_prefix("0b") {
+        i128::from_str_radix(bin, 2).ok()?
+    } else if let Some(oct) = s.strip_prefix("0o") {
+        i128::from_str_radix(oct, 8).ok()?
+    } else {
+        s.parse::<i128>().ok()?
+    };
+    Some(if neg { -value } else { value })
+}
+
+/// Split a stringified expression into tokens, separating brackets
+#[cfg(any(feature = "ksy", feature = "wireshark"))]
+pub(crate) fn expr_tokens(expr: &str) -> alloc::vec::Vec<alloc::string::String> {
+    expr.replace('(', " ( ")
+        .replace(')', " ) ")
+        .replace('[', " [ ")
+        .replace(']', " ] ")
+        .split_whitespace()
+        .map(alloc::string::String::from)
+        .collect()
+}
@@ -0,0 +1,1112 @@
+/*!
+Generate a [Wireshark](https://www.wireshark.org) Lua dissector from a [Schema]
+
+Requires the `wireshark` feature.
+
+The generated script declares a `Proto` named after the root type, a `ProtoField`
+for each field, value strings for enum ids and one dissector function per type.
+Fields are read at bit offsets, so bit-sized fields are shown with their own
+value, and deku types given in the `types` argument of [to_lua] are shown as
+subtrees. Expressions used by `count`, `cond`, `bytes_read` and similar attributes
+are translated to Lua where possible; dissection stops at the first field which
+can't be decoded, such as fields using a custom reader or `map`.
+
+Registering the dissector is left to the user, e.g.
+`DissectorTable.get("udp.port"):add(5000, header)`.
+
+# Example
+
+```rust
+use deku::prelude::*;
+
+#[derive(DekuRead, DekuWrite, DekuSchema)]
+#[deku(endian = "big")]
+struct Header {
+    #[deku(bits = "4")]
+    version: u8,
+    #[deku(bits = "4")]
+    count: u8,
+    #[deku(count = "count")]
+    items: Vec<u16>,
+}
+
+let lua = deku::schema::wireshark::to_lua(&Header::SCHEMA, &[]);
+assert!(lua.contains(r#"local header = Proto("header", "Header")"#));
+assert!(lua.contains(r#"f["version"] = ProtoField.uint8("header.version", "version", base.DEC)"#));
+assert!(lua.contains("    for _ = 1, v_count do\n"));
+```
+*/
+
+use super::{
+    enum_table_of, parse_int, split_top_level, EnumSchema, FieldSchema, Primitive, Schema,
+    SchemaData, TypeShape,
+};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Functions used by the generated dissectors
+const HELPERS: &str = r#"-- range covering `bits` bits from the bit offset `offset`
+local function bit_range(buffer, offset, bits)
+    return buffer(math.floor(offset / 8), math.ceil((offset % 8 + bits) / 8))
+end
+
+local function read_uint(buffer, offset, bits, little)
+    local range = bit_range(buffer, offset, bits)
+    if offset % 8 ~= 0 or bits % 8 ~= 0 then
+        return range:bitfield(offset % 8, bits)
+    elseif bits > 32 then
+        return little and range:le_uint64() or range:uint64()
+    else
+        return little and range:le_uint() or range:uint()
+    end
+end
+
+local function read_int(buffer, offset, bits, little)
+    local range = bit_range(buffer, offset, bits)
+    if offset % 8 ~= 0 or bits % 8 ~= 0 then
+        local value = range:bitfield(offset % 8, bits)
+        if value >= 2 ^ (bits - 1) then
+            value = value - 2 ^ bits
+        end
+        return value
+    elseif bits > 32 then
+        return little and range:le_int64() or range:int64()
+    else
+        return little and range:le_int() or range:int()
+    end
+end
+
+local function read_float(buffer, offset, bits, little)
+    local range = bit_range(buffer, offset, bits)
+    return little and range:le_float() or range:float()
+end
+
+local function add_value(tree, field, buffer, offset, bits, value)
+    return tree:add(field, bit_range(buffer, offset, bits), value)
+end
+
+local function add_range(tree, field, range, little)
+    if little then
+        return tree:add_le(field, range)
+    end
+    return tree:add(field, range)
+end
+
+-- dissect a nested type in its own subtree
+local function add_nested(tree, label, buffer, offset, dissect, ...)
+    local subtree = tree:add(buffer(math.floor(offset / 8)), label)
+    local end_offset = dissect(buffer, subtree, offset, ...)
+    if end_offset ~= nil then
+        subtree:set_len(math.ceil(end_offset / 8) - math.floor(offset / 8))
+    end
+    return end_offset
+end
+"#;
+
+/// Generate a Lua dissector for `root`
+///
+/// `types` are the schemas of deku types used by the fields of `root` (and
+/// recursively of those types), each gets its own dissector function.
+pub fn to_lua(root: &Schema, types: &[Schema]) -> String {
+    let proto = super::snake_case(root.ident);
+
+    let mut lua = Lua {
+        proto: proto.clone(),
+        types,
+        value_strings: Vec::new(),
+        fields: Vec::new(),
+        functions: String::new(),
+        align_start: "start",
+    };
+
+    let mut emitted = Vec::new();
+    lua.emit_dissect_fn(root, String::new());
+    emitted.push(root.ident);
+    for ty in types {
+        if !emitted.contains(&ty.ident) {
+            lua.emit_dissect_fn(ty, format!("{}.", super::snake_case(ty.ident)));
+            emitted.push(ty.ident);
+        }
+    }
+
+    let mut out = format!(
+        "-- Wireshark dissector for `{}`, generated by deku\n\n",
+        root.ident
+    );
+    out += &format!("local {proto} = Proto(\"{proto}\", \"{}\")\n\n", root.ident);
+
+    for value_string in &lua.value_strings {
+        out += value_string;
+        out.push('\n');
+    }
+    if !lua.value_strings.is_empty() {
+        out.push('\n');
+    }
+
+    out += &format!("local f = {proto}.fields\n");
+    for (key, decl) in &lua.fields {
+        out += &format!("f[\"{key}\"] = {decl}\n");
+    }
+    out.push('\n');
+
+    out += HELPERS;
+    out.push('\n');
+
+    let names = emitted
+        .iter()
+        .map(|ident| format!("dissect_{}", super::snake_case(ident)))
+        .collect::<Vec<_>>();
+    out += &format!("local {}\n\n", names.join(", "));
+    out += &lua.functions;
+
+    // context parameters of the root type come from `ctx_default`
+    let root_args = root
+        .ctx_default
+        .map(split_top_level)
+        .unwrap_or_default()
+        .into_iter()
+        .zip(super::ctx_params(root.ctx))
+        .filter(|(_, (_, shape))| matches!(shape, TypeShape::Primitive(_)))
+        .map(|(arg, _)| lua_expr(arg, &[]).unwrap_or_else(|| "nil".to_string()))
+        .fold(String::new(), |acc, arg| acc + ", " + &arg);
+    let root_little = match root.endian {
+        Some("big") => "false",
+        _ => "true",
+    };
+
+    out += &format!("function {proto}.dissector(buffer, pinfo, tree)\n");
+    out += &format!("    pinfo.cols.protocol = {proto}.name\n");
+    out += &format!(
+        "    local subtree = tree:add({proto}, buffer(), \"{}\")\n",
+        root.ident
+    );
+    out += &format!(
+        "    local offset = dissect_{proto}(buffer, subtree, 0, {root_little}{root_args})\n"
+    );
+    out += "    if offset == nil then\n";
+    out += "        return 0\n";
+    out += "    end\n";
+    out += "    subtree:set_len(math.ceil(offset / 8))\n";
+    out += "    return math.ceil(offset / 8)\n";
+    out += "end\n";
+
+    out
+}
+
+struct Lua<'a> {
+    proto: String,
+    types: &'a [Schema],
+    /// `local name = { ... }` tables of enum ids
+    value_strings: Vec<String>,
+    /// `ProtoField` declarations, by key in the fields table
+    fields: Vec<(String, String)>,
+    functions: String,
+    /// Lua offset alignment of the current type is relative to
+    align_start: &'static str,
+}
+
+/// Name of a field in the generated code and Lua expression of its value
+type Locals = Vec<(String, String)>;
+
+fn line(out: &mut String, indent: usize, s: &str) {
+    for _ in 0..indent {
+        out.push_str("    ");
+    }
+    out.push_str(s);
+    out.push('\n');
+}
+
+impl<'a> Lua<'a> {
+    /// Declare a `ProtoField` once, `key` is relative to the protocol name
+    fn declare(&mut self, key: &str, label: &str, kind: &str, extra: &str) {
+        if self.fields.iter().any(|(k, _)| k == key) {
+            return;
+        }
+        let decl = format!(
+            "ProtoField.{kind}(\"{}.{key}\", \"{label}\"{extra})",
+            self.proto
+        );
+        self.fields.push((key.to_string(), decl));
+    }
+
+    fn emit_dissect_fn(&mut self, schema: &Schema, prefix: String) {
+        let name = super::snake_case(schema.ident);
+
+        let mut params = String::new();
+        let mut locals = Locals::new();
+        for (param, shape) in super::ctx_params(schema.ctx) {
+            if let TypeShape::Primitive(_) = shape {
+                params += &format!(", v_{param}");
+                locals.push((param.to_string(), format!("v_{param}")));
+            }
+        }
+
+        self.align_start = if schema.align_input { "0" } else { "start" };
+
+        let mut body = String::new();
+        line(&mut body, 1, "local start = offset");
+
+        if let Some(magic) = schema.magic {
+            self.emit_magic(&mut body, 1, &prefix, magic);
+        }
+
+        match schema.data {
+            SchemaData::Struct(fields) => {
+                // the endianness selected by `endian_from` isn't known to the dissector
+                let (fields, rest) = match schema.endian_from_index() {
+                    Some(index) => fields.split_at(index + 1),
+                    None => (fields, &[][..]),
+                };
+                self.emit_fields(&mut body, 1, &prefix, &mut locals, fields, schema.endian);
+                if let Some(f) = rest.first() {
+                    let name = f.ident.unwrap_or("field").trim_start_matches("r#");
+                    emit_not_decoded(&mut body, 1, name, "endianness selected at runtime");
+                }
+            }
+            SchemaData::Enum(ref data) => {
+                self.emit_enum(&mut body, &name, &prefix, &mut locals, data, schema.endian);
+            }
+        }
+
+        if let Err(reason) =
+            emit_alignment(&mut body, 1, &locals, self.align_start, None, schema.align)
+        {
+            emit_not_decoded(&mut body, 1, "align", &reason);
+        }
+
+        line(&mut body, 1, "return offset");
+
+        self.functions +=
+            &format!("function dissect_{name}(buffer, tree, offset, little{params})\n");
+        self.functions += &body;
+        self.functions += "end\n\n";
+    }
+
+    fn emit_enum(
+        &mut self,
+        out: &mut String,
+        name: &str,
+        prefix: &str,
+        locals: &mut Locals,
+        data: &EnumSchema,
+        endian: Option<&str>,
+    ) {
+        let switch_on = if let Some(id) = data.id {
+            match lua_expr(id, locals) {
+                Some(id) => id,
+                None => {
+                    emit_not_decoded(out, 1, "variant", &unsupported_expr(id));
+                    return;
+                }
+            }
+        } else if data.custom_id {
+            emit_not_decoded(out, 1, "variant_id", "custom reader");
+            return;
+        } else {
+            let bits = match data.id_type.map(TypeShape::parse) {
+                Some(TypeShape::Primitive(p @ (Primitive::Unsigned(_) | Primitive::Signed(_)))) => {
+                    let bits = data
+                        .bits
+                        .or(data.bytes.map(|b| b * 8))
+                        .unwrap_or(p.bit_size());
+                    (p, bits)
+                }
+                _ => {
+                    emit_not_decoded(out, 1, "variant_id", "unsupported id type");
+                    return;
+                }
+            };
+            let (p, bits) = bits;
+
+            let table = enum_table_of(data);
+            let extra = if table.is_empty() {
+                ", base.DEC".to_string()
+            } else {
+                let entries = table
+                    .iter()
+                    .map(|(id, ident)| format!("[{id}] = \"{ident}\""))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                self.value_strings
+                    .push(format!("local {name}_ids = {{ {entries} }}"));
+                format!(", base.DEC, {name}_ids")
+            };
+
+            let key = format!("{prefix}variant_id");
+            let little = endian_of(endian);
+            let (kind, read) = int_field(p, bits);
+            self.declare(&key, "variant_id", &kind, &extra);
+            line(
+                out,
+                1,
+                &format!("local v_variant_id = {read}(buffer, offset, {bits}, {little})"),
+            );
+            line(
+                out,
+                1,
+                &format!("add_value(tree, f[\"{key}\"], buffer, offset, {bits}, v_variant_id)"),
+            );
+            line(out, 1, &format!("offset = offset + {bits}"));
+            "v_variant_id".to_string()
+        };
+
+        let mut branches = Vec::new();
+        let mut default = None;
+        for variant in data.variants {
+            if variant.default || variant.unknown || variant.id_pat == Some("_") {
+                default = Some(variant);
+                continue;
+            }
+            let matches = if variant.id_pat.is_some() {
+                variant.id_pat.and_then(|pat| lua_pattern(pat, &switch_on))
+            } else {
+                variant
+                    .id_str()
+                    .and_then(parse_int)
+                    .map(|id| format!("{switch_on} == {id}"))
+            };
+            match matches {
+                Some(matches) => branches.push((matches, variant)),
+                None => line(
+                    out,
+                    1,
+                    &format!("-- {} isn't decoded: unsupported id", variant.ident),
+                ),
+            }
+        }
+
+        for (i, (matches, variant)) in branches.iter().enumerate() {
+            let keyword = if i == 0 { "if" } else { "elseif" };
+            line(out, 1, &format!("{keyword} {matches} then"));
+            self.emit_variant(out, prefix, locals, variant, endian);
+        }
+
+        if branches.is_empty() {
+            line(out, 1, "do");
+        } else {
+            line(out, 1, "else");
+        }
+        match default {
+            Some(variant) => self.emit_variant(out, prefix, locals, variant, endian),
+            None => emit_not_decoded(out, 2, "variant", "unknown id"),
+        }
+        line(out, 1, "end");
+    }
+
+    fn emit_variant(
+        &mut self,
+        out: &mut String,
+        prefix: &str,
+        locals: &Locals,
+        variant: &super::VariantSchema,
+        endian: Option<&str>,
+    ) {
+        line(out, 2, &format!("-- {}", variant.ident));
+        if variant.custom {
+            emit_not_decoded(out, 2, variant.ident, "custom reader");
+            return;
+        }
+        let prefix = format!("{prefix}{}.", super::snake_case(variant.ident));
+        if let Some(magic) = variant.magic {
+            self.emit_magic(out, 2, &prefix, magic);
+        }
+        let mut locals = locals.clone();
+        self.emit_fields(out, 2, &prefix, &mut locals, variant.data_fields(), endian);
+    }
+
+    /// Check and add a `magic` value, the dissector rejects the packet if it doesn't match
+    fn emit_magic(&mut self, out: &mut String, indent: usize, prefix: &str, magic: &[u8]) {
+        let hex = magic.iter().map(|b| format!("{b:02x}")).collect::<String>();
+        let key = format!("{prefix}magic");
+        self.declare(&key, "magic", "bytes", "");
+        let range = format!("buffer(math.floor(offset / 8), {})", magic.len());
+        line(
+            out,
+            indent,
+            &format!("if {range}:bytes() ~= ByteArray.new(\"{hex}\") then"),
+        );
+        line(out, indent + 1, "return nil");
+        line(out, indent, "end");
+        line(out, indent, &format!("tree:add(f[\"{key}\"], {range})"));
+        line(
+            out,
+            indent,
+            &format!("offset = offset + {}", magic.len() * 8),
+        );
+    }
+
+    fn emit_fields(
+        &mut self,
+        out: &mut String,
+        indent: usize,
+        prefix: &str,
+        locals: &mut Locals,
+        fields: &[FieldSchema],
+        endian: Option<&str>,
+    ) {
+        for (i, f) in fields.iter().enumerate() {
+            let name = match f.ident {
+                Some(ident) => ident.trim_start_matches("r#").to_string(),
+                None => format!("field_{i}"),
+            };
+
+            let mut field_out = String::new();
+            match self.emit_field(&mut field_out, indent, prefix, locals, &name, f, endian) {
+                Ok(()) => *out += &field_out,
+                Err(reason) => {
+                    emit_not_decoded(out, indent, &name, &reason);
+                    return;
+                }
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn emit_field(
+        &mut self,
+        out: &mut String,
+        indent: usize,
+        prefix: &str,
+        locals: &mut Locals,
+        name: &str,
+        f: &FieldSchema,
+        endian: Option<&str>,
+    ) -> Result<(), String> {
+        let var = format!("v_{name}");
+
+        // skipped fields are not present in the data
+        if f.skip && f.cond.is_none() {
+            if let Some(default) = f.default.and_then(|d| lua_expr(d, locals)) {
+                line(out, indent, &format!("local {var} = {default}"));
+                locals.push((name.to_string(), var));
+            }
+            return Ok(());
+        }
+
+        if f.custom {
+            return Err("custom reader".to_string());
+        }
+        if f.map.is_some() {
+            return Err("mapped value".to_string());
+        }
+
+        let key = format!("{prefix}{name}");
+        let endian = f.endian.or(endian);
+
+        let cond = match f.cond {
+            Some(cond) => {
+                let expr = lua_expr(cond, locals).ok_or_else(|| unsupported_expr(cond))?;
+                Some(if f.skip {
+                    format!("not ({expr})")
+                } else {
+                    expr
+                })
+            }
+            None => None,
+        };
+
+        emit_padding(out, indent, locals, f.reserved_bits, f.reserved_bytes)?;
+        emit_padding(out, indent, locals, f.pad_bits_before, f.pad_bytes_before)?;
+        emit_alignment(
+            out,
+            indent,
+            locals,
+            self.align_start,
+            f.align_bits_before,
+            f.align_before,
+        )?;
+
+        let (inner_indent, assign) = match cond {
+            Some(ref cond) => {
+                line(out, indent, &format!("local {var}"));
+                line(out, indent, &format!("if {cond} then"));
+                (indent + 1, var.clone())
+            }
+            None => (indent, format!("local {var}")),
+        };
+
+        // the following fields resume after the window, unless the unread bytes are kept
+        let window_end = match (f.window_bytes, f.window_rest) {
+            (Some(_), Some("keep")) | (None, _) => None,
+            (Some(window_bytes), _) => {
+                let end = format!("w_{name}");
+                let size =
+                    lua_expr(window_bytes, locals).ok_or_else(|| unsupported_expr(window_bytes))?;
+                line(
+                    out,
+                    inner_indent,
+                    &format!("local {end} = offset + {} * 8", paren(&size)),
+                );
+                Some(end)
+            }
+        };
+
+        // the field is read at its offset from the start, the following fields resume here
+        let at_resume = match f.at {
+            Some(at) => {
+                let resume = format!("r_{name}");
+                let pos = lua_expr(at, locals).ok_or_else(|| unsupported_expr(at))?;
+                line(out, inner_indent, &format!("local {resume} = offset"));
+                line(
+                    out,
+                    inner_indent,
+                    &format!("offset = start + {} * 8", paren(&pos)),
+                );
+                Some(resume)
+            }
+            None => None,
+        };
+
+        let element = match f.shape() {
+            TypeShape::Option(inner) => inner,
+            _ => f.wire_ty(),
+        };
+        match TypeShape::parse(element) {
+            TypeShape::Vec(inner) => self.emit_container(
+                out,
+                inner_indent,
+                &key,
+                name,
+                f,
+                inner,
+                None,
+                endian,
+                locals,
+                &assign,
+            )?,
+            TypeShape::Array(inner, len) => self.emit_container(
+                out,
+                inner_indent,
+                &key,
+                name,
+                f,
+                inner,
+                Some(len),
+                endian,
+                locals,
+                &assign,
+            )?,
+            _ => {
+                self.emit_value(
+                    out,
+                    inner_indent,
+                    &key,
+                    name,
+                    element,
+                    f,
+                    endian,
+                    locals,
+                    (&assign, &var),
+                )?;
+            }
+        }
+
+        if let Some(window_end) = window_end {
+            line(out, inner_indent, &format!("offset = {window_end}"));
+        }
+        if let Some(at_resume) = at_resume {
+            line(out, inner_indent, &format!("offset = {at_resume}"));
+        }
+
+        if cond.is_some() {
+            if let Some(default) = f.default.and_then(|d| lua_expr(d, locals)) {
+                line(out, indent, "else");
+                line(out, indent + 1, &format!("{var} = {default}"));
+            }
+            line(out, indent, "end");
+        }
+        locals.push((name.to_string(), var));
+
+        emit_padding(out, indent, locals, f.pad_bits_after, f.pad_bytes_after)?;
+        emit_alignment(
+            out,
+            indent,
+            locals,
+            self.align_start,
+            f.align_bits_after,
+            f.align_after,
+        )?;
+
+        Ok(())
+    }
+
+    /// Emit a `Vec` or array, either as raw bytes or as a loop over its elements
+    #[allow(clippy::too_many_arguments)]
+    fn emit_container(
+        &mut self,
+        out: &mut String,
+        indent: usize,
+        key: &str,
+        name: &str,
+        f: &FieldSchema,
+        inner: &str,
+        len: Option<&str>,
+        endian: Option<&str>,
+        locals: &Locals,
+        assign: &str,
+    ) -> Result<(), String> {
+        let expr = |e: &str| lua_expr(e, locals).ok_or_else(|| unsupported_expr(e));
+
+        let is_bytes = inner == "u8" && f.bits.is_none() && f.until.is_none();
+        if let (true, Some(size)) = (is_bytes, f.count.or(f.bytes_read).or(len)) {
+            let size = expr(size)?;
+            self.declare(key, name, "bytes", "");
+            line(
+                out,
+                indent,
+                &format!("tree:add(f[\"{key}\"], buffer(math.floor(offset / 8), {size}))"),
+            );
+            line(
+                out,
+                indent,
+                &format!("offset = offset + {} * 8", paren(&size)),
+            );
+            return Ok(());
+        }
+
+        let var = assign.trim_start_matches("local ");
+        line(out, indent, &format!("{assign} = {{}}"));
+
+        let mut element_locals = locals.clone();
+        let close = if let Some(count) = f.count.or(len) {
+            line(out, indent, &format!("for _ = 1, {} do", expr(count)?));
+            "end".to_string()
+        } else if let Some(until) = f.until {
+            let (arg, body) = closure(until).ok_or_else(|| unsupported_expr(until))?;
+            element_locals.push((arg.to_string(), "value".to_string()));
+            let pred = lua_expr(body, &element_locals).ok_or_else(|| unsupported_expr(until))?;
+            line(out, indent, "repeat");
+            format!("until {pred}")
+        } else if let Some(terminator) = f.terminator {
+            let pred = match f.until_exclusive {
+                Some(until) => {
+                    let (arg, body) = closure(until).ok_or_else(|| unsupported_expr(until))?;
+                    element_locals.push((arg.to_string(), "value".to_string()));
+                    lua_expr(body, &element_locals).ok_or_else(|| unsupported_expr(until))?
+                }
+                None => format!("value == {}", paren(&expr(terminator)?)),
+            };
+            line(out, indent, "repeat");
+            format!("until {pred}")
+        } else if let Some(size) = f.bytes_read {
+            let end = format!("offset + {} * 8", paren(&expr(size)?));
+            line(out, indent, &format!("local e_{name} = {end}"));
+            line(out, indent, &format!("while offset < e_{name} do"));
+            "end".to_string()
+        } else if let Some(size) = f.bits_read {
+            line(
+                out,
+                indent,
+                &format!("local e_{name} = offset + {}", expr(size)?),
+            );
+            line(out, indent, &format!("while offset < e_{name} do"));
+            "end".to_string()
+        } else {
+            line(out, indent, "while offset < buffer:len() * 8 do");
+            "end".to_string()
+        };
+
+        let has_value = self.emit_value(
+            out,
+            indent + 1,
+            key,
+            name,
+            inner,
+            f,
+            endian,
+            &element_locals,
+            ("local value", "value"),
+        )?;
+        if has_value {
+            line(out, indent + 1, &format!("{var}[#{var} + 1] = value"));
+        }
+        line(out, indent, &close);
+        if f.terminator.is_some() {
+            // the terminating element is shown in the tree, but not stored
+            line(out, indent, &format!("{var}[#{var}] = nil"));
+        }
+
+        Ok(())
+    }
+
+    /// Emit the dissection of a single value, returns `true` if `target` was assigned
+    #[allow(clippy::too_many_arguments)]
+    fn emit_value(
+        &mut self,
+        out: &mut String,
+        indent: usize,
+        key: &str,
+        label: &str,
+        ty: &str,
+        f: &FieldSchema,
+        endian: Option<&str>,
+        locals: &Locals,
+        (assign, var): (&str, &str),
+    ) -> Result<bool, String> {
+        let little = endian_of(endian);
+        match TypeShape::parse(ty) {
+            TypeShape::Primitive(p) => {
+                let bits = f.bit_size().unwrap_or(p.bit_size());
+                let (kind, read) = int_field(p, bits);
+                self.declare(
+                    key,
+                    label,
+                    &kind,
+                    if kind == "bool" { "" } else { ", base.DEC" },
+                );
+                let read = format!("{read}(buffer, offset, {bits}, {little})");
+                let read = if p == Primitive::Bool {
+                    format!("{read} ~= 0")
+                } else {
+                    read
+                };
+                line(out, indent, &format!("{assign} = {read}"));
+                line(
+                    out,
+                    indent,
+                    &format!("add_value(tree, f[\"{key}\"], buffer, offset, {bits}, {var})"),
+                );
+                line(out, indent, &format!("offset = offset + {bits}"));
+                Ok(true)
+            }
+            TypeShape::Option(inner) => self.emit_value(
+                out,
+                indent,
+                key,
+                label,
+                inner,
+                f,
+                endian,
+                locals,
+                (assign, var),
+            ),
+            TypeShape::Other("CString") => {
+                self.declare(key, label, "stringz", "");
+                let range = "buffer(math.floor(offset / 8)";
+                line(out, indent, &format!("{assign} = {range}):stringz()"));
+                line(
+                    out,
+                    indent,
+                    &format!("tree:add(f[\"{key}\"], {range}, #{var} + 1))"),
+                );
+                line(out, indent, &format!("offset = offset + (#{var} + 1) * 8"));
+                Ok(true)
+            }
+            TypeShape::Other(addr @ ("Ipv4Addr" | "Ipv6Addr")) => {
+                let (kind, bits) = if addr == "Ipv4Addr" {
+                    ("ipv4", 32)
+                } else {
+                    ("ipv6", 128)
+                };
+                self.declare(key, label, kind, "");
+                line(
+                    out,
+                    indent,
+                    &format!(
+                        "add_range(tree, f[\"{key}\"], bit_range(buffer, offset, {bits}), {little})"
+                    ),
+                );
+                line(out, indent, &format!("offset = offset + {bits}"));
+                Ok(false)
+            }
+            TypeShape::Other(name) => {
+                let schema = self
+                    .types
+                    .iter()
+                    .find(|t| t.ident == name)
+                    .ok_or_else(|| format!("unknown type `{name}`"))?;
+
+                // `endian`, `bits` and `bytes` are passed before `ctx`, see `gen_field_args`
+                let implicit = [endian.is_some(), f.bits.is_some(), f.bytes.is_some()]
+                    .iter()
+                    .filter(|v| **v)
+                    .count();
+                let mut all_args = alloc::vec![None; implicit];
+                all_args.extend(
+                    f.ctx
+                        .map(split_top_level)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(Some),
+                );
+
+                let mut args = String::new();
+                for ((_, shape), arg) in super::ctx_params(schema.ctx).into_iter().zip(all_args) {
+                    if let TypeShape::Primitive(_) = shape {
+                        let arg = arg.ok_or_else(|| format!("missing ctx for `{name}`"))?;
+                        args += ", ";
+                        args += &lua_expr(arg, locals).ok_or_else(|| unsupported_expr(arg))?;
+                    }
+                }
+
+                let dissect = format!("dissect_{}", super::snake_case(name));
+                line(
+                    out,
+                    indent,
+                    &format!(
+                        "offset = add_nested(tree, \"{label}\", buffer, offset, {dissect}, {little}{args})"
+                    ),
+                );
+                line(out, indent, "if offset == nil then");
+                line(out, indent + 1, "return nil");
+                line(out, indent, "end");
+                Ok(false)
+            }
+            TypeShape::Vec(_) | TypeShape::Array(..) => Err(format!("nested container `{ty}`")),
+        }
+    }
+}
+
+/// Lua expression of the endianness: `true` for little endian
+fn endian_of(endian: Option<&str>) -> &'static str {
+    match endian {
+        Some("big") => "false",
+        Some("little") => "true",
+        // an expression from the `ctx` of the type, or inherited from the parent
+        _ => "little",
+    }
+}
+
+/// `ProtoField` kind and reader function of a primitive
+fn int_field(p: Primitive, bits: usize) -> (String, &'static str) {
+    let size = match bits {
+        0..=8 => 8,
+        9..=16 => 16,
+        17..=24 => 24,
+        25..=32 => 32,
+        _ => 64,
+    };
+    match p {
+        Primitive::Unsigned(_) => (format!("uint{size}"), "read_uint"),
+        Primitive::Signed(_) => (format!("int{size}"), "read_int"),
+        Primitive::Float(32) => ("float".to_string(), "read_float"),
+        Primitive::Float(_) => ("double".to_string(), "read_float"),
+        Primitive::Bool => ("bool".to_string(), "read_uint"),
+    }
+}
+
+fn emit_padding(
+    out: &mut String,
+    indent: usize,
+    locals: &Locals,
+    bits: Option<&str>,
+    bytes: Option<&str>,
+) -> Result<(), String> {
+    if let Some(bits) = bits {
+        let bits = lua_expr(bits, locals).ok_or_else(|| unsupported_expr(bits))?;
+        line(out, indent, &format!("offset = offset + {bits}"));
+    }
+    if let Some(bytes) = bytes {
+        let bytes = lua_expr(bytes, locals).ok_or_else(|| unsupported_expr(bytes))?;
+        line(
+            out,
+            indent,
+            &format!("offset = offset + {} * 8", paren(&bytes)),
+        );
+    }
+    Ok(())
+}
+
+/// Advance `offset` to a multiple of `bits` or `bytes` from `start`
+fn emit_alignment(
+    out: &mut String,
+    indent: usize,
+    locals: &Locals,
+    start: &str,
+    bits: Option<&str>,
+    bytes: Option<&str>,
+) -> Result<(), String> {
+    let align = match (bits, bytes) {
+        (Some(bits), _) => {
+            let bits = lua_expr(bits, locals).ok_or_else(|| unsupported_expr(bits))?;
+            paren(&bits)
+        }
+        (None, Some(bytes)) => {
+            let bytes = lua_expr(bytes, locals).ok_or_else(|| unsupported_expr(bytes))?;
+            format!("({} * 8)", paren(&bytes))
+        }
+        (None, None) => return Ok(()),
+    };
+    line(
+        out,
+        indent,
+        &format!("offset = offset + ({align} - (offset - {start}) % {align}) % {align}"),
+    );
+    Ok(())
+}
+
+/// Stop dissecting the current type
+fn emit_not_decoded(out: &mut String, indent: usize, name: &str, reason: &str) {
+    let reason = reason.replace('\\', "\\\\").replace('"', "\\\"");
+    line(
+        out,
+        indent,
+        &format!("tree:add(buffer(math.floor(offset / 8)), \"{name}: not decoded ({reason})\")"),
+    );
+    line(out, indent, "do return nil end");
+}
+
+fn unsupported_expr(expr: &str) -> String {
+    format!("unsupported expression `{expr}`")
+}
+
+fn paren(expr: &str) -> String {
+    if expr.contains(' ') {
+        format!("({expr})")
+    } else {
+        expr.to_string()
+    }
+}
+
+/// Split an `until` closure into its argument and body: `| v : & u8 | * v == 0`
+fn closure(until: &str) -> Option<(&str, &str)> {
+    let mut parts = until.splitn(3, '|');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(""), Some(arg), Some(body)) => {
+            Some((arg.split(':').next().unwrap_or("").trim(), body.trim()))
+        }
+        _ => None,
+    }
+}
+
+/// Translate an `id_pat` such as `1 | 3 ..= 5` into a Lua condition on `id`
+fn lua_pattern(pat: &str, id: &str) -> Option<String> {
+    let alternatives = pat
+        .split('|')
+        .map(|alt| {
+            let alt = alt.trim();
+            if let Some((start, end)) = alt.split_once("..=") {
+                let (start, end) = (parse_int(start)?, parse_int(end)?);
+                Some(format!("({id} >= {start} and {id} <= {end})"))
+            } else {
+                Some(format!("{id} == {}", parse_int(alt)?))
+            }
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(alternatives.join(" or "))
+}
+
+/// Translate a Rust expression into Lua, `None` if it uses unsupported constructs
+///
+/// Identifiers must be fields read earlier or `ctx` parameters, found in `locals`
+fn lua_expr(expr: &str, locals: &[(String, String)]) -> Option<String> {
+    let tokens = super::expr_tokens(expr);
+    let mut out: Vec<String> = Vec::with_capacity(tokens.len());
+    // true if the previous token ends an operand, to tell unary from binary operators
+    let mut prev_is_value = false;
+
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = tokens[i].as_str();
+        let mut is_value = true;
+        match token {
+            // unary dereference/reference
+            "*" | "&" if !prev_is_value => is_value = false,
+            "&&" | "||" | "!" | "!=" | "+" | "-" | "*" | "%" | "==" | "<" | ">" | "<=" | ">="
+            | "(" => {
+                let lua = match token {
+                    "&&" => "and",
+                    "||" => "or",
+                    "!" => "not",
+                    "!=" => "~=",
+                    _ => token,
+                };
+                out.push(lua.into());
+                is_value = false;
+            }
+            ")" => out.push(token.into()),
+            // casts
+            "as" => i += 1,
+            "true" | "false" => out.push(token.into()),
+            "__deku_bit_offset" => out.push("(offset - start)".into()),
+            "__deku_byte_offset" => out.push("math.floor((offset - start) / 8)".into()),
+            _ => {
+                if let Some((_, lua)) = locals.iter().find(|(name, _)| name == token) {
+                    // `.len()` of a container read into a table
+                    if tokens
+                        .get(i + 1..i + 5)
+                        .is_some_and(|t| t == [".", "len", "(", ")"])
+                    {
+                        out.push(format!("#{lua}"));
+                        i += 4;
+                    } else {
+                        out.push(lua.clone());
+                    }
+                } else {
+                    out.push(parse_int(strip_int_suffix(token))?.to_string());
+                }
+            }
+        }
+        prev_is_value = is_value;
+        i += 1;
+    }
+
+    let mut joined = String::with_capacity(expr.len());
+    let mut prev: Option<&str> = None;
+    for token in &out {
+        let glue = matches!(
+            (prev, token.as_str()),
+            (None, _) | (Some(_), ")") | (Some("("), _)
+        );
+        if !glue {
+            joined.push(' ');
+        }
+        joined.push_str(token);
+        prev = Some(token);
+    }
+    Some(joined)
+}
+
+/// `5u8` -> `5`
+fn strip_int_suffix(s: &str) -> &str {
+    for suffix in [
+        "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize",
+    ] {
+        if let Some(s) = s.strip_suffix(suffix) {
+            return s.trim_end_matches('_');
+        }
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest(
+        input,
+        expected,
+        case("count", Some("v_count")),
+        case("* field_a == 0x01", Some("v_field_a == 1")),
+        case("(* count as usize) - 4", Some("(v_count) - 4")),
+        case("* flag && ! * other != 2u8", Some("v_flag and not v_other ~= 2")),
+        case("data . len () * 2", Some("#v_data * 2")),
+        case("__deku_bit_offset % 8", Some("(offset - start) % 8")),
+        case("count . pow(2)", None),
+        case("unknown + 1", None),
+        case("* count >> 1", None)
+    )]
+    fn test_lua_expr(input: &str, expected: Option<&str>) {
+        let locals = ["count", "field_a", "flag", "other", "data"]
+            .iter()
+            .map(|n| (n.to_string(), format!("v_{n}")))
+            .collect::<Vec<_>>();
+        assert_eq!(expected.map(String::from), lua_expr(input, &locals));
+    }
+
+    #[rstest(
+        input,
+        expected,
+        case("1", Some("id == 1")),
+        case("0x01 | 0x02", Some("id == 1 or id == 2")),
+        case("3 ..= 5", Some("(id >= 3 and id <= 5)")),
+        case("Foo", None)
+    )]
+    fn test_lua_pattern(input: &str, expected: Option<&str>) {
+        assert_eq!(expected.map(String::from), lua_pattern(input, "id"));
+    }
+}
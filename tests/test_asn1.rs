@@ -0,0 +1,150 @@
+#![cfg(feature = "asn1")]
+
+use deku::asn1::{Class, Contents, Rules, SequenceOf, Tag, Tlv};
+use deku::bitvec::BitView;
+use deku::prelude::*;
+use hexlit::hex;
+
+/// GetRequest-PDU of SNMP, `[0] IMPLICIT PDU`
+const GET_REQUEST: Tag = Tag::context_specific(0, true);
+
+/// VarBind ::= SEQUENCE { name ObjectName, value ObjectSyntax }
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(ctx = "rules: Rules", ctx_default = "Rules::Ber")]
+struct VarBind {
+    #[deku(ctx = "rules", assert = "name.tag == Tag::OBJECT_IDENTIFIER")]
+    name: Tlv<Contents>,
+    #[deku(ctx = "rules")]
+    value: Tlv<Contents>,
+}
+
+/// PDU ::= SEQUENCE { request-id, error-status, error-index, variable-bindings }
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(ctx = "rules: Rules", ctx_default = "Rules::Ber")]
+struct Pdu {
+    #[deku(ctx = "rules", assert = "request_id.tag == Tag::INTEGER")]
+    request_id: Tlv<Contents>,
+    #[deku(ctx = "rules", assert = "error_status.tag == Tag::INTEGER")]
+    error_status: Tlv<Contents>,
+    #[deku(ctx = "rules", assert = "error_index.tag == Tag::INTEGER")]
+    error_index: Tlv<Contents>,
+    #[deku(ctx = "rules", assert = "variable_bindings.tag == Tag::SEQUENCE")]
+    variable_bindings: Tlv<SequenceOf<Tlv<VarBind>>>,
+}
+
+/// Message ::= SEQUENCE { version INTEGER, community OCTET STRING, data PDUs }
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(ctx = "rules: Rules", ctx_default = "Rules::Ber")]
+struct Message {
+    #[deku(ctx = "rules", assert = "version.tag == Tag::INTEGER")]
+    version: Tlv<Contents>,
+    #[deku(ctx = "rules", assert = "community.tag == Tag::OCTET_STRING")]
+    community: Tlv<Contents>,
+    #[deku(ctx = "rules", assert = "data.tag == GET_REQUEST")]
+    data: Tlv<Pdu>,
+}
+
+fn integer(value: u8) -> Tlv<Contents> {
+    Tlv::new(Tag::INTEGER, Contents(vec![value]))
+}
+
+fn get_sys_descr() -> Tlv<Message> {
+    let var_bind = VarBind {
+        name: Tlv::new(
+            Tag::OBJECT_IDENTIFIER,
+            Contents(hex!("2B06010201010100").to_vec()),
+        ),
+        value: Tlv::new(Tag::NULL, Contents(vec![])),
+    };
+    Tlv::new(
+        Tag::SEQUENCE,
+        Message {
+            version: integer(1),
+            community: Tlv::new(Tag::OCTET_STRING, Contents(b"public".to_vec())),
+            data: Tlv::new(
+                GET_REQUEST,
+                Pdu {
+                    request_id: integer(1),
+                    error_status: integer(0),
+                    error_index: integer(0),
+                    variable_bindings: Tlv::new(
+                        Tag::SEQUENCE,
+                        SequenceOf(vec![Tlv::new(Tag::SEQUENCE, var_bind)]),
+                    ),
+                },
+            ),
+        },
+    )
+}
+
+#[test]
+fn test_snmp_get_request() {
+    let input = hex!(
+        "3026 020101 0406 7075626C6963"
+        "A019 020101 020100 020100"
+        "300E 300C 0608 2B06010201010100 0500"
+    );
+
+    let (rest, message) = Tlv::<Message>::from_bytes((&input, 0)).unwrap();
+    assert!(rest.0.is_empty());
+    assert_eq!(get_sys_descr(), message);
+    assert_eq!(input.to_vec(), message.to_bytes().unwrap());
+
+    let (rest, der) = Tlv::<Message>::read(input.view_bits(), Rules::Der).unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(message, der);
+}
+
+#[test]
+fn test_snmp_get_request_indefinite_length() {
+    let input = hex!(
+        "3080 020101 0406 7075626C6963"
+        "A080 020101 020100 020100"
+        "3080 300C 0608 2B06010201010100 0500 0000"
+        "0000"
+        "0000"
+    );
+
+    let (rest, message) = Tlv::<Message>::from_bytes((&input, 0)).unwrap();
+    assert!(rest.0.is_empty());
+    assert_eq!(get_sys_descr(), message);
+
+    // written as DER
+    assert_eq!(
+        get_sys_descr().to_bytes().unwrap(),
+        message.to_bytes().unwrap()
+    );
+
+    assert_eq!(
+        Err(DekuError::Parse("DER: indefinite length".into())),
+        Tlv::<Message>::read(input.view_bits(), Rules::Der)
+    );
+}
+
+#[test]
+fn test_unexpected_tag() {
+    // the community is an INTEGER
+    let input = hex!(
+        "3023 020101 0203 010203"
+        "A019 020101 020100 020100"
+        "300E 300C 0608 2B06010201010100 0500"
+    );
+    assert!(matches!(
+        Tlv::<Message>::from_bytes((&input, 0)),
+        Err(DekuError::Assertion(_))
+    ));
+}
+
+#[test]
+fn test_high_tag_number() {
+    // [APPLICATION 1000] IMPLICIT SEQUENCE { INTEGER }
+    let tag = Tag::new(Class::Application, true, 1000);
+    let value = Tlv::new(tag, SequenceOf(vec![integer(7)]));
+
+    let output = value.to_bytes().unwrap();
+    assert_eq!(hex!("7F8768 03 020107").to_vec(), output);
+
+    let (_, read) = Tlv::<SequenceOf<Tlv<Contents>>>::read(output.view_bits(), Rules::Der).unwrap();
+    assert_eq!(value, read);
+    assert_eq!("[APPLICATION 1000]", read.tag.to_string());
+}
@@ -1,10 +1,17 @@
+mod test_align;
+mod test_as_type;
 mod test_assert;
 mod test_assert_eq;
+mod test_at;
 mod test_cond;
 mod test_ctx;
+mod test_endian_from;
 mod test_limits;
 mod test_map;
 mod test_padding;
+mod test_reserved;
 mod test_skip;
 mod test_temp;
 mod test_update;
+mod test_version;
+mod test_window;
@@ -0,0 +1,280 @@
+use deku::bitvec::BitView;
+use deku::prelude::*;
+use hexlit::hex;
+use rstest::rstest;
+use std::convert::{TryFrom, TryInto};
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+#[deku(endian = "little")]
+struct TestAlignBefore {
+    field_a: u8,
+    #[deku(align_before = "4")]
+    field_b: u16,
+}
+
+#[rstest(input, expected, expected_write,
+    case(&hex!("AA FFFFFF BBCC"), TestAlignBefore { field_a: 0xAA, field_b: 0xCCBB }, hex!("AA 000000 BBCC").to_vec()),
+
+    #[should_panic(expected = "Incomplete(NeedSize { bits: 24 })")]
+    case(&hex!("AA FFFF"), TestAlignBefore { field_a: 0xAA, field_b: 0 }, vec![]),
+)]
+fn test_align_before(input: &[u8], expected: TestAlignBefore, expected_write: Vec<u8>) {
+    let ret_read = TestAlignBefore::try_from(input).unwrap();
+    assert_eq!(expected, ret_read);
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(expected_write, ret_write);
+}
+
+#[test]
+fn test_align_bits_before_after() {
+    #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+    struct TestStruct {
+        #[deku(bits = 1, align_bits_after = "4")]
+        field_a: u8,
+        #[deku(bits = 2, align_bits_before = "4")]
+        field_b: u8,
+        #[deku(align_bits_before = "8")]
+        field_c: u8,
+    }
+
+    let data: Vec<u8> = vec![0b1_111_10_11, 0xCC];
+
+    let ret_read = TestStruct::try_from(data.as_ref()).unwrap();
+    assert_eq!(
+        TestStruct {
+            field_a: 0b1,
+            field_b: 0b10,
+            field_c: 0xCC,
+        },
+        ret_read
+    );
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(vec![0b1_000_10_00, 0xCC], ret_write);
+}
+
+#[test]
+fn test_align_already_aligned() {
+    #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+    struct TestStruct {
+        #[deku(count = "4", align_after = "4")]
+        field_a: Vec<u8>,
+        field_b: u8,
+    }
+
+    let data: Vec<u8> = vec![0xAA, 0xBB, 0xCC, 0xDD, 0xEE];
+
+    let ret_read = TestStruct::try_from(data.as_ref()).unwrap();
+    assert_eq!(
+        TestStruct {
+            field_a: vec![0xAA, 0xBB, 0xCC, 0xDD],
+            field_b: 0xEE,
+        },
+        ret_read
+    );
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(data, ret_write);
+}
+
+/// Alignment is relative to the start of the struct, not to the input
+#[test]
+fn test_align_nested() {
+    #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+    #[deku(magic = b"N")]
+    struct Note {
+        len: u8,
+        #[deku(count = "len", align_after = "4")]
+        name: Vec<u8>,
+    }
+
+    #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+    struct TestStruct {
+        header: u8,
+        #[deku(count = "2")]
+        notes: Vec<Note>,
+    }
+
+    let data: Vec<u8> = hex!("FF 4E 01 41 00 4E 02 42 43").to_vec();
+
+    let ret_read = TestStruct::try_from(data.as_ref()).unwrap();
+    assert_eq!(
+        TestStruct {
+            header: 0xFF,
+            notes: vec![
+                Note {
+                    len: 1,
+                    name: b"A".to_vec(),
+                },
+                Note {
+                    len: 2,
+                    name: b"BC".to_vec(),
+                },
+            ],
+        },
+        ret_read
+    );
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(data, ret_write);
+}
+
+#[test]
+fn test_align_top_level() {
+    #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+    #[deku(type = "u8", align = "2")]
+    enum TestEnum {
+        #[deku(id = "1")]
+        VarA(u8),
+        #[deku(id = "2")]
+        VarB(u8, u8),
+    }
+
+    #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+    struct TestStruct {
+        #[deku(count = "2")]
+        field_a: Vec<TestEnum>,
+        field_b: u8,
+    }
+
+    let data: Vec<u8> = hex!("02 AA BB FF 01 CC DD").to_vec();
+
+    let ret_read = TestStruct::try_from(data.as_ref()).unwrap();
+    assert_eq!(
+        TestStruct {
+            field_a: vec![TestEnum::VarB(0xAA, 0xBB), TestEnum::VarA(0xCC)],
+            field_b: 0xDD,
+        },
+        ret_read
+    );
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(hex!("02 AA BB 00 01 CC DD").to_vec(), ret_write);
+}
+
+#[test]
+fn test_align_ctx() {
+    #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+    #[deku(ctx = "alignment: usize", align = "alignment")]
+    struct Aligned {
+        field_a: u8,
+    }
+
+    #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+    struct TestStruct {
+        alignment: u8,
+        #[deku(ctx = "usize::from(*alignment)")]
+        field_a: Aligned,
+        field_b: u8,
+    }
+
+    let data: Vec<u8> = hex!("03 AA FFFF BB").to_vec();
+
+    let ret_read = TestStruct::try_from(data.as_ref()).unwrap();
+    assert_eq!(
+        TestStruct {
+            alignment: 3,
+            field_a: Aligned { field_a: 0xAA },
+            field_b: 0xBB,
+        },
+        ret_read
+    );
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(hex!("03 AA 0000 BB").to_vec(), ret_write);
+}
+
+#[test]
+fn test_align_input() {
+    #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+    #[deku(align_input, align = "2")]
+    struct TestStruct {
+        field_a: u8,
+        #[deku(align_before = "4")]
+        field_b: u8,
+    }
+
+    let data: Vec<u8> = hex!("FFFF AA EE BB CC DD 11").to_vec();
+
+    // the struct starts at byte 2 of the input
+    let (rest, ret_read) = TestStruct::from_bytes((&data, 16)).unwrap();
+    assert_eq!((rest.0, rest.1), (&hex!("DD 11")[..], 0));
+    assert_eq!(
+        TestStruct {
+            field_a: 0xAA,
+            field_b: 0xBB,
+        },
+        ret_read
+    );
+
+    // the struct starts at byte 0 of the input
+    let (rest, ret_read) = TestStruct::from_bytes((&data[2..], 0)).unwrap();
+    assert_eq!((rest.0.len(), rest.1), (0, 0));
+    assert_eq!(
+        TestStruct {
+            field_a: 0xAA,
+            field_b: 0xDD,
+        },
+        ret_read
+    );
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(hex!("AA 000000 DD 00").to_vec(), ret_write);
+}
+
+#[test]
+fn test_align_input_nested() {
+    #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+    #[deku(align_input)]
+    struct Inner {
+        field_a: u8,
+        #[deku(align_before = "4")]
+        field_b: u8,
+    }
+
+    #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+    struct TestStruct {
+        tag: u8,
+        inner: Inner,
+    }
+
+    // `inner` is aligned from the start of the input, when reading and writing
+    let data: Vec<u8> = hex!("01 AA 0000 BB").to_vec();
+    let ret_read = TestStruct::try_from(data.as_ref()).unwrap();
+    assert_eq!(
+        TestStruct {
+            tag: 0x01,
+            inner: Inner {
+                field_a: 0xAA,
+                field_b: 0xBB,
+            },
+        },
+        ret_read
+    );
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(data, ret_write);
+
+    // including the bit offset given to `from_bytes`
+    let data: Vec<u8> = hex!("FF 01 AA 00 BB").to_vec();
+    let (_, ret_read) = TestStruct::from_bytes((data.as_ref(), 8)).unwrap();
+    assert_eq!(0xBB, ret_read.inner.field_b);
+}
+
+#[rstest(align,
+    #[should_panic(expected = r#"InvalidParam("Invalid alignment param \"(((align) * 8))\": must be greater than 0")"#)]
+    case(0),
+    #[should_panic(expected = r#"InvalidParam("Invalid alignment param \"(((align) * 8))\": cannot convert to usize")"#)]
+    case(-1),
+)]
+fn test_align_invalid(align: isize) {
+    #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+    #[deku(ctx = "align: isize")]
+    struct TestStruct {
+        #[deku(align_before = "align")]
+        field_a: u8,
+    }
+
+    let _ = TestStruct::read([0u8][..].view_bits(), align).unwrap();
+}
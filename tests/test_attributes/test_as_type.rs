@@ -0,0 +1,109 @@
+use deku::prelude::*;
+use hexlit::hex;
+use std::convert::{TryFrom, TryInto};
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+struct Port(u16);
+
+impl From<u16> for Port {
+    fn from(v: u16) -> Self {
+        Port(v)
+    }
+}
+
+impl From<Port> for u16 {
+    fn from(v: Port) -> Self {
+        v.0
+    }
+}
+
+/// Percentage, fails to convert from values over 100
+#[derive(PartialEq, Debug, Clone, Copy)]
+struct Percent(u8);
+
+impl TryFrom<u8> for Percent {
+    type Error = ();
+
+    fn try_from(v: u8) -> Result<Self, Self::Error> {
+        if v <= 100 {
+            Ok(Percent(v))
+        } else {
+            Err(())
+        }
+    }
+}
+
+impl TryFrom<Percent> for u8 {
+    type Error = ();
+
+    fn try_from(v: Percent) -> Result<Self, Self::Error> {
+        if v.0 <= 100 {
+            Ok(v.0)
+        } else {
+            Err(())
+        }
+    }
+}
+
+#[test]
+fn test_as_type_from() {
+    #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+    struct TestStruct {
+        #[deku(as_type = "u16", endian = "big")]
+        field_a: Port,
+        #[deku(as_type = "u16", endian = "big", bits = 12)]
+        field_b: Port,
+        #[deku(bits = 4)]
+        field_c: u8,
+    }
+
+    let test_data = hex!("1F 90 AB CD");
+    let ret_read = TestStruct::try_from(test_data.as_ref()).unwrap();
+    assert_eq!(
+        TestStruct {
+            field_a: Port(8080),
+            field_b: Port(0xABC),
+            field_c: 0xD,
+        },
+        ret_read
+    );
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(test_data.to_vec(), ret_write);
+}
+
+#[test]
+fn test_as_type_try_from() {
+    #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+    struct TestStruct {
+        #[deku(as_type = "u8", try_from)]
+        field_a: Percent,
+    }
+
+    let ret_read = TestStruct::try_from(hex!("64").as_ref()).unwrap();
+    assert_eq!(
+        TestStruct {
+            field_a: Percent(100)
+        },
+        ret_read
+    );
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(vec![0x64], ret_write);
+
+    assert_eq!(
+        Err(DekuError::Parse(
+            "Invalid TestStruct.field_a: cannot convert from u8".to_string()
+        )),
+        TestStruct::try_from(hex!("65").as_ref())
+    );
+    assert_eq!(
+        Err(DekuError::InvalidParam(
+            "Invalid TestStruct.field_a: cannot convert into u8".to_string()
+        )),
+        TestStruct {
+            field_a: Percent(101)
+        }
+        .to_bytes()
+    );
+}
@@ -40,3 +40,195 @@ fn test_assert_write(input: TestStruct, expected: Vec<u8>) {
     let ret_write: Vec<u8> = input.try_into().unwrap();
     assert_eq!(expected, ret_write);
 }
+
+#[derive(Default, PartialEq, Debug, DekuRead, DekuWrite)]
+#[deku(assert = "*header_len <= *total_len")]
+struct TestStructAssert {
+    header_len: u8,
+    total_len: u8,
+}
+
+#[rstest(input, expected,
+    case(&hex!("0204"), TestStructAssert {
+        header_len: 0x02,
+        total_len: 0x04,
+    }),
+
+    #[should_panic(expected = r#"Assertion("TestStructAssert failed assertion: * header_len <= * total_len")"#)]
+    case(&hex!("0402"), TestStructAssert::default())
+)]
+fn test_struct_assert(input: &[u8], expected: TestStructAssert) {
+    let ret_read = TestStructAssert::try_from(input).unwrap();
+    assert_eq!(expected, ret_read);
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(input.to_vec(), ret_write);
+}
+
+#[test]
+#[should_panic(
+    expected = r#"Assertion("TestStructAssert failed assertion: * header_len <= * total_len")"#
+)]
+fn test_struct_assert_write() {
+    let value = TestStructAssert {
+        header_len: 0x04,
+        total_len: 0x02,
+    };
+    let _: Vec<u8> = value.try_into().unwrap();
+}
+
+fn is_sorted(value: &TestValidate) -> bool {
+    value.values.windows(2).all(|w| w[0] <= w[1])
+}
+
+#[derive(Default, PartialEq, Debug, DekuRead, DekuWrite)]
+#[deku(validate = "is_sorted")]
+struct TestValidate {
+    count: u8,
+    #[deku(count = "count")]
+    values: Vec<u8>,
+}
+
+#[rstest(input, expected,
+    case(&hex!("03010203"), TestValidate {
+        count: 3,
+        values: vec![1, 2, 3],
+    }),
+
+    #[should_panic(expected = r#"Assertion("TestValidate failed validation: is_sorted")"#)]
+    case(&hex!("03030201"), TestValidate::default())
+)]
+fn test_validate(input: &[u8], expected: TestValidate) {
+    let ret_read = TestValidate::try_from(input).unwrap();
+    assert_eq!(expected, ret_read);
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(input.to_vec(), ret_write);
+}
+
+#[test]
+#[should_panic(expected = r#"Assertion("TestValidate failed validation: is_sorted")"#)]
+fn test_validate_write() {
+    let value = TestValidate {
+        count: 2,
+        values: vec![2, 1],
+    };
+    let _: Vec<u8> = value.try_into().unwrap();
+}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+#[deku(type = "u8", validate = "TestEnumValidate::is_valid")]
+enum TestEnumValidate {
+    #[deku(id = "1")]
+    Range { start: u8, end: u8 },
+}
+
+impl TestEnumValidate {
+    fn is_valid(&self) -> bool {
+        match self {
+            TestEnumValidate::Range { start, end } => start <= end,
+        }
+    }
+}
+
+#[test]
+fn test_enum_validate() {
+    let ret_read = TestEnumValidate::try_from(hex!("010102").as_ref()).unwrap();
+    assert_eq!(TestEnumValidate::Range { start: 1, end: 2 }, ret_read);
+
+    assert_eq!(
+        Err(DekuError::Assertion(
+            "TestEnumValidate failed validation: TestEnumValidate :: is_valid".to_string()
+        )),
+        TestEnumValidate::try_from(hex!("010201").as_ref())
+    );
+    assert_eq!(
+        Err(DekuError::Assertion(
+            "TestEnumValidate failed validation: TestEnumValidate :: is_valid".to_string()
+        )),
+        TestEnumValidate::Range { start: 2, end: 1 }.to_bytes()
+    );
+}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+struct TestAssertMsg {
+    len: u8,
+    #[deku(assert = "*len <= 4", assert_msg = "length {len} exceeds maximum of 4")]
+    kind: u8,
+}
+
+#[test]
+fn test_assert_msg() {
+    let ret_read = TestAssertMsg::try_from(hex!("0401").as_ref()).unwrap();
+    assert_eq!(TestAssertMsg { len: 4, kind: 1 }, ret_read);
+
+    assert_eq!(
+        Err(DekuError::Assertion(
+            "length 5 exceeds maximum of 4".to_string()
+        )),
+        TestAssertMsg::try_from(hex!("0501").as_ref())
+    );
+    assert_eq!(
+        Err(DekuError::Assertion(
+            "length 6 exceeds maximum of 4".to_string()
+        )),
+        TestAssertMsg { len: 6, kind: 1 }.to_bytes()
+    );
+}
+
+#[derive(Debug, PartialEq)]
+struct VersionError(u8);
+
+impl core::fmt::Display for VersionError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "unsupported version {}", self.0)
+    }
+}
+
+impl std::error::Error for VersionError {}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+struct TestAssertErr {
+    #[deku(assert_eq = "1", assert_err = "VersionError(*version)")]
+    version: u8,
+}
+
+#[test]
+fn test_assert_err() {
+    let ret_read = TestAssertErr::try_from(hex!("01").as_ref()).unwrap();
+    assert_eq!(TestAssertErr { version: 1 }, ret_read);
+
+    let err = TestAssertErr::try_from(hex!("02").as_ref()).unwrap_err();
+    match &err {
+        DekuError::Custom(custom) => {
+            assert_eq!(
+                Some(&VersionError(2)),
+                custom.downcast_ref::<VersionError>()
+            )
+        }
+        _ => panic!("unexpected error: {:?}", err),
+    }
+    assert_eq!("Custom error: unsupported version 2", err.to_string());
+
+    let err = TestAssertErr { version: 3 }.to_bytes().unwrap_err();
+    assert_eq!("Custom error: unsupported version 3", err.to_string());
+}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+#[deku(assert = "*start <= *end", assert_msg = "invalid range {start}..{end}")]
+struct TestStructAssertMsg {
+    start: u8,
+    end: u8,
+}
+
+#[test]
+fn test_struct_assert_msg() {
+    assert_eq!(
+        Err(DekuError::Assertion("invalid range 2..1".to_string())),
+        TestStructAssertMsg::try_from(hex!("0201").as_ref())
+    );
+    assert_eq!(
+        Err(DekuError::Assertion("invalid range 3..1".to_string())),
+        TestStructAssertMsg { start: 3, end: 1 }.to_bytes()
+    );
+}
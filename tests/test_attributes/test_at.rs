@@ -0,0 +1,263 @@
+use deku::prelude::*;
+use hexlit::hex;
+use std::convert::{TryFrom, TryInto};
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+struct TestAt {
+    name_offset: u8,
+    name_len: u8,
+    #[deku(at = "name_offset", count = "name_len")]
+    name: Vec<u8>,
+    kind: u16,
+}
+
+#[test]
+fn test_at() {
+    let input = hex!("0404ABCD6465_6B75");
+    let ret_read = TestAt::try_from(input.as_ref()).unwrap();
+    assert_eq!(
+        TestAt {
+            name_offset: 4,
+            name_len: 4,
+            name: b"deku".to_vec(),
+            kind: 0xABCD,
+        },
+        ret_read
+    );
+
+    // the referenced data is laid out after the other fields
+    let ret_write: Vec<u8> = ret_read.to_bytes().unwrap();
+    assert_eq!(input.to_vec(), ret_write);
+    assert_eq!(ret_read, TestAt::try_from(ret_write.as_ref()).unwrap());
+}
+
+#[test]
+fn test_at_gap() {
+    let input = hex!("0604ABCD00FF6465_6B75");
+    let (rest, ret_read) = TestAt::from_bytes((input.as_ref(), 0)).unwrap();
+    assert_eq!(
+        TestAt {
+            name_offset: 6,
+            name_len: 4,
+            name: b"deku".to_vec(),
+            kind: 0xABCD,
+        },
+        ret_read
+    );
+    // the rest starts after the referenced data
+    assert!(rest.0.is_empty());
+
+    // the gap isn't kept, the offset is filled in
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(hex!("0404ABCD6465_6B75").to_vec(), ret_write);
+}
+
+#[test]
+fn test_at_past_end() {
+    assert_eq!(
+        Err(DekuError::Parse(
+            "TestAt.name field offset is past the end of the input: 72 of 48 bits".to_string()
+        )),
+        TestAt::from_bytes((hex!("0902ABCD0000").as_ref(), 0))
+    );
+}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+struct TestAtFixed {
+    #[deku(at = "2")]
+    first: u16,
+    #[deku(at = "3")]
+    second: u8,
+}
+
+#[test]
+fn test_at_write_overlap() {
+    let value = TestAtFixed {
+        first: 0xAABB,
+        second: 0xCC,
+    };
+    assert_eq!(
+        Err(DekuError::InvalidParam(
+            "TestAtFixed.second field offset overlaps the TestAtFixed.first field: 24 of 16..32 bits"
+                .to_string()
+        )),
+        value.to_bytes()
+    );
+}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+struct Entry {
+    offset: u8,
+    #[deku(at = "offset")]
+    value: u8,
+}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+struct TestAtNested {
+    tag: u8,
+    // offsets of the entries are relative to the start of the input
+    #[deku(count = "2")]
+    entries: Vec<Entry>,
+}
+
+#[test]
+fn test_at_nested() {
+    let input = hex!("010304AABB");
+    let (rest, ret_read) = TestAtNested::from_bytes((input.as_ref(), 0)).unwrap();
+    assert!(rest.0.is_empty());
+    assert_eq!(
+        TestAtNested {
+            tag: 0x01,
+            entries: vec![
+                Entry {
+                    offset: 3,
+                    value: 0xAA,
+                },
+                Entry {
+                    offset: 4,
+                    value: 0xBB,
+                },
+            ],
+        },
+        ret_read
+    );
+
+    let ret_write = ret_read.to_bytes().unwrap();
+    assert_eq!(input.to_vec(), ret_write);
+    assert_eq!(
+        ret_read,
+        TestAtNested::from_bytes((ret_write.as_ref(), 0)).unwrap().1
+    );
+}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+struct TestAtNestedLast {
+    tag: u8,
+    inner: TestAtInner,
+}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+struct TestAtInner {
+    offset: u8,
+    len: u8,
+    #[deku(at = "offset", count = "len")]
+    data: Vec<u8>,
+}
+
+#[test]
+fn test_at_nested_write() {
+    let input = hex!("01 0302 AABB CC");
+    let (rest, ret_read) = TestAtNestedLast::from_bytes((input.as_ref(), 0)).unwrap();
+    assert_eq!((hex!("CC").as_ref(), 0), rest);
+    assert_eq!(
+        TestAtNestedLast {
+            tag: 0x01,
+            inner: TestAtInner {
+                offset: 3,
+                len: 2,
+                data: vec![0xAA, 0xBB],
+            },
+        },
+        ret_read
+    );
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(input[..5].to_vec(), ret_write);
+}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+struct TestAtChained {
+    offset: u8,
+    // the referenced data has a field with `at` too
+    #[deku(at = "offset")]
+    inner: TestAtInner,
+}
+
+#[test]
+fn test_at_chained() {
+    let input = hex!("01 0301 AA");
+    let (rest, ret_read) = TestAtChained::from_bytes((input.as_ref(), 0)).unwrap();
+    assert!(rest.0.is_empty());
+    assert_eq!(
+        TestAtChained {
+            offset: 1,
+            inner: TestAtInner {
+                offset: 3,
+                len: 1,
+                data: vec![0xAA],
+            },
+        },
+        ret_read
+    );
+
+    let ret_write = ret_read.to_bytes().unwrap();
+    assert_eq!(input.to_vec(), ret_write);
+}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+struct TestAtUnordered {
+    first_offset: u8,
+    second_offset: u8,
+    #[deku(at = "second_offset")]
+    second: u8,
+    #[deku(at = "first_offset")]
+    first: u8,
+    #[deku(at = "0")]
+    header: u16,
+}
+
+#[test]
+fn test_at_unordered() {
+    let input = hex!("0305AA11BB22");
+    let (_, ret_read) = TestAtUnordered::from_bytes((input.as_ref(), 0)).unwrap();
+    assert_eq!(
+        TestAtUnordered {
+            first_offset: 3,
+            second_offset: 5,
+            second: 0x22,
+            first: 0x11,
+            header: u16::from_le_bytes([0x03, 0x05]),
+        },
+        ret_read
+    );
+
+    // the data at a given offset can't overlap the other fields
+    assert_eq!(
+        Err(DekuError::InvalidParam(
+            "TestAtUnordered.header field offset overlaps the preceding fields: 0 of 16 bits"
+                .to_string()
+        )),
+        ret_read.to_bytes()
+    );
+}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+struct TestAtLayout {
+    data_offset: u8,
+    #[deku(update = "self.data.len()")]
+    data_len: u8,
+    #[deku(at = "data_offset", count = "data_len")]
+    data: Vec<u8>,
+    #[deku(at = "2")]
+    header: u16,
+}
+
+#[test]
+fn test_at_layout() {
+    let mut value = TestAtLayout {
+        data_offset: 0,
+        data_len: 0,
+        data: vec![0xAA, 0xBB],
+        header: 0x1122,
+    };
+    value.update().unwrap();
+
+    // the data is laid out after the data at a given offset
+    let ret_write = value.to_bytes().unwrap();
+    assert_eq!(hex!("0402_2211_AABB").to_vec(), ret_write);
+
+    let (_, ret_read) = TestAtLayout::from_bytes((ret_write.as_ref(), 0)).unwrap();
+    value.data_offset = 4;
+    assert_eq!(value, ret_read);
+}
@@ -40,3 +40,133 @@ fn test_cond_deku() {
     let ret_write: Vec<u8> = ret_read.try_into().unwrap();
     assert_eq!(test_data, ret_write);
 }
+
+#[test]
+fn test_cond_write() {
+    #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+    struct TestStruct {
+        field_a: u8,
+        #[deku(cond = "*field_a == 0x01", default = "0x05")]
+        field_b: u8,
+    }
+
+    // `cond` is false, the field isn't written
+    let test_data: Vec<u8> = [0x02].to_vec();
+
+    let ret_read = TestStruct::try_from(test_data.as_ref()).unwrap();
+    assert_eq!(
+        TestStruct {
+            field_a: 0x02,
+            field_b: 0x05,
+        },
+        ret_read
+    );
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(test_data, ret_write);
+
+    // not `cond_strict`, the value is dropped
+    let ret_write: Vec<u8> = TestStruct {
+        field_a: 0x02,
+        field_b: 0x03,
+    }
+    .try_into()
+    .unwrap();
+    assert_eq!(test_data, ret_write);
+}
+
+#[test]
+fn test_cond_strict() {
+    #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+    #[deku(cond_strict)]
+    struct TestStruct {
+        field_a: u8,
+        #[deku(cond = "*field_a == 0x01", default = "0x05")]
+        field_b: u8,
+    }
+
+    let ret_write: Vec<u8> = TestStruct {
+        field_a: 0x01,
+        field_b: 0x03,
+    }
+    .try_into()
+    .unwrap();
+    assert_eq!(vec![0x01, 0x03], ret_write);
+
+    let ret_write: Vec<u8> = TestStruct {
+        field_a: 0x02,
+        field_b: 0x05,
+    }
+    .try_into()
+    .unwrap();
+    assert_eq!(vec![0x02], ret_write);
+
+    assert_eq!(
+        Err(DekuError::InvalidParam(
+            "TestStruct.field_b field differs from the default with false condition: * field_a == 0x01"
+                .to_string()
+        )),
+        TestStruct {
+            field_a: 0x02,
+            field_b: 0x03,
+        }
+        .to_bytes()
+    );
+}
+
+#[test]
+fn test_cond_write_ctx() {
+    // the `ctx` arguments are used rather than the fields of the same name, like when reading
+    #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+    #[deku(ctx = "a: u8", ctx_default = "1")]
+    struct TestStruct {
+        #[deku(cond = "a == 1")]
+        a: Option<u8>,
+        #[deku(cond = "a.is_some()")]
+        b: Option<u8>,
+    }
+
+    let test_data: Vec<u8> = [0x01, 0x02].to_vec();
+
+    let ret_read = TestStruct::try_from(test_data.as_ref()).unwrap();
+    assert_eq!(
+        TestStruct {
+            a: Some(0x01),
+            b: Some(0x02),
+        },
+        ret_read
+    );
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(test_data, ret_write);
+}
+
+#[test]
+fn test_cond_write_rest() {
+    // `deku::rest` doesn't exist when writing, the field is always written
+    #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+    struct TestStruct {
+        field_a: u8,
+        #[deku(cond = "!deku::rest.is_empty()")]
+        field_b: Option<u8>,
+    }
+
+    let test_data: Vec<u8> = [0x01].to_vec();
+
+    let ret_read = TestStruct::try_from(test_data.as_ref()).unwrap();
+    assert_eq!(
+        TestStruct {
+            field_a: 0x01,
+            field_b: None,
+        },
+        ret_read
+    );
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(test_data, ret_write);
+
+    let test_data: Vec<u8> = [0x01, 0x02].to_vec();
+    let ret_read = TestStruct::try_from(test_data.as_ref()).unwrap();
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(test_data, ret_write);
+}
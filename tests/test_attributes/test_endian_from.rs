@@ -0,0 +1,79 @@
+use deku::ctx::Endian;
+use deku::prelude::*;
+use hexlit::hex;
+use rstest::rstest;
+use std::convert::{TryFrom, TryInto};
+
+/// TIFF style byte order mark
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+#[deku(type = "[u8; 2]")]
+enum ByteOrder {
+    #[deku(id = b"II")]
+    Little,
+    #[deku(id = b"MM")]
+    Big,
+}
+
+impl From<&ByteOrder> for Endian {
+    fn from(byte_order: &ByteOrder) -> Self {
+        match byte_order {
+            ByteOrder::Little => Endian::Little,
+            ByteOrder::Big => Endian::Big,
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+#[deku(ctx = "endian: Endian", endian = "endian")]
+struct Child {
+    value: u16,
+}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+#[deku(endian_from = "byte_order")]
+struct Header {
+    len: u8,
+    byte_order: ByteOrder,
+    magic: u16,
+    #[deku(endian = "little")]
+    flags: u16,
+    child: Child,
+}
+
+#[rstest(input, expected,
+    case(&hex!("0149492A0001000200"), Header {
+        len: 1,
+        byte_order: ByteOrder::Little,
+        magic: 42,
+        flags: 1,
+        child: Child { value: 2 },
+    }),
+    case(&hex!("014D4D002A01000002"), Header {
+        len: 1,
+        byte_order: ByteOrder::Big,
+        magic: 42,
+        flags: 1,
+        child: Child { value: 2 },
+    }),
+)]
+fn test_endian_from(input: &[u8], expected: Header) {
+    let ret_read = Header::try_from(input).unwrap();
+    assert_eq!(expected, ret_read);
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(input.to_vec(), ret_write);
+}
+
+#[test]
+fn test_endian_from_unnamed() {
+    #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+    #[deku(endian_from = "0")]
+    struct Header(ByteOrder, u16);
+
+    let input = hex!("4D4D0102");
+    let ret_read = Header::try_from(input.as_ref()).unwrap();
+    assert_eq!(Header(ByteOrder::Big, 0x0102), ret_read);
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(input.to_vec(), ret_write);
+}
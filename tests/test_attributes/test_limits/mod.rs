@@ -1,4 +1,5 @@
 mod test_bits_read;
 mod test_bytes_read;
 mod test_count;
+mod test_terminator;
 mod test_until;
@@ -0,0 +1,113 @@
+use deku::prelude::*;
+use hexlit::hex;
+use rstest::rstest;
+use std::convert::{TryFrom, TryInto};
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+struct TestTerminator {
+    #[deku(terminator = "0x00")]
+    data: Vec<u8>,
+    #[deku(endian = "big", terminator = "0xFFFF")]
+    words: Vec<u16>,
+}
+
+#[rstest(input, expected,
+    case(&hex!("00FFFF"), TestTerminator {
+        data: vec![],
+        words: vec![],
+    }),
+    case(&hex!("AABB00CCDDFFFF"), TestTerminator {
+        data: vec![0xAA, 0xBB],
+        words: vec![0xCCDD],
+    }),
+
+    #[should_panic(expected = "Incomplete(NeedSize { bits: 8 })")]
+    case(&hex!("AABB"), TestTerminator {
+        data: vec![],
+        words: vec![],
+    }),
+)]
+fn test_terminator(input: &[u8], expected: TestTerminator) {
+    let ret_read = TestTerminator::try_from(input).unwrap();
+    assert_eq!(expected, ret_read);
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(input.to_vec(), ret_write);
+}
+
+#[test]
+fn test_terminator_write_contains() {
+    let value = TestTerminator {
+        data: vec![0xAA, 0x00],
+        words: vec![],
+    };
+    assert_eq!(
+        Err(DekuError::InvalidParam(
+            "TestTerminator.data field contains the terminator".to_string()
+        )),
+        value.to_bytes()
+    );
+}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+#[deku(type = "u8")]
+enum Record {
+    #[deku(id = "0")]
+    End,
+    #[deku(id = "1")]
+    Value(u16),
+}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+struct TestEndMarker {
+    #[deku(terminator = "Record::End")]
+    records: Vec<Record>,
+    last: u8,
+}
+
+#[test]
+fn test_terminator_end_marker() {
+    let input = hex!("01AABB01CCDD00FF");
+    let ret_read = TestEndMarker::try_from(input.as_ref()).unwrap();
+    assert_eq!(
+        TestEndMarker {
+            records: vec![Record::Value(0xBBAA), Record::Value(0xDDCC)],
+            last: 0xFF,
+        },
+        ret_read
+    );
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(input.to_vec(), ret_write);
+}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+struct TestUntilExclusive {
+    #[deku(until_exclusive = "|v: &u8| *v & 0x80 != 0", terminator = "0x80")]
+    data: Vec<u8>,
+}
+
+#[test]
+fn test_until_exclusive() {
+    let ret_read = TestUntilExclusive::try_from(hex!("0102FF").as_ref()).unwrap();
+    assert_eq!(
+        TestUntilExclusive {
+            data: vec![0x01, 0x02]
+        },
+        ret_read
+    );
+
+    // the canonical terminator is written
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(hex!("010280").to_vec(), ret_write);
+
+    let value = TestUntilExclusive {
+        data: vec![0x01, 0x90],
+    };
+    assert_eq!(
+        Err(DekuError::InvalidParam(
+            "TestUntilExclusive.data field contains the terminator".to_string()
+        )),
+        value.to_bytes()
+    );
+}
@@ -1,5 +1,5 @@
 use deku::prelude::*;
-use std::convert::TryFrom;
+use std::convert::{TryFrom, TryInto};
 
 #[test]
 fn test_map() {
@@ -28,3 +28,63 @@ fn test_map() {
         ret_read
     );
 }
+
+#[test]
+fn test_write_map() {
+    #[deku_derive(DekuRead, DekuWrite)]
+    #[derive(PartialEq, Debug)]
+    struct TestStruct {
+        #[deku(
+            map = "|field: u8| -> Result<_, DekuError> { Ok(field.to_string()) }",
+            write_map = "|field: &String| -> Result<u8, DekuError> { field.parse().map_err(|_| DekuError::InvalidParam(field.clone())) }"
+        )]
+        field_a: String,
+        #[deku(
+            bits = 4,
+            endian = "big",
+            map = "TestStruct::map_field_b",
+            write_map = "TestStruct::write_map_field_b"
+        )]
+        field_b: bool,
+        #[deku(
+            bits = 4,
+            temp,
+            temp_value = "5",
+            write_map = "|v: &u8| -> Result<u8, DekuError> { Ok(*v * 2) }"
+        )]
+        field_c: u8,
+    }
+
+    impl TestStruct {
+        fn map_field_b(field_b: u8) -> Result<bool, DekuError> {
+            Ok(field_b != 0)
+        }
+
+        fn write_map_field_b(field_b: &bool) -> Result<u8, DekuError> {
+            Ok(if *field_b { 0xF } else { 0 })
+        }
+    }
+
+    let test_data: Vec<u8> = [0x01, 0xF0].to_vec();
+
+    let ret_read = TestStruct::try_from(test_data.as_ref()).unwrap();
+    assert_eq!(
+        TestStruct {
+            field_a: "1".to_string(),
+            field_b: true,
+        },
+        ret_read
+    );
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(vec![0x01, 0xFA], ret_write);
+
+    let invalid = TestStruct {
+        field_a: "256".to_string(),
+        field_b: false,
+    };
+    assert_eq!(
+        Err(DekuError::InvalidParam("256".to_string())),
+        invalid.to_bytes()
+    );
+}
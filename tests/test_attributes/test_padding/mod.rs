@@ -5,6 +5,7 @@ mod test_pad_bits_after;
 mod test_pad_bits_before;
 mod test_pad_bytes_after;
 mod test_pad_bytes_before;
+mod test_pad_fill;
 
 #[test]
 #[allow(clippy::identity_op)]
@@ -0,0 +1,111 @@
+use deku::prelude::*;
+use hexlit::hex;
+use rstest::rstest;
+use std::convert::{TryFrom, TryInto};
+
+#[test]
+fn test_pad_fill() {
+    #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+    struct TestStruct {
+        field_a: u8,
+        #[deku(pad_bytes_before = "2", pad_fill = "0xFF")]
+        field_b: u8,
+    }
+
+    let data: Vec<u8> = hex!("AA 0000 BB").to_vec();
+
+    // not strict, padding isn't checked
+    let ret_read = TestStruct::try_from(data.as_ref()).unwrap();
+    assert_eq!(
+        TestStruct {
+            field_a: 0xAA,
+            field_b: 0xBB,
+        },
+        ret_read
+    );
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(hex!("AA FFFF BB").to_vec(), ret_write);
+}
+
+#[test]
+fn test_pad_fill_pattern() {
+    #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+    #[deku(pad_fill = b"\xAA\x55")]
+    struct TestStruct {
+        #[deku(bits = 4, pad_bits_after = "12")]
+        field_a: u8,
+        #[deku(pad_bytes_before = "3", pad_fill = "0x00")]
+        field_b: u8,
+        #[deku(align_before = "4")]
+        field_c: u8,
+    }
+
+    let data: Vec<u8> = hex!("FFFF 000000 BB FFFF CC").to_vec();
+
+    let ret_read = TestStruct::try_from(data.as_ref()).unwrap();
+    assert_eq!(
+        TestStruct {
+            field_a: 0xF,
+            field_b: 0xBB,
+            field_c: 0xCC,
+        },
+        ret_read
+    );
+
+    // the pattern restarts for each padding
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(hex!("FAA5 000000 BB AA55 CC").to_vec(), ret_write);
+}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+#[deku(pad_strict, align = "4")]
+struct TestStrict {
+    #[deku(bits = 4, pad_bits_after = "4")]
+    field_a: u8,
+    #[deku(pad_bytes_before = "1", pad_fill = b"\xDE\xAD")]
+    field_b: u8,
+}
+
+#[rstest(input, expected,
+    case(&hex!("F0 DE BB 00"), TestStrict { field_a: 0xF, field_b: 0xBB }),
+
+    #[should_panic(expected = r#"Parse("Invalid padding in TestStrict.field_a: expected fill 0")"#)]
+    case(&hex!("F1 DE BB 00"), TestStrict { field_a: 0xF, field_b: 0xBB }),
+
+    #[should_panic(expected = r#"Parse("Invalid padding in TestStrict.field_b: expected fill b\"\\xDE\\xAD\"")"#)]
+    case(&hex!("F0 DF BB 00"), TestStrict { field_a: 0xF, field_b: 0xBB }),
+
+    #[should_panic(expected = r#"Parse("Invalid padding in TestStrict: expected fill 0")"#)]
+    case(&hex!("F0 DE BB 80"), TestStrict { field_a: 0xF, field_b: 0xBB }),
+)]
+fn test_pad_strict(input: &[u8], expected: TestStrict) {
+    let ret_read = TestStrict::try_from(input).unwrap();
+    assert_eq!(expected, ret_read);
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(input.to_vec(), ret_write);
+}
+
+#[test]
+#[should_panic(expected = r#"Parse("Invalid padding in TestStruct.field_b: expected fill 0xFF")"#)]
+fn test_pad_strict_field() {
+    #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+    struct TestStruct {
+        #[deku(pad_bytes_after = "1")]
+        field_a: u8,
+        #[deku(pad_bytes_before = "1", pad_fill = "0xFF", pad_strict)]
+        field_b: u8,
+    }
+
+    let ret_read = TestStruct::try_from(&hex!("AA 12 FF BB")[..]).unwrap();
+    assert_eq!(
+        TestStruct {
+            field_a: 0xAA,
+            field_b: 0xBB,
+        },
+        ret_read
+    );
+
+    let _ = TestStruct::try_from(&hex!("AA 12 FE BB")[..]).unwrap();
+}
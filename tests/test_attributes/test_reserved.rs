@@ -0,0 +1,102 @@
+use deku::prelude::*;
+use hexlit::hex;
+use rstest::rstest;
+use std::convert::{TryFrom, TryInto};
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+struct TestLenient {
+    #[deku(bits = 4)]
+    field_a: u8,
+    #[deku(reserved_bits = "3", bits = 1)]
+    field_b: u8,
+    #[deku(reserved_bytes = "1")]
+    field_c: u8,
+}
+
+#[rstest(input, expected,
+    case(&hex!("A1 00 CC"), TestLenient { field_a: 0xA, field_b: 1, field_c: 0xCC }),
+    case(&hex!("AF FF CC"), TestLenient { field_a: 0xA, field_b: 1, field_c: 0xCC }),
+)]
+fn test_reserved_lenient(input: &[u8], expected: TestLenient) {
+    let ret_read = TestLenient::try_from(input).unwrap();
+    assert_eq!(expected, ret_read);
+
+    // reserved bits are always written as zero
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(hex!("A1 00 CC").to_vec(), ret_write);
+}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+#[deku(reserved_strict)]
+struct TestStrict {
+    #[deku(bits = 4)]
+    field_a: u8,
+    #[deku(reserved_bits = "3", reserved_value = "0b101", bits = 1)]
+    field_b: u8,
+    #[deku(reserved_bytes = "1")]
+    field_c: u8,
+}
+
+#[rstest(input, expected,
+    case(&hex!("AB 00 CC"), TestStrict { field_a: 0xA, field_b: 1, field_c: 0xCC }),
+
+    #[should_panic(expected = r#"Parse("Invalid reserved bits before TestStrict.field_b: expected 0x5")"#)]
+    case(&hex!("A1 00 CC"), TestStrict { field_a: 0xA, field_b: 1, field_c: 0xCC }),
+
+    #[should_panic(expected = r#"Parse("Invalid reserved bits before TestStrict.field_c: expected 0x0")"#)]
+    case(&hex!("AB 01 CC"), TestStrict { field_a: 0xA, field_b: 1, field_c: 0xCC }),
+
+    #[should_panic(expected = "Incomplete(NeedSize { bits: 8 })")]
+    case(&hex!("AB"), TestStrict { field_a: 0xA, field_b: 1, field_c: 0xCC }),
+)]
+fn test_reserved_strict(input: &[u8], expected: TestStrict) {
+    let ret_read = TestStrict::try_from(input).unwrap();
+    assert_eq!(expected, ret_read);
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(input.to_vec(), ret_write);
+}
+
+#[test]
+fn test_reserved_field_strict() {
+    #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+    struct TestStruct {
+        #[deku(reserved_bits = "4", bits = 4)]
+        field_a: u8,
+        #[deku(reserved_bits = "4", reserved_strict, bits = 4)]
+        field_b: u8,
+    }
+
+    let ret_read = TestStruct::try_from(&hex!("F1 02")[..]).unwrap();
+    assert_eq!(
+        TestStruct {
+            field_a: 1,
+            field_b: 2,
+        },
+        ret_read
+    );
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(hex!("01 02").to_vec(), ret_write);
+
+    assert_eq!(
+        Err(DekuError::Parse(
+            "Invalid reserved bits before TestStruct.field_b: expected 0x0".to_string()
+        )),
+        TestStruct::try_from(&hex!("01 F2")[..])
+    );
+}
+
+#[test]
+fn test_reserved_value_too_large() {
+    #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+    struct TestStruct {
+        #[deku(reserved_bits = "2", reserved_value = "0b100", bits = 6)]
+        field_a: u8,
+    }
+
+    let expected =
+        DekuError::InvalidParam("Invalid reserved value 0x4: does not fit in 2 bits".to_string());
+    assert_eq!(expected, TestStruct::try_from(&hex!("01")[..]).unwrap_err());
+    assert_eq!(expected, TestStruct { field_a: 1 }.to_bytes().unwrap_err());
+}
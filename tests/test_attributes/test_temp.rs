@@ -160,3 +160,25 @@ fn test_temp_enum_field_write() {
     let ret_write: Vec<u8> = TestEnum::VarB(0x10).to_bytes().unwrap();
     assert_eq!(test_data, ret_write);
 }
+
+#[test]
+fn test_temp_enum_field_only() {
+    #[deku_derive(DekuRead, DekuWrite)]
+    #[derive(PartialEq, Debug)]
+    #[deku(type = "u8")]
+    enum TestEnum {
+        #[deku(id = "0xAB")]
+        VarA {
+            #[deku(temp, temp_value = "2")]
+            field_a: u8,
+        },
+    }
+
+    let test_data: Vec<u8> = [0xAB, 0x02].to_vec();
+
+    let ret_read = TestEnum::try_from(test_data.as_ref()).unwrap();
+    assert_eq!(TestEnum::VarA {}, ret_read);
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(test_data, ret_write);
+}
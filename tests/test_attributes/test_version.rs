@@ -0,0 +1,127 @@
+use deku::prelude::*;
+use hexlit::hex;
+use rstest::rstest;
+use std::convert::{TryFrom, TryInto};
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+#[deku(version = "*version")]
+struct TestVersion {
+    version: u8,
+    field_a: u8,
+    #[deku(since = "2")]
+    field_b: u8,
+    #[deku(until_version = "2")]
+    field_c: u8,
+    #[deku(since = "2", until_version = "3", default = "0xff")]
+    field_d: u8,
+}
+
+#[rstest(input, expected,
+    case(&hex!("01AACC"), TestVersion {
+        version: 1,
+        field_a: 0xAA,
+        field_b: 0,
+        field_c: 0xCC,
+        field_d: 0xFF,
+    }),
+    case(&hex!("02AABBCCDD"), TestVersion {
+        version: 2,
+        field_a: 0xAA,
+        field_b: 0xBB,
+        field_c: 0xCC,
+        field_d: 0xDD,
+    }),
+    case(&hex!("03AABBDD"), TestVersion {
+        version: 3,
+        field_a: 0xAA,
+        field_b: 0xBB,
+        field_c: 0,
+        field_d: 0xDD,
+    }),
+    case(&hex!("04AABB"), TestVersion {
+        version: 4,
+        field_a: 0xAA,
+        field_b: 0xBB,
+        field_c: 0,
+        field_d: 0xFF,
+    }),
+)]
+fn test_version(input: &[u8], expected: TestVersion) {
+    let (rest, ret_read) = TestVersion::from_bytes((input, 0)).unwrap();
+    assert_eq!(expected, ret_read);
+    assert_eq!(&input[input.len()..], rest.0);
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(input.to_vec(), ret_write);
+}
+
+#[rstest(input, expected,
+    case(TestVersion {
+        version: 1,
+        field_a: 0xAA,
+        field_b: 0xBB,
+        field_c: 0xCC,
+        field_d: 0xFF,
+    }, "TestVersion.field_b field is not present in version: (* version) >= (2)"),
+    case(TestVersion {
+        version: 3,
+        field_a: 0xAA,
+        field_b: 0xBB,
+        field_c: 0xCC,
+        field_d: 0xDD,
+    }, "TestVersion.field_c field is not present in version: (* version) <= (2)"),
+    case(TestVersion {
+        version: 4,
+        field_a: 0xAA,
+        field_b: 0xBB,
+        field_c: 0,
+        field_d: 0,
+    }, "TestVersion.field_d field is not present in version: (* version) >= (2) && (* version) <= (3)"),
+)]
+fn test_version_write_unsupported(input: TestVersion, expected: &str) {
+    assert_eq!(
+        Err(DekuError::InvalidParam(expected.to_string())),
+        input.to_bytes()
+    );
+}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+#[deku(
+    type = "u8",
+    ctx = "version: u8",
+    ctx_default = "1",
+    version = "version"
+)]
+enum TestVersionCtx {
+    #[deku(id = "1")]
+    Point {
+        x: u8,
+        #[deku(since = "2")]
+        y: Option<u8>,
+    },
+}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+struct TestVersionHeader {
+    version: u8,
+    #[deku(ctx = "*version")]
+    point: TestVersionCtx,
+}
+
+#[rstest(input, expected,
+    case(&hex!("010101"), TestVersionHeader {
+        version: 1,
+        point: TestVersionCtx::Point { x: 1, y: None },
+    }),
+    case(&hex!("02010102"), TestVersionHeader {
+        version: 2,
+        point: TestVersionCtx::Point { x: 1, y: Some(2) },
+    }),
+)]
+fn test_version_ctx(input: &[u8], expected: TestVersionHeader) {
+    let ret_read = TestVersionHeader::try_from(input).unwrap();
+    assert_eq!(expected, ret_read);
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(input.to_vec(), ret_write);
+}
@@ -0,0 +1,138 @@
+use deku::prelude::*;
+use hexlit::hex;
+use rstest::rstest;
+use std::convert::{TryFrom, TryInto};
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+struct Child {
+    a: u8,
+    b: u8,
+}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+struct TestWindowSkip {
+    len: u8,
+    #[deku(window_bytes = "len", rest = "skip")]
+    child: Child,
+    trailer: u8,
+}
+
+#[rstest(input, expected, expected_write,
+    case(&hex!("02AABBFF"), TestWindowSkip {
+        len: 2,
+        child: Child { a: 0xAA, b: 0xBB },
+        trailer: 0xFF,
+    }, hex!("02AABBFF").to_vec()),
+    // unknown trailing bytes are skipped, and written back as padding
+    case(&hex!("04AABBCCDDFF"), TestWindowSkip {
+        len: 4,
+        child: Child { a: 0xAA, b: 0xBB },
+        trailer: 0xFF,
+    }, hex!("04AABB0000FF").to_vec()),
+
+    #[should_panic(expected = "Incomplete(NeedSize { bits: 8 })")]
+    case(&hex!("01AABBFF"), TestWindowSkip {
+        len: 0,
+        child: Child { a: 0, b: 0 },
+        trailer: 0,
+    }, vec![]),
+    #[should_panic(expected = "Incomplete(NeedSize { bits: 32 })")]
+    case(&hex!("04AABB"), TestWindowSkip {
+        len: 0,
+        child: Child { a: 0, b: 0 },
+        trailer: 0,
+    }, vec![]),
+)]
+fn test_window_skip(input: &[u8], expected: TestWindowSkip, expected_write: Vec<u8>) {
+    let ret_read = TestWindowSkip::try_from(input).unwrap();
+    assert_eq!(expected, ret_read);
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(expected_write, ret_write);
+}
+
+#[test]
+fn test_window_write_too_large() {
+    let value = TestWindowSkip {
+        len: 1,
+        child: Child { a: 0xAA, b: 0xBB },
+        trailer: 0xFF,
+    };
+    assert_eq!(
+        Err(DekuError::InvalidParam(
+            "TestWindowSkip.child field is larger than its window: 16 of 8 bits".to_string()
+        )),
+        value.to_bytes()
+    );
+}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+struct TestWindowError {
+    len: u8,
+    #[deku(window_bytes = "len")]
+    child: Child,
+}
+
+#[test]
+fn test_window_error() {
+    let ret_read = TestWindowError::try_from(hex!("02AABB").as_ref()).unwrap();
+    assert_eq!(
+        TestWindowError {
+            len: 2,
+            child: Child { a: 0xAA, b: 0xBB },
+        },
+        ret_read
+    );
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(hex!("02AABB").to_vec(), ret_write);
+
+    assert_eq!(
+        Err(DekuError::Parse(
+            "TestWindowError.child field has 8 unread bits in its window".to_string()
+        )),
+        TestWindowError::try_from(hex!("03AABBCC").as_ref())
+    );
+
+    let value = TestWindowError {
+        len: 3,
+        child: Child { a: 0xAA, b: 0xBB },
+    };
+    assert_eq!(
+        Err(DekuError::InvalidParam(
+            "TestWindowError.child field doesn't fill its window: 16 of 24 bits".to_string()
+        )),
+        value.to_bytes()
+    );
+}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+struct TestWindowKeep {
+    len: u8,
+    #[deku(window_bytes = "len", rest = "keep", count = "2")]
+    data: Vec<u8>,
+    #[deku(count = "*len as usize - data.len()")]
+    extra: Vec<u8>,
+}
+
+#[test]
+fn test_window_keep() {
+    let input = hex!("04AABBCCDD");
+    let ret_read = TestWindowKeep::try_from(input.as_ref()).unwrap();
+    assert_eq!(
+        TestWindowKeep {
+            len: 4,
+            data: vec![0xAA, 0xBB],
+            extra: vec![0xCC, 0xDD],
+        },
+        ret_read
+    );
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(input.to_vec(), ret_write);
+
+    // the window bounds the field
+    assert_eq!(
+        Err(DekuError::Incomplete(deku::error::NeedSize::new(8))),
+        TestWindowKeep::try_from(hex!("01AABBCC").as_ref())
+    );
+}
@@ -0,0 +1,345 @@
+#![cfg(feature = "c_header")]
+
+use deku::prelude::*;
+use deku::schema::c_header::to_c_header;
+use std::net::Ipv4Addr;
+
+/// Same layout as `examples/ipv4.rs`
+#[derive(Debug, PartialEq, DekuRead, DekuWrite, DekuSchema)]
+#[deku(endian = "big")]
+struct Ipv4Header {
+    #[deku(bits = "4")]
+    version: u8,
+    #[deku(bits = "4")]
+    ihl: u8,
+    #[deku(bits = "6")]
+    dscp: u8,
+    #[deku(bits = "2")]
+    ecn: u8,
+    length: u16,
+    identification: u16,
+    #[deku(bits = "3")]
+    flags: u8,
+    #[deku(bits = "13")]
+    offset: u16,
+    ttl: u8,
+    protocol: u8,
+    checksum: u16,
+    src: Ipv4Addr,
+    dst: Ipv4Addr,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite, DekuSchema)]
+#[deku(magic = b"DK", endian = "little")]
+struct Record {
+    kind: Kind,
+    #[deku(bits = "10")]
+    level: u16,
+    #[deku(bits = "2")]
+    delta: i8,
+    #[deku(pad_bytes_before = "1")]
+    scale: f32,
+    enabled: bool,
+    points: [Point; 2],
+    command: Command,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite, DekuSchema)]
+#[deku(
+    type = "u8",
+    bits = "4",
+    endian = "endian",
+    ctx = "endian: deku::ctx::Endian"
+)]
+enum Kind {
+    Off = 0,
+    On = 1,
+    Blinking = 2,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite, DekuSchema)]
+#[deku(endian = "endian", ctx = "endian: deku::ctx::Endian")]
+struct Point {
+    #[deku(bits = "12")]
+    x: u16,
+    #[deku(bits = "12", endian = "big")]
+    y: i16,
+}
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite, DekuSchema)]
+#[deku(type = "u8", endian = "endian", ctx = "endian: deku::ctx::Endian")]
+enum Command {
+    #[deku(id = "0x01")]
+    Move { dx: i8, dy: i8 },
+    #[deku(id = "0x02")]
+    Wait(u16),
+    #[deku(id_pat = "_")]
+    Other(u8, u16),
+}
+
+#[test]
+fn test_c_header_ipv4() {
+    let header = to_c_header(&Ipv4Header::SCHEMA, &[]).unwrap();
+    assert_eq!(include_str!("test_c_header/ipv4_header.h"), header);
+}
+
+#[test]
+fn test_c_header_nested() {
+    // the golden file was checked against these bytes with a C compiler
+    let record = Record {
+        kind: Kind::Blinking,
+        level: 0x2A5,
+        delta: -2,
+        scale: 1.5,
+        enabled: true,
+        points: [Point { x: 0xABC, y: -2 }, Point { x: 0x123, y: 1000 }],
+        command: Command::Move { dx: -1, dy: 5 },
+    };
+    assert_eq!(
+        vec![
+            0x44, 0x4b, 0x2a, 0x5a, 0x00, 0x00, 0x00, 0xc0, 0x3f, 0x01, 0xbc, 0xaf, 0xfe, 0x23,
+            0x13, 0xe8, 0x01, 0xff, 0x05
+        ],
+        record.to_bytes().unwrap()
+    );
+
+    let header = to_c_header(
+        &Record::SCHEMA,
+        &[Kind::SCHEMA, Point::SCHEMA, Command::SCHEMA],
+    )
+    .unwrap();
+    assert_eq!(include_str!("test_c_header/record.h"), header);
+}
+
+#[test]
+fn test_c_header_not_fixed_size() {
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    struct Variable {
+        count: u8,
+        #[deku(count = "count")]
+        data: Vec<u8>,
+    }
+
+    assert_eq!(
+        Err(DekuError::InvalidParam(
+            "cannot generate C for `Variable`: field `data`: `Vec` and `Option` aren't fixed size"
+                .to_string()
+        )),
+        to_c_header(&Variable::SCHEMA, &[])
+    );
+
+    // nested types must be given
+    assert_eq!(
+        Err(DekuError::InvalidParam(
+            "cannot generate C for `Record`: field `kind`: unknown type `Kind`".to_string()
+        )),
+        to_c_header(&Record::SCHEMA, &[])
+    );
+}
+
+#[test]
+fn test_c_header_align() {
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    #[deku(align = "4")]
+    struct Aligned {
+        #[deku(bits = "3", align_bits_after = "8")]
+        flags: u8,
+        #[deku(align_before = "2")]
+        value: u8,
+    }
+
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    #[deku(align_input)]
+    struct AlignedInput {
+        #[deku(align_before = "2")]
+        value: u8,
+    }
+
+    let header = to_c_header(&Aligned::SCHEMA, &[]).unwrap();
+    assert!(header.contains("#define ALIGNED_SIZE 4\n"));
+    assert!(header.contains(
+        "    value->value = (uint8_t)deku_read_bits(in, offset + 16, 8, DEKU_NATIVE_LITTLE);\n"
+    ));
+
+    assert_eq!(
+        Err(DekuError::InvalidParam(
+            "cannot generate C for `AlignedInput`: alignment relative to the input".to_string()
+        )),
+        to_c_header(&AlignedInput::SCHEMA, &[])
+    );
+}
+
+#[test]
+fn test_c_header_pad_fill() {
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    #[deku(pad_fill = "0xFF", pad_strict)]
+    struct Filled {
+        #[deku(bits = "4", pad_bits_after = "12")]
+        a: u8,
+        b: u8,
+    }
+
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    struct FilledPattern {
+        #[deku(pad_bytes_after = "1", pad_fill = b"\xAA\x55")]
+        a: u8,
+    }
+
+    let header = to_c_header(&Filled::SCHEMA, &[]).unwrap();
+    assert!(header.contains(
+        r#"    value->a = (uint8_t)deku_read_bits(in, offset, 4, DEKU_NATIVE_LITTLE);
+    if (deku_read_bits(in, offset + 4, 8, 0) != 0xff) {
+        return -1;
+    }
+    if (deku_read_bits(in, offset + 12, 4, 0) != 0x0f) {
+        return -1;
+    }
+"#
+    ));
+    assert!(header.contains(
+        r#"    deku_write_bits(out, offset + 4, 8, 0, 0xff);
+    deku_write_bits(out, offset + 12, 4, 0, 0x0f);
+"#
+    ));
+
+    assert_eq!(
+        Err(DekuError::InvalidParam(
+            r#"cannot generate C for `FilledPattern`: field `a`: padding fill `b"\xAA\x55"` isn't a literal byte"#
+                .to_string()
+        )),
+        to_c_header(&FilledPattern::SCHEMA, &[])
+    );
+}
+
+#[test]
+fn test_c_header_reserved() {
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    #[deku(reserved_strict)]
+    struct Reserved {
+        #[deku(reserved_bits = "4", reserved_value = "0b1010", bits = "4")]
+        a: u8,
+    }
+
+    let header = to_c_header(&Reserved::SCHEMA, &[]).unwrap();
+    assert!(header.contains(
+        r#"    if (deku_read_bits(in, offset, 4, 0) != 0xa) {
+        return -1;
+    }
+    value->a = (uint8_t)deku_read_bits(in, offset + 4, 4, DEKU_NATIVE_LITTLE);
+"#
+    ));
+    assert!(header.contains(
+        r#"    deku_write_bits(out, offset, 4, 0, 0xa);
+"#
+    ));
+}
+
+#[test]
+fn test_c_header_unknown() {
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    #[deku(type = "u8")]
+    enum Message {
+        #[deku(id = "1")]
+        Ping,
+        #[deku(unknown)]
+        Unknown(u8),
+    }
+
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    #[deku(type = "u8")]
+    enum MessageRest {
+        #[deku(id = "1")]
+        Ping,
+        #[deku(unknown)]
+        Unknown(u8, Vec<u8>),
+    }
+
+    // any id is accepted and packed back
+    let header = to_c_header(&Message::SCHEMA, &[]).unwrap();
+    assert!(header.contains(
+        r#"    if (value->id == MESSAGE_PING) {
+        /* Ping */
+    } else {
+        /* Unknown */
+    }
+    return 0;
+"#
+    ));
+    assert!(header.contains(
+        r#"    } else {
+        /* Unknown */
+        deku_write_bits(out, offset, 8, DEKU_NATIVE_LITTLE, value->id);
+    }
+"#
+    ));
+
+    assert_eq!(
+        Err(DekuError::InvalidParam(
+            "cannot generate C for `MessageRest`: remaining bytes of an `unknown` variant"
+                .to_string()
+        )),
+        to_c_header(&MessageRest::SCHEMA, &[])
+    );
+}
+
+#[test]
+fn test_c_header_variant_magic() {
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    #[deku(type = "u8")]
+    enum Header {
+        #[deku(id = "1", magic = b"\x2a")]
+        Little(u8),
+        #[deku(id = "2", magic = b"\x2b")]
+        Big(u8),
+    }
+
+    // the variant magic follows the id
+    let header = to_c_header(&Header::SCHEMA, &[]).unwrap();
+    assert!(header.contains(
+        r#"    if (value->id == HEADER_LITTLE) {
+        /* Little */
+        if (deku_read_bits(in, offset + 8, 8, 0) != 0x2a) {
+            return -1;
+        }
+        value->variant.little.field_0 = (uint8_t)deku_read_bits(in, offset + 16, 8, DEKU_NATIVE_LITTLE);
+"#
+    ));
+    assert!(header.contains(
+        r#"        deku_write_bits(out, offset, 8, DEKU_NATIVE_LITTLE, value->id);
+        deku_write_bits(out, offset + 8, 8, 0, 0x2a);
+        deku_write_bits(out, offset + 16, 8, DEKU_NATIVE_LITTLE, value->variant.little.field_0);
+"#
+    ));
+}
+
+#[test]
+fn test_c_header_endian_from() {
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    #[deku(endian_from = "0")]
+    struct Header(Endianness, u16);
+
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    #[deku(type = "u8")]
+    enum Endianness {
+        #[deku(id = "0")]
+        Little,
+        #[deku(id = "1")]
+        Big,
+    }
+
+    impl From<&Endianness> for deku::ctx::Endian {
+        fn from(endianness: &Endianness) -> Self {
+            match endianness {
+                Endianness::Little => deku::ctx::Endian::Little,
+                Endianness::Big => deku::ctx::Endian::Big,
+            }
+        }
+    }
+
+    assert_eq!(
+        Err(DekuError::InvalidParam(
+            "cannot generate C for `Header`: endianness selected by `endian_from`".to_string()
+        )),
+        to_c_header(&Header::SCHEMA, &[Endianness::SCHEMA])
+    );
+}
@@ -0,0 +1,170 @@
+/* Generated by deku from `Ipv4Header` */
+
+#ifndef IPV4_HEADER_H
+#define IPV4_HEADER_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <string.h>
+
+#ifndef DEKU_HELPERS
+#define DEKU_HELPERS
+
+#ifndef DEKU_NATIVE_LITTLE
+#define DEKU_NATIVE_LITTLE (__BYTE_ORDER__ == __ORDER_LITTLE_ENDIAN__)
+#endif
+
+/* Read `bits` bits at the bit `offset` of `buf`, most significant bit first.
+ * Little endian values are made of 8 bit chunks, the least significant first. */
+static inline uint64_t deku_read_bits(const uint8_t *buf, size_t offset, unsigned bits, int little)
+{
+    uint64_t value = 0;
+    if (little) {
+        unsigned shift = 0;
+        while (bits > 0) {
+            unsigned n = bits < 8 ? bits : 8;
+            value |= deku_read_bits(buf, offset, n, 0) << shift;
+            offset += n;
+            bits -= n;
+            shift += 8;
+        }
+        return value;
+    }
+    for (unsigned i = 0; i < bits; i++) {
+        size_t bit = offset + i;
+        value = (value << 1) | ((buf[bit / 8] >> (7 - bit % 8)) & 1u);
+    }
+    return value;
+}
+
+/* Write the low `bits` bits of `value` at the bit `offset` of `buf` */
+static inline void deku_write_bits(uint8_t *buf, size_t offset, unsigned bits, int little, uint64_t value)
+{
+    if (little) {
+        while (bits > 0) {
+            unsigned n = bits < 8 ? bits : 8;
+            deku_write_bits(buf, offset, n, 0, value);
+            value >>= 8;
+            offset += n;
+            bits -= n;
+        }
+        return;
+    }
+    for (unsigned i = 0; i < bits; i++) {
+        size_t bit = offset + i;
+        uint8_t mask = (uint8_t)(0x80u >> (bit % 8));
+        if ((value >> (bits - 1 - i)) & 1u) {
+            buf[bit / 8] |= mask;
+        } else {
+            buf[bit / 8] &= (uint8_t)~mask;
+        }
+    }
+}
+
+static inline int64_t deku_sign_extend(uint64_t value, unsigned bits)
+{
+    uint64_t sign = (uint64_t)1 << (bits - 1);
+    return (int64_t)((value ^ sign) - sign);
+}
+
+static inline float deku_f32(uint64_t bits)
+{
+    uint32_t v = (uint32_t)bits;
+    float f;
+    memcpy(&f, &v, sizeof(f));
+    return f;
+}
+
+static inline double deku_f64(uint64_t bits)
+{
+    double d;
+    memcpy(&d, &bits, sizeof(d));
+    return d;
+}
+
+static inline uint64_t deku_f32_bits(float f)
+{
+    uint32_t v;
+    memcpy(&v, &f, sizeof(v));
+    return v;
+}
+
+static inline uint64_t deku_f64_bits(double d)
+{
+    uint64_t v;
+    memcpy(&v, &d, sizeof(v));
+    return v;
+}
+
+#endif /* DEKU_HELPERS */
+
+/* `Ipv4Header` */
+
+#define IPV4_HEADER_SIZE 20
+
+typedef struct {
+    uint8_t version : 4;
+    uint8_t ihl : 4;
+    uint8_t dscp : 6;
+    uint8_t ecn : 2;
+    uint16_t length;
+    uint16_t identification;
+    uint8_t flags : 3;
+    uint16_t offset : 13;
+    uint8_t ttl;
+    uint8_t protocol;
+    uint16_t checksum;
+    uint32_t src;
+    uint32_t dst;
+} ipv4_header_t;
+
+static inline int ipv4_header_unpack_at(ipv4_header_t *value, const uint8_t *in, size_t offset)
+{
+    value->version = (uint8_t)deku_read_bits(in, offset, 4, 0);
+    value->ihl = (uint8_t)deku_read_bits(in, offset + 4, 4, 0);
+    value->dscp = (uint8_t)deku_read_bits(in, offset + 8, 6, 0);
+    value->ecn = (uint8_t)deku_read_bits(in, offset + 14, 2, 0);
+    value->length = (uint16_t)deku_read_bits(in, offset + 16, 16, 0);
+    value->identification = (uint16_t)deku_read_bits(in, offset + 32, 16, 0);
+    value->flags = (uint8_t)deku_read_bits(in, offset + 48, 3, 0);
+    value->offset = (uint16_t)deku_read_bits(in, offset + 51, 13, 0);
+    value->ttl = (uint8_t)deku_read_bits(in, offset + 64, 8, 0);
+    value->protocol = (uint8_t)deku_read_bits(in, offset + 72, 8, 0);
+    value->checksum = (uint16_t)deku_read_bits(in, offset + 80, 16, 0);
+    value->src = (uint32_t)deku_read_bits(in, offset + 96, 32, 0);
+    value->dst = (uint32_t)deku_read_bits(in, offset + 128, 32, 0);
+    return 0;
+}
+
+static inline void ipv4_header_pack_at(const ipv4_header_t *value, uint8_t *out, size_t offset)
+{
+    deku_write_bits(out, offset, 4, 0, value->version);
+    deku_write_bits(out, offset + 4, 4, 0, value->ihl);
+    deku_write_bits(out, offset + 8, 6, 0, value->dscp);
+    deku_write_bits(out, offset + 14, 2, 0, value->ecn);
+    deku_write_bits(out, offset + 16, 16, 0, value->length);
+    deku_write_bits(out, offset + 32, 16, 0, value->identification);
+    deku_write_bits(out, offset + 48, 3, 0, value->flags);
+    deku_write_bits(out, offset + 51, 13, 0, value->offset);
+    deku_write_bits(out, offset + 64, 8, 0, value->ttl);
+    deku_write_bits(out, offset + 72, 8, 0, value->protocol);
+    deku_write_bits(out, offset + 80, 16, 0, value->checksum);
+    deku_write_bits(out, offset + 96, 32, 0, value->src);
+    deku_write_bits(out, offset + 128, 32, 0, value->dst);
+}
+
+/* Unpack `IPV4_HEADER_SIZE` bytes of `in`, returns 0 on success */
+static inline int ipv4_header_unpack(ipv4_header_t *value, const uint8_t *in)
+{
+    return ipv4_header_unpack_at(value, in, 0);
+}
+
+/* Pack `value` into `IPV4_HEADER_SIZE` bytes of `out` */
+static inline void ipv4_header_pack(const ipv4_header_t *value, uint8_t *out)
+{
+    memset(out, 0, IPV4_HEADER_SIZE);
+    ipv4_header_pack_at(value, out, 0);
+}
+
+#endif /* IPV4_HEADER_H */
@@ -0,0 +1,331 @@
+/* Generated by deku from `Record` */
+
+#ifndef RECORD_H
+#define RECORD_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <string.h>
+
+#ifndef DEKU_HELPERS
+#define DEKU_HELPERS
+
+#ifndef DEKU_NATIVE_LITTLE
+#define DEKU_NATIVE_LITTLE (__BYTE_ORDER__ == __ORDER_LITTLE_ENDIAN__)
+#endif
+
+/* Read `bits` bits at the bit `offset` of `buf`, most significant bit first.
+ * Little endian values are made of 8 bit chunks, the least significant first. */
+static inline uint64_t deku_read_bits(const uint8_t *buf, size_t offset, unsigned bits, int little)
+{
+    uint64_t value = 0;
+    if (little) {
+        unsigned shift = 0;
+        while (bits > 0) {
+            unsigned n = bits < 8 ? bits : 8;
+            value |= deku_read_bits(buf, offset, n, 0) << shift;
+            offset += n;
+            bits -= n;
+            shift += 8;
+        }
+        return value;
+    }
+    for (unsigned i = 0; i < bits; i++) {
+        size_t bit = offset + i;
+        value = (value << 1) | ((buf[bit / 8] >> (7 - bit % 8)) & 1u);
+    }
+    return value;
+}
+
+/* Write the low `bits` bits of `value` at the bit `offset` of `buf` */
+static inline void deku_write_bits(uint8_t *buf, size_t offset, unsigned bits, int little, uint64_t value)
+{
+    if (little) {
+        while (bits > 0) {
+            unsigned n = bits < 8 ? bits : 8;
+            deku_write_bits(buf, offset, n, 0, value);
+            value >>= 8;
+            offset += n;
+            bits -= n;
+        }
+        return;
+    }
+    for (unsigned i = 0; i < bits; i++) {
+        size_t bit = offset + i;
+        uint8_t mask = (uint8_t)(0x80u >> (bit % 8));
+        if ((value >> (bits - 1 - i)) & 1u) {
+            buf[bit / 8] |= mask;
+        } else {
+            buf[bit / 8] &= (uint8_t)~mask;
+        }
+    }
+}
+
+static inline int64_t deku_sign_extend(uint64_t value, unsigned bits)
+{
+    uint64_t sign = (uint64_t)1 << (bits - 1);
+    return (int64_t)((value ^ sign) - sign);
+}
+
+static inline float deku_f32(uint64_t bits)
+{
+    uint32_t v = (uint32_t)bits;
+    float f;
+    memcpy(&f, &v, sizeof(f));
+    return f;
+}
+
+static inline double deku_f64(uint64_t bits)
+{
+    double d;
+    memcpy(&d, &bits, sizeof(d));
+    return d;
+}
+
+static inline uint64_t deku_f32_bits(float f)
+{
+    uint32_t v;
+    memcpy(&v, &f, sizeof(v));
+    return v;
+}
+
+static inline uint64_t deku_f64_bits(double d)
+{
+    uint64_t v;
+    memcpy(&v, &d, sizeof(v));
+    return v;
+}
+
+#endif /* DEKU_HELPERS */
+
+/* `Kind` */
+
+#define KIND_OFF 0
+#define KIND_ON 1
+#define KIND_BLINKING 2
+
+#define KIND_SIZE 1
+
+typedef struct {
+    uint8_t id : 4;
+} kind_t;
+
+static inline int kind_unpack_at(kind_t *value, const uint8_t *in, size_t offset, int little)
+{
+    value->id = (uint8_t)deku_read_bits(in, offset, 4, little);
+    if (value->id == KIND_OFF) {
+        /* Off */
+    } else if (value->id == KIND_ON) {
+        /* On */
+    } else if (value->id == KIND_BLINKING) {
+        /* Blinking */
+    } else {
+        return -1;
+    }
+    return 0;
+}
+
+static inline void kind_pack_at(const kind_t *value, uint8_t *out, size_t offset, int little)
+{
+    if (value->id == KIND_OFF) {
+        /* Off */
+        deku_write_bits(out, offset, 4, little, value->id);
+    } else if (value->id == KIND_ON) {
+        /* On */
+        deku_write_bits(out, offset, 4, little, value->id);
+    } else if (value->id == KIND_BLINKING) {
+        /* Blinking */
+        deku_write_bits(out, offset, 4, little, value->id);
+    } else {
+        deku_write_bits(out, offset, 4, little, value->id);
+    }
+}
+
+/* Unpack `KIND_SIZE` bytes of `in`, returns 0 on success */
+static inline int kind_unpack(kind_t *value, const uint8_t *in, int little)
+{
+    return kind_unpack_at(value, in, 0, little);
+}
+
+/* Pack `value` into `KIND_SIZE` bytes of `out` */
+static inline void kind_pack(const kind_t *value, uint8_t *out, int little)
+{
+    memset(out, 0, KIND_SIZE);
+    kind_pack_at(value, out, 0, little);
+}
+
+/* `Point` */
+
+#define POINT_SIZE 3
+
+typedef struct {
+    uint16_t x : 12;
+    int16_t y : 12;
+} point_t;
+
+static inline int point_unpack_at(point_t *value, const uint8_t *in, size_t offset, int little)
+{
+    value->x = (uint16_t)deku_read_bits(in, offset, 12, little);
+    value->y = (int16_t)deku_sign_extend(deku_read_bits(in, offset + 12, 12, 0), 12);
+    return 0;
+}
+
+static inline void point_pack_at(const point_t *value, uint8_t *out, size_t offset, int little)
+{
+    deku_write_bits(out, offset, 12, little, value->x);
+    deku_write_bits(out, offset + 12, 12, 0, (uint64_t)value->y);
+}
+
+/* Unpack `POINT_SIZE` bytes of `in`, returns 0 on success */
+static inline int point_unpack(point_t *value, const uint8_t *in, int little)
+{
+    return point_unpack_at(value, in, 0, little);
+}
+
+/* Pack `value` into `POINT_SIZE` bytes of `out` */
+static inline void point_pack(const point_t *value, uint8_t *out, int little)
+{
+    memset(out, 0, POINT_SIZE);
+    point_pack_at(value, out, 0, little);
+}
+
+/* `Command` */
+
+#define COMMAND_MOVE 0x01
+#define COMMAND_WAIT 0x02
+
+#define COMMAND_SIZE 3
+
+typedef struct {
+    uint8_t id;
+    union {
+        struct {
+            int8_t dx;
+            int8_t dy;
+        } move;
+        struct {
+            uint16_t field_0;
+        } wait;
+        struct {
+            uint8_t field_0;
+            uint16_t field_1;
+        } other;
+    } variant;
+} command_t;
+
+static inline int command_unpack_at(command_t *value, const uint8_t *in, size_t offset, int little)
+{
+    value->id = (uint8_t)deku_read_bits(in, offset, 8, little);
+    if (value->id == COMMAND_MOVE) {
+        /* Move */
+        value->variant.move.dx = (int8_t)deku_sign_extend(deku_read_bits(in, offset + 8, 8, little), 8);
+        value->variant.move.dy = (int8_t)deku_sign_extend(deku_read_bits(in, offset + 16, 8, little), 8);
+    } else if (value->id == COMMAND_WAIT) {
+        /* Wait */
+        value->variant.wait.field_0 = (uint16_t)deku_read_bits(in, offset + 8, 16, little);
+    } else {
+        /* Other */
+        value->variant.other.field_0 = (uint8_t)deku_read_bits(in, offset, 8, little);
+        value->variant.other.field_1 = (uint16_t)deku_read_bits(in, offset + 8, 16, little);
+    }
+    return 0;
+}
+
+static inline void command_pack_at(const command_t *value, uint8_t *out, size_t offset, int little)
+{
+    if (value->id == COMMAND_MOVE) {
+        /* Move */
+        deku_write_bits(out, offset, 8, little, value->id);
+        deku_write_bits(out, offset + 8, 8, little, (uint64_t)value->variant.move.dx);
+        deku_write_bits(out, offset + 16, 8, little, (uint64_t)value->variant.move.dy);
+    } else if (value->id == COMMAND_WAIT) {
+        /* Wait */
+        deku_write_bits(out, offset, 8, little, value->id);
+        deku_write_bits(out, offset + 8, 16, little, value->variant.wait.field_0);
+    } else {
+        /* Other */
+        deku_write_bits(out, offset, 8, little, value->variant.other.field_0);
+        deku_write_bits(out, offset + 8, 16, little, value->variant.other.field_1);
+    }
+}
+
+/* Unpack `COMMAND_SIZE` bytes of `in`, returns 0 on success */
+static inline int command_unpack(command_t *value, const uint8_t *in, int little)
+{
+    return command_unpack_at(value, in, 0, little);
+}
+
+/* Pack `value` into `COMMAND_SIZE` bytes of `out` */
+static inline void command_pack(const command_t *value, uint8_t *out, int little)
+{
+    memset(out, 0, COMMAND_SIZE);
+    command_pack_at(value, out, 0, little);
+}
+
+/* `Record` */
+
+#define RECORD_SIZE 19
+
+typedef struct {
+    kind_t kind;
+    uint16_t level : 10;
+    int8_t delta : 2;
+    float scale;
+    bool enabled;
+    point_t points[2];
+    command_t command;
+} record_t;
+
+static inline int record_unpack_at(record_t *value, const uint8_t *in, size_t offset)
+{
+    if (deku_read_bits(in, offset, 8, 0) != 0x44 || deku_read_bits(in, offset + 8, 8, 0) != 0x4b) {
+        return -1;
+    }
+    if (kind_unpack_at(&value->kind, in, offset + 16, 1) != 0) {
+        return -1;
+    }
+    value->level = (uint16_t)deku_read_bits(in, offset + 20, 10, 1);
+    value->delta = (int8_t)deku_sign_extend(deku_read_bits(in, offset + 30, 2, 1), 2);
+    value->scale = deku_f32(deku_read_bits(in, offset + 40, 32, 1));
+    value->enabled = deku_read_bits(in, offset + 72, 8, 1) != 0;
+    for (size_t i = 0; i < 2; i++) {
+        if (point_unpack_at(&value->points[i], in, offset + 80 + i * 24, 1) != 0) {
+            return -1;
+        }
+    }
+    if (command_unpack_at(&value->command, in, offset + 128, 1) != 0) {
+        return -1;
+    }
+    return 0;
+}
+
+static inline void record_pack_at(const record_t *value, uint8_t *out, size_t offset)
+{
+    deku_write_bits(out, offset, 8, 0, 0x44);
+    deku_write_bits(out, offset + 8, 8, 0, 0x4b);
+    kind_pack_at(&value->kind, out, offset + 16, 1);
+    deku_write_bits(out, offset + 20, 10, 1, value->level);
+    deku_write_bits(out, offset + 30, 2, 1, (uint64_t)value->delta);
+    deku_write_bits(out, offset + 40, 32, 1, deku_f32_bits(value->scale));
+    deku_write_bits(out, offset + 72, 8, 1, value->enabled ? 1 : 0);
+    for (size_t i = 0; i < 2; i++) {
+        point_pack_at(&value->points[i], out, offset + 80 + i * 24, 1);
+    }
+    command_pack_at(&value->command, out, offset + 128, 1);
+}
+
+/* Unpack `RECORD_SIZE` bytes of `in`, returns 0 on success */
+static inline int record_unpack(record_t *value, const uint8_t *in)
+{
+    return record_unpack_at(value, in, 0);
+}
+
+/* Pack `value` into `RECORD_SIZE` bytes of `out` */
+static inline void record_pack(const record_t *value, uint8_t *out)
+{
+    memset(out, 0, RECORD_SIZE);
+    record_pack_at(value, out, 0);
+}
+
+#endif /* RECORD_H */
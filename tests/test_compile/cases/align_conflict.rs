@@ -0,0 +1,15 @@
+use deku::prelude::*;
+
+#[derive(DekuRead)]
+struct Test1 {
+    #[deku(align_bits_before = "8", align_before = "1")]
+    a: u8,
+}
+
+#[derive(DekuRead)]
+struct Test2 {
+    #[deku(align_bits_after = "8", align_after = "1")]
+    a: u8,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use deku::prelude::*;
+
+#[derive(DekuRead)]
+struct Test1 {
+    #[deku(as_type = "u8", map = "|v: u8| -> Result<_, DekuError> { Ok(v) }")]
+    a: u8,
+}
+
+#[derive(DekuRead)]
+struct Test2 {
+    #[deku(try_from)]
+    a: u8,
+}
+
+fn main() {}
@@ -0,0 +1,24 @@
+use deku::prelude::*;
+
+// test `assert_msg` must be used with an assertion
+#[derive(DekuRead)]
+struct Test1 {
+    #[deku(assert_msg = "invalid")]
+    a: u8,
+}
+
+// test `assert_err` must be used with an assertion
+#[derive(DekuRead)]
+#[deku(assert_err = "std::fmt::Error")]
+struct Test2 {
+    a: u8,
+}
+
+// test conflicting `assert_msg` and `assert_err`
+#[derive(DekuRead)]
+struct Test3 {
+    #[deku(assert = "*a == 1", assert_msg = "invalid", assert_err = "std::fmt::Error")]
+    a: u8,
+}
+
+fn main() {}
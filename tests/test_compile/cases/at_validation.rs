@@ -0,0 +1,21 @@
+use deku::prelude::*;
+
+// test `at` cannot be used with attributes moving the cursor
+#[derive(DekuRead)]
+struct Test1 {
+    offset: u8,
+    #[deku(at = "offset", pad_bytes_before = "1")]
+    a: u8,
+}
+
+// test fields with `at` can't share the field holding their offset
+#[derive(DekuRead)]
+struct Test2 {
+    offset: u8,
+    #[deku(at = "offset")]
+    a: u8,
+    #[deku(at = "offset")]
+    b: u8,
+}
+
+fn main() {}
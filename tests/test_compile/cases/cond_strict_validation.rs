@@ -0,0 +1,32 @@
+use deku::prelude::*;
+
+// test `cond_strict` must be used with `cond`
+#[derive(DekuWrite)]
+struct Test1 {
+    #[deku(cond_strict)]
+    a: u8,
+}
+
+// test `cond_strict` can't be used with `skip`
+#[derive(DekuWrite)]
+struct Test2 {
+    a: u8,
+    #[deku(skip, cond = "*a == 1", cond_strict)]
+    b: u8,
+}
+
+// test `cond_strict` can't be used with a `cond` reading the input
+#[derive(DekuWrite)]
+struct Test3 {
+    #[deku(cond = "!deku::rest.is_empty()", cond_strict)]
+    a: u8,
+}
+
+// test `cond` of a `skip` field can't read the input when writing
+#[derive(DekuWrite)]
+struct Test4 {
+    #[deku(skip, cond = "deku::byte_offset == 0")]
+    a: u8,
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+use deku::prelude::*;
+
+// test `endian_from` must name a field
+#[derive(DekuRead)]
+#[deku(endian_from = "byte_order")]
+struct Test1 {
+    a: u8,
+}
+
+// test `endian_from` only supported on struct
+#[derive(DekuRead)]
+#[deku(type = "u8", endian_from = "a")]
+enum Test2 {
+    #[deku(id = "1")]
+    A { a: u8 },
+}
+
+fn main() {}
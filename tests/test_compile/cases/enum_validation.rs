@@ -75,4 +75,84 @@ enum Test11 {
     B(u8)
 }
 
+// test `bits` must fit in the `repr`
+#[derive(DekuRead)]
+#[deku(bits = "9")]
+#[repr(u8)]
+enum Test12 {
+    A = 1,
+}
+
+// test `bytes` must fit in the `repr`
+#[derive(DekuRead)]
+#[deku(bytes = "2")]
+#[repr(u8)]
+enum Test13 {
+    A = 1,
+}
+
+// test unique ids
+#[derive(DekuRead)]
+#[repr(u8)]
+enum Test14 {
+    #[deku(id = "2")]
+    A = 1,
+    B = 2,
+}
+
+// test `id_reader` only supported on enum
+#[derive(DekuRead)]
+#[deku(id_reader = "u8::read(deku::rest, ())")]
+struct Test15 {
+    a: u8,
+}
+
+// test `id_reader` conflicts with `id`
+#[derive(DekuRead)]
+#[deku(ctx = "my_id: u8", id = "my_id", id_reader = "u8::read(deku::rest, ())")]
+enum Test16 {
+    #[deku(id = "1")]
+    A,
+}
+
+// test variant `magic` conflicts with `id_pat`
+#[derive(DekuRead)]
+#[deku(type = "u8")]
+enum Test17 {
+    #[deku(id_pat = "_", magic = b"a")]
+    A(u8),
+}
+
+// test variant `magic` conflicts with `unknown`
+#[derive(DekuRead)]
+#[deku(type = "u8")]
+enum Test18 {
+    #[deku(unknown, magic = b"a")]
+    A(u8),
+}
+
+// test `assert` only supported on struct
+#[derive(DekuRead)]
+#[deku(type = "u8", assert = "true")]
+enum Test19 {
+    #[deku(id = "1")]
+    A,
+}
+
+// test variant `ctx` must be bindings
+#[derive(DekuRead)]
+#[deku(type = "u8")]
+enum Test20 {
+    #[deku(id = "1", ctx = "1")]
+    A(u8),
+}
+
+// test variant `ctx` binding conflicts with a field
+#[derive(DekuRead)]
+#[deku(type = "u8")]
+enum Test21 {
+    #[deku(id = "1", ctx = "a = 1")]
+    A { a: u8 },
+}
+
 fn main() {}
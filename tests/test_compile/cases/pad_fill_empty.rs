@@ -0,0 +1,15 @@
+use deku::prelude::*;
+
+#[derive(DekuRead)]
+#[deku(pad_fill = b"")]
+struct Test1 {
+    a: u8,
+}
+
+#[derive(DekuRead)]
+struct Test2 {
+    #[deku(pad_bytes_before = "1", pad_fill = b"")]
+    a: u8,
+}
+
+fn main() {}
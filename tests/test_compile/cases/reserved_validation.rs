@@ -0,0 +1,21 @@
+use deku::prelude::*;
+
+#[derive(DekuRead)]
+struct Test1 {
+    #[deku(reserved_bits = "4", reserved_bytes = "1")]
+    a: u8,
+}
+
+#[derive(DekuRead)]
+struct Test2 {
+    #[deku(reserved_value = "1")]
+    a: u8,
+}
+
+#[derive(DekuRead)]
+struct Test3 {
+    #[deku(reserved_strict)]
+    a: u8,
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use deku::prelude::*;
+
+// test `terminator` can't be used with other limits
+#[derive(DekuRead)]
+struct Test1 {
+    #[deku(count = "2", terminator = "0")]
+    a: Vec<u8>,
+}
+
+// test `until_exclusive` must be used with `terminator`
+#[derive(DekuRead)]
+struct Test2 {
+    #[deku(until_exclusive = "|v: &u8| *v == 0")]
+    a: Vec<u8>,
+}
+
+fn main() {}
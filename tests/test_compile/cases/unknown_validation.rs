@@ -0,0 +1,39 @@
+use deku::prelude::*;
+
+// test `unknown` needs the id field
+#[derive(DekuRead)]
+#[deku(type = "u8")]
+enum Test1 {
+    #[deku(unknown)]
+    A,
+}
+
+// test conflict `unknown` and `id`
+#[derive(DekuRead)]
+#[deku(type = "u8")]
+enum Test2 {
+    #[deku(unknown, id = "1")]
+    A(u8),
+}
+
+// test `unknown` specified once
+#[derive(DekuRead)]
+#[deku(type = "u8")]
+enum Test3 {
+    #[deku(unknown)]
+    A(u8),
+    #[deku(unknown)]
+    B(u8),
+}
+
+// test conflict `unknown` and `default`
+#[derive(DekuRead)]
+#[deku(type = "u8")]
+enum Test4 {
+    #[deku(unknown)]
+    A(u8),
+    #[deku(id = "1", default)]
+    B(u8),
+}
+
+fn main() {}
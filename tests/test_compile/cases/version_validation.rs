@@ -0,0 +1,31 @@
+use deku::prelude::*;
+
+// test `since` must be used with a top-level `version`
+#[derive(DekuRead)]
+struct Test1 {
+    version: u8,
+    #[deku(since = "2")]
+    a: u8,
+}
+
+// test `until_version` must be used with a top-level `version`, on enum variants too
+#[derive(DekuRead)]
+#[deku(type = "u8")]
+enum Test2 {
+    #[deku(id = "1")]
+    A {
+        #[deku(until_version = "2")]
+        a: u8,
+    },
+}
+
+// test conflicting `skip` and `since`
+#[derive(DekuRead)]
+#[deku(version = "*version")]
+struct Test3 {
+    version: u8,
+    #[deku(skip, since = "2")]
+    a: u8,
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use deku::prelude::*;
+
+// test `rest` must be used with `window_bytes`
+#[derive(DekuRead)]
+struct Test1 {
+    #[deku(rest = "skip")]
+    a: u8,
+}
+
+// test `rest` must be a known handling
+#[derive(DekuRead)]
+struct Test2 {
+    #[deku(window_bytes = "2", rest = "ignore")]
+    a: u8,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use deku::prelude::*;
+
+#[derive(DekuWrite)]
+struct Test {
+    #[deku(write_map = "|v: &u8| -> Result<u8, DekuError> { Ok(*v) }", writer = "Ok(())")]
+    a: u8,
+}
+
+fn main() {}
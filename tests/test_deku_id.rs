@@ -102,13 +102,28 @@ fn test_litbytestr() {
 }
 
 #[test]
-#[should_panic(expected = "called `Result::unwrap()` on an `Err` value: IdVariantNotFound")]
-fn test_no_id_discriminant() {
+fn test_id_discriminant() {
     #[derive(Debug, DekuRead, PartialEq, DekuWrite)]
     #[deku(type = "u8")]
     enum Discriminant {
         Cats = 0x01,
         Dogs,
     }
-    Discriminant::Dogs.deku_id().unwrap();
+    assert_eq!(0x01, Discriminant::Cats.deku_id().unwrap());
+    assert_eq!(0x02, Discriminant::Dogs.deku_id().unwrap());
+}
+
+#[test]
+fn test_id_repr() {
+    #[derive(Debug, DekuRead, PartialEq, DekuWrite)]
+    #[repr(u16)]
+    enum Discriminant {
+        Cats = 0x0100,
+        Dogs,
+        #[deku(id = "0x0300")]
+        Birds = 0x0200,
+    }
+    assert_eq!(0x0100u16, Discriminant::Cats.deku_id().unwrap());
+    assert_eq!(0x0101u16, Discriminant::Dogs.deku_id().unwrap());
+    assert_eq!(0x0300u16, Discriminant::Birds.deku_id().unwrap());
 }
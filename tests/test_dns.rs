@@ -0,0 +1,86 @@
+#![cfg(feature = "dns")]
+
+use deku::dns::{Compressor, DomainName};
+use deku::prelude::*;
+use hexlit::hex;
+use std::convert::TryFrom;
+
+#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[deku(
+    endian = "big",
+    ctx = "_names: Compressor",
+    ctx_default = "Compressor::new()"
+)]
+struct Response {
+    id: u16,
+    flags: u16,
+    qdcount: u16,
+    ancount: u16,
+    nscount: u16,
+    arcount: u16,
+    #[deku(
+        reader = "DomainName::read(deku::rest, deku::input_bits)",
+        writer = "qname.write(deku::output, &_names)"
+    )]
+    qname: DomainName,
+    qtype: u16,
+    qclass: u16,
+    #[deku(
+        reader = "DomainName::read(deku::rest, deku::input_bits)",
+        writer = "name.write(deku::output, &_names)"
+    )]
+    name: DomainName,
+    rtype: u16,
+    rclass: u16,
+    ttl: u32,
+    #[deku(update = "self.rdata.len()")]
+    rdlength: u16,
+    #[deku(count = "rdlength")]
+    rdata: Vec<u8>,
+}
+
+#[test]
+fn test_dns_response() {
+    // A record of example.com, the answer name points to the question
+    let input = hex!(
+        "ABCD 8180 0001 0001 0000 0000"
+        "07 6578616D706C65 03 636F6D 00 0001 0001"
+        "C00C 0001 0001 00000E10 0004 5DB8D822"
+    );
+
+    let (rest, response) = Response::from_bytes((input.as_ref(), 0)).unwrap();
+    assert!(rest.0.is_empty());
+    assert_eq!(
+        Response {
+            id: 0xABCD,
+            flags: 0x8180,
+            qdcount: 1,
+            ancount: 1,
+            nscount: 0,
+            arcount: 0,
+            qname: DomainName::try_from("example.com").unwrap(),
+            qtype: 1,
+            qclass: 1,
+            name: DomainName::try_from("example.com").unwrap(),
+            rtype: 1,
+            rclass: 1,
+            ttl: 3600,
+            rdlength: 4,
+            rdata: vec![93, 184, 216, 34],
+        },
+        response
+    );
+
+    assert_eq!(input.to_vec(), response.to_bytes().unwrap());
+}
+
+#[test]
+fn test_dns_pointer_loop() {
+    let input = hex!("ABCD 8180 0001 0000 0000 0000 C00C 0001 0001");
+    assert_eq!(
+        Err(DekuError::Parse(
+            "domain name compression pointer loop at offset 12".to_string()
+        )),
+        Response::from_bytes((input.as_ref(), 0))
+    );
+}
@@ -1,3 +1,5 @@
+use deku::bitvec::{BitSlice, BitVec, Msb0};
+use deku::ctx::{BitSize, Endian};
 use deku::prelude::*;
 use hexlit::hex;
 use rstest::*;
@@ -86,6 +88,156 @@ fn test_enum_discriminant(input: &[u8], expected: TestEnumDiscriminant) {
     assert_eq!(input.to_vec(), ret_write);
 }
 
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+#[deku(endian = "big")]
+#[repr(u16)]
+enum TestEnumRepr {
+    VarA = 0x0100,
+    VarB,
+    #[deku(id = "0x0300")]
+    VarC = 0x0200,
+}
+
+#[rstest(input, expected,
+    case(&hex!("0100"), TestEnumRepr::VarA),
+    case(&hex!("0101"), TestEnumRepr::VarB),
+    case(&hex!("0300"), TestEnumRepr::VarC),
+
+    #[should_panic(expected = "Could not match enum variant id = 512 on enum `TestEnumRepr`")]
+    case(&hex!("0200"), TestEnumRepr::VarA),
+)]
+fn test_enum_repr(input: &[u8], expected: TestEnumRepr) {
+    let ret_read = TestEnumRepr::try_from(input).unwrap();
+    assert_eq!(expected, ret_read);
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(input.to_vec(), ret_write);
+}
+
+#[test]
+fn test_enum_repr_bits() {
+    #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+    #[deku(bits = 4)]
+    #[repr(u8)]
+    enum TestEnum {
+        VarA = 0x1,
+        VarB = 0x2,
+    }
+
+    #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+    struct TestStruct {
+        field_a: TestEnum,
+        field_b: TestEnum,
+    }
+
+    let ret_read = TestStruct::try_from(hex!("21").as_ref()).unwrap();
+    assert_eq!(
+        TestStruct {
+            field_a: TestEnum::VarB,
+            field_b: TestEnum::VarA,
+        },
+        ret_read
+    );
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(hex!("21").to_vec(), ret_write);
+}
+
+#[test]
+fn test_enum_repr_u64() {
+    #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+    #[deku(endian = "big")]
+    #[repr(u64)]
+    enum TestEnum {
+        VarA = 0x01,
+        VarB = 0xFFFF_FFFF_FFFF_FFFE,
+        VarC,
+    }
+
+    assert_eq!(Ok(0xFFFF_FFFF_FFFF_FFFE), TestEnum::VarB.deku_id());
+    assert_eq!(Ok(u64::MAX), TestEnum::VarC.deku_id());
+
+    let input = hex!("FFFFFFFFFFFFFFFE");
+    let ret_read = TestEnum::try_from(input.as_ref()).unwrap();
+    assert_eq!(TestEnum::VarB, ret_read);
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(input.to_vec(), ret_write);
+
+    let input = hex!("FFFFFFFFFFFFFFFF");
+    let ret_read = TestEnum::try_from(input.as_ref()).unwrap();
+    assert_eq!(TestEnum::VarC, ret_read);
+}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+#[deku(type = "u8")]
+enum TestEnumUnknown {
+    #[deku(id = "1")]
+    VarA(u8),
+    #[deku(unknown)]
+    Unknown(u8),
+}
+
+#[rstest(input, expected,
+    case(&hex!("01AB"), TestEnumUnknown::VarA(0xAB)),
+    case(&hex!("FF"), TestEnumUnknown::Unknown(0xFF)),
+)]
+fn test_enum_unknown(input: &[u8], expected: TestEnumUnknown) {
+    let ret_read = TestEnumUnknown::try_from(input).unwrap();
+    assert_eq!(expected, ret_read);
+    assert_eq!(input[0], ret_read.deku_id().unwrap());
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(input.to_vec(), ret_write);
+}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+#[deku(type = "u16", endian = "big")]
+enum TestEnumUnknownRest {
+    #[deku(id = "1")]
+    VarA(u8),
+    #[deku(unknown)]
+    Unknown { id: u16, rest: Vec<u8> },
+}
+
+#[rstest(input, expected,
+    case(&hex!("0001AB"), TestEnumUnknownRest::VarA(0xAB)),
+    case(&hex!("0102"), TestEnumUnknownRest::Unknown { id: 0x0102, rest: vec![] }),
+    case(&hex!("0102AABBCC"), TestEnumUnknownRest::Unknown { id: 0x0102, rest: vec![0xAA, 0xBB, 0xCC] }),
+)]
+fn test_enum_unknown_rest(input: &[u8], expected: TestEnumUnknownRest) {
+    let ret_read = TestEnumUnknownRest::try_from(input).unwrap();
+    assert_eq!(expected, ret_read);
+    assert_eq!(
+        u16::from_be_bytes([input[0], input[1]]),
+        ret_read.deku_id().unwrap()
+    );
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(input.to_vec(), ret_write);
+}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+#[deku(type = "u8")]
+enum TestEnumUnknownFirst {
+    #[deku(unknown)]
+    Unknown(u8, Vec<u8>),
+    #[deku(id = "1")]
+    VarA(u8),
+}
+
+#[rstest(input, expected,
+    case(&hex!("0107"), TestEnumUnknownFirst::VarA(0x07)),
+    case(&hex!("FF07"), TestEnumUnknownFirst::Unknown(0xFF, vec![0x07])),
+)]
+fn test_enum_unknown_first(input: &[u8], expected: TestEnumUnknownFirst) {
+    let ret_read = TestEnumUnknownFirst::try_from(input).unwrap();
+    assert_eq!(expected, ret_read);
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(input.to_vec(), ret_write);
+}
+
 #[test]
 fn test_enum_array_type() {
     #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
@@ -105,3 +257,211 @@ fn test_enum_array_type() {
     let ret_write: Vec<u8> = ret_read.try_into().unwrap();
     assert_eq!(input.to_vec(), ret_write);
 }
+
+#[test]
+fn test_enum_unknown_ctx_id() {
+    #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+    #[deku(ctx = "my_id: u8", id = "my_id")]
+    enum TestEnum {
+        #[deku(id = "1")]
+        VarA(u8),
+        #[deku(unknown)]
+        Unknown(u8, Vec<u8>),
+    }
+
+    #[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+    struct TestStruct {
+        my_id: u8,
+        #[deku(ctx = "*my_id")]
+        value: TestEnum,
+    }
+
+    let input = hex!("02AABB");
+    let ret_read = TestStruct::try_from(input.as_ref()).unwrap();
+    assert_eq!(
+        TestStruct {
+            my_id: 0x02,
+            value: TestEnum::Unknown(0x02, vec![0xAA, 0xBB]),
+        },
+        ret_read
+    );
+    assert_eq!(0x02, ret_read.value.deku_id().unwrap());
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(input.to_vec(), ret_write);
+}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+#[deku(type = "(u8, u8)")]
+enum TestEnumTupleId {
+    #[deku(id = "(1, 1)")]
+    VarA(u8),
+    #[deku(id = "(1, 2)")]
+    VarB,
+    #[deku(id_pat = "(2, _)")]
+    VarC { class: u8, op: u8 },
+}
+
+#[rstest(input, expected,
+    case(&hex!("0101AB"), TestEnumTupleId::VarA(0xAB)),
+    case(&hex!("0102"), TestEnumTupleId::VarB),
+    case(&hex!("0205"), TestEnumTupleId::VarC { class: 2, op: 5 }),
+
+    #[should_panic(expected = "Could not match enum variant id = (1, 3) on enum `TestEnumTupleId`")]
+    case(&hex!("0103"), TestEnumTupleId::VarB),
+)]
+fn test_enum_tuple_id(input: &[u8], expected: TestEnumTupleId) {
+    let ret_read = TestEnumTupleId::try_from(input).unwrap();
+    assert_eq!(expected, ret_read);
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(input.to_vec(), ret_write);
+}
+
+#[test]
+fn test_enum_tuple_deku_id() {
+    assert_eq!((1, 1), TestEnumTupleId::VarA(0xAB).deku_id().unwrap());
+    assert_eq!((1, 2), TestEnumTupleId::VarB.deku_id().unwrap());
+}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+struct TestKey {
+    class: u8,
+    op: u8,
+}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+#[deku(type = "TestKey")]
+enum TestEnumStructId {
+    #[deku(id = "TestKey { class: 1, op: 1 }")]
+    VarA(u8),
+    #[deku(id_pat = "TestKey { class: 2, .. }")]
+    VarB(TestKey),
+}
+
+#[rstest(input, expected,
+    case(&hex!("0101AB"), TestEnumStructId::VarA(0xAB)),
+    case(&hex!("0207"), TestEnumStructId::VarB(TestKey { class: 2, op: 7 })),
+)]
+fn test_enum_struct_id(input: &[u8], expected: TestEnumStructId) {
+    let ret_read = TestEnumStructId::try_from(input).unwrap();
+    assert_eq!(expected, ret_read);
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(input.to_vec(), ret_write);
+}
+
+/// Id of 7 bits, or 15 bits when the first bit is set
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+#[deku(
+    type = "u16",
+    id_reader = "TestEnumIdReader::read_id(deku::rest)",
+    id_writer = "TestEnumIdReader::write_id(deku::output, deku::id)"
+)]
+enum TestEnumIdReader {
+    #[deku(id = "0x01")]
+    VarA(u8),
+    #[deku(id = "0x1234")]
+    VarB,
+    #[deku(unknown)]
+    Unknown(u16),
+}
+
+impl TestEnumIdReader {
+    fn read_id(rest: &BitSlice<u8, Msb0>) -> Result<(&BitSlice<u8, Msb0>, u16), DekuError> {
+        let (rest, long) = bool::read(rest, BitSize(1))?;
+        let size = if long { 15 } else { 7 };
+        u16::read(rest, (Endian::Big, BitSize(size)))
+    }
+
+    fn write_id(output: &mut BitVec<u8, Msb0>, id: u16) -> Result<(), DekuError> {
+        let long = id >= 0x80;
+        long.write(output, BitSize(1))?;
+        let size = if long { 15 } else { 7 };
+        id.write(output, (Endian::Big, BitSize(size)))
+    }
+}
+
+#[rstest(input, expected,
+    case(&hex!("01AB"), TestEnumIdReader::VarA(0xAB)),
+    case(&hex!("9234"), TestEnumIdReader::VarB),
+    case(&hex!("7F"), TestEnumIdReader::Unknown(0x7F)),
+    case(&hex!("8100"), TestEnumIdReader::Unknown(0x0100)),
+)]
+fn test_enum_id_reader(input: &[u8], expected: TestEnumIdReader) {
+    let ret_read = TestEnumIdReader::try_from(input).unwrap();
+    assert_eq!(expected, ret_read);
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(input.to_vec(), ret_write);
+}
+
+#[test]
+fn test_enum_id_reader_deku_id() {
+    assert_eq!(0x1234, TestEnumIdReader::VarB.deku_id().unwrap());
+    assert_eq!(0x0100, TestEnumIdReader::Unknown(0x0100).deku_id().unwrap());
+}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+#[deku(type = "[u8; 2]")]
+enum TestEnumVariantMagic {
+    #[deku(id = b"II", magic = b"\x2a\x00", endian = "little")]
+    Little { offset: u32 },
+    #[deku(id = b"MM", magic = b"\x00\x2a", endian = "big")]
+    Big {
+        offset: u32,
+        #[deku(endian = "little")]
+        flags: u16,
+    },
+}
+
+#[rstest(input, expected,
+    case(&hex!("49492A0008000000"), TestEnumVariantMagic::Little { offset: 8 }),
+    case(&hex!("4D4D002A000000080100"), TestEnumVariantMagic::Big { offset: 8, flags: 1 }),
+
+    #[should_panic(expected = "Parse(\"Missing magic value [0, 42]\")")]
+    case(&hex!("4D4D2A0000000008"), TestEnumVariantMagic::Little { offset: 8 }),
+)]
+fn test_enum_variant_magic(input: &[u8], expected: TestEnumVariantMagic) {
+    let ret_read = TestEnumVariantMagic::try_from(input).unwrap();
+    assert_eq!(expected, ret_read);
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(input.to_vec(), ret_write);
+}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+#[deku(ctx = "endian: deku::ctx::Endian")]
+struct TestCtxValue {
+    #[deku(endian = "endian")]
+    value: u16,
+}
+
+#[derive(PartialEq, Debug, DekuRead, DekuWrite)]
+#[deku(type = "u8")]
+enum TestEnumVariantCtx {
+    #[deku(id = "0x01", ctx = "endian = deku::ctx::Endian::Little")]
+    Little(
+        #[deku(ctx = "endian")] TestCtxValue,
+        #[deku(ctx = "endian")] TestCtxValue,
+    ),
+    #[deku(id = "0x02", ctx = "endian = deku::ctx::Endian::Big, size = 2")]
+    Big {
+        #[deku(ctx = "endian")]
+        value: TestCtxValue,
+        #[deku(endian = "endian", count = "size")]
+        data: Vec<u16>,
+    },
+}
+
+#[rstest(input, expected,
+    case(&hex!("0101000200"), TestEnumVariantCtx::Little(TestCtxValue { value: 1 }, TestCtxValue { value: 2 })),
+    case(&hex!("02000100020003"), TestEnumVariantCtx::Big { value: TestCtxValue { value: 1 }, data: vec![2, 3] }),
+)]
+fn test_enum_variant_ctx(input: &[u8], expected: TestEnumVariantCtx) {
+    let ret_read = TestEnumVariantCtx::try_from(input).unwrap();
+    assert_eq!(expected, ret_read);
+
+    let ret_write: Vec<u8> = ret_read.try_into().unwrap();
+    assert_eq!(input.to_vec(), ret_write);
+}
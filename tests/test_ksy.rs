@@ -0,0 +1,457 @@
+#![cfg(feature = "ksy")]
+
+use deku::prelude::*;
+use deku::schema::ksy::to_ksy;
+
+#[test]
+fn test_ksy_struct() {
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    #[deku(endian = "big")]
+    struct Ipv4Header {
+        #[deku(bits = "4")]
+        version: u8,
+        #[deku(bits = "4")]
+        ihl: u8,
+        #[deku(bits = "6")]
+        dscp: u8,
+        #[deku(bits = "2")]
+        ecn: u8,
+        length: u16,
+        #[deku(endian = "little")]
+        identification: u16,
+        #[deku(bits = "3")]
+        flags: u8,
+        #[deku(bits = "13")]
+        offset: u16,
+        ttl: i8,
+        #[deku(pad_bytes_before = "1", cond = "*ttl > 0")]
+        checksum: Option<u16>,
+        src: std::net::Ipv4Addr,
+        #[deku(count = "*ihl - 5")]
+        options: Vec<u32>,
+        #[deku(until = "|v: &u8| *v == 0")]
+        name: Vec<u8>,
+        #[deku(bytes_read = "length - 20")]
+        payload: Vec<u8>,
+    }
+
+    assert_eq!(
+        r#"meta:
+  id: ipv4_header
+  endian: be
+  bit-endian: be
+seq:
+  - id: version
+    type: b4
+  - id: ihl
+    type: b4
+  - id: dscp
+    type: b6
+  - id: ecn
+    type: b2
+  - id: length
+    type: u2
+  - id: identification
+    type: u2le
+  - id: flags
+    type: b3
+  - id: offset
+    type: b13
+  - id: ttl
+    type: s1
+  - id: checksum_pad_bytes_before
+    size: 1
+  - id: checksum
+    type: u2
+    if: ttl > 0
+  - id: src
+    type: u4
+  - id: options
+    type: u4
+    repeat: expr
+    repeat-expr: ihl - 5
+  - id: name
+    type: u1
+    repeat: until
+    repeat-until: _ == 0
+  - id: payload
+    size: length - 20
+"#,
+        to_ksy(&Ipv4Header::SCHEMA, &[])
+    );
+}
+
+#[test]
+fn test_ksy_enum() {
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    #[deku(type = "u8", endian = "little")]
+    enum Message {
+        #[deku(id = "0x01")]
+        Ping,
+        #[deku(id = "0x02")]
+        Data { len: u16, inner: Inner },
+        #[deku(id_pat = "_")]
+        Unknown(u8),
+    }
+
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    #[deku(magic = b"in", ctx = "endian: deku::ctx::Endian", endian = "endian")]
+    struct Inner {
+        #[deku(bytes = "2")]
+        value: u32,
+        #[deku(skip)]
+        cached: u8,
+        #[deku(skip, cond = "*value == 0")]
+        extra: u8,
+    }
+
+    assert_eq!(
+        r#"meta:
+  id: message
+  endian: le
+  bit-endian: be
+seq:
+  - id: variant_id
+    type: u1
+    enum: message_id
+  - id: body
+    type:
+      switch-on: variant_id
+      cases:
+        message_id::data: message_data
+        _: message_unknown
+types:
+  message_data:
+    seq:
+      - id: len
+        type: u2
+      - id: inner
+        type: inner
+  message_unknown:
+    seq:
+      - id: field_0
+        type: u1
+  inner:
+    seq:
+      - id: magic
+        contents: [0x69, 0x6e]
+      - id: value
+        type: u2
+      - id: extra
+        type: u1
+        if: not (value == 0)
+enums:
+  message_id:
+    1: ping
+    2: data
+"#,
+        to_ksy(&Message::SCHEMA, &[Inner::SCHEMA])
+    );
+}
+
+#[test]
+fn test_ksy_ctx() {
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    #[deku(ctx = "endian: deku::ctx::Endian, count: u8", endian = "endian")]
+    struct Child {
+        #[deku(count = "count")]
+        items: Vec<u16>,
+    }
+
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    #[deku(endian = "big")]
+    struct Parent {
+        count: u8,
+        #[deku(ctx = "*count")]
+        child: Child,
+    }
+
+    assert_eq!(
+        r#"meta:
+  id: parent
+  endian: be
+  bit-endian: be
+seq:
+  - id: count
+    type: u1
+  - id: child
+    type: child(count)
+types:
+  child:
+    params:
+      - id: count
+        type: u1
+    seq:
+      - id: items
+        type: u2
+        repeat: expr
+        repeat-expr: count
+"#,
+        to_ksy(&Parent::SCHEMA, &[Child::SCHEMA])
+    );
+}
+
+#[test]
+fn test_ksy_align() {
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    #[deku(align = "4")]
+    struct Aligned {
+        #[deku(bits = "3", align_bits_after = "8")]
+        flags: u8,
+        #[deku(align_before = "2")]
+        value: u8,
+    }
+
+    assert_eq!(
+        r#"meta:
+  id: aligned
+  endian: le
+  bit-endian: be
+seq:
+  - id: flags
+    type: b3
+  - id: flags_align_bits_after
+    size: 0
+    doc: deku aligns to `8` bits
+  - id: value_align_before
+    size: (2 - _io.pos % 2) % 2
+    doc: deku aligns relative to the start of `Aligned`
+  - id: value
+    type: u1
+  - id: aligned_align_end
+    size: (4 - _io.pos % 4) % 4
+    doc: deku aligns relative to the start of `Aligned`
+"#,
+        to_ksy(&Aligned::SCHEMA, &[])
+    );
+}
+
+#[test]
+fn test_ksy_reserved() {
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    #[deku(reserved_strict)]
+    struct Reserved {
+        #[deku(reserved_bits = "4", reserved_value = "0b1010", bits = "4")]
+        flags: u8,
+        #[deku(reserved_bytes = "16")]
+        value: u8,
+    }
+
+    assert_eq!(
+        r#"meta:
+  id: reserved
+  endian: le
+  bit-endian: be
+seq:
+  - id: flags_reserved
+    type: b4
+    valid: 10
+  - id: flags
+    type: b4
+  - id: value_reserved
+    size: 16
+  - id: value
+    type: u1
+"#,
+        to_ksy(&Reserved::SCHEMA, &[])
+    );
+}
+
+#[test]
+fn test_ksy_unknown() {
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    #[deku(type = "u8")]
+    enum Message {
+        #[deku(id = "1")]
+        Ping(u8),
+        #[deku(unknown)]
+        Unknown { id: u8, rest: Vec<u8> },
+    }
+
+    assert_eq!(
+        r#"meta:
+  id: message
+  endian: le
+  bit-endian: be
+seq:
+  - id: variant_id
+    type: u1
+    enum: message_id
+  - id: body
+    type:
+      switch-on: variant_id
+      cases:
+        message_id::ping: message_ping
+        _: message_unknown
+types:
+  message_ping:
+    seq:
+      - id: field_0
+        type: u1
+  message_unknown:
+    seq:
+      - id: rest
+        size-eos: true
+enums:
+  message_id:
+    1: ping
+"#,
+        to_ksy(&Message::SCHEMA, &[])
+    );
+}
+
+#[test]
+fn test_ksy_variant_magic() {
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    #[deku(type = "u8")]
+    enum Header {
+        #[deku(id = "1", magic = b"\x2a\x00", endian = "little")]
+        Little { offset: u32 },
+        #[deku(id = "2", magic = b"\x00\x2a", endian = "big")]
+        Big { offset: u32 },
+    }
+
+    assert_eq!(
+        r#"meta:
+  id: header
+  endian: le
+  bit-endian: be
+seq:
+  - id: variant_id
+    type: u1
+    enum: header_id
+  - id: body
+    type:
+      switch-on: variant_id
+      cases:
+        header_id::little: header_little
+        header_id::big: header_big
+types:
+  header_little:
+    seq:
+      - id: magic
+        contents: [0x2a, 0x00]
+      - id: offset
+        type: u4
+  header_big:
+    seq:
+      - id: magic
+        contents: [0x00, 0x2a]
+      - id: offset
+        type: u4be
+enums:
+  header_id:
+    1: little
+    2: big
+"#,
+        to_ksy(&Header::SCHEMA, &[])
+    );
+}
+
+#[test]
+fn test_ksy_terminator() {
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    #[deku(endian = "big")]
+    struct Terminated {
+        #[deku(terminator = "0")]
+        name: Vec<u8>,
+        #[deku(terminator = "0xFFFF")]
+        values: Vec<u16>,
+        #[deku(until_exclusive = "|v: &u8| *v >= 0x80", terminator = "0x80")]
+        flags: Vec<u8>,
+    }
+
+    assert_eq!(
+        r#"meta:
+  id: terminated
+  endian: be
+  bit-endian: be
+seq:
+  - id: name
+    terminator: 0
+  - id: values
+    type: u2
+    repeat: until
+    repeat-until: _ == 0xFFFF
+    doc: the terminating element isn't stored by deku
+  - id: flags
+    type: u1
+    repeat: until
+    repeat-until: _ >= 0x80
+    doc: the terminating element isn't stored by deku
+"#,
+        to_ksy(&Terminated::SCHEMA, &[])
+    );
+}
+
+#[test]
+fn test_ksy_window() {
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    struct Record {
+        len: u8,
+        #[deku(window_bytes = "len", rest = "skip")]
+        body: Body,
+        #[deku(window_bytes = "2", rest = "keep")]
+        tail: u8,
+    }
+
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    struct Body {
+        kind: u8,
+    }
+
+    assert_eq!(
+        r#"meta:
+  id: record
+  endian: le
+  bit-endian: be
+seq:
+  - id: len
+    type: u1
+  - id: body
+    type: body
+    size: len
+  - id: tail
+    type: u1
+    doc: read from `2` bytes, the unread bytes are kept for the following fields
+types:
+  body:
+    seq:
+      - id: kind
+        type: u1
+"#,
+        to_ksy(&Record::SCHEMA, &[Body::SCHEMA])
+    );
+}
+
+#[test]
+fn test_ksy_at() {
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    struct Table {
+        offset: u8,
+        len: u8,
+        #[deku(at = "offset", count = "len")]
+        data: Vec<u8>,
+        kind: u8,
+    }
+
+    assert_eq!(
+        r#"meta:
+  id: table
+  endian: le
+  bit-endian: be
+seq:
+  - id: offset
+    type: u1
+  - id: len
+    type: u1
+  - id: kind
+    type: u1
+instances:
+  data:
+    pos: offset
+    size: len
+"#,
+        to_ksy(&Table::SCHEMA, &[])
+    );
+}
@@ -0,0 +1,195 @@
+#![cfg(feature = "pcap")]
+
+use deku::bitvec::BitVec;
+use deku::ctx::Endian;
+use deku::formats::pcap::ng::{Block, BlockOption, PcapngReader, PcapngWriter, SectionHeader};
+use deku::formats::pcap::{GlobalHeader, Magic, PcapReader, PcapWriter, Record};
+use deku::prelude::*;
+use rstest::rstest;
+
+#[rstest(input, magic, fracs,
+    case(include_bytes!("test_pcap/le_micros.pcap"), Magic::LittleMicros, [123_456, 999_999]),
+    case(include_bytes!("test_pcap/be_nanos.pcap"), Magic::BigNanos, [123_456_789, 999_999_999]),
+)]
+fn test_pcap(input: &[u8], magic: Magic, fracs: [u32; 2]) {
+    let reader = PcapReader::new(input).unwrap();
+    let header = reader.header().clone();
+    assert_eq!(magic, header.magic);
+    assert_eq!((2, 4), (header.version_major, header.version_minor));
+    assert_eq!(262_144, header.snaplen);
+    assert_eq!(1, header.network);
+
+    let records = reader.collect::<Result<Vec<Record>, _>>().unwrap();
+    assert_eq!(2, records.len());
+    assert_eq!(
+        (1_700_000_000, fracs[0], 60, 60),
+        (
+            records[0].header.ts_sec,
+            records[0].header.ts_frac,
+            records[0].header.incl_len,
+            records[0].header.orig_len
+        )
+    );
+    assert_eq!((0..60).collect::<Vec<u8>>(), records[0].data);
+    assert_eq!(
+        (1_700_000_001, fracs[1], 42, 1514),
+        (
+            records[1].header.ts_sec,
+            records[1].header.ts_frac,
+            records[1].header.incl_len,
+            records[1].header.orig_len
+        )
+    );
+    assert_eq!(vec![0xFF; 42], records[1].data);
+
+    let mut writer = PcapWriter::new(&header).unwrap();
+    for record in &records {
+        writer.write(record).unwrap();
+    }
+    assert_eq!(input.to_vec(), writer.finish());
+}
+
+#[test]
+fn test_pcap_write() {
+    let mut writer = PcapWriter::new(&GlobalHeader::new(Magic::BigMicros, 65535, 101)).unwrap();
+    writer
+        .write(&Record::new(10, 20, vec![0x45, 0x00]))
+        .unwrap();
+    assert_eq!(
+        vec![
+            0xA1, 0xB2, 0xC3, 0xD4, 0x00, 0x02, 0x00, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00, 0x00, 0x65, // header
+            0x00, 0x00, 0x00, 0x0A, 0x00, 0x00, 0x00, 0x14, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00,
+            0x00, 0x02, 0x45, 0x00, // record
+        ],
+        writer.finish()
+    );
+}
+
+#[test]
+fn test_pcapng() {
+    let input = include_bytes!("test_pcap/sample.pcapng");
+    let mut reader = PcapngReader::new(input);
+    let mut blocks = vec![];
+    let mut output = BitVec::new();
+    while let Some(block) = reader.next() {
+        let block = block.unwrap();
+        let endian = reader.endian().unwrap();
+        block
+            .to_raw(endian)
+            .unwrap()
+            .write(&mut output, endian)
+            .unwrap();
+        blocks.push((endian, block));
+    }
+    assert_eq!(input.to_vec(), output.into_vec());
+    assert_eq!(8, blocks.len());
+
+    // first section, little endian
+    match &blocks[0] {
+        (Endian::Little, Block::SectionHeader(section)) => {
+            assert_eq!(
+                (1, 0, -1),
+                (
+                    section.major_version,
+                    section.minor_version,
+                    section.section_length
+                )
+            );
+            assert_eq!(
+                vec![BlockOption::new(4, b"deku".to_vec()), BlockOption::end()],
+                section.options
+            );
+        }
+        block => panic!("unexpected block: {:?}", block),
+    }
+    match &blocks[1] {
+        (Endian::Little, Block::InterfaceDescription(interface)) => {
+            assert_eq!((1, 65535), (interface.link_type, interface.snap_len));
+            assert_eq!(
+                vec![
+                    BlockOption::new(2, b"eth0".to_vec()),
+                    BlockOption::new(9, vec![6]),
+                    BlockOption::end()
+                ],
+                interface.options
+            );
+        }
+        block => panic!("unexpected block: {:?}", block),
+    }
+    match &blocks[2] {
+        (Endian::Little, Block::EnhancedPacket(packet)) => {
+            assert_eq!(0, packet.interface_id);
+            assert_eq!(1_700_000_000_123_456, packet.timestamp());
+            assert_eq!(61, packet.original_len);
+            assert_eq!((0..61).collect::<Vec<u8>>(), packet.data);
+            assert!(packet.options.is_empty());
+        }
+        block => panic!("unexpected block: {:?}", block),
+    }
+    match &blocks[3] {
+        (Endian::Little, Block::Other(raw)) => {
+            assert_eq!(3, raw.block_type);
+            assert_eq!(b"\x05\x00\x00\x00hello\x00\x00\x00".to_vec(), raw.body);
+        }
+        block => panic!("unexpected block: {:?}", block),
+    }
+    match &blocks[4] {
+        (Endian::Little, Block::EnhancedPacket(packet)) => {
+            assert_eq!(1_700_000_000_223_456, packet.timestamp());
+            assert_eq!(1514, packet.original_len);
+            assert_eq!(vec![0xAA; 64], packet.data);
+            assert_eq!(
+                vec![
+                    BlockOption::new(1, b"truncated".to_vec()),
+                    BlockOption::end()
+                ],
+                packet.options
+            );
+        }
+        block => panic!("unexpected block: {:?}", block),
+    }
+
+    // second section, big endian
+    assert!(matches!(blocks[5], (Endian::Big, Block::SectionHeader(_))));
+    match &blocks[6] {
+        (Endian::Big, Block::InterfaceDescription(interface)) => {
+            assert_eq!((101, 0), (interface.link_type, interface.snap_len));
+        }
+        block => panic!("unexpected block: {:?}", block),
+    }
+    match &blocks[7] {
+        (Endian::Big, Block::EnhancedPacket(packet)) => {
+            assert_eq!(1_700_000_001_000_000, packet.timestamp());
+            assert_eq!(20, packet.data.len());
+        }
+        block => panic!("unexpected block: {:?}", block),
+    }
+}
+
+#[test]
+fn test_pcapng_write() {
+    let input = include_bytes!("test_pcap/sample.pcapng");
+    let blocks = PcapngReader::new(input)
+        .map(Result::unwrap)
+        .take_while(|block| !matches!(block, Block::Other(_)))
+        .collect::<Vec<_>>();
+
+    // rewrite the start of the first section in big endian
+    let mut writer = PcapngWriter::new(Endian::Big, &SectionHeader::new()).unwrap();
+    for block in &blocks[1..] {
+        writer.write(block).unwrap();
+    }
+    let output = writer.finish();
+
+    let mut reader = PcapngReader::new(&output);
+    assert_eq!(
+        Some(Ok(Block::SectionHeader(SectionHeader::new()))),
+        reader.next()
+    );
+    assert_eq!(Some(Endian::Big), reader.endian());
+    assert_eq!(
+        blocks[1..].to_vec(),
+        reader.map(Result::unwrap).collect::<Vec<_>>()
+    );
+}
@@ -0,0 +1,173 @@
+#![cfg(feature = "protocols")]
+
+use deku::prelude::*;
+use deku::protocols::ethernet::{
+    EthernetHeader, MacAddress, VlanTag, ETHERTYPE_IPV4, ETHERTYPE_IPV6,
+};
+use deku::protocols::icmpv6::{Icmpv6Body, Icmpv6Header, ECHO_REQUEST};
+use deku::protocols::ip_protocol::{ICMPV6, TCP, UDP};
+use deku::protocols::ipv4::{Ipv4Header, Ipv4Option};
+use deku::protocols::ipv6::{ExtensionHeader, FragmentHeader, Ipv6Header, Ipv6Option};
+use deku::protocols::tcp::{TcpHeader, TcpOption};
+use deku::protocols::udp::UdpHeader;
+use hexlit::hex;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+/// Rewrite each header and check that the packet is unchanged
+fn assert_round_trip(input: &[u8], headers: &[Vec<u8>], payload: &[u8]) {
+    assert_eq!(
+        input.to_vec(),
+        [headers.concat(), payload.to_vec()].concat()
+    );
+}
+
+#[test]
+fn test_vlan_ipv4_tcp() {
+    let input = hex!(
+        "0050568A0001 000C29AABBCC 8100 0064 0800"
+        "4600 0040 1C46 4000 4006 4992 0A000001 5DB8D822 94040000"
+        "C350 01BB 11223344 00000000 A002 FAF0 0347 0000 020405B4 0402080A 0000007B 00000000 01030307"
+    );
+
+    let ((rest, _), ethernet) = EthernetHeader::from_bytes((&input, 0)).unwrap();
+    assert_eq!(MacAddress(hex!("0050568A0001")), ethernet.dst);
+    assert_eq!("00:0c:29:aa:bb:cc", ethernet.src.to_string());
+    assert_eq!(vec![VlanTag::new(100)], ethernet.vlan_tags);
+    assert_eq!(ETHERTYPE_IPV4, ethernet.ethertype);
+
+    let ((rest, _), ip) = Ipv4Header::from_bytes((rest, 0)).unwrap();
+    assert_eq!(Ipv4Addr::new(10, 0, 0, 1), ip.src);
+    assert_eq!(Ipv4Addr::new(93, 184, 216, 34), ip.dst);
+    assert_eq!(TCP, ip.protocol);
+    assert_eq!(
+        vec![Ipv4Option::Other {
+            kind: 0x94,
+            data: vec![0, 0]
+        }],
+        ip.options
+    );
+    assert_eq!(24, ip.header_len());
+    assert_eq!(40, ip.payload_len());
+    assert!(ip.verify_checksum().unwrap());
+
+    let ((payload, _), tcp) = TcpHeader::from_bytes((rest, 0)).unwrap();
+    assert_eq!((50000, 443), (tcp.src_port, tcp.dst_port));
+    assert!(tcp.has_flags(TcpHeader::SYN));
+    assert_eq!(TcpOption::MaxSegmentSize { mss: 1460 }, tcp.options[0]);
+    assert!(payload.is_empty());
+    assert!(tcp.verify_checksum(&ip.pseudo_header(), payload).unwrap());
+
+    // changing a field invalidates the checksums until they're updated
+    let mut ip = ip;
+    let mut tcp = tcp;
+    ip.src = Ipv4Addr::new(10, 0, 0, 2);
+    assert!(!ip.verify_checksum().unwrap());
+    assert!(!tcp.verify_checksum(&ip.pseudo_header(), payload).unwrap());
+    ip.update().unwrap();
+    tcp.update().unwrap();
+    tcp.update_checksum(&ip.pseudo_header(), payload).unwrap();
+    assert!(ip.verify_checksum().unwrap());
+    assert!(tcp.verify_checksum(&ip.pseudo_header(), payload).unwrap());
+
+    ip.src = Ipv4Addr::new(10, 0, 0, 1);
+    ip.update().unwrap();
+    tcp.update_checksum(&ip.pseudo_header(), payload).unwrap();
+    assert_round_trip(
+        &input,
+        &[
+            ethernet.to_bytes().unwrap(),
+            ip.to_bytes().unwrap(),
+            tcp.to_bytes().unwrap(),
+        ],
+        payload,
+    );
+}
+
+#[test]
+fn test_ipv6_hop_by_hop_icmpv6() {
+    let input = hex!(
+        "333300000001 000C29AABBCC 86DD"
+        "6001 2345 001A 00 40 20010DB8000000000000000000000001 20010DB8000000000000000000000002"
+        "3A00 05020000 0100"
+        "8000 DCEA 4242 0007 30313233343536373839"
+    );
+
+    let ((rest, _), ethernet) = EthernetHeader::from_bytes((&input, 0)).unwrap();
+    assert!(ethernet.vlan_tags.is_empty());
+    assert_eq!(ETHERTYPE_IPV6, ethernet.ethertype);
+
+    let ((rest, _), ip) = Ipv6Header::from_bytes((rest, 0)).unwrap();
+    assert_eq!(0x12345, ip.flow_label);
+    assert_eq!("2001:db8::1".parse::<Ipv6Addr>().unwrap(), ip.src);
+    assert_eq!(ICMPV6, ip.upper_layer_protocol());
+    assert_eq!(8, ip.extensions_len().unwrap());
+    match &ip.extensions[..] {
+        [ExtensionHeader::HopByHop(header)] => assert_eq!(
+            Ipv6Option::Other {
+                option_type: 5,
+                data: vec![0, 0]
+            },
+            header.options[0]
+        ),
+        extensions => panic!("unexpected extensions: {:?}", extensions),
+    }
+
+    let ((payload, _), icmp) = Icmpv6Header::from_bytes((rest, 0)).unwrap();
+    assert_eq!(ECHO_REQUEST, icmp.icmp_type);
+    assert_eq!(
+        Icmpv6Body::Echo {
+            identifier: 0x4242,
+            sequence: 7
+        },
+        icmp.body
+    );
+    assert_eq!(b"0123456789", payload);
+    assert!(icmp.verify_checksum(&ip.pseudo_header(), payload).unwrap());
+
+    assert_round_trip(
+        &input,
+        &[
+            ethernet.to_bytes().unwrap(),
+            ip.to_bytes().unwrap(),
+            icmp.to_bytes().unwrap(),
+        ],
+        payload,
+    );
+}
+
+#[test]
+fn test_ipv6_fragment_udp() {
+    let input = hex!(
+        "6000 0000 0015 2C FF 20010DB8000000000000000000000001 20010DB8000000000000000000000002"
+        "11 00 0001 DEADBEEF"
+        "14E9 14E9 000D 36BB 68656C6C6F"
+    );
+
+    let ((rest, _), ip) = Ipv6Header::from_bytes((&input, 0)).unwrap();
+    assert_eq!(
+        vec![ExtensionHeader::Fragment(FragmentHeader {
+            next_header: UDP,
+            fragment_offset: 0,
+            more_fragments: true,
+            identification: 0xDEADBEEF,
+        })],
+        ip.extensions
+    );
+    assert_eq!(UDP, ip.upper_layer_protocol());
+
+    let ((payload, _), udp) = UdpHeader::from_bytes((rest, 0)).unwrap();
+    assert_eq!((5353, 5353, 13), (udp.src_port, udp.dst_port, udp.length));
+    assert!(udp.verify_checksum(&ip.pseudo_header(), payload).unwrap());
+
+    // over IPv6 the checksum is mandatory
+    let unchecked = UdpHeader { checksum: 0, ..udp };
+    assert!(!unchecked
+        .verify_checksum(&ip.pseudo_header(), payload)
+        .unwrap());
+
+    assert_round_trip(
+        &input,
+        &[ip.to_bytes().unwrap(), udp.to_bytes().unwrap()],
+        payload,
+    );
+}
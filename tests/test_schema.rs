@@ -0,0 +1,353 @@
+use deku::prelude::*;
+use deku::schema::{FieldSchema, Primitive, SchemaData, TypeShape};
+
+#[test]
+fn test_schema_struct() {
+    #[derive(PartialEq, Debug, DekuRead, DekuWrite, DekuSchema)]
+    #[deku(endian = "big", magic = b"deku")]
+    struct TestStruct {
+        #[deku(bits = "4")]
+        field_a: u8,
+        #[deku(bits = "4", pad_bytes_after = "1")]
+        count: u8,
+        #[deku(count = "count", endian = "little")]
+        field_b: Vec<u16>,
+        #[deku(cond = "*field_a == 0x01", default = "5")]
+        field_c: u8,
+        #[deku(until = "|v: &u8| *v == 0", assert = "field_d.len() < 10")]
+        field_d: Vec<u8>,
+    }
+
+    let schema = TestStruct::SCHEMA;
+    assert_eq!("TestStruct", schema.ident);
+    assert_eq!(Some("big"), schema.endian);
+    assert_eq!(Some(&b"deku"[..]), schema.magic);
+    assert_eq!(None, schema.ctx);
+    assert_eq!(None, schema.as_enum());
+
+    let fields = schema.fields().unwrap();
+    assert_eq!(5, fields.len());
+
+    assert_eq!(Some("field_a"), fields[0].ident);
+    assert_eq!("u8", fields[0].ty);
+    assert_eq!(Some(4), fields[0].bits);
+    assert_eq!(Some(4), fields[0].bit_size());
+
+    assert_eq!(Some("1"), fields[1].pad_bytes_after);
+
+    assert_eq!("Vec<u16>", fields[2].ty);
+    assert_eq!(Some("count"), fields[2].count);
+    assert_eq!(Some("little"), fields[2].endian);
+    assert_eq!(None, fields[2].bit_size());
+
+    assert_eq!(Some("* field_a == 0x01"), fields[3].cond);
+    assert_eq!(Some("5"), fields[3].default);
+
+    assert!(fields[4].until.is_some());
+    assert!(fields[4].assert.is_some());
+    assert_eq!(None, fields[4].default);
+}
+
+#[test]
+fn test_schema_unnamed_struct() {
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    #[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+    struct TestStruct(#[deku(bytes = "2")] u32, #[deku(skip)] u8, [u8; 4]);
+
+    let schema = TestStruct::SCHEMA;
+    assert_eq!(Some("endian"), schema.endian);
+    assert_eq!(Some("endian : deku :: ctx :: Endian"), schema.ctx);
+
+    let fields = schema.fields().unwrap();
+    assert_eq!(None, fields[0].ident);
+    assert_eq!(Some(16), fields[0].bit_size());
+    assert!(fields[1].skip);
+    assert_eq!(Some("Default :: default()"), fields[1].default);
+    assert_eq!("[u8; 4]", fields[2].ty);
+}
+
+#[test]
+fn test_schema_enum() {
+    #[derive(PartialEq, Debug, DekuRead, DekuWrite, DekuSchema)]
+    #[deku(type = "u8", bits = "4")]
+    enum TestEnum {
+        #[deku(id = "0x01")]
+        VarA(u8),
+        #[deku(id = "0x02")]
+        VarB { field_a: u16 },
+        #[deku(id_pat = "_")]
+        VarC(u8),
+    }
+
+    let schema = TestEnum::SCHEMA;
+    assert_eq!(None, schema.fields());
+
+    let data = match schema.data {
+        SchemaData::Enum(data) => data,
+        SchemaData::Struct(_) => panic!("expected enum"),
+    };
+    assert_eq!(Some("u8"), data.id_type);
+    assert_eq!(Some(4), data.bits);
+    assert_eq!(3, data.variants.len());
+
+    assert_eq!("VarA", data.variants[0].ident);
+    assert_eq!(Some("0x01"), data.variants[0].id);
+    assert_eq!(Some("0x01"), data.variants[0].id_str());
+
+    let field: FieldSchema = data.variants[1].fields[0];
+    assert_eq!(Some("field_a"), field.ident);
+    assert_eq!("u16", field.ty);
+
+    assert_eq!(None, data.variants[2].id);
+    assert_eq!(Some("_"), data.variants[2].id_pat);
+}
+
+#[test]
+fn test_schema_enum_discriminant() {
+    #[derive(PartialEq, Debug, DekuRead, DekuWrite, DekuSchema)]
+    #[deku(type = "u8")]
+    enum TestEnum {
+        VarA = 0x01,
+        #[deku(default)]
+        VarB = 0x02,
+    }
+
+    let data = *TestEnum::SCHEMA.as_enum().unwrap();
+    assert_eq!(Some("0x01"), data.variants[0].discriminant);
+    assert_eq!(Some("0x01"), data.variants[0].id_str());
+    assert!(!data.variants[0].default);
+    assert!(data.variants[1].default);
+}
+
+#[test]
+fn test_schema_enum_repr() {
+    #[derive(PartialEq, Debug, DekuRead, DekuWrite, DekuSchema)]
+    #[repr(u16)]
+    enum TestEnum {
+        VarA = 0x01,
+        VarB = 0x02,
+    }
+
+    let data = *TestEnum::SCHEMA.as_enum().unwrap();
+    assert_eq!(Some("u16"), data.id_type);
+}
+
+#[test]
+fn test_schema_enum_custom_id() {
+    use deku::bitvec::{BitSlice, BitVec, Msb0};
+
+    fn read_id(rest: &BitSlice<u8, Msb0>) -> Result<(&BitSlice<u8, Msb0>, u8), DekuError> {
+        u8::read(rest, ())
+    }
+
+    fn write_id(output: &mut BitVec<u8, Msb0>, id: u8) -> Result<(), DekuError> {
+        id.write(output, ())
+    }
+
+    #[derive(PartialEq, Debug, DekuRead, DekuWrite, DekuSchema)]
+    #[deku(
+        type = "u8",
+        id_reader = "read_id(deku::rest)",
+        id_writer = "write_id(deku::output, deku::id)"
+    )]
+    enum TestEnum {
+        #[deku(id = "0x01")]
+        VarA,
+    }
+
+    let data = *TestEnum::SCHEMA.as_enum().unwrap();
+    assert!(data.custom_id);
+}
+
+#[test]
+fn test_schema_generic() {
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    struct TestStruct<T>
+    where
+        T: deku::DekuWrite + for<'a> deku::DekuRead<'a>,
+    {
+        field_a: T,
+    }
+
+    let fields = <TestStruct<u8>>::SCHEMA.fields().unwrap();
+    assert_eq!("T", fields[0].ty);
+}
+
+#[test]
+fn test_schema_align() {
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    #[deku(align = "4", align_input, pad_fill = "0xFF")]
+    struct TestStruct {
+        #[deku(bits = "3", align_bits_after = "8")]
+        field_a: u8,
+        #[deku(align_before = "2", pad_fill = b"\xAA", pad_strict)]
+        field_b: u8,
+    }
+
+    let schema = TestStruct::SCHEMA;
+    assert_eq!(Some("4"), schema.align);
+    assert!(schema.align_input);
+    assert_eq!(Some("0xFF"), schema.pad_fill);
+    assert!(!schema.pad_strict);
+
+    let fields = schema.fields().unwrap();
+    assert_eq!(Some("8"), fields[0].align_bits_after);
+    assert_eq!(None, fields[0].align_after);
+    assert_eq!(Some("2"), fields[1].align_before);
+    assert_eq!(None, fields[1].align_bits_before);
+    assert_eq!(Some("b\"\\xAA\""), fields[1].pad_fill);
+    assert!(fields[1].pad_strict);
+}
+
+#[test]
+fn test_schema_reserved() {
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    #[deku(reserved_strict)]
+    struct TestStruct {
+        #[deku(reserved_bits = "4", reserved_value = "0b1010", bits = "4")]
+        field_a: u8,
+        #[deku(reserved_bytes = "2", reserved_strict)]
+        field_b: u8,
+    }
+
+    let schema = TestStruct::SCHEMA;
+    assert!(schema.reserved_strict);
+
+    let fields = schema.fields().unwrap();
+    assert_eq!(Some("4"), fields[0].reserved_bits);
+    assert_eq!(None, fields[0].reserved_bytes);
+    assert_eq!(Some("0b1010"), fields[0].reserved_value);
+    assert!(!fields[0].reserved_strict);
+    assert_eq!(None, fields[1].reserved_bits);
+    assert_eq!(Some("2"), fields[1].reserved_bytes);
+    assert_eq!(None, fields[1].reserved_value);
+    assert!(fields[1].reserved_strict);
+}
+
+#[test]
+fn test_schema_as_type() {
+    #[derive(PartialEq, Debug, Clone, Copy)]
+    struct Port(u16);
+
+    impl From<u16> for Port {
+        fn from(v: u16) -> Self {
+            Port(v)
+        }
+    }
+
+    impl From<Port> for u16 {
+        fn from(v: Port) -> Self {
+            v.0
+        }
+    }
+
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    struct TestStruct {
+        #[deku(as_type = "u16", endian = "big")]
+        port: Port,
+        field_b: u8,
+    }
+
+    let fields = TestStruct::SCHEMA.fields().unwrap();
+    assert_eq!("Port", fields[0].ty);
+    assert_eq!(Some("u16"), fields[0].as_type);
+    assert!(!fields[0].try_from);
+    assert_eq!("u16", fields[0].wire_ty());
+    assert_eq!(
+        TypeShape::Primitive(Primitive::Unsigned(16)),
+        fields[0].shape()
+    );
+    assert_eq!(None, fields[1].as_type);
+    assert_eq!("u8", fields[1].wire_ty());
+}
+
+#[test]
+fn test_schema_variant_attributes() {
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    #[deku(type = "u8")]
+    enum TestEnum {
+        #[deku(
+            id = "1",
+            magic = b"\x2a",
+            endian = "big",
+            ctx = "size = deku :: ctx :: BitSize(4)"
+        )]
+        VarA {
+            #[deku(ctx = "size")]
+            a: u8,
+            #[deku(endian = "little", ctx = "deku :: ctx :: BitSize(12)")]
+            b: u16,
+        },
+    }
+
+    let variant = TestEnum::SCHEMA.as_enum().unwrap().variants[0];
+    assert_eq!(Some(b"\x2a".as_ref()), variant.magic);
+    assert_eq!(Some("big"), variant.endian);
+    assert_eq!(Some("size = deku :: ctx :: BitSize(4)"), variant.ctx);
+
+    // fields without their own `endian` use the variant's
+    assert_eq!(Some("big"), variant.fields[0].endian);
+    assert_eq!(Some("size"), variant.fields[0].ctx);
+    assert_eq!(Some("little"), variant.fields[1].endian);
+    assert_eq!(Some("deku :: ctx :: BitSize(12)"), variant.fields[1].ctx);
+}
+
+#[test]
+fn test_schema_endian_from() {
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    #[deku(type = "u8")]
+    enum ByteOrder {
+        #[deku(id = "0")]
+        Little,
+        #[deku(id = "1")]
+        Big,
+    }
+
+    impl From<&ByteOrder> for deku::ctx::Endian {
+        fn from(byte_order: &ByteOrder) -> Self {
+            match byte_order {
+                ByteOrder::Little => deku::ctx::Endian::Little,
+                ByteOrder::Big => deku::ctx::Endian::Big,
+            }
+        }
+    }
+
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    #[deku(endian_from = "byte_order")]
+    struct Named {
+        len: u8,
+        byte_order: ByteOrder,
+        value: u16,
+    }
+
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    #[deku(endian_from = "0")]
+    struct Unnamed(ByteOrder, u16);
+
+    assert_eq!(Some("byte_order"), Named::SCHEMA.endian_from);
+    assert_eq!(Some(1), Named::SCHEMA.endian_from_index());
+    assert_eq!(Some(0), Unnamed::SCHEMA.endian_from_index());
+    assert_eq!(None, ByteOrder::SCHEMA.endian_from_index());
+}
+
+#[test]
+fn test_schema_version() {
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    #[deku(version = "*version")]
+    struct Test {
+        version: u8,
+        #[deku(since = "2")]
+        field_a: u8,
+        #[deku(since = "2", until_version = "3", cond = "*field_a != 0")]
+        field_b: u8,
+    }
+
+    let fields = Test::SCHEMA.fields().unwrap();
+    assert_eq!(None, fields[0].cond);
+    assert_eq!(Some("(* version) >= (2)"), fields[1].cond);
+    assert_eq!(Some("Default :: default()"), fields[1].default);
+    assert_eq!(
+        Some("((* version) >= (2) && (* version) <= (3)) && (* field_a != 0)"),
+        fields[2].cond
+    );
+}
@@ -0,0 +1,380 @@
+#![cfg(feature = "wireshark")]
+
+use deku::prelude::*;
+use deku::schema::wireshark::to_lua;
+use std::net::Ipv4Addr;
+
+#[test]
+fn test_wireshark_ipv4() {
+    // Same layout as `examples/ipv4.rs`
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    #[deku(endian = "big")]
+    struct Ipv4Header {
+        #[deku(bits = "4")]
+        version: u8,
+        #[deku(bits = "4")]
+        ihl: u8,
+        #[deku(bits = "6")]
+        dscp: u8,
+        #[deku(bits = "2")]
+        ecn: u8,
+        length: u16,
+        identification: u16,
+        #[deku(bits = "3")]
+        flags: u8,
+        #[deku(bits = "13")]
+        offset: u16,
+        ttl: u8,
+        protocol: u8,
+        checksum: u16,
+        src: Ipv4Addr,
+        dst: Ipv4Addr,
+        #[deku(count = "(*ihl - 5) * 4")]
+        options: Vec<u8>,
+    }
+
+    let lua = to_lua(&Ipv4Header::SCHEMA, &[]);
+
+    assert!(lua.starts_with(
+        r#"-- Wireshark dissector for `Ipv4Header`, generated by deku
+
+local ipv4_header = Proto("ipv4_header", "Ipv4Header")
+
+local f = ipv4_header.fields
+f["version"] = ProtoField.uint8("ipv4_header.version", "version", base.DEC)
+f["ihl"] = ProtoField.uint8("ipv4_header.ihl", "ihl", base.DEC)
+f["dscp"] = ProtoField.uint8("ipv4_header.dscp", "dscp", base.DEC)
+f["ecn"] = ProtoField.uint8("ipv4_header.ecn", "ecn", base.DEC)
+f["length"] = ProtoField.uint16("ipv4_header.length", "length", base.DEC)
+f["identification"] = ProtoField.uint16("ipv4_header.identification", "identification", base.DEC)
+f["flags"] = ProtoField.uint8("ipv4_header.flags", "flags", base.DEC)
+f["offset"] = ProtoField.uint16("ipv4_header.offset", "offset", base.DEC)
+f["ttl"] = ProtoField.uint8("ipv4_header.ttl", "ttl", base.DEC)
+f["protocol"] = ProtoField.uint8("ipv4_header.protocol", "protocol", base.DEC)
+f["checksum"] = ProtoField.uint16("ipv4_header.checksum", "checksum", base.DEC)
+f["src"] = ProtoField.ipv4("ipv4_header.src", "src")
+f["dst"] = ProtoField.ipv4("ipv4_header.dst", "dst")
+f["options"] = ProtoField.bytes("ipv4_header.options", "options")
+"#
+    ));
+
+    assert!(lua.contains(
+        r#"local dissect_ipv4_header
+
+function dissect_ipv4_header(buffer, tree, offset, little)
+    local start = offset
+    local v_version = read_uint(buffer, offset, 4, false)
+    add_value(tree, f["version"], buffer, offset, 4, v_version)
+    offset = offset + 4
+    local v_ihl = read_uint(buffer, offset, 4, false)
+    add_value(tree, f["ihl"], buffer, offset, 4, v_ihl)
+    offset = offset + 4
+    local v_dscp = read_uint(buffer, offset, 6, false)
+    add_value(tree, f["dscp"], buffer, offset, 6, v_dscp)
+    offset = offset + 6
+    local v_ecn = read_uint(buffer, offset, 2, false)
+    add_value(tree, f["ecn"], buffer, offset, 2, v_ecn)
+    offset = offset + 2
+    local v_length = read_uint(buffer, offset, 16, false)
+    add_value(tree, f["length"], buffer, offset, 16, v_length)
+    offset = offset + 16
+    local v_identification = read_uint(buffer, offset, 16, false)
+    add_value(tree, f["identification"], buffer, offset, 16, v_identification)
+    offset = offset + 16
+    local v_flags = read_uint(buffer, offset, 3, false)
+    add_value(tree, f["flags"], buffer, offset, 3, v_flags)
+    offset = offset + 3
+    local v_offset = read_uint(buffer, offset, 13, false)
+    add_value(tree, f["offset"], buffer, offset, 13, v_offset)
+    offset = offset + 13
+    local v_ttl = read_uint(buffer, offset, 8, false)
+    add_value(tree, f["ttl"], buffer, offset, 8, v_ttl)
+    offset = offset + 8
+    local v_protocol = read_uint(buffer, offset, 8, false)
+    add_value(tree, f["protocol"], buffer, offset, 8, v_protocol)
+    offset = offset + 8
+    local v_checksum = read_uint(buffer, offset, 16, false)
+    add_value(tree, f["checksum"], buffer, offset, 16, v_checksum)
+    offset = offset + 16
+    add_range(tree, f["src"], bit_range(buffer, offset, 32), false)
+    offset = offset + 32
+    add_range(tree, f["dst"], bit_range(buffer, offset, 32), false)
+    offset = offset + 32
+    tree:add(f["options"], buffer(math.floor(offset / 8), (v_ihl - 5) * 4))
+    offset = offset + ((v_ihl - 5) * 4) * 8
+    return offset
+end
+
+function ipv4_header.dissector(buffer, pinfo, tree)
+    pinfo.cols.protocol = ipv4_header.name
+    local subtree = tree:add(ipv4_header, buffer(), "Ipv4Header")
+    local offset = dissect_ipv4_header(buffer, subtree, 0, false)
+    if offset == nil then
+        return 0
+    end
+    subtree:set_len(math.ceil(offset / 8))
+    return math.ceil(offset / 8)
+end
+"#
+    ));
+}
+
+#[test]
+fn test_wireshark_enum() {
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    #[deku(type = "u8", endian = "little")]
+    enum Message {
+        #[deku(id = "0x01")]
+        Ping,
+        #[deku(id = "0x02")]
+        Data {
+            len: u16,
+            #[deku(count = "len")]
+            values: Vec<i16>,
+            #[deku(cond = "*len > 0")]
+            inner: Option<Inner>,
+        },
+        #[deku(id_pat = "_")]
+        Unknown(u8),
+    }
+
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    #[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+    struct Inner {
+        flag: bool,
+        #[deku(until = "|v: &u8| *v == 0")]
+        name: Vec<u8>,
+    }
+
+    let lua = to_lua(&Message::SCHEMA, &[Inner::SCHEMA]);
+
+    assert!(lua.contains(
+        r#"local message_ids = { [1] = "Ping", [2] = "Data" }
+
+local f = message.fields
+f["variant_id"] = ProtoField.uint8("message.variant_id", "variant_id", base.DEC, message_ids)
+f["data.len"] = ProtoField.uint16("message.data.len", "len", base.DEC)
+f["data.values"] = ProtoField.int16("message.data.values", "values", base.DEC)
+f["unknown.field_0"] = ProtoField.uint8("message.unknown.field_0", "field_0", base.DEC)
+f["inner.flag"] = ProtoField.bool("message.inner.flag", "flag")
+f["inner.name"] = ProtoField.uint8("message.inner.name", "name", base.DEC)
+"#
+    ));
+
+    assert!(lua.contains(
+        r#"local dissect_message, dissect_inner
+
+function dissect_message(buffer, tree, offset, little)
+    local start = offset
+    local v_variant_id = read_uint(buffer, offset, 8, true)
+    add_value(tree, f["variant_id"], buffer, offset, 8, v_variant_id)
+    offset = offset + 8
+    if v_variant_id == 1 then
+        -- Ping
+    elseif v_variant_id == 2 then
+        -- Data
+        local v_len = read_uint(buffer, offset, 16, true)
+        add_value(tree, f["data.len"], buffer, offset, 16, v_len)
+        offset = offset + 16
+        local v_values = {}
+        for _ = 1, v_len do
+            local value = read_int(buffer, offset, 16, true)
+            add_value(tree, f["data.values"], buffer, offset, 16, value)
+            offset = offset + 16
+            v_values[#v_values + 1] = value
+        end
+        local v_inner
+        if v_len > 0 then
+            offset = add_nested(tree, "inner", buffer, offset, dissect_inner, true)
+            if offset == nil then
+                return nil
+            end
+        end
+    else
+        -- Unknown
+        local v_field_0 = read_uint(buffer, offset, 8, true)
+        add_value(tree, f["unknown.field_0"], buffer, offset, 8, v_field_0)
+        offset = offset + 8
+    end
+    return offset
+end
+
+function dissect_inner(buffer, tree, offset, little)
+    local start = offset
+    local v_flag = read_uint(buffer, offset, 8, little) ~= 0
+    add_value(tree, f["inner.flag"], buffer, offset, 8, v_flag)
+    offset = offset + 8
+    local v_name = {}
+    repeat
+        local value = read_uint(buffer, offset, 8, little)
+        add_value(tree, f["inner.name"], buffer, offset, 8, value)
+        offset = offset + 8
+        v_name[#v_name + 1] = value
+    until value == 0
+    return offset
+end
+"#
+    ));
+}
+
+#[test]
+fn test_wireshark_magic_ctx() {
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    #[deku(magic = b"DK", endian = "big")]
+    struct Parent {
+        count: u8,
+        #[deku(ctx = "*count")]
+        child: Child,
+        #[deku(map = "|v: u8| -> Result<_, DekuError> { Ok(v * 2) }")]
+        mapped: u8,
+        after: u8,
+    }
+
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    #[deku(ctx = "endian: deku::ctx::Endian, count: u8", endian = "endian")]
+    struct Child {
+        #[deku(bytes_read = "count")]
+        data: Vec<u16>,
+    }
+
+    let lua = to_lua(&Parent::SCHEMA, &[Child::SCHEMA]);
+
+    assert!(lua.contains(
+        r#"function dissect_parent(buffer, tree, offset, little)
+    local start = offset
+    if buffer(math.floor(offset / 8), 2):bytes() ~= ByteArray.new("444b") then
+        return nil
+    end
+    tree:add(f["magic"], buffer(math.floor(offset / 8), 2))
+    offset = offset + 16
+    local v_count = read_uint(buffer, offset, 8, false)
+    add_value(tree, f["count"], buffer, offset, 8, v_count)
+    offset = offset + 8
+    offset = add_nested(tree, "child", buffer, offset, dissect_child, false, v_count)
+    if offset == nil then
+        return nil
+    end
+    tree:add(buffer(math.floor(offset / 8)), "mapped: not decoded (mapped value)")
+    do return nil end
+    return offset
+end
+
+function dissect_child(buffer, tree, offset, little, v_count)
+    local start = offset
+    local v_data = {}
+    local e_data = offset + v_count * 8
+    while offset < e_data do
+        local value = read_uint(buffer, offset, 16, little)
+        add_value(tree, f["child.data"], buffer, offset, 16, value)
+        offset = offset + 16
+        v_data[#v_data + 1] = value
+    end
+    return offset
+end
+"#
+    ));
+}
+
+#[test]
+fn test_wireshark_align() {
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    #[deku(align = "4")]
+    struct Aligned {
+        #[deku(bits = "3", align_bits_after = "8")]
+        flags: u8,
+        #[deku(align_before = "2")]
+        value: u8,
+    }
+
+    let lua = to_lua(&Aligned::SCHEMA, &[]);
+
+    assert!(lua.contains(
+        r#"function dissect_aligned(buffer, tree, offset, little)
+    local start = offset
+    local v_flags = read_uint(buffer, offset, 3, little)
+    add_value(tree, f["flags"], buffer, offset, 3, v_flags)
+    offset = offset + 3
+    offset = offset + (8 - (offset - start) % 8) % 8
+    offset = offset + ((2 * 8) - (offset - start) % (2 * 8)) % (2 * 8)
+    local v_value = read_uint(buffer, offset, 8, little)
+    add_value(tree, f["value"], buffer, offset, 8, v_value)
+    offset = offset + 8
+    offset = offset + ((4 * 8) - (offset - start) % (4 * 8)) % (4 * 8)
+    return offset
+end
+"#
+    ));
+}
+
+#[test]
+fn test_wireshark_terminator() {
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    struct Terminated {
+        #[deku(terminator = "0xFFFF")]
+        values: Vec<u16>,
+    }
+
+    let lua = to_lua(&Terminated::SCHEMA, &[]);
+
+    assert!(lua.contains(
+        r#"function dissect_terminated(buffer, tree, offset, little)
+    local start = offset
+    local v_values = {}
+    repeat
+        local value = read_uint(buffer, offset, 16, little)
+        add_value(tree, f["values"], buffer, offset, 16, value)
+        offset = offset + 16
+        v_values[#v_values + 1] = value
+    until value == 65535
+    v_values[#v_values] = nil
+    return offset
+end
+"#
+    ));
+}
+
+#[test]
+fn test_wireshark_window() {
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    struct Record {
+        len: u8,
+        #[deku(window_bytes = "len", rest = "skip")]
+        kind: u8,
+    }
+
+    let lua = to_lua(&Record::SCHEMA, &[]);
+
+    assert!(lua.contains(
+        r#"    local w_kind = offset + v_len * 8
+    local v_kind = read_uint(buffer, offset, 8, little)
+    add_value(tree, f["kind"], buffer, offset, 8, v_kind)
+    offset = offset + 8
+    offset = w_kind
+    return offset
+"#
+    ));
+}
+
+#[test]
+fn test_wireshark_at() {
+    #[derive(DekuRead, DekuWrite, DekuSchema)]
+    struct Table {
+        offset: u8,
+        #[deku(at = "offset")]
+        value: u8,
+        kind: u8,
+    }
+
+    let lua = to_lua(&Table::SCHEMA, &[]);
+
+    assert!(lua.contains(
+        r#"    local r_value = offset
+    offset = start + v_offset * 8
+    local v_value = read_uint(buffer, offset, 8, little)
+    add_value(tree, f["value"], buffer, offset, 8, v_value)
+    offset = offset + 8
+    offset = r_value
+    local v_kind = read_uint(buffer, offset, 8, little)
+"#
+    ));
+}